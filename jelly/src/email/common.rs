@@ -1,5 +1,6 @@
 use std::env;
 use std::env::var;
+use std::fmt;
 use std::sync::{Arc, RwLock};
 use tera::{Context, Tera};
 
@@ -22,8 +23,25 @@ pub fn env_exists_and_not_empty(env: &str) {
     }
 }
 
+/// A provider refused the email for good because of its recipient, e.g. an invalid or
+/// inactive address. Any other failure is transient, and sending again may work.
+#[derive(Debug)]
+pub struct Rejected(pub String);
+
+impl fmt::Display for Rejected {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for Rejected {}
+
 #[derive(Debug, Default, Serialize)]
 pub struct Email {
+    /// The template it was rendered from, as recorded in the outbox.
+    #[serde(skip)]
+    pub template: String,
+
     /// Who's sending this.
     #[serde(rename = "From")]
     pub from: String,
@@ -89,6 +107,7 @@ impl Email {
             .map_err(Error::msg)?;
 
         Ok(Email {
+            template: template_name.to_string(),
             to: to.join(","),
             from: var("EMAIL_DEFAULT_FROM").expect("EMAIL_DEFAULT_FROM not set!"),
             body_html,
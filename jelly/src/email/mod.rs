@@ -1,10 +1,11 @@
 pub (crate) use common::Configurable;
-pub use common::Email;
+pub use common::{Email, Rejected};
 pub use tera::Context;
 
 use anyhow::anyhow;
 
 pub (crate) mod common;
+pub mod outbox;
 #[cfg(feature = "email-postmark")]
 pub mod postmark;
 #[cfg(feature = "email-sendgrid")]
@@ -28,7 +29,9 @@ impl Configurable for Email {
 }
 
 impl Email {
-    pub fn send(self) -> Result<(), anyhow::Error> {
+    /// Sends the email with the first configured provider that accepts it, and
+    /// returns the provider's response.
+    pub fn send(self) -> Result<String, anyhow::Error> {
        #[allow(unused_mut)]
        let mut res = Result::Err(anyhow!("No email provider configured"));
         #[cfg(feature = "email-postmark")]
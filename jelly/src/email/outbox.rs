@@ -0,0 +1,144 @@
+//! Records every email sent with `Email::deliver` in the `jelly_email_outbox` table,
+//! which the app has to create with a migration, so that support can find out what
+//! was sent to whom and what the provider answered.
+//!
+//! Only the rendered subject is kept: bodies can hold secrets like password reset links.
+
+use anyhow::anyhow;
+use chrono::{DateTime, Utc};
+use diesel::prelude::*;
+use serde::Serialize;
+
+use super::common::{Email, Rejected};
+use crate::DieselPgPool;
+
+table! {
+    jelly_email_outbox (id) {
+        id -> Int4,
+        template -> Text,
+        recipient -> Text,
+        subject -> Text,
+        status -> Text,
+        provider_response -> Nullable<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+pub const EMAIL_STATUS_SENDING: &str = "sending";
+pub const EMAIL_STATUS_SENT: &str = "sent";
+/// Failed transiently, the job sending it is retried.
+pub const EMAIL_STATUS_DEFERRED: &str = "deferred";
+/// Refused by the provider, it is not sent again.
+pub const EMAIL_STATUS_REJECTED: &str = "rejected";
+
+#[derive(Clone, Debug, Queryable, Serialize)]
+pub struct OutboxEmail {
+    pub id: i32,
+    pub template: String,
+    pub recipient: String,
+    pub subject: String,
+    pub status: String,
+    pub provider_response: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl OutboxEmail {
+    fn record(email: &Email, conn: &PgConnection) -> QueryResult<i32> {
+        let now = Utc::now();
+        diesel::insert_into(jelly_email_outbox::table)
+            .values((
+                jelly_email_outbox::template.eq(&email.template),
+                jelly_email_outbox::recipient.eq(&email.to),
+                jelly_email_outbox::subject.eq(&email.subject),
+                jelly_email_outbox::status.eq(EMAIL_STATUS_SENDING),
+                jelly_email_outbox::created_at.eq(now),
+                jelly_email_outbox::updated_at.eq(now),
+            ))
+            .returning(jelly_email_outbox::id)
+            .get_result(conn)
+    }
+
+    fn set_status(
+        id: i32,
+        status: &str,
+        provider_response: &str,
+        conn: &PgConnection,
+    ) -> QueryResult<()> {
+        diesel::update(jelly_email_outbox::table.find(id))
+            .set((
+                jelly_email_outbox::status.eq(status),
+                jelly_email_outbox::provider_response.eq(provider_response),
+                jelly_email_outbox::updated_at.eq(Utc::now()),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Emails whose recipient, subject or template contains `query`, newest first,
+    /// with the total count.
+    pub fn search(
+        query: &str,
+        page: i64,
+        per_page: i64,
+        pool: &DieselPgPool,
+    ) -> crate::Result<(Vec<Self>, i64)> {
+        let conn = pool.get()?;
+        let escaped = query
+            .replace('\\', "\\\\")
+            .replace('%', "\\%")
+            .replace('_', "\\_");
+        let pattern = format!("%{}%", escaped);
+        let matching = || {
+            jelly_email_outbox::table
+                .filter(
+                    jelly_email_outbox::recipient
+                        .ilike(pattern.clone())
+                        .or(jelly_email_outbox::subject.ilike(pattern.clone()))
+                        .or(jelly_email_outbox::template.ilike(pattern.clone())),
+                )
+                .into_boxed()
+        };
+        let total_count = matching().count().get_result::<i64>(&conn)?;
+        let emails = matching()
+            .order_by(jelly_email_outbox::id.desc())
+            .offset((page - 1).max(0) * per_page)
+            .limit(per_page)
+            .load::<Self>(&conn)?;
+        Ok((emails, total_count))
+    }
+}
+
+impl Email {
+    /// Sends the email and records it in the outbox. Transient failures are returned,
+    /// so that the job sending the email gets retried, while an email the provider
+    /// rejected is only recorded as such.
+    pub fn deliver(self, pool: &DieselPgPool) -> Result<(), anyhow::Error> {
+        let conn = pool.get()?;
+        // Failing to record an email must not keep it from being sent
+        let id = OutboxEmail::record(&self, &conn)
+            .map_err(|e| error!("Cannot record email to {} in the outbox: {:?}", self.to, e))
+            .ok();
+        let to = self.to.clone();
+        let result = self.send();
+        let (status, provider_response) = match &result {
+            Ok(response) => (EMAIL_STATUS_SENT, response.clone()),
+            Err(e) if e.is::<Rejected>() => (EMAIL_STATUS_REJECTED, format!("{:#}", e)),
+            Err(e) => (EMAIL_STATUS_DEFERRED, format!("{:#}", e)),
+        };
+        if let Some(id) = id {
+            if let Err(e) = OutboxEmail::set_status(id, status, &provider_response, &conn) {
+                error!("Cannot record the delivery of email {}: {:?}", id, e);
+            }
+        }
+        match result {
+            Ok(_) => Ok(()),
+            Err(e) if status == EMAIL_STATUS_REJECTED => {
+                warn!("Email to {} was rejected: {:#}", to, e);
+                Ok(())
+            }
+            Err(e) => Err(anyhow!("Cannot send email to {}: {:#}", to, e)),
+        }
+    }
+}
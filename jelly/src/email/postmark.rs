@@ -7,9 +7,13 @@
 use std::env::var;
 use anyhow::{anyhow, Context, Result};
 
-use super::common::env_exists_and_not_empty;
+use super::common::{env_exists_and_not_empty, Rejected};
 pub use super::common::Email;
 
+/// Postmark error codes, see https://postmarkapp.com/developer/api/overview#error-codes
+const INVALID_EMAIL_REQUEST: i64 = 300;
+const INACTIVE_RECIPIENT: i64 = 406;
+
 /// Check that all needed environment variables are set and not empty.
 pub fn check_conf() {
     ["POSTMARK_API_KEY", "POSTMARK_MESSAGE_STREAM"]
@@ -18,9 +22,9 @@ pub fn check_conf() {
 }
 
 impl Email {
-    /// Send the email. Relies on you ensuring that `POSTMARK_API_KEY`
-    /// is set in your `.env`.
-    pub fn send_via_postmark(&self, base_url_api: &str) -> Result<(), anyhow::Error> {
+    /// Send the email, and return the response of Postmark. Relies on you ensuring
+    /// that `POSTMARK_API_KEY` is set in your `.env`.
+    pub fn send_via_postmark(&self, base_url_api: &str) -> Result<String, anyhow::Error> {
         let api_key = var("POSTMARK_API_KEY").expect("POSTMARK_API_KEY not set!");
 
         let resp = minreq::post(base_url_api.to_owned() + "/email")
@@ -31,15 +35,33 @@ impl Email {
 
         if resp.status_code == 200 {
             debug!("Mail sent to {} via postmark.", &self.to);
-            Ok(())
+            Ok(resp.as_str()?.to_string())
         } else {
-            Err(anyhow!(
+            let body = resp.as_str()?;
+            let message = format!(
                 "Sending mail to {} via postmark failed. API call returns code {} : {} \n {} ",
-                &self.to,
-                resp.status_code,
-                resp.reason_phrase,
-                resp.as_str()?
-            ))
+                &self.to, resp.status_code, resp.reason_phrase, body
+            );
+            if resp.status_code == 422 && refuses_recipient(body) {
+                Err(Rejected(message).into())
+            } else {
+                Err(anyhow!(message))
+            }
         }
     }
 }
+
+/// Whether Postmark refused the recipient itself, i.e. an inactive recipient or an
+/// invalid `To` address. Bad credentials and other errors are worth sending again.
+fn refuses_recipient(body: &str) -> bool {
+    let error = match serde_json::from_str::<serde_json::Value>(body) {
+        Ok(error) => error,
+        Err(_) => return false,
+    };
+    let message = error["Message"].as_str().unwrap_or_default();
+    match error["ErrorCode"].as_i64() {
+        Some(INACTIVE_RECIPIENT) => true,
+        Some(INVALID_EMAIL_REQUEST) => message.contains("'To'"),
+        _ => false,
+    }
+}
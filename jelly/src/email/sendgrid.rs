@@ -1,4 +1,4 @@
-use super::common::env_exists_and_not_empty;
+use super::common::{env_exists_and_not_empty, Rejected};
 pub use super::common::Email;
 use anyhow::{anyhow, Context, Result};
 use serde::Serialize;
@@ -36,8 +36,8 @@ pub fn check_conf() {
 }
 
 impl Email {
-    /// Send the email.
-    pub fn send_via_sendgrid(&self, base_api_url: &str) -> Result<(), anyhow::Error> {
+    /// Send the email, and return the status and message id given by Sendgrid.
+    pub fn send_via_sendgrid(&self, base_api_url: &str) -> Result<String, anyhow::Error> {
         let text_plain = "text/plain".to_string();
         let text_html = "text/html".to_string();
         let data = SendgridV3Data {
@@ -69,15 +69,40 @@ impl Email {
 
         if resp.status_code >= 200 && resp.status_code <= 299 {
             debug!("Mail sent to {} via sendgrid.", &self.to);
-            Ok(())
-        } else {
-            Err(anyhow!(
-                "Sending mail to {} via sendgrid failed. API call returns code {} : {} \n {} ",
-                &self.to,
+            Ok(format!(
+                "{} {} {}",
                 resp.status_code,
                 resp.reason_phrase,
-                resp.as_str()?
-            ))
+                resp.headers
+                    .get("x-message-id")
+                    .map(String::as_str)
+                    .unwrap_or_default()
+            )
+            .trim_end()
+            .to_string())
+        } else {
+            let body = resp.as_str()?;
+            let message = format!(
+                "Sending mail to {} via sendgrid failed. API call returns code {} : {} \n {} ",
+                &self.to, resp.status_code, resp.reason_phrase, body
+            );
+            if resp.status_code == 400 && refuses_recipient(body) {
+                Err(Rejected(message).into())
+            } else {
+                Err(anyhow!(message))
+            }
         }
     }
 }
+
+/// Whether SendGrid refused the email because of its recipient, e.g. an invalid `to`
+/// address. Bad credentials and other errors are worth sending again.
+fn refuses_recipient(body: &str) -> bool {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|response| response["errors"].as_array().cloned())
+        .unwrap_or_default()
+        .iter()
+        .filter_map(|error| error["field"].as_str())
+        .any(|field| field.starts_with("personalizations") && field.contains(".to"))
+}
@@ -2,8 +2,8 @@ use std::env::var;
 
 use anyhow::Result;
 
-use super::common::{env_exists_and_not_empty, Email};
-use lettre::message::MultiPart;
+use super::common::{env_exists_and_not_empty, Email, Rejected};
+use lettre::message::{Mailbox, MultiPart};
 use lettre::transport::smtp::{authentication::Credentials, client::Tls};
 use lettre::{Message, SmtpTransport, Transport};
 
@@ -23,8 +23,8 @@ pub fn check_conf() {
 impl Email {
     /// Send the email. Relies on you ensuring that `EMAIL_DEFAULT_FROM`,
     /// `EMAIL_SMTP_HOST`, `EMAIL_SMTP_USERNAME`, and `EMAIL_SMTP_PASSWORD`
-    /// are set in your `.env`.
-    pub fn send_via_smtp(&self) -> Result<(), anyhow::Error> {
+    /// are set in your `.env`. Returns the response of the SMTP server.
+    pub fn send_via_smtp(&self) -> Result<String, anyhow::Error> {
         let host = var("EMAIL_SMTP_HOST").expect("EMAIL_SMTP_HOST not set!");
         let port = var("EMAIL_SMTP_PORT").expect("EMAIL_SMTP_PORT not set!");
         let username = var("EMAIL_SMTP_USERNAME").expect("EMAIL_SMTP_USERNAME not set!");
@@ -33,10 +33,14 @@ impl Email {
             .or_else::<anyhow::Error, _>(|_v| Ok(self.from.clone()))
             .unwrap();
 
+        let to: Mailbox = self
+            .to
+            .parse()
+            .map_err(|e| Rejected(format!("Invalid recipient {}: {}", self.to, e)))?;
         let email = Message::builder()
             .from(self.from.parse()?)
             .reply_to(reply_to.parse()?)
-            .to(self.to.parse()?)
+            .to(to)
             .subject(&self.subject)
             .multipart(MultiPart::alternative_plain_html(
                 self.body.clone(),
@@ -57,9 +61,9 @@ impl Email {
         }
 
         let mailer = mailer_builder.build();
-        mailer.send(&email)?;
+        let response = mailer.send(&email)?;
         debug!("Mail sent to {} via smtp.", &self.to);
 
-        Ok(())
+        Ok(format!("{:?}", response))
     }
 }
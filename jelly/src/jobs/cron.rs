@@ -0,0 +1,195 @@
+//! Cron expressions for recurring jobs, see `Server::register_schedule`.
+
+use anyhow::{anyhow, Error};
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
+
+/// Any expression that can match at all matches again within this many days,
+/// e.g. `0 0 29 2 *` around a century that skips its leap year.
+const MAX_DAYS_BETWEEN_MATCHES: i64 = 366 * 8;
+
+/// A standard five fields cron expression: minute, hour, day of month, month and
+/// day of week, evaluated in UTC. Fields accept `*`, values, ranges, lists and steps,
+/// e.g. `*/15 9-17 * * 1-5`. Sunday is both `0` and `7`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Cron {
+    expression: String,
+    minutes: Vec<bool>,
+    hours: Vec<bool>,
+    days_of_month: Vec<bool>,
+    months: Vec<bool>,
+    days_of_week: Vec<bool>,
+    // As in Vixie cron, a day matches either field when both are restricted
+    days_restricted: bool,
+}
+
+impl Cron {
+    pub fn parse(expression: &str) -> Result<Self, Error> {
+        let fields: Vec<&str> = expression.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(anyhow!(
+                "Cron expression needs 5 fields, got {:?}",
+                expression
+            ));
+        }
+        let mut days_of_week = parse_field(fields[4], 0, 7)?;
+        if days_of_week[7] {
+            days_of_week[0] = true;
+        }
+        days_of_week.truncate(7);
+
+        let cron = Cron {
+            expression: fields.join(" "),
+            minutes: parse_field(fields[0], 0, 59)?,
+            hours: parse_field(fields[1], 0, 23)?,
+            days_of_month: parse_field(fields[2], 1, 31)?,
+            months: parse_field(fields[3], 1, 12)?,
+            days_of_week,
+            days_restricted: !fields[2].starts_with('*') && !fields[4].starts_with('*'),
+        };
+        if cron.next_after(Utc::now()).is_none() {
+            return Err(anyhow!("Cron expression never matches: {:?}", expression));
+        }
+        Ok(cron)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.expression
+    }
+
+    /// The first minute strictly after `after` that matches the expression.
+    pub fn next_after(&self, after: DateTime<Utc>) -> Option<DateTime<Utc>> {
+        let mut time = after.with_second(0)?.with_nanosecond(0)? + Duration::minutes(1);
+        let give_up_at = time + Duration::days(MAX_DAYS_BETWEEN_MATCHES);
+        while time < give_up_at {
+            if !self.months[time.month() as usize] {
+                let (year, month) = if time.month() == 12 {
+                    (time.year() + 1, 1)
+                } else {
+                    (time.year(), time.month() + 1)
+                };
+                time = Utc.ymd(year, month, 1).and_hms(0, 0, 0);
+            } else if !self.matches_day(&time) {
+                time = (time.date() + Duration::days(1)).and_hms(0, 0, 0);
+            } else if !self.hours[time.hour() as usize] {
+                time = time.with_minute(0)? + Duration::hours(1);
+            } else if !self.minutes[time.minute() as usize] {
+                time = time + Duration::minutes(1);
+            } else {
+                return Some(time);
+            }
+        }
+        None
+    }
+
+    fn matches_day(&self, time: &DateTime<Utc>) -> bool {
+        let day_of_month = self.days_of_month[time.day() as usize];
+        let day_of_week = self.days_of_week[time.weekday().num_days_from_sunday() as usize];
+        if self.days_restricted {
+            day_of_month || day_of_week
+        } else {
+            day_of_month && day_of_week
+        }
+    }
+}
+
+/// The values allowed by a field, indexed by value.
+fn parse_field(field: &str, min: u32, max: u32) -> Result<Vec<bool>, Error> {
+    let invalid = || anyhow!("Invalid cron field {:?}", field);
+    let mut allowed = vec![false; max as usize + 1];
+    for part in field.split(',') {
+        let mut range_and_step = part.splitn(2, '/');
+        let range = range_and_step.next().unwrap_or_default();
+        let step = match range_and_step.next() {
+            Some(step) => step
+                .parse::<usize>()
+                .ok()
+                .filter(|step| *step > 0)
+                .ok_or_else(invalid)?,
+            None => 1,
+        };
+        let (start, end) = if range == "*" {
+            (min, max)
+        } else {
+            let mut bounds = range.splitn(2, '-');
+            let start = bounds
+                .next()
+                .and_then(|start| start.parse::<u32>().ok())
+                .ok_or_else(invalid)?;
+            match bounds.next() {
+                Some(end) => (start, end.parse::<u32>().map_err(|_| invalid())?),
+                // `5/15` starts at 5 and goes on every 15
+                None if step > 1 => (start, max),
+                None => (start, start),
+            }
+        };
+        if start < min || end > max || start > end {
+            return Err(invalid());
+        }
+        for value in (start..=end).step_by(step) {
+            allowed[value as usize] = true;
+        }
+    }
+    Ok(allowed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(year: i32, month: u32, day: u32, hour: u32, minute: u32) -> DateTime<Utc> {
+        Utc.ymd(year, month, day).and_hms(hour, minute, 0)
+    }
+
+    #[test]
+    fn parse_rejects_invalid_expressions() {
+        assert!(Cron::parse("0 3 * *").is_err());
+        assert!(Cron::parse("60 * * * *").is_err());
+        assert!(Cron::parse("* 5-2 * * *").is_err());
+        assert!(Cron::parse("*/0 * * * *").is_err());
+        assert!(Cron::parse("a * * * *").is_err());
+        assert!(Cron::parse("0 0 30 2 *").is_err());
+        assert_eq!(Cron::parse(" 0  3 * * * ").unwrap().as_str(), "0 3 * * *");
+    }
+
+    #[test]
+    fn next_after_works() {
+        let daily = Cron::parse("0 3 * * *").unwrap();
+        assert_eq!(
+            daily.next_after(at(2022, 11, 23, 2, 59)),
+            Some(at(2022, 11, 23, 3, 0))
+        );
+        assert_eq!(
+            daily.next_after(at(2022, 11, 23, 3, 0)),
+            Some(at(2022, 11, 24, 3, 0))
+        );
+        assert_eq!(
+            daily.next_after(at(2022, 12, 31, 4, 0)),
+            Some(at(2023, 1, 1, 3, 0))
+        );
+
+        let quarterly = Cron::parse("*/15 9-17 * * 1-5").unwrap();
+        // Friday evening to Monday morning
+        assert_eq!(
+            quarterly.next_after(at(2022, 11, 25, 17, 45)),
+            Some(at(2022, 11, 28, 9, 0))
+        );
+        assert_eq!(
+            quarterly.next_after(at(2022, 11, 28, 9, 7)),
+            Some(at(2022, 11, 28, 9, 15))
+        );
+    }
+
+    #[test]
+    fn next_after_matches_either_restricted_day() {
+        // The 1st of the month or a Sunday
+        let cron = Cron::parse("0 0 1 * 7").unwrap();
+        assert_eq!(
+            cron.next_after(at(2022, 11, 23, 0, 0)),
+            Some(at(2022, 11, 27, 0, 0))
+        );
+        assert_eq!(
+            cron.next_after(at(2022, 11, 27, 0, 0)),
+            Some(at(2022, 12, 1, 0, 0))
+        );
+    }
+}
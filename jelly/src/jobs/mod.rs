@@ -5,6 +5,11 @@ use tera::Tera;
 use crate::DieselPgPool;
 
 pub use background_jobs::{Job, WorkerConfig};
+pub use storage::RegisterJob;
+
+pub mod cron;
+pub mod schedule;
+pub mod storage;
 
 pub const DEFAULT_QUEUE: &'static str = "default";
 
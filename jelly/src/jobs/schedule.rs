@@ -0,0 +1,249 @@
+//! Recurring jobs, registered with `Server::register_schedule`.
+//!
+//! Every process polls the `jelly_schedules` table, which the app has to create with
+//! a migration. A due schedule is claimed by a single process, which holds a lease on
+//! its row while the job runs, so that several web processes never run it twice.
+
+use std::collections::HashSet;
+use std::future::Future;
+use std::panic::AssertUnwindSafe;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::thread;
+
+use actix_web::rt::time::delay_for;
+use anyhow::Error;
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use futures::FutureExt;
+use serde::Serialize;
+
+use super::cron::Cron;
+use super::{Job, JobState};
+use crate::DieselPgPool;
+
+table! {
+    jelly_schedules (name) {
+        name -> Text,
+        cron -> Text,
+        next_run_at -> Timestamptz,
+        last_started_at -> Nullable<Timestamptz>,
+        last_finished_at -> Nullable<Timestamptz>,
+        last_error -> Nullable<Text>,
+        locked_until -> Nullable<Timestamptz>,
+    }
+}
+
+/// How often every process looks for due schedules.
+const POLL_INTERVAL_SECONDS: u64 = 30;
+/// A claimed schedule stays locked this long, and the lease is renewed on every poll
+/// while its job runs. The lock of a process that died is released when it runs out.
+const LEASE_SECONDS: i64 = 300;
+
+type JobRunner =
+    Box<dyn Fn(JobState) -> Pin<Box<dyn Future<Output = Result<(), Error>> + Send>> + Send + Sync>;
+
+/// A job to run whenever its cron expression matches, named after the job.
+pub struct Schedule {
+    pub name: &'static str,
+    pub cron: Cron,
+    runner: JobRunner,
+}
+
+impl Schedule {
+    pub fn new<J, F>(cron: Cron, factory: F) -> Self
+    where
+        J: Job<State = JobState>,
+        J::Future: 'static,
+        F: Fn() -> J + Send + Sync + 'static,
+    {
+        Schedule {
+            name: J::NAME,
+            cron,
+            runner: Box::new(move |state| Box::pin(factory().run(state))),
+        }
+    }
+
+    fn next_run_after(&self, after: DateTime<Utc>) -> DateTime<Utc> {
+        self.cron
+            .next_after(after)
+            .expect("Cron expressions are checked to match when parsed")
+    }
+}
+
+/// The last and next run of a schedule.
+#[derive(Clone, Debug, Queryable, Serialize)]
+pub struct ScheduleRecord {
+    pub name: String,
+    pub cron: String,
+    pub next_run_at: DateTime<Utc>,
+    pub last_started_at: Option<DateTime<Utc>>,
+    pub last_finished_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub locked_until: Option<DateTime<Utc>>,
+}
+
+impl ScheduleRecord {
+    pub fn all(pool: &DieselPgPool) -> crate::Result<Vec<Self>> {
+        let conn = pool.get()?;
+        Ok(jelly_schedules::table
+            .order_by(jelly_schedules::name.asc())
+            .load::<Self>(&conn)?)
+    }
+
+    /// Stores a schedule, and computes its next run again when its expression changed.
+    pub fn register(schedule: &Schedule, conn: &PgConnection) -> QueryResult<()> {
+        let current_cron = jelly_schedules::table
+            .find(schedule.name)
+            .select(jelly_schedules::cron)
+            .first::<String>(conn)
+            .optional()?;
+        if current_cron.as_deref() == Some(schedule.cron.as_str()) {
+            return Ok(());
+        }
+        let next_run_at = schedule.next_run_after(Utc::now());
+        diesel::insert_into(jelly_schedules::table)
+            .values((
+                jelly_schedules::name.eq(schedule.name),
+                jelly_schedules::cron.eq(schedule.cron.as_str()),
+                jelly_schedules::next_run_at.eq(next_run_at),
+            ))
+            .on_conflict(jelly_schedules::name)
+            .do_update()
+            .set((
+                jelly_schedules::cron.eq(schedule.cron.as_str()),
+                jelly_schedules::next_run_at.eq(next_run_at),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Locks a due schedule that no other process is running, and moves it to its next run.
+    /// Returns whether this process got to run it.
+    pub fn claim(schedule: &Schedule, conn: &PgConnection) -> QueryResult<bool> {
+        let now = Utc::now();
+        let claimed = diesel::update(
+            jelly_schedules::table
+                .find(schedule.name)
+                .filter(jelly_schedules::next_run_at.le(now))
+                .filter(
+                    jelly_schedules::locked_until
+                        .is_null()
+                        .or(jelly_schedules::locked_until.lt(now)),
+                ),
+        )
+        .set((
+            jelly_schedules::next_run_at.eq(schedule.next_run_after(now)),
+            jelly_schedules::last_started_at.eq(now),
+            jelly_schedules::locked_until.eq(now + Duration::seconds(LEASE_SECONDS)),
+        ))
+        .execute(conn)?;
+        Ok(claimed == 1)
+    }
+
+    fn renew_leases(names: &[&str], conn: &PgConnection) -> QueryResult<()> {
+        diesel::update(jelly_schedules::table.filter(jelly_schedules::name.eq_any(names)))
+            .set(jelly_schedules::locked_until.eq(Utc::now() + Duration::seconds(LEASE_SECONDS)))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    fn finish(name: &str, error: Option<String>, conn: &PgConnection) -> QueryResult<()> {
+        diesel::update(jelly_schedules::table.find(name))
+            .set((
+                jelly_schedules::last_finished_at.eq(Utc::now()),
+                jelly_schedules::last_error.eq(error),
+                jelly_schedules::locked_until.eq(None::<DateTime<Utc>>),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+}
+
+/// Runs the schedules forever, every claimed job on its own thread.
+pub(crate) async fn run_schedules(schedules: Vec<Schedule>, state: JobState) {
+    match state.pool.get() {
+        Ok(conn) => {
+            for schedule in schedules.iter() {
+                if let Err(e) = ScheduleRecord::register(schedule, &conn) {
+                    error!("Cannot register schedule {}: {:?}", schedule.name, e);
+                }
+            }
+        }
+        Err(e) => error!("Cannot register schedules: {:?}", e),
+    }
+
+    let running: Arc<Mutex<HashSet<&'static str>>> = Arc::new(Mutex::new(HashSet::new()));
+    loop {
+        match state.pool.get() {
+            Ok(conn) => {
+                let running_names: Vec<&str> = running.lock().unwrap().iter().copied().collect();
+                if let Err(e) = ScheduleRecord::renew_leases(&running_names, &conn) {
+                    error!("Cannot renew the leases of running schedules: {:?}", e);
+                }
+                for schedule in schedules.iter() {
+                    if running.lock().unwrap().contains(schedule.name) {
+                        continue;
+                    }
+                    match ScheduleRecord::claim(schedule, &conn) {
+                        Ok(true) => {
+                            running.lock().unwrap().insert(schedule.name);
+                            spawn_job(schedule, state.clone(), running.clone());
+                        }
+                        Ok(false) => {}
+                        Err(e) => error!("Cannot claim schedule {}: {:?}", schedule.name, e),
+                    }
+                }
+            }
+            Err(e) => error!("Cannot check schedules: {:?}", e),
+        }
+        delay_for(std::time::Duration::from_secs(POLL_INTERVAL_SECONDS)).await;
+    }
+}
+
+/// Runs the job on a thread of its own, so that a job blocking on synchronous work never
+/// holds up the poll loop renewing the leases of the running schedules.
+fn spawn_job(schedule: &Schedule, state: JobState, running: Arc<Mutex<HashSet<&'static str>>>) {
+    let name = schedule.name;
+    let pool = state.pool.clone();
+    let job = (schedule.runner)(state);
+    let (thread_pool, thread_running) = (pool.clone(), running.clone());
+    let spawned = thread::Builder::new()
+        .name(format!("schedule-{}", name))
+        .spawn(move || {
+            info!("Running scheduled job {}", name);
+            // A panicking job must not keep its schedule locked
+            let error =
+                match actix_rt::System::new(name).block_on(AssertUnwindSafe(job).catch_unwind()) {
+                    Ok(Ok(())) => None,
+                    Ok(Err(e)) => Some(format!("{:?}", e)),
+                    Err(_) => Some("The job panicked".to_string()),
+                };
+            finish_job(name, error, &thread_pool, &thread_running);
+        });
+    if let Err(e) = spawned {
+        let error = format!("Cannot start a thread for the job: {:?}", e);
+        finish_job(name, Some(error), &pool, &running);
+    }
+}
+
+fn finish_job(
+    name: &'static str,
+    error: Option<String>,
+    pool: &DieselPgPool,
+    running: &Mutex<HashSet<&'static str>>,
+) {
+    match &error {
+        Some(e) => error!("Scheduled job {} failed: {}", name, e),
+        None => info!("Finished scheduled job {}", name),
+    }
+    match pool.get() {
+        Ok(conn) => {
+            if let Err(e) = ScheduleRecord::finish(name, error, &conn) {
+                error!("Cannot record the run of schedule {}: {:?}", name, e);
+            }
+        }
+        Err(e) => error!("Cannot record the run of schedule {}: {:?}", name, e),
+    }
+    running.lock().unwrap().remove(name);
+}
@@ -0,0 +1,480 @@
+//! A `background_jobs` storage backed by Postgres, so queued jobs survive restarts and
+//! deploys and are shared by every worker and process.
+//!
+//! Jobs are stored in the `jelly_jobs` table, which the app has to create with a
+//! migration. A failed job is queued again with an exponential backoff until it has
+//! been attempted `JOBS_MAX_ATTEMPTS` times, then it is kept as `dead` until it is
+//! retried by hand.
+//!
+//! `background_jobs` only tells the storage whether a job failed, so jobs registered
+//! with `RegisterJob::register_job` keep the error of their last failure for it.
+
+use std::collections::HashMap;
+use std::env;
+use std::error::Error as StdError;
+use std::fmt;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+
+use anyhow::Error;
+use background_jobs::dev::{JobInfo, ReturnJobInfo, Storage};
+use background_jobs::{Stats, WorkerConfig};
+use chrono::{DateTime, Duration, Utc};
+use diesel::prelude::*;
+use diesel::r2d2::PoolError;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use super::{Job, JobState};
+use crate::DieselPgPool;
+
+table! {
+    jelly_jobs (id) {
+        id -> Uuid,
+        queue -> Text,
+        name -> Text,
+        info -> Jsonb,
+        status -> Text,
+        attempts -> Int4,
+        run_at -> Timestamptz,
+        started_at -> Nullable<Timestamptz>,
+        runner_id -> Nullable<Uuid>,
+        last_error -> Nullable<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+pub const JOB_STATUS_PENDING: &str = "pending";
+pub const JOB_STATUS_RUNNING: &str = "running";
+pub const JOB_STATUS_DEAD: &str = "dead";
+
+/// Used when `JOBS_MAX_ATTEMPTS` is not set.
+const DEFAULT_MAX_ATTEMPTS: i32 = 5;
+/// Used when `JOBS_RETRY_BACKOFF_SECONDS` is not set. Doubled after every attempt.
+const DEFAULT_BACKOFF_SECONDS: i64 = 30;
+/// A job still running after this long belonged to a process that died, and is
+/// picked up again.
+const ABANDONED_AFTER_MINUTES: i64 = 30;
+
+#[derive(Debug)]
+pub enum StorageError {
+    Pool(PoolError),
+    Database(diesel::result::Error),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for StorageError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            StorageError::Pool(e) => write!(f, "Cannot get a database connection: {}", e),
+            StorageError::Database(e) => write!(f, "Job storage query failed: {}", e),
+            StorageError::Json(e) => write!(f, "Cannot (de)serialize job: {}", e),
+        }
+    }
+}
+
+impl StdError for StorageError {}
+
+impl From<PoolError> for StorageError {
+    fn from(e: PoolError) -> Self {
+        StorageError::Pool(e)
+    }
+}
+
+impl From<diesel::result::Error> for StorageError {
+    fn from(e: diesel::result::Error) -> Self {
+        StorageError::Database(e)
+    }
+}
+
+impl From<serde_json::Error> for StorageError {
+    fn from(e: serde_json::Error) -> Self {
+        StorageError::Json(e)
+    }
+}
+
+type StorageResult<T> = Result<T, StorageError>;
+
+lazy_static! {
+    /// The errors of the failed runs not yet returned to the storage, by job name and
+    /// arguments since the job does not know its id.
+    static ref FAILURES: Mutex<HashMap<String, String>> = Mutex::new(HashMap::new());
+}
+
+fn failure_key(name: &str, args: &serde_json::Value) -> String {
+    format!("{}:{}", name, args)
+}
+
+/// Registers jobs so that the error of their failures is recorded, use it instead of
+/// `WorkerConfig::register`.
+pub trait RegisterJob {
+    fn register_job<J>(self) -> Self
+    where
+        J: Job<State = JobState>,
+        J::Future: Send + 'static;
+}
+
+impl RegisterJob for WorkerConfig<JobState> {
+    fn register_job<J>(self) -> Self
+    where
+        J: Job<State = JobState>,
+        J::Future: Send + 'static,
+    {
+        self.register::<RecordedJob<J>>()
+    }
+}
+
+/// Runs as the job it wraps, which it (de)serializes as, and remembers why it failed.
+#[derive(Serialize, Deserialize)]
+#[serde(transparent)]
+struct RecordedJob<J>(J);
+
+impl<J> Job for RecordedJob<J>
+where
+    J: Job<State = JobState>,
+    J::Future: Send + 'static,
+{
+    type State = JobState;
+    type Future = Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
+
+    const NAME: &'static str = J::NAME;
+    const QUEUE: &'static str = J::QUEUE;
+
+    fn run(self, state: JobState) -> Self::Future {
+        let key = serde_json::to_value(&self.0)
+            .ok()
+            .map(|args| failure_key(J::NAME, &args));
+        let job = self.0.run(state);
+        Box::pin(async move {
+            let result = job.await;
+            if let (Err(e), Some(key)) = (&result, key) {
+                FAILURES.lock().unwrap().insert(key, format!("{:?}", e));
+            }
+            result
+        })
+    }
+}
+
+#[derive(Clone)]
+pub struct PgStorage {
+    pool: DieselPgPool,
+    max_attempts: i32,
+    backoff_seconds: i64,
+    // Only used by `QueueHandle::get_stats`, so they are not worth a table
+    stats: Arc<Mutex<Stats>>,
+}
+
+impl PgStorage {
+    pub fn new(pool: DieselPgPool) -> Self {
+        PgStorage {
+            pool,
+            max_attempts: env::var("JOBS_MAX_ATTEMPTS")
+                .ok()
+                .and_then(|attempts| attempts.parse::<i32>().ok())
+                .unwrap_or(DEFAULT_MAX_ATTEMPTS),
+            backoff_seconds: env::var("JOBS_RETRY_BACKOFF_SECONDS")
+                .ok()
+                .and_then(|seconds| seconds.parse::<i64>().ok())
+                .unwrap_or(DEFAULT_BACKOFF_SECONDS),
+            stats: Arc::new(Mutex::new(Stats::default())),
+        }
+    }
+
+    /// Marks the next due job of the queue as running and returns its id and info. Due
+    /// jobs locked by another worker are skipped, and jobs abandoned by a dead process
+    /// are either picked up again or buried once they used up their attempts.
+    pub fn claim_next_job(&self, queue: &str) -> StorageResult<Option<(Uuid, serde_json::Value)>> {
+        let conn = self.pool.get()?;
+        let now = Utc::now();
+        let abandoned_before = now - Duration::minutes(ABANDONED_AFTER_MINUTES);
+        let max_attempts = self.max_attempts;
+        conn.transaction(|| -> StorageResult<Option<(Uuid, serde_json::Value)>> {
+            diesel::update(
+                jelly_jobs::table
+                    .filter(jelly_jobs::status.eq(JOB_STATUS_RUNNING))
+                    .filter(jelly_jobs::started_at.lt(abandoned_before))
+                    .filter(jelly_jobs::attempts.ge(max_attempts)),
+            )
+            .set((
+                jelly_jobs::status.eq(JOB_STATUS_DEAD),
+                jelly_jobs::runner_id.eq(None::<Uuid>),
+                jelly_jobs::last_error.eq("Abandoned while running"),
+                jelly_jobs::updated_at.eq(now),
+            ))
+            .execute(&conn)?;
+            let next = jelly_jobs::table
+                .filter(jelly_jobs::queue.eq(queue))
+                .filter(
+                    jelly_jobs::status
+                        .eq(JOB_STATUS_PENDING)
+                        .and(jelly_jobs::run_at.le(now))
+                        .or(jelly_jobs::status
+                            .eq(JOB_STATUS_RUNNING)
+                            .and(jelly_jobs::started_at.lt(abandoned_before))),
+                )
+                .order_by(jelly_jobs::run_at.asc())
+                .select((jelly_jobs::id, jelly_jobs::info))
+                .for_update()
+                .skip_locked()
+                .first::<(Uuid, serde_json::Value)>(&conn)
+                .optional()?;
+            let (id, info) = match next {
+                Some(next) => next,
+                None => return Ok(None),
+            };
+            diesel::update(jelly_jobs::table.find(id))
+                .set((
+                    jelly_jobs::status.eq(JOB_STATUS_RUNNING),
+                    jelly_jobs::attempts.eq(jelly_jobs::attempts + 1),
+                    jelly_jobs::started_at.eq(now),
+                    jelly_jobs::updated_at.eq(now),
+                ))
+                .execute(&conn)?;
+            Ok(Some((id, info)))
+        })
+    }
+
+    /// Queues a failed job again after its backoff, or buries it once it used up
+    /// its attempts.
+    pub fn fail_job(&self, id: Uuid, error: &str) -> StorageResult<()> {
+        let conn = self.pool.get()?;
+        conn.transaction(|| -> StorageResult<()> {
+            let attempts = jelly_jobs::table
+                .find(id)
+                .select(jelly_jobs::attempts)
+                .for_update()
+                .first::<i32>(&conn)
+                .optional()?;
+            let attempts = match attempts {
+                Some(attempts) => attempts,
+                None => return Ok(()),
+            };
+            let now = Utc::now();
+            if attempts >= self.max_attempts {
+                warn!("Job {} failed {} times and is now dead", id, attempts);
+                diesel::update(jelly_jobs::table.find(id))
+                    .set((
+                        jelly_jobs::status.eq(JOB_STATUS_DEAD),
+                        jelly_jobs::runner_id.eq(None::<Uuid>),
+                        jelly_jobs::last_error.eq(error),
+                        jelly_jobs::updated_at.eq(now),
+                    ))
+                    .execute(&conn)?;
+            } else {
+                diesel::update(jelly_jobs::table.find(id))
+                    .set((
+                        jelly_jobs::status.eq(JOB_STATUS_PENDING),
+                        jelly_jobs::run_at.eq(now + backoff(self.backoff_seconds, attempts)),
+                        jelly_jobs::runner_id.eq(None::<Uuid>),
+                        jelly_jobs::last_error.eq(error),
+                        jelly_jobs::updated_at.eq(now),
+                    ))
+                    .execute(&conn)?;
+            }
+            Ok(())
+        })
+    }
+
+    /// The error the job recorded when it failed, if it is a `RecordedJob`.
+    fn take_failure(&self, id: Uuid) -> StorageResult<Option<String>> {
+        let info = match self.fetch_info(id)? {
+            Some(info) => info,
+            None => return Ok(None),
+        };
+        let name = info["name"].as_str().unwrap_or_default();
+        let key = failure_key(name, &info["args"]);
+        Ok(FAILURES.lock().unwrap().remove(&key))
+    }
+
+    fn fetch_info(&self, id: Uuid) -> StorageResult<Option<serde_json::Value>> {
+        let conn = self.pool.get()?;
+        Ok(jelly_jobs::table
+            .find(id)
+            .select(jelly_jobs::info)
+            .first::<serde_json::Value>(&conn)
+            .optional()?)
+    }
+}
+
+/// Delay before the next attempt of a job that failed `attempts` times.
+fn backoff(base_seconds: i64, attempts: i32) -> Duration {
+    let exponent = (attempts.max(1) - 1).min(16) as u32;
+    Duration::seconds(base_seconds * 2i64.pow(exponent))
+}
+
+#[async_trait::async_trait]
+impl Storage for PgStorage {
+    type Error = StorageError;
+
+    async fn generate_id(&self) -> StorageResult<Uuid> {
+        Ok(Uuid::new_v4())
+    }
+
+    async fn save_job(&self, job: JobInfo) -> StorageResult<()> {
+        let conn = self.pool.get()?;
+        let now = Utc::now();
+        let info = serde_json::to_value(&job)?;
+        diesel::insert_into(jelly_jobs::table)
+            .values((
+                jelly_jobs::id.eq(job.id()),
+                jelly_jobs::queue.eq(job.queue()),
+                jelly_jobs::name.eq(job.name()),
+                jelly_jobs::info.eq(&info),
+                jelly_jobs::status.eq(JOB_STATUS_PENDING),
+                jelly_jobs::run_at.eq(now),
+                jelly_jobs::created_at.eq(now),
+                jelly_jobs::updated_at.eq(now),
+            ))
+            .on_conflict(jelly_jobs::id)
+            .do_update()
+            .set((jelly_jobs::info.eq(&info), jelly_jobs::updated_at.eq(now)))
+            .execute(&conn)?;
+        Ok(())
+    }
+
+    async fn fetch_job(&self, id: Uuid) -> StorageResult<Option<JobInfo>> {
+        let info = self.fetch_info(id)?;
+        Ok(info.map(serde_json::from_value).transpose()?)
+    }
+
+    /// Claims the next due job of the queue, so that no other worker gets it.
+    async fn fetch_job_from_queue(&self, queue: &str) -> StorageResult<Option<JobInfo>> {
+        let info = self.claim_next_job(queue)?.map(|(_, info)| info);
+        Ok(info.map(serde_json::from_value).transpose()?)
+    }
+
+    async fn queue_job(&self, _queue: &str, id: Uuid) -> StorageResult<()> {
+        let conn = self.pool.get()?;
+        diesel::update(jelly_jobs::table.find(id))
+            .set((
+                jelly_jobs::status.eq(JOB_STATUS_PENDING),
+                jelly_jobs::runner_id.eq(None::<Uuid>),
+                jelly_jobs::updated_at.eq(Utc::now()),
+            ))
+            .execute(&conn)?;
+        Ok(())
+    }
+
+    async fn run_job(&self, id: Uuid, runner_id: Uuid) -> StorageResult<()> {
+        let conn = self.pool.get()?;
+        diesel::update(jelly_jobs::table.find(id))
+            .set((
+                jelly_jobs::runner_id.eq(runner_id),
+                jelly_jobs::updated_at.eq(Utc::now()),
+            ))
+            .execute(&conn)?;
+        Ok(())
+    }
+
+    async fn delete_job(&self, id: Uuid) -> StorageResult<()> {
+        let conn = self.pool.get()?;
+        diesel::delete(jelly_jobs::table.find(id)).execute(&conn)?;
+        Ok(())
+    }
+
+    async fn get_stats(&self) -> StorageResult<Stats> {
+        Ok(self.stats.lock().unwrap().clone())
+    }
+
+    async fn update_stats<F>(&self, f: F) -> StorageResult<()>
+    where
+        F: Fn(Stats) -> Stats + Send + 'static,
+    {
+        let mut stats = self.stats.lock().unwrap();
+        *stats = f(stats.clone());
+        Ok(())
+    }
+
+    /// Unlike the default, failed jobs are kept as dead instead of being deleted.
+    async fn return_job(&self, return_job_info: ReturnJobInfo) -> StorageResult<()> {
+        // The fields of `ReturnJobInfo` are private, but it serializes them
+        let value = serde_json::to_value(&return_job_info)?;
+        let id: Uuid = serde_json::from_value(value["id"].clone())?;
+        let result = match value["result"].as_str() {
+            Some("Success") => return self.delete_job(id).await,
+            Some(result) => result.to_string(),
+            None => value["result"].to_string(),
+        };
+        // Jobs that panicked or could not run did not record an error
+        let error = self.take_failure(id)?.unwrap_or(result);
+        self.fail_job(id, &error)
+    }
+}
+
+/// A dead job, as listed for admins.
+#[derive(Clone, Debug, Queryable, Serialize)]
+pub struct DeadJob {
+    pub id: Uuid,
+    pub queue: String,
+    pub name: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl DeadJob {
+    /// Dead jobs, most recently buried first, with the total count.
+    pub fn paginated(
+        page: i64,
+        per_page: i64,
+        pool: &DieselPgPool,
+    ) -> crate::Result<(Vec<Self>, i64)> {
+        let conn = pool.get()?;
+        let total_count = jelly_jobs::table
+            .filter(jelly_jobs::status.eq(JOB_STATUS_DEAD))
+            .count()
+            .get_result::<i64>(&conn)?;
+        let jobs = jelly_jobs::table
+            .filter(jelly_jobs::status.eq(JOB_STATUS_DEAD))
+            .order_by(jelly_jobs::updated_at.desc())
+            .select((
+                jelly_jobs::id,
+                jelly_jobs::queue,
+                jelly_jobs::name,
+                jelly_jobs::attempts,
+                jelly_jobs::last_error,
+                jelly_jobs::created_at,
+                jelly_jobs::updated_at,
+            ))
+            .offset((page - 1).max(0) * per_page)
+            .limit(per_page)
+            .load::<Self>(&conn)?;
+        Ok((jobs, total_count))
+    }
+
+    /// Queues a dead job again with a fresh set of attempts. Returns whether it was dead.
+    pub fn retry(id: Uuid, pool: &DieselPgPool) -> crate::Result<bool> {
+        let conn = pool.get()?;
+        let now = Utc::now();
+        let retried = diesel::update(
+            jelly_jobs::table
+                .find(id)
+                .filter(jelly_jobs::status.eq(JOB_STATUS_DEAD)),
+        )
+        .set((
+            jelly_jobs::status.eq(JOB_STATUS_PENDING),
+            jelly_jobs::attempts.eq(0),
+            jelly_jobs::run_at.eq(now),
+            jelly_jobs::updated_at.eq(now),
+        ))
+        .execute(&conn)?;
+        Ok(retried == 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_doubles_after_every_attempt() {
+        assert_eq!(backoff(30, 1), Duration::seconds(30));
+        assert_eq!(backoff(30, 2), Duration::seconds(60));
+        assert_eq!(backoff(30, 4), Duration::seconds(240));
+        assert_eq!(backoff(30, 100), backoff(30, 17));
+    }
+}
@@ -18,11 +18,14 @@ pub use futures;
 pub use serde;
 pub use serde_json;
 pub use tera;
-pub use diesel;
+pub use uuid;
 
 #[macro_use]
 pub extern crate log;
 
+#[macro_use]
+pub extern crate diesel;
+
 pub mod accounts;
 pub mod email;
 pub mod error;
@@ -2,14 +2,16 @@ use std::env;
 use std::sync::Arc;
 
 use crate::email::{Configurable, Email};
-use crate::jobs::{JobState, DEFAULT_QUEUE};
+use crate::jobs::cron::Cron;
+use crate::jobs::schedule::{self, Schedule};
+use crate::jobs::storage::PgStorage;
+use crate::jobs::{Job, JobState, DEFAULT_QUEUE};
 use crate::request::Render;
 use crate::{database, DieselPgPool};
 use actix_session::CookieSession;
 use actix_web::web::ServiceConfig;
 use actix_web::{dev, middleware, web, App, HttpResponse, HttpServer};
 use actix_web_middleware_redirect_scheme::RedirectSchemeBuilder;
-use background_jobs::memory_storage::Storage;
 use background_jobs::{create_server, WorkerConfig};
 use middleware::normalize::TrailingSlash;
 
@@ -19,6 +21,7 @@ pub struct Server {
     apps: Vec<Box<dyn Fn(&mut ServiceConfig) + Send + Sync + 'static>>,
     jobs:
         Vec<Box<dyn Fn(WorkerConfig<JobState>) -> WorkerConfig<JobState> + Send + Sync + 'static>>,
+    schedules: Vec<Schedule>,
 }
 
 impl Server {
@@ -27,6 +30,7 @@ impl Server {
         Self {
             apps: vec![],
             jobs: vec![],
+            schedules: vec![],
         }
     }
 
@@ -48,6 +52,21 @@ impl Server {
         self
     }
 
+    /// Registers a job to run whenever the cron expression matches, e.g. `"0 3 * * *"`
+    /// for every day at 3am UTC. `factory` builds the job for every run. Only one
+    /// process runs a schedule at a time, see `jelly::jobs::schedule`.
+    pub fn register_schedule<J, F>(mut self, cron: &str, factory: F) -> Self
+    where
+        J: Job<State = JobState>,
+        J::Future: 'static,
+        F: Fn() -> J + Send + Sync + 'static,
+    {
+        let cron = Cron::parse(cron)
+            .unwrap_or_else(|e| panic!("Invalid schedule for {}: {:?}", J::NAME, e));
+        self.schedules.push(Schedule::new(cron, factory));
+        self
+    }
+
     /// Consumes and then runs the server, with default settings that we
     /// generally want.
     pub async fn run(self) -> std::io::Result<(dev::Server, DieselPgPool)> {
@@ -76,6 +95,11 @@ impl Server {
         let apps = Arc::new(self.apps);
         let jobs = Arc::new(self.jobs);
 
+        if !self.schedules.is_empty() {
+            let state = JobState::new("JobState", pool.clone(), templates.clone());
+            actix_rt::spawn(schedule::run_schedules(self.schedules, state));
+        }
+
         let server = HttpServer::new(move || {
             // !production needs no domain set, because browsers.
             #[cfg(not(feature = "production"))]
@@ -134,7 +158,7 @@ impl Server {
                 app = app.configure(|c| handler(c));
             }
 
-            let storage = Storage::new();
+            let storage = PgStorage::new(pool.clone());
             let queue = create_server(storage);
             let state = JobState::new("JobState", pool.clone(), templates.clone());
             let mut worker_config = WorkerConfig::new(move || state.clone());
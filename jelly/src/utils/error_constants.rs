@@ -24,3 +24,84 @@ pub const MSG_SUCCESSFULLY_REMOVED_COLLABORATOR: &str = "Collaborator is removed
 pub const MSG_INVITATION_EXPIRED: &str = "Invitation expired.";
 pub const MSG_INVITATION_ALREADY_EXISTED: &str = "Invitation already sent.";
 pub const MSG_COLLABORATOR_ALREADY_EXISTED: &str = "Collaborator already existed.";
+pub const MSG_UNAUTHORIZED_TO_CHANGE_ROLE: &str =
+    "Sorry, only the owner can change the role of collaborators.";
+pub const MSG_SUCCESSFULLY_CHANGED_ROLE: &str = "Collaborator role is changed successfully.";
+pub const MSG_LAST_OWNER_OF_PACKAGE: &str = "Sorry, a package needs at least one owner.";
+pub const MSG_OWNER_APPROVAL_REQUESTED: &str =
+    "Another owner has to approve this action before it takes effect.";
+pub const MSG_OWNER_APPROVAL_ALREADY_REQUESTED: &str =
+    "This action is already waiting for the approval of another owner.";
+pub const MSG_OWNER_APPROVAL_NOT_FOUND: &str = "Approval request not found.";
+pub const MSG_UNAUTHORIZED_TO_APPROVE: &str =
+    "Sorry, only another owner of this package can approve this action.";
+pub const MSG_UNAUTHORIZED_TO_DISMISS_APPROVAL: &str =
+    "Sorry, only the owners of this package can dismiss this request.";
+pub const MSG_OWNER_APPROVAL_OUTDATED: &str =
+    "The owner who requested this action is not an owner anymore, the request is dismissed.";
+pub const MSG_SUCCESSFULLY_APPROVED_ACTION: &str = "Action is approved successfully.";
+pub const MSG_SUCCESSFULLY_DISMISSED_APPROVAL: &str = "Approval request is dismissed.";
+pub const MSG_LOGIN_REQUIRED_TO_CLAIM: &str = "Please log in to claim this package.";
+pub const MSG_PACKAGE_ALREADY_CLAIMED: &str = "This package already has owners.";
+pub const MSG_GITHUB_ACCOUNT_NOT_LINKED: &str =
+    "Please link your GitHub account in your profile settings first.";
+pub const MSG_GITHUB_SIGN_IN_REQUIRED_TO_CLAIM: &str =
+    "Please verify with GitHub first so that we can check your repository permissions.";
+pub const MSG_CLAIM_NOT_VERIFIED: &str =
+    "We could not verify that you own this repository. Check the verification and try again.";
+pub const MSG_SUCCESSFULLY_CLAIMED_PACKAGE: &str = "You are now the owner of this package.";
+pub const MSG_UNAUTHORIZED_TO_MANAGE_INVITATIONS: &str =
+    "Sorry, only the owner or collaborator can manage the invitations of this package.";
+pub const MSG_INVALID_EXPIRATION_DAYS: &str = "Invitations can stay valid for 1 to 30 days.";
+pub const MSG_INVALID_BULK_INVITATION: &str = "Invite between 1 and 20 people at once.";
+pub const MSG_SUCCESSFULLY_RESENT_INVITATION: &str = "Invitation is sent again.";
+pub const MSG_SUCCESSFULLY_REVOKED_INVITATION: &str = "Invitation is revoked.";
+
+pub const MSG_UNAUTHORIZED_TO_DEPRECATE_PACKAGE: &str =
+    "Sorry, only the owner can deprecate this package.";
+pub const MSG_DEPRECATION_REASON_REQUIRED: &str = "Please explain why the package is deprecated.";
+pub const MSG_SUCCESSOR_NOT_FOUND: &str = "Successor package not found.";
+pub const MSG_SUCCESSFULLY_DEPRECATED_PACKAGE: &str = "Package is deprecated successfully.";
+pub const MSG_SUCCESSFULLY_UNDEPRECATED_PACKAGE: &str = "Package is no longer deprecated.";
+pub const MSG_UNAUTHORIZED_TO_RENAME_PACKAGE: &str =
+    "Sorry, only the owner can rename this package.";
+pub const MSG_INVALID_PACKAGE_SLUG: &str =
+    "Package URLs can only contain lowercase letters, digits and dashes.";
+pub const MSG_PACKAGE_SLUG_TAKEN: &str = "This URL is already used by another package.";
+pub const MSG_SUCCESSFULLY_RENAMED_PACKAGE: &str = "Package URL is changed successfully.";
+pub const MSG_UNAUTHORIZED_TO_ARCHIVE_PACKAGE: &str =
+    "Sorry, only the owner can archive this package.";
+pub const MSG_UNAUTHORIZED_TO_DELETE_PACKAGE: &str =
+    "Sorry, only the owner can delete this package.";
+pub const MSG_PACKAGE_HAS_DEPENDENTS: &str =
+    "Packages depending on this package would break if it was deleted. You can archive it instead.";
+pub const MSG_PACKAGE_HAS_TOO_MANY_DOWNLOADS: &str =
+    "This package is downloaded too often to be deleted. You can archive it instead.";
+pub const MSG_SUCCESSFULLY_ARCHIVED_PACKAGE: &str = "Package is archived successfully.";
+pub const MSG_SUCCESSFULLY_UNARCHIVED_PACKAGE: &str = "Package is unarchived successfully.";
+pub const MSG_SUCCESSFULLY_SCHEDULED_PACKAGE_DELETION: &str = "Package is scheduled for deletion.";
+pub const MSG_SUCCESSFULLY_RESTORED_PACKAGE: &str = "Package is restored successfully.";
+pub const MSG_UNAUTHORIZED_TO_TRANSFER_TO_ORGANIZATION: &str =
+    "Sorry, only the owner can transfer this package to an organization they administer.";
+pub const MSG_PACKAGE_ALREADY_OWNED_BY_ORGANIZATION: &str =
+    "This package is already owned by an organization.";
+pub const MSG_SUCCESSFULLY_TRANSFERRED_TO_ORGANIZATION: &str =
+    "Package is transferred to the organization successfully.";
+
+pub const MSG_ORGANIZATION_NOT_FOUND: &str = "Organization not found.";
+pub const MSG_INVALID_ORGANIZATION_NAME: &str =
+    "Organization names must contain at least one letter or digit.";
+pub const MSG_ORGANIZATION_SLUG_TAKEN: &str = "This name is already used by another organization.";
+pub const MSG_UNAUTHORIZED_TO_MANAGE_ORGANIZATION: &str =
+    "Sorry, only the admins of this organization can manage its members.";
+pub const MSG_MEMBER_ALREADY_EXISTED: &str =
+    "This account is already a member of the organization.";
+pub const MSG_MEMBER_NOT_FOUND: &str = "Member not found.";
+pub const MSG_LAST_ORGANIZATION_ADMIN: &str = "An organization needs at least one admin.";
+pub const MSG_SUCCESSFULLY_CREATED_ORGANIZATION: &str = "Organization is created successfully.";
+pub const MSG_SUCCESSFULLY_INVITED_MEMBER: &str =
+    "Invitation is sent, the account becomes a member once it accepts.";
+pub const MSG_SUCCESSFULLY_UPDATED_MEMBER: &str = "Member role is updated successfully.";
+pub const MSG_SUCCESSFULLY_REMOVED_MEMBER: &str = "Member is removed successfully.";
+pub const MSG_SUCCESSFULLY_JOINED_ORGANIZATION: &str = "You joined the organization.";
+pub const MSG_SUCCESSFULLY_DECLINED_INVITATION: &str = "Invitation is declined.";
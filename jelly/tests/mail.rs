@@ -12,6 +12,7 @@ use log::debug;
 mod send_via_sendgrid_should {
     use super::*;
     use jelly::email::sendgrid::Email;
+    use jelly::email::Rejected;
     use test_log::test; // Automatically log tests
 
     #[test]
@@ -116,6 +117,40 @@ mod send_via_sendgrid_should {
         debug!("{}", resstr);
         assert!(resstr.contains("Sending mail to a@exemple.com,b@example.com via sendgrid failed. API call returns code 401 : Unauthorized"));
         assert!(resstr.contains("Permission denied, wrong credentials"));
+        assert!(!res.unwrap_err().is::<Rejected>());
+        Ok(())
+    }
+
+    #[test]
+    fn reject_invalid_recipients() -> Result<()> {
+        let server = MockServer::start();
+        let mut templates = Tera::default();
+        templates.add_raw_template("t.html", "test")?;
+        templates.add_raw_template("t.txt", "test")?;
+
+        std::env::set_var("SENDGRID_API_KEY", "sapikey");
+        std::env::set_var("POSTMARK_MESSAGE_STREAM", "default");
+        std::env::set_var("EMAIL_DEFAULT_FROM", "owner@example.com");
+
+        let server_mock = server.mock(|expect, resp_with| {
+            expect.method(POST).path("/v3/mail/send");
+            resp_with
+                .status(400)
+                .header("content-type", "text/json")
+                .body(r#"{"errors":[{"message":"Does not contain a valid address.","field":"personalizations.0.to.0.email","help":null}]}"#);
+        });
+
+        let email = Email::new(
+            "t",
+            &vec!["a@exemple".to_string()],
+            "subject line",
+            Context::new(),
+            Arc::new(RwLock::new(templates)),
+        )?;
+        let res = email.send_via_sendgrid(&server.url(""));
+
+        server_mock.assert();
+        assert!(res.unwrap_err().is::<Rejected>());
         Ok(())
     }
 }
@@ -124,6 +159,7 @@ mod send_via_sendgrid_should {
 mod send_via_postmark_should {
     use super::*;
     use jelly::email::postmark::Email;
+    use jelly::email::Rejected;
 
     #[test]
     fn send_expected_json() -> Result<()> {
@@ -224,6 +260,70 @@ mod send_via_postmark_should {
         debug!("{}", resstr);
         assert!(resstr.contains("Sending mail to a@exemple.com,b@example.com via postmark failed. API call returns code 401 : Unauthorized"));
         assert!(resstr.contains("Permission denied, wrong credentials"));
+        assert!(!res.unwrap_err().is::<Rejected>());
+        Ok(())
+    }
+
+    #[test]
+    fn treat_server_errors_as_transient() -> Result<()> {
+        let server = MockServer::start();
+        let mut templates = Tera::default();
+        templates.add_raw_template("t.html", "test")?;
+        templates.add_raw_template("t.txt", "test")?;
+
+        std::env::set_var("POSTMARK_API_KEY", "papikey");
+        std::env::set_var("POSTMARK_MESSAGE_STREAM", "default");
+        std::env::set_var("EMAIL_DEFAULT_FROM", "owner@example.com");
+
+        let server_mock = server.mock(|expect, resp_with| {
+            expect.method(POST).path("/email");
+            resp_with.status(503).body("Service Unavailable");
+        });
+
+        let email = Email::new(
+            "t",
+            &vec!["a@exemple.com".to_string()],
+            "subject line",
+            Context::new(),
+            Arc::new(RwLock::new(templates)),
+        )?;
+        let res = email.send_via_postmark(&server.url(""));
+
+        server_mock.assert();
+        assert!(!res.unwrap_err().is::<Rejected>());
+        Ok(())
+    }
+
+    #[test]
+    fn reject_inactive_recipients() -> Result<()> {
+        let server = MockServer::start();
+        let mut templates = Tera::default();
+        templates.add_raw_template("t.html", "test")?;
+        templates.add_raw_template("t.txt", "test")?;
+
+        std::env::set_var("POSTMARK_API_KEY", "papikey");
+        std::env::set_var("POSTMARK_MESSAGE_STREAM", "default");
+        std::env::set_var("EMAIL_DEFAULT_FROM", "owner@example.com");
+
+        let server_mock = server.mock(|expect, resp_with| {
+            expect.method(POST).path("/email");
+            resp_with
+                .status(422)
+                .header("content-type", "text/json")
+                .body(r#"{"ErrorCode":406,"Message":"You tried to send to recipient(s) that have been marked as inactive."}"#);
+        });
+
+        let email = Email::new(
+            "t",
+            &vec!["a@exemple.com".to_string()],
+            "subject line",
+            Context::new(),
+            Arc::new(RwLock::new(templates)),
+        )?;
+        let res = email.send_via_postmark(&server.url(""));
+
+        server_mock.assert();
+        assert!(res.unwrap_err().is::<Rejected>());
         Ok(())
     }
 }
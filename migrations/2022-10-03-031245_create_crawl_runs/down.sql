@@ -0,0 +1,5 @@
+DROP TABLE crawl_cursors;
+
+DROP TABLE crawl_errors;
+
+DROP TABLE crawl_runs;
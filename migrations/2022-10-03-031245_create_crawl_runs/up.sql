@@ -0,0 +1,28 @@
+CREATE TABLE crawl_runs (
+    id SERIAL PRIMARY KEY,
+    mode TEXT NOT NULL,
+    status TEXT NOT NULL DEFAULT 'running',
+    found_count INTEGER NOT NULL DEFAULT 0,
+    new_count INTEGER NOT NULL DEFAULT 0,
+    updated_count INTEGER NOT NULL DEFAULT 0,
+    failed_count INTEGER NOT NULL DEFAULT 0,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    finished_at TIMESTAMPTZ
+);
+
+CREATE TABLE crawl_errors (
+    id SERIAL PRIMARY KEY,
+    crawl_run_id INTEGER NOT NULL REFERENCES crawl_runs (id) ON DELETE CASCADE,
+    url TEXT NOT NULL,
+    message TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX crawl_errors_crawl_run_id_index ON crawl_errors (crawl_run_id);
+
+CREATE TABLE crawl_cursors (
+    name TEXT PRIMARY KEY,
+    next_page INTEGER NOT NULL DEFAULT 1,
+    completed_at TIMESTAMPTZ,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
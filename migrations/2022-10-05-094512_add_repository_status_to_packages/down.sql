@@ -0,0 +1,8 @@
+DROP TRIGGER set_updated_at ON packages;
+SELECT diesel_manage_updated_at('packages');
+DROP FUNCTION packages_set_updated_at();
+
+DROP INDEX packages_metadata_refreshed_at_index;
+
+ALTER TABLE packages DROP COLUMN metadata_refreshed_at;
+ALTER TABLE packages DROP COLUMN repository_status;
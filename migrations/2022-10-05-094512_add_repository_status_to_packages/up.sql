@@ -0,0 +1,29 @@
+ALTER TABLE packages ADD COLUMN repository_status TEXT NOT NULL DEFAULT 'active';
+ALTER TABLE packages ADD COLUMN metadata_refreshed_at TIMESTAMPTZ;
+
+CREATE INDEX packages_metadata_refreshed_at_index ON packages (metadata_refreshed_at NULLS FIRST);
+
+-- The metadata refresh, and later other background jobs and status changes, also write to
+-- packages. Only changes to the other columns move a package up the 'Recently Updated' sort
+-- order, the columns to ignore are the trigger arguments.
+CREATE FUNCTION packages_set_updated_at() RETURNS trigger AS $$
+BEGIN
+    IF (
+        NEW.updated_at IS NOT DISTINCT FROM OLD.updated_at AND
+        to_jsonb(NEW) - TG_ARGV IS DISTINCT FROM to_jsonb(OLD) - TG_ARGV
+    ) THEN
+        NEW.updated_at := current_timestamp;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER set_updated_at ON packages;
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON packages
+FOR EACH ROW EXECUTE PROCEDURE packages_set_updated_at(
+    'tsv',
+    'stars_count',
+    'forks_count',
+    'repository_status',
+    'metadata_refreshed_at'
+);
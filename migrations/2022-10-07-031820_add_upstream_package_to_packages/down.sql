@@ -0,0 +1,15 @@
+DROP TRIGGER set_updated_at ON packages;
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON packages
+FOR EACH ROW EXECUTE PROCEDURE packages_set_updated_at(
+    'tsv',
+    'stars_count',
+    'forks_count',
+    'repository_status',
+    'metadata_refreshed_at'
+);
+
+DROP INDEX package_versions_manifest_hash_index;
+DROP INDEX packages_upstream_package_id_index;
+
+ALTER TABLE package_versions DROP COLUMN manifest_hash;
+ALTER TABLE packages DROP COLUMN upstream_package_id;
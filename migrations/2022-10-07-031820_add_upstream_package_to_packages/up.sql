@@ -0,0 +1,17 @@
+ALTER TABLE packages ADD COLUMN upstream_package_id INTEGER REFERENCES packages (id) ON DELETE SET NULL;
+ALTER TABLE package_versions ADD COLUMN manifest_hash TEXT;
+
+CREATE INDEX packages_upstream_package_id_index ON packages (upstream_package_id);
+CREATE INDEX package_versions_manifest_hash_index ON package_versions (manifest_hash);
+
+-- linking a fork to its upstream package is no update of the package
+DROP TRIGGER set_updated_at ON packages;
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON packages
+FOR EACH ROW EXECUTE PROCEDURE packages_set_updated_at(
+    'tsv',
+    'stars_count',
+    'forks_count',
+    'repository_status',
+    'metadata_refreshed_at',
+    'upstream_package_id'
+);
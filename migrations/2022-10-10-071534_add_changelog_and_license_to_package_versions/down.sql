@@ -0,0 +1,2 @@
+ALTER TABLE package_versions DROP COLUMN license_content;
+ALTER TABLE package_versions DROP COLUMN changelog_content;
@@ -0,0 +1,2 @@
+ALTER TABLE package_versions ADD COLUMN changelog_content TEXT;
+ALTER TABLE package_versions ADD COLUMN license_content TEXT;
@@ -0,0 +1 @@
+ALTER TABLE package_versions DROP COLUMN readme_html;
@@ -0,0 +1 @@
+ALTER TABLE package_versions ADD COLUMN readme_html TEXT;
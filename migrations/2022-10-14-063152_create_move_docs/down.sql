@@ -0,0 +1,7 @@
+ALTER TABLE package_versions DROP COLUMN docs_generated_at;
+
+DROP TABLE move_functions;
+
+DROP TABLE move_structs;
+
+DROP TABLE move_modules;
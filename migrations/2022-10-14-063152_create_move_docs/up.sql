@@ -0,0 +1,37 @@
+CREATE TABLE move_modules (
+    id SERIAL PRIMARY KEY,
+    package_version_id INTEGER NOT NULL REFERENCES package_versions (id) ON DELETE CASCADE,
+    address TEXT NOT NULL,
+    name TEXT NOT NULL,
+    doc TEXT NOT NULL DEFAULT '',
+    file_path TEXT NOT NULL,
+    used_modules TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT now()
+);
+
+CREATE INDEX move_modules_package_version_id_index ON move_modules (package_version_id);
+CREATE INDEX move_modules_name_address_index ON move_modules (name, address);
+
+CREATE TABLE move_structs (
+    id SERIAL PRIMARY KEY,
+    move_module_id INTEGER NOT NULL REFERENCES move_modules (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    signature TEXT NOT NULL,
+    doc TEXT NOT NULL DEFAULT ''
+);
+
+CREATE INDEX move_structs_move_module_id_index ON move_structs (move_module_id);
+
+CREATE TABLE move_functions (
+    id SERIAL PRIMARY KEY,
+    move_module_id INTEGER NOT NULL REFERENCES move_modules (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    visibility TEXT NOT NULL,
+    is_entry BOOLEAN NOT NULL DEFAULT FALSE,
+    signature TEXT NOT NULL,
+    doc TEXT NOT NULL DEFAULT ''
+);
+
+CREATE INDEX move_functions_move_module_id_index ON move_functions (move_module_id);
+
+ALTER TABLE package_versions ADD COLUMN docs_generated_at TIMESTAMPTZ;
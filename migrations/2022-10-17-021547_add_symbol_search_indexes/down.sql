@@ -0,0 +1,3 @@
+DROP INDEX move_functions_lower_name_index;
+
+DROP INDEX move_modules_lower_name_index;
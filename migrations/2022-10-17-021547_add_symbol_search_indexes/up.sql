@@ -0,0 +1,2 @@
+CREATE INDEX move_modules_lower_name_index ON move_modules (lower(name) text_pattern_ops);
+CREATE INDEX move_functions_lower_name_index ON move_functions (lower(name) text_pattern_ops);
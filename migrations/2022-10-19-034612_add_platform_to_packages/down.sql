@@ -0,0 +1,19 @@
+DROP TRIGGER set_updated_at ON packages;
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON packages
+FOR EACH ROW EXECUTE PROCEDURE packages_set_updated_at(
+    'tsv',
+    'stars_count',
+    'forks_count',
+    'repository_status',
+    'metadata_refreshed_at',
+    'upstream_package_id'
+);
+
+DROP TABLE package_version_dependencies;
+
+DROP INDEX package_versions_undetected_platform_index;
+DROP INDEX packages_platform_index;
+
+ALTER TABLE packages DROP COLUMN platform;
+ALTER TABLE package_versions DROP COLUMN platform_detected;
+ALTER TABLE package_versions DROP COLUMN platform;
@@ -0,0 +1,35 @@
+ALTER TABLE package_versions ADD COLUMN platform TEXT NOT NULL DEFAULT 'unknown';
+-- the metadata refresh reads the Move.toml of existing versions once to detect their platform,
+-- new versions are classified when created
+ALTER TABLE package_versions ADD COLUMN platform_detected BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE package_versions ALTER COLUMN platform_detected SET DEFAULT TRUE;
+ALTER TABLE packages ADD COLUMN platform TEXT NOT NULL DEFAULT 'unknown';
+
+CREATE INDEX packages_platform_index ON packages (platform);
+CREATE INDEX package_versions_undetected_platform_index ON package_versions (id)
+    WHERE NOT platform_detected;
+
+CREATE TABLE package_version_dependencies (
+    id SERIAL PRIMARY KEY,
+    package_version_id INTEGER NOT NULL REFERENCES package_versions (id) ON DELETE CASCADE,
+    name TEXT NOT NULL,
+    git_url TEXT,
+    rev TEXT,
+    subdir TEXT,
+    local_path TEXT
+);
+
+CREATE INDEX package_version_dependencies_package_version_id_index ON package_version_dependencies (package_version_id);
+
+-- the platform follows the latest version, whose creation already counts as an update
+DROP TRIGGER set_updated_at ON packages;
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON packages
+FOR EACH ROW EXECUTE PROCEDURE packages_set_updated_at(
+    'tsv',
+    'stars_count',
+    'forks_count',
+    'repository_status',
+    'metadata_refreshed_at',
+    'upstream_package_id',
+    'platform'
+);
@@ -0,0 +1,19 @@
+DROP TRIGGER set_updated_at ON packages;
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON packages
+FOR EACH ROW EXECUTE PROCEDURE packages_set_updated_at(
+    'tsv',
+    'stars_count',
+    'forks_count',
+    'repository_status',
+    'metadata_refreshed_at',
+    'upstream_package_id',
+    'platform'
+);
+
+DROP INDEX package_versions_unnormalized_license_index;
+DROP INDEX packages_license_spdx_index;
+
+ALTER TABLE packages DROP COLUMN license_spdx;
+ALTER TABLE package_versions DROP COLUMN license_normalized;
+ALTER TABLE package_versions DROP COLUMN license_spdx;
+ALTER TABLE package_versions DROP COLUMN manifest_license;
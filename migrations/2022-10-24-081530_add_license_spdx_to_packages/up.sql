@@ -0,0 +1,27 @@
+ALTER TABLE package_versions ADD COLUMN manifest_license TEXT;
+ALTER TABLE package_versions ADD COLUMN license_spdx TEXT;
+ALTER TABLE packages ADD COLUMN license_spdx TEXT;
+
+CREATE INDEX packages_license_spdx_index ON packages (license_spdx);
+
+-- versions crawled so far only have the license name returned by GitHub, the metadata
+-- refresh normalizes them once with utils::license. New versions are normalized when created.
+ALTER TABLE package_versions ADD COLUMN license_normalized BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE package_versions ALTER COLUMN license_normalized SET DEFAULT TRUE;
+
+CREATE INDEX package_versions_unnormalized_license_index ON package_versions (id)
+    WHERE NOT license_normalized;
+
+-- the license follows the latest version and the metadata refresh
+DROP TRIGGER set_updated_at ON packages;
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON packages
+FOR EACH ROW EXECUTE PROCEDURE packages_set_updated_at(
+    'tsv',
+    'stars_count',
+    'forks_count',
+    'repository_status',
+    'metadata_refreshed_at',
+    'upstream_package_id',
+    'platform',
+    'license_spdx'
+);
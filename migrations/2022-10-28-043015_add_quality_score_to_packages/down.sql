@@ -0,0 +1,16 @@
+DROP TRIGGER set_updated_at ON packages;
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON packages
+FOR EACH ROW EXECUTE PROCEDURE packages_set_updated_at(
+    'tsv',
+    'stars_count',
+    'forks_count',
+    'repository_status',
+    'metadata_refreshed_at',
+    'upstream_package_id',
+    'platform',
+    'license_spdx'
+);
+
+DROP INDEX packages_quality_score_index;
+
+ALTER TABLE packages DROP COLUMN quality_score;
@@ -0,0 +1,18 @@
+ALTER TABLE packages ADD COLUMN quality_score INTEGER NOT NULL DEFAULT 0;
+
+CREATE INDEX packages_quality_score_index ON packages (quality_score);
+
+-- scores are recomputed by a scheduled job
+DROP TRIGGER set_updated_at ON packages;
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON packages
+FOR EACH ROW EXECUTE PROCEDURE packages_set_updated_at(
+    'tsv',
+    'stars_count',
+    'forks_count',
+    'repository_status',
+    'metadata_refreshed_at',
+    'upstream_package_id',
+    'platform',
+    'license_spdx',
+    'quality_score'
+);
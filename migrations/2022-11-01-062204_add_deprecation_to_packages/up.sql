@@ -0,0 +1,21 @@
+ALTER TABLE packages ADD COLUMN deprecated_at TIMESTAMPTZ;
+ALTER TABLE packages ADD COLUMN deprecation_reason TEXT;
+ALTER TABLE packages ADD COLUMN deprecation_successor TEXT;
+
+-- deprecating is a status change, not an update of the package
+DROP TRIGGER set_updated_at ON packages;
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON packages
+FOR EACH ROW EXECUTE PROCEDURE packages_set_updated_at(
+    'tsv',
+    'stars_count',
+    'forks_count',
+    'repository_status',
+    'metadata_refreshed_at',
+    'upstream_package_id',
+    'platform',
+    'license_spdx',
+    'quality_score',
+    'deprecated_at',
+    'deprecation_reason',
+    'deprecation_successor'
+);
@@ -0,0 +1,2 @@
+DROP TABLE account_slug_histories;
+DROP TABLE package_slug_histories;
@@ -0,0 +1,21 @@
+DROP TRIGGER set_updated_at ON packages;
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON packages
+FOR EACH ROW EXECUTE PROCEDURE packages_set_updated_at(
+    'tsv',
+    'stars_count',
+    'forks_count',
+    'repository_status',
+    'metadata_refreshed_at',
+    'upstream_package_id',
+    'platform',
+    'license_spdx',
+    'quality_score',
+    'deprecated_at',
+    'deprecation_reason',
+    'deprecation_successor'
+);
+
+DROP INDEX packages_deletion_scheduled_at_idx;
+DROP TABLE package_audit_logs;
+ALTER TABLE packages DROP COLUMN deletion_scheduled_at;
+ALTER TABLE packages DROP COLUMN archived_at;
@@ -0,0 +1,36 @@
+ALTER TABLE packages ADD COLUMN archived_at TIMESTAMPTZ;
+ALTER TABLE packages ADD COLUMN deletion_scheduled_at TIMESTAMPTZ;
+
+CREATE TABLE package_audit_logs (
+    id SERIAL PRIMARY KEY,
+    -- Not a foreign key, the log of a package outlives the package itself
+    package_id INTEGER NOT NULL,
+    package_slug TEXT NOT NULL,
+    account_id INTEGER REFERENCES accounts(id) ON DELETE SET NULL,
+    action TEXT NOT NULL,
+    details TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX package_audit_logs_package_id_idx ON package_audit_logs (package_id);
+CREATE INDEX packages_deletion_scheduled_at_idx ON packages (deletion_scheduled_at);
+
+-- archiving and scheduling the deletion are status changes
+DROP TRIGGER set_updated_at ON packages;
+CREATE TRIGGER set_updated_at BEFORE UPDATE ON packages
+FOR EACH ROW EXECUTE PROCEDURE packages_set_updated_at(
+    'tsv',
+    'stars_count',
+    'forks_count',
+    'repository_status',
+    'metadata_refreshed_at',
+    'upstream_package_id',
+    'platform',
+    'license_spdx',
+    'quality_score',
+    'deprecated_at',
+    'deprecation_reason',
+    'deprecation_successor',
+    'archived_at',
+    'deletion_scheduled_at'
+);
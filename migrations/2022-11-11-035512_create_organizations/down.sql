@@ -0,0 +1,4 @@
+DROP INDEX packages_organization_id_idx;
+ALTER TABLE packages DROP COLUMN organization_id;
+DROP TABLE organization_members;
+DROP TABLE organizations;
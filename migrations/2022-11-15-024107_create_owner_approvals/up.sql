@@ -0,0 +1,12 @@
+CREATE TABLE owner_approvals (
+    id SERIAL PRIMARY KEY,
+    package_id INTEGER NOT NULL REFERENCES packages(id) ON DELETE CASCADE,
+    action TEXT NOT NULL,
+    target_account_id INTEGER REFERENCES accounts(id) ON DELETE CASCADE,
+    target_role INTEGER,
+    organization_id INTEGER REFERENCES organizations(id) ON DELETE CASCADE,
+    requested_by INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX owner_approvals_package_id_idx ON owner_approvals (package_id);
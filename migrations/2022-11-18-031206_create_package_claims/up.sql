@@ -0,0 +1,9 @@
+CREATE TABLE package_claims (
+    package_id INTEGER NOT NULL REFERENCES packages(id) ON DELETE CASCADE,
+    account_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    token TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (package_id, account_id)
+);
+
+CREATE INDEX package_claims_account_id_idx ON package_claims (account_id);
@@ -0,0 +1,2 @@
+ALTER TABLE owner_invitations DROP COLUMN expires_at;
+ALTER TABLE external_invitations DROP COLUMN expires_at;
@@ -0,0 +1,3 @@
+-- Invitations without an expiry fall back to OWNERSHIP_INVITATIONS_EXPIRATION_DAYS
+ALTER TABLE owner_invitations ADD COLUMN expires_at TIMESTAMP;
+ALTER TABLE external_invitations ADD COLUMN expires_at TIMESTAMP;
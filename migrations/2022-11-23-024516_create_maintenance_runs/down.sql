@@ -0,0 +1,3 @@
+ALTER TABLE api_tokens DROP COLUMN prune_warned_at;
+
+DROP TABLE maintenance_runs;
@@ -0,0 +1,14 @@
+CREATE TABLE maintenance_runs (
+    id SERIAL PRIMARY KEY,
+    status TEXT NOT NULL DEFAULT 'running',
+    expired_invitations_count INTEGER NOT NULL DEFAULT 0,
+    expired_accounts_count INTEGER NOT NULL DEFAULT 0,
+    warned_tokens_count INTEGER NOT NULL DEFAULT 0,
+    pruned_tokens_count INTEGER NOT NULL DEFAULT 0,
+    failed_count INTEGER NOT NULL DEFAULT 0,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT now(),
+    finished_at TIMESTAMPTZ
+);
+
+-- Unused tokens are pruned some time after their owner is warned
+ALTER TABLE api_tokens ADD COLUMN prune_warned_at TIMESTAMPTZ;
@@ -0,0 +1,10 @@
+-- Recurring jobs registered with jelly's Server::register_schedule
+CREATE TABLE jelly_schedules (
+    name TEXT PRIMARY KEY,
+    cron TEXT NOT NULL,
+    next_run_at TIMESTAMPTZ NOT NULL,
+    last_started_at TIMESTAMPTZ,
+    last_finished_at TIMESTAMPTZ,
+    last_error TEXT,
+    locked_until TIMESTAMPTZ
+);
@@ -0,0 +1,17 @@
+-- Background jobs queued through jelly's Postgres storage
+CREATE TABLE jelly_jobs (
+    id UUID PRIMARY KEY,
+    queue TEXT NOT NULL,
+    name TEXT NOT NULL,
+    info JSONB NOT NULL,
+    status TEXT NOT NULL DEFAULT 'pending',
+    attempts INTEGER NOT NULL DEFAULT 0,
+    run_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    started_at TIMESTAMPTZ,
+    runner_id UUID,
+    last_error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX jelly_jobs_queue_status_run_at_idx ON jelly_jobs (queue, status, run_at);
@@ -0,0 +1 @@
+DROP TABLE jelly_email_outbox;
@@ -0,0 +1,13 @@
+-- Every email sent with jelly's Email::deliver, without its body
+CREATE TABLE jelly_email_outbox (
+    id SERIAL PRIMARY KEY,
+    template TEXT NOT NULL,
+    recipient TEXT NOT NULL,
+    subject TEXT NOT NULL,
+    status TEXT NOT NULL,
+    provider_response TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX jelly_email_outbox_recipient_idx ON jelly_email_outbox (recipient);
@@ -0,0 +1,10 @@
+CREATE TABLE package_version_files (
+    id SERIAL PRIMARY KEY,
+    package_version_id INTEGER NOT NULL REFERENCES package_versions (id) ON DELETE CASCADE,
+    path TEXT NOT NULL,
+    size INTEGER NOT NULL DEFAULT 0,
+    content TEXT
+);
+
+CREATE UNIQUE INDEX package_version_files_package_version_id_path_index
+    ON package_version_files (package_version_id, path);
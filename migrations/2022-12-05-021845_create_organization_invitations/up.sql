@@ -0,0 +1,9 @@
+CREATE TABLE organization_invitations (
+    organization_id INTEGER NOT NULL REFERENCES organizations(id) ON DELETE CASCADE,
+    invited_user_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    invited_by_user_id INTEGER NOT NULL REFERENCES accounts(id) ON DELETE CASCADE,
+    role INTEGER NOT NULL,
+    token TEXT NOT NULL UNIQUE,
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (invited_user_id, organization_id)
+);
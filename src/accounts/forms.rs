@@ -1,4 +1,4 @@
-use jelly::forms::{EmailField, PasswordField, Validation};
+use jelly::forms::{EmailField, PasswordField, TextField, Validation};
 use serde::{Deserialize, Serialize};
 
 fn default_redirect_path() -> String {
@@ -51,6 +51,18 @@ impl Validation for EmailForm {
     }
 }
 
+#[derive(Default, Debug, Deserialize, Serialize)]
+pub struct ChangeNameForm {
+    pub name: TextField,
+}
+
+impl Validation for ChangeNameForm {
+    fn is_valid(&mut self) -> bool {
+        self.name.value = self.name.value.trim().to_string();
+        self.name.is_valid()
+    }
+}
+
 #[derive(Default, Debug, Deserialize, Serialize)]
 pub struct ChangePasswordViaEmailForm {
     // Unused in rendering, but stored here to enable password
@@ -1,4 +1,4 @@
-use jelly::jobs::{JobState, WorkerConfig};
+use jelly::jobs::{JobState, RegisterJob, WorkerConfig};
 
 mod verify;
 pub use verify::build_context as build_verify_context;
@@ -13,8 +13,8 @@ pub use odd_registration_attempt::build_context as build_odd_registration_attemp
 pub use odd_registration_attempt::SendAccountOddRegisterAttemptEmail;
 
 pub fn configure(config: WorkerConfig<JobState>) -> WorkerConfig<JobState> {
-    let mut config = config.register::<SendResetPasswordEmail>();
-    config = config.register::<SendPasswordWasResetEmail>();
-    config = config.register::<SendAccountOddRegisterAttemptEmail>();
-    config.register::<SendVerifyAccountEmail>()
+    let mut config = config.register_job::<SendResetPasswordEmail>();
+    config = config.register_job::<SendPasswordWasResetEmail>();
+    config = config.register_job::<SendAccountOddRegisterAttemptEmail>();
+    config.register_job::<SendVerifyAccountEmail>()
 }
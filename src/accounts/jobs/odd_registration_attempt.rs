@@ -59,7 +59,7 @@ impl Job for SendAccountOddRegisterAttemptEmail {
                 state.templates,
             );
 
-            email?.send()?;
+            email?.deliver(&state.pool)?;
 
             Ok(())
         })
@@ -52,7 +52,7 @@ impl Job for SendResetPasswordEmail {
                 state.templates,
             );
 
-            email?.send()?;
+            email?.deliver(&state.pool)?;
 
             Ok(())
         })
@@ -80,7 +80,7 @@ impl Job for SendPasswordWasResetEmail {
                 state.templates,
             );
 
-            email?.send()?;
+            email?.deliver(&state.pool)?;
 
             Ok(())
         })
@@ -53,7 +53,7 @@ impl Job for SendVerifyAccountEmail {
                 state.templates,
             );
 
-            email?.send()?;
+            email?.deliver(&state.pool)?;
 
             Ok(())
         })
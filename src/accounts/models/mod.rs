@@ -9,7 +9,7 @@ use diesel::result::Error as DBError;
 use diesel::{AsChangeset, Identifiable, Insertable, Queryable};
 
 use jelly::accounts::{OneTimeUseTokenGenerator, User};
-use jelly::chrono::{offset, DateTime, Utc};
+use jelly::chrono::{offset, DateTime, Duration, Utc};
 use jelly::djangohashers::{check_password, make_password};
 use jelly::error::Error;
 use jelly::error::Error::Generic;
@@ -19,6 +19,7 @@ use jelly::{DieselPgConnection, DieselPgPool};
 use super::forms::{LoginForm, NewAccountForm};
 use super::views::avatar::Gravatar;
 use super::views::verify::GithubOauthUser;
+use crate::schema::account_slug_histories;
 use crate::schema::accounts;
 use crate::schema::accounts::dsl::*;
 use crate::schema::api_tokens::dsl::{
@@ -356,18 +357,42 @@ impl Account {
 
     pub fn check_and_update_slug(&self, pool: &DieselPgPool) -> Result<bool, Error> {
         let conn = pool.get()?;
+        self.update_slug(&conn)
+    }
+
+    fn update_slug(&self, conn: &DieselPgConnection) -> Result<bool, Error> {
         let maximum_allowed_collisions = std::env::var("MAX_COLLISIONS_ALLOWED")
             .unwrap_or_else(|_| "3".to_string())
             .parse::<usize>()
             .unwrap();
         let slug_ = self.make_slug();
+        // Keep the current slug, with the suffix added after a collision, while the name is unchanged
+        if let Some(current_slug) = &self.slug {
+            if is_slug_derived_from(current_slug, &slug_) {
+                return Ok(true);
+            }
+        }
         let mut extended_slug = slug_.clone();
         for _ in 0..maximum_allowed_collisions {
-            match diesel::update(accounts.filter(id.eq(self.id)))
-                .set(accounts::slug.eq(&extended_slug))
-                .execute(&conn)
-            {
-                Ok(_) => return Ok(true),
+            // A savepoint, so that a collision doesn't abort the transaction of `update_name`
+            let result = conn.transaction(|| -> Result<(), Error> {
+                // Old slugs of other accounts keep redirecting to their profile
+                if self.is_slug_in_others_history(&extended_slug, conn)? {
+                    return Err(Error::Generic(format!(
+                        "Slug {} redirects to another account",
+                        extended_slug
+                    )));
+                }
+                diesel::update(accounts.filter(id.eq(self.id)))
+                    .set(accounts::slug.eq(&extended_slug))
+                    .execute(conn)?;
+                if let Some(old_slug) = &self.slug {
+                    self.keep_slug_history(old_slug, &extended_slug, conn)?;
+                }
+                Ok(())
+            });
+            match result {
+                Ok(()) => return Ok(true),
                 Err(_) => {
                     extended_slug = format!("{}-{}", &slug_, generate_secure_alphanumeric_string(4))
                 }
@@ -375,6 +400,125 @@ impl Account {
         }
         Ok(false)
     }
+
+    fn is_slug_in_others_history(
+        &self,
+        slug_: &str,
+        conn: &DieselPgConnection,
+    ) -> Result<bool, Error> {
+        let count = account_slug_histories::table
+            .filter(account_slug_histories::slug.eq(slug_))
+            .filter(account_slug_histories::account_id.ne(self.id))
+            .count()
+            .get_result::<i64>(conn)?;
+        Ok(count > 0)
+    }
+
+    // Old slugs keep redirecting to the public profile
+    fn keep_slug_history(
+        &self,
+        old_slug: &str,
+        new_slug: &str,
+        conn: &DieselPgConnection,
+    ) -> Result<(), Error> {
+        // Taking back one of its own old slugs ends its redirect
+        diesel::delete(
+            account_slug_histories::table
+                .filter(account_slug_histories::slug.eq(new_slug))
+                .filter(account_slug_histories::account_id.eq(self.id)),
+        )
+        .execute(conn)?;
+        diesel::insert_into(account_slug_histories::table)
+            .values((
+                account_slug_histories::account_id.eq(self.id),
+                account_slug_histories::slug.eq(old_slug),
+            ))
+            .on_conflict(account_slug_histories::slug)
+            .do_update()
+            .set(account_slug_histories::account_id.eq(self.id))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Current slug of an account whose slug was `old_slug`.
+    pub fn get_renamed_slug(old_slug: &str, pool: &DieselPgPool) -> Result<Option<String>, Error> {
+        let connection = pool.get()?;
+        let result = account_slug_histories::table
+            .inner_join(accounts::table)
+            .filter(account_slug_histories::slug.eq(old_slug))
+            .select(accounts::slug)
+            .first::<Option<String>>(&connection)
+            .optional()?;
+
+        Ok(result.flatten())
+    }
+
+    /// Changes the display name, and the slug of the public profile with it.
+    /// Returns false, leaving the account unchanged, when no free slug was found for the name.
+    pub fn update_name(uid: i32, new_name: &str, pool: &DieselPgPool) -> Result<bool, Error> {
+        let connection = pool.get()?;
+        let result = connection.transaction(|| -> Result<(), Error> {
+            let record = diesel::update(accounts.filter(id.eq(uid)))
+                .set(name.eq(new_name.trim()))
+                .get_result::<Account>(&connection)?;
+            if !record.update_slug(&connection)? {
+                return Err(Error::Database(DBError::RollbackTransaction));
+            }
+            Ok(())
+        });
+        match result {
+            Ok(()) => Ok(true),
+            Err(Error::Database(DBError::RollbackTransaction)) => {
+                warn!("No free slug for the name of account {}", uid);
+                Ok(false)
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Accounts registered over `expiration_days` ago that never verified their email.
+    pub fn get_expired_unverified(
+        expiration_days: i64,
+        conn: &DieselPgConnection,
+    ) -> Result<Vec<i32>, Error> {
+        let registered_before = Utc::now() - Duration::days(expiration_days);
+        Ok(accounts
+            .filter(has_verified_email.eq(false))
+            .filter(created.lt(registered_before))
+            .select(id)
+            .load::<i32>(conn)?)
+    }
+
+    /// Deletes an account, unless it got verified in the meantime.
+    pub fn delete_unverified(uid: i32, conn: &DieselPgConnection) -> Result<usize, Error> {
+        conn.transaction(|| -> Result<usize, Error> {
+            let unverified = accounts
+                .filter(id.eq(uid))
+                .filter(has_verified_email.eq(false))
+                .select(id)
+                .for_update()
+                .first::<i32>(conn)
+                .optional()?;
+            if unverified.is_none() {
+                return Ok(0);
+            }
+            diesel::delete(api_tokens.filter(api_tokens_account_id.eq(uid))).execute(conn)?;
+            Ok(diesel::delete(accounts.filter(id.eq(uid))).execute(conn)?)
+        })
+    }
+}
+
+// `slug_` itself, or `slug_` with the random suffix added after a collision
+fn is_slug_derived_from(current_slug: &str, slug_: &str) -> bool {
+    match current_slug.strip_prefix(slug_) {
+        Some("") => true,
+        Some(suffix) => {
+            suffix.len() == 5
+                && suffix.starts_with('-')
+                && suffix[1..].chars().all(|c| c.is_ascii_alphanumeric())
+        }
+        None => false,
+    }
 }
 
 #[cfg(any(test, feature = "test"))]
@@ -586,6 +586,85 @@ async fn register_from_github_will_check_and_update_slug() {
     );
 }
 
+#[actix_rt::test]
+async fn check_and_update_slug_keeps_slug_with_collision_suffix() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+
+    setup_github_account(None, None, None);
+    let another_github_user = setup_github_account(
+        Some(145_346),
+        Some("-github__name-"),
+        Some("another_email@domain.com"),
+    );
+    let slug_before = another_github_user.slug.clone().unwrap();
+    assert!(another_github_user.check_and_update_slug(&DB_POOL).unwrap());
+
+    let another_github_user = Account::get(another_github_user.id, &DB_POOL).unwrap();
+    assert_eq!(another_github_user.slug.unwrap(), slug_before);
+    assert!(Account::get_renamed_slug(&slug_before, &DB_POOL)
+        .unwrap()
+        .is_none());
+}
+
+#[actix_rt::test]
+async fn update_name_changes_slug_and_keeps_old_slug() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+
+    let uid = setup_user(None, None);
+    assert_eq!(Account::get(uid, &DB_POOL).unwrap().slug.unwrap(), "email");
+
+    assert!(Account::update_name(uid, " New Name ", &DB_POOL).unwrap());
+    let account = Account::get(uid, &DB_POOL).unwrap();
+    assert_eq!(account.name, "New Name");
+    assert_eq!(account.slug.unwrap(), "new-name");
+    assert_eq!(
+        Account::get_renamed_slug("email", &DB_POOL).unwrap(),
+        Some("new-name".to_string())
+    );
+
+    // The old slug keeps redirecting, another account gets a suffixed slug instead
+    let other_uid = setup_user(Some("other@host.com".to_string()), None);
+    assert!(Account::update_name(other_uid, "email", &DB_POOL).unwrap());
+    let other_slug = Account::get(other_uid, &DB_POOL).unwrap().slug.unwrap();
+    assert!(other_slug.starts_with("email-"));
+    assert_eq!(
+        Account::get_renamed_slug("email", &DB_POOL).unwrap(),
+        Some("new-name".to_string())
+    );
+    assert_eq!(
+        Account::get_renamed_slug("other", &DB_POOL).unwrap(),
+        Some(other_slug)
+    );
+
+    // An account can take back its own old slug
+    assert!(Account::update_name(uid, "email", &DB_POOL).unwrap());
+    assert_eq!(Account::get(uid, &DB_POOL).unwrap().slug.unwrap(), "email");
+    assert!(Account::get_renamed_slug("email", &DB_POOL)
+        .unwrap()
+        .is_none());
+}
+
+#[actix_rt::test]
+async fn update_name_keeps_account_unchanged_without_a_free_slug() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+
+    let uid = setup_user(None, None);
+    std::env::set_var("MAX_COLLISIONS_ALLOWED", "0");
+    let updated = Account::update_name(uid, "New Name", &DB_POOL).unwrap();
+    std::env::remove_var("MAX_COLLISIONS_ALLOWED");
+    assert!(!updated);
+
+    let account = Account::get(uid, &DB_POOL).unwrap();
+    assert_eq!(account.name, "");
+    assert_eq!(account.slug.unwrap(), "email");
+    assert!(Account::get_renamed_slug("email", &DB_POOL)
+        .unwrap()
+        .is_none());
+}
+
 #[actix_rt::test]
 async fn register_will_check_and_update_slug_to_avoid_collision() {
     crate::test::init();
@@ -649,3 +728,41 @@ async fn make_slug_works() {
     let slug_ = account.make_slug();
     assert_eq!(slug_, "a-github-username");
 }
+
+#[actix_rt::test]
+async fn delete_unverified_only_deletes_expired_unverified_accounts() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let conn = DB_POOL.get().unwrap();
+    let form = NewAccountForm {
+        email: EmailField {
+            value: "unverified@host.com".to_string(),
+            errors: vec![],
+        },
+        password: PasswordField {
+            value: "So$trongpas0word!".to_string(),
+            errors: vec![],
+            hints: vec![],
+        },
+    };
+    let unverified_uid = Account::register(&form, &DB_POOL).unwrap();
+    let verified_uid = setup_user(None, None);
+    assert!(Account::get_expired_unverified(30, &conn)
+        .unwrap()
+        .is_empty());
+
+    diesel::update(accounts.filter(id.eq_any(vec![unverified_uid, verified_uid])))
+        .set(created.eq(Utc::now() - Duration::days(31)))
+        .execute(&conn)
+        .unwrap();
+    let expired = Account::get_expired_unverified(30, &conn).unwrap();
+    assert_eq!(expired, vec![unverified_uid]);
+
+    assert_eq!(Account::delete_unverified(verified_uid, &conn).unwrap(), 0);
+    assert_eq!(
+        Account::delete_unverified(unverified_uid, &conn).unwrap(),
+        1
+    );
+    assert!(Account::get(unverified_uid, &DB_POOL).is_err());
+    assert!(Account::get(verified_uid, &DB_POOL).is_ok());
+}
@@ -1,5 +1,9 @@
 use crate::accounts::views::utils::validate_token;
 use crate::accounts::Account;
+use crate::api::package::controller::check_github_claim;
+use crate::constants::{CLAIM_PACKAGE_SESSION_KEY, GITHUB_ADMIN_REPOSITORY_SESSION_KEY};
+#[double]
+use crate::github_service::GithubService;
 use crate::package_collaborators::models::external_invitation::ExternalInvitation;
 use crate::package_collaborators::models::owner_invitation::OwnerInvitation;
 #[cfg(test)]
@@ -15,6 +19,7 @@ use jelly::actix_web::{web, web::Path};
 use jelly::request::DatabasePool;
 use jelly::Result;
 use jelly::{prelude::*, DieselPgPool};
+use mockall_double::double;
 use oauth2::basic::BasicClient;
 use oauth2::reqwest::http_client;
 use oauth2::{AuthorizationCode, TokenResponse};
@@ -63,6 +68,7 @@ pub async fn with_token(
                     invitation.package_id,
                     None,
                     Some(invitation.created_at),
+                    invitation.expires_at,
                     &conn,
                 )?;
                 invitation.delete(&conn)?;
@@ -107,6 +113,19 @@ pub async fn callback_github(
                         .send()?;
 
                     let oauth_response: GithubOauthResponse = response.json().unwrap();
+                    let claimed_package = request
+                        .get_session()
+                        .get::<String>(CLAIM_PACKAGE_SESSION_KEY)?;
+                    if let Some(package_slug) = claimed_package {
+                        request.get_session().remove(CLAIM_PACKAGE_SESSION_KEY);
+                        return verify_github_claim(
+                            &request,
+                            package_slug,
+                            oauth_response.id,
+                            token.access_token().secret().to_string(),
+                        )
+                        .await;
+                    }
                     let db = request.db_pool()?;
 
                     let updated_account =
@@ -142,6 +161,38 @@ pub async fn callback_github(
     };
 }
 
+/// Ends the GitHub sign in started from the claim page of a package. The signed in account
+/// stays as it is, only whether GitHub lets it administer the repository is kept.
+async fn verify_github_claim(
+    request: &HttpRequest,
+    package_slug: String,
+    github_id: i64,
+    github_token: String,
+) -> Result<HttpResponse> {
+    let user = request.user()?;
+    let db = request.db_pool()?;
+    let claim_url = format!("/packages/{}/claim", package_slug);
+    let result = web::block(move || {
+        check_github_claim(
+            &package_slug,
+            &user,
+            github_id,
+            &github_token,
+            GithubService::new(),
+            db,
+        )
+    })
+    .await;
+    match result {
+        Ok(Some(repository_url)) => request
+            .get_session()
+            .set(GITHUB_ADMIN_REPOSITORY_SESSION_KEY, repository_url)?,
+        Ok(None) => {}
+        Err(e) => error!("Unable to check the claim with Github: {:?}", e),
+    }
+    request.redirect(&claim_url)
+}
+
 fn link_github_to_movey_account(
     current_user: User,
     oauth_response: &GithubOauthResponse,
@@ -0,0 +1,31 @@
+use jelly::actix_web::web::{get, post, resource, scope, ServiceConfig};
+
+pub mod views;
+
+#[cfg(test)]
+mod tests;
+
+use crate::utils::new_auth;
+
+pub fn configure(config: &mut ServiceConfig) {
+    let guard = new_auth();
+
+    config.service(
+        scope("/admin")
+            .wrap(guard)
+            .service(resource("/crawls").route(get().to(views::controller::show_crawl_runs)))
+            .service(
+                resource("/crawls/{crawl_run_id}")
+                    .route(get().to(views::controller::show_crawl_run)),
+            )
+            .service(
+                resource("/maintenance").route(get().to(views::controller::show_maintenance_runs)),
+            )
+            .service(resource("/schedules").route(get().to(views::controller::show_schedules)))
+            .service(resource("/jobs").route(get().to(views::controller::show_dead_jobs)))
+            .service(resource("/emails").route(get().to(views::controller::show_emails)))
+            .service(
+                resource("/jobs/{job_id}/retry").route(post().to(views::controller::retry_job)),
+            ),
+    );
+}
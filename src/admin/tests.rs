@@ -0,0 +1,129 @@
+use crate::test::{DatabaseTestContext, DB_POOL};
+use jelly::email::outbox::{
+    OutboxEmail, EMAIL_STATUS_DEFERRED, EMAIL_STATUS_REJECTED, EMAIL_STATUS_SENT,
+};
+use jelly::email::Email;
+
+fn email(template: &str, to: &str, subject: &str) -> Email {
+    Email {
+        template: template.to_string(),
+        from: "test@example.com".to_string(),
+        to: to.to_string(),
+        subject: subject.to_string(),
+        body: "body".to_string(),
+        body_html: "<p>body</p>".to_string(),
+        ..Default::default()
+    }
+}
+
+fn outbox_entry(recipient: &str) -> OutboxEmail {
+    let (emails, total) = OutboxEmail::search(recipient, 1, 10, &DB_POOL).unwrap();
+    assert_eq!(total, 1);
+    emails[0].clone()
+}
+
+#[actix_rt::test]
+async fn deliver_records_sent_emails() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+
+    email("email/verify", "sent@example.com", "Verify your account")
+        .deliver(&DB_POOL)
+        .unwrap();
+
+    let entry = outbox_entry("sent@example.com");
+    assert_eq!(entry.status, EMAIL_STATUS_SENT);
+    assert_eq!(entry.template, "email/verify");
+    assert_eq!(entry.subject, "Verify your account");
+    assert!(entry.provider_response.is_some());
+}
+
+#[actix_rt::test]
+async fn deliver_records_deferred_emails_and_fails() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+
+    let mut deferred = email("email/verify", "deferred@example.com", "Verify");
+    deferred.from = "not an address".to_string();
+    assert!(deferred.deliver(&DB_POOL).is_err());
+
+    let entry = outbox_entry("deferred@example.com");
+    assert_eq!(entry.status, EMAIL_STATUS_DEFERRED);
+    assert!(entry.provider_response.is_some());
+}
+
+#[actix_rt::test]
+async fn deliver_records_rejected_emails_without_failing() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+
+    email("email/verify", "rejected.example.com", "Verify")
+        .deliver(&DB_POOL)
+        .unwrap();
+
+    let entry = outbox_entry("rejected.example.com");
+    assert_eq!(entry.status, EMAIL_STATUS_REJECTED);
+    assert!(entry
+        .provider_response
+        .unwrap()
+        .contains("Invalid recipient"));
+}
+
+#[actix_rt::test]
+async fn outbox_search_matches_recipient_subject_or_template() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+
+    email("email/verify", "alice@example.com", "Verify your account")
+        .deliver(&DB_POOL)
+        .unwrap();
+    email(
+        "email/reset-password",
+        "bob@example.com",
+        "Reset your password",
+    )
+    .deliver(&DB_POOL)
+    .unwrap();
+
+    let (emails, total) = OutboxEmail::search("ALICE", 1, 10, &DB_POOL).unwrap();
+    assert_eq!(total, 1);
+    assert_eq!(emails[0].recipient, "alice@example.com");
+    let (emails, _) = OutboxEmail::search("password", 1, 10, &DB_POOL).unwrap();
+    assert_eq!(emails[0].recipient, "bob@example.com");
+    let (emails, _) = OutboxEmail::search("email/verify", 1, 10, &DB_POOL).unwrap();
+    assert_eq!(emails[0].recipient, "alice@example.com");
+
+    // newest first, paginated
+    let (emails, total) = OutboxEmail::search("example.com", 2, 1, &DB_POOL).unwrap();
+    assert_eq!(total, 2);
+    assert_eq!(emails.len(), 1);
+    assert_eq!(emails[0].recipient, "alice@example.com");
+}
+
+#[actix_rt::test]
+async fn outbox_search_matches_wildcards_literally() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+
+    email("email/verify", "a@example.com", "100% done")
+        .deliver(&DB_POOL)
+        .unwrap();
+    email("email/verify", "b@example.com", "1000 done")
+        .deliver(&DB_POOL)
+        .unwrap();
+    email("email/verify", "first_last@example.com", "Hi")
+        .deliver(&DB_POOL)
+        .unwrap();
+    email("email/verify", "firstxlast@example.com", "Hi")
+        .deliver(&DB_POOL)
+        .unwrap();
+
+    let (emails, total) = OutboxEmail::search("100%", 1, 10, &DB_POOL).unwrap();
+    assert_eq!(total, 1);
+    assert_eq!(emails[0].subject, "100% done");
+    let (emails, total) = OutboxEmail::search("first_last", 1, 10, &DB_POOL).unwrap();
+    assert_eq!(total, 1);
+    assert_eq!(emails[0].recipient, "first_last@example.com");
+    let (_, total) = OutboxEmail::search("%", 1, 10, &DB_POOL).unwrap();
+    assert_eq!(total, 1);
+}
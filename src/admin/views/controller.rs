@@ -0,0 +1,184 @@
+use jelly::actix_web::web::{Path, Query};
+use jelly::email::outbox::OutboxEmail;
+use jelly::jobs::schedule::ScheduleRecord;
+use jelly::jobs::storage::DeadJob;
+use jelly::prelude::*;
+use jelly::request::DatabasePool;
+use jelly::uuid::Uuid;
+use jelly::Result;
+use serde::Deserialize;
+
+use crate::jobs::models::CrawlRun;
+use crate::maintenance::models::MaintenanceRun;
+
+const DEAD_JOBS_PER_PAGE: i64 = 20;
+const EMAILS_PER_PAGE: i64 = 50;
+
+#[derive(Deserialize)]
+pub struct AdminPageParams {
+    pub page: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct EmailSearchParams {
+    pub page: Option<i64>,
+    pub query: Option<String>,
+}
+
+/// Admin pages are hidden behind a 404 for everyone else.
+fn forbid_non_admin(request: &HttpRequest) -> Result<Option<HttpResponse>> {
+    if request.user()?.is_admin {
+        Ok(None)
+    } else {
+        Ok(Some(request.render(404, "404.html", Context::new())?))
+    }
+}
+
+pub async fn show_crawl_runs(
+    request: HttpRequest,
+    params: Query<AdminPageParams>,
+) -> Result<HttpResponse> {
+    if let Some(response) = forbid_non_admin(&request)? {
+        return Ok(response);
+    }
+    let current_page = params.page.unwrap_or(1);
+    if current_page < 1 {
+        return Err(Error::Generic(String::from("Invalid page number.")));
+    }
+    let db = request.db_pool()?;
+    let (crawl_runs, total_count, total_pages) = CrawlRun::all_paginated(Some(current_page), db)?;
+
+    request.render(200, "admin/crawls.html", {
+        let mut ctx = Context::new();
+        ctx.insert("crawl_runs", &crawl_runs);
+        ctx.insert("total_count", &total_count);
+        ctx.insert("total_pages", &total_pages);
+        ctx.insert("current_page", &current_page);
+        ctx.insert("admin_tab", "crawls");
+        ctx
+    })
+}
+
+pub async fn show_crawl_run(
+    request: HttpRequest,
+    Path(crawl_run_id): Path<i32>,
+) -> Result<HttpResponse> {
+    if let Some(response) = forbid_non_admin(&request)? {
+        return Ok(response);
+    }
+    let db = request.db_pool()?;
+    let crawl_run = CrawlRun::get(crawl_run_id, db)?;
+    let crawl_errors = crawl_run.errors(db)?;
+
+    request.render(200, "admin/crawl.html", {
+        let mut ctx = Context::new();
+        ctx.insert("crawl_run", &crawl_run);
+        ctx.insert("crawl_errors", &crawl_errors);
+        ctx.insert("admin_tab", "crawls");
+        ctx
+    })
+}
+
+pub async fn show_maintenance_runs(
+    request: HttpRequest,
+    params: Query<AdminPageParams>,
+) -> Result<HttpResponse> {
+    if let Some(response) = forbid_non_admin(&request)? {
+        return Ok(response);
+    }
+    let current_page = params.page.unwrap_or(1);
+    if current_page < 1 {
+        return Err(Error::Generic(String::from("Invalid page number.")));
+    }
+    let db = request.db_pool()?;
+    let (maintenance_runs, total_count, total_pages) =
+        MaintenanceRun::all_paginated(Some(current_page), db)?;
+
+    request.render(200, "admin/maintenance.html", {
+        let mut ctx = Context::new();
+        ctx.insert("maintenance_runs", &maintenance_runs);
+        ctx.insert("total_count", &total_count);
+        ctx.insert("total_pages", &total_pages);
+        ctx.insert("current_page", &current_page);
+        ctx.insert("admin_tab", "maintenance");
+        ctx
+    })
+}
+
+pub async fn show_schedules(request: HttpRequest) -> Result<HttpResponse> {
+    if let Some(response) = forbid_non_admin(&request)? {
+        return Ok(response);
+    }
+    let schedules = ScheduleRecord::all(request.db_pool()?)?;
+
+    request.render(200, "admin/schedules.html", {
+        let mut ctx = Context::new();
+        ctx.insert("schedules", &schedules);
+        ctx.insert("admin_tab", "schedules");
+        ctx
+    })
+}
+
+pub async fn show_dead_jobs(
+    request: HttpRequest,
+    params: Query<AdminPageParams>,
+) -> Result<HttpResponse> {
+    if let Some(response) = forbid_non_admin(&request)? {
+        return Ok(response);
+    }
+    let current_page = params.page.unwrap_or(1);
+    if current_page < 1 {
+        return Err(Error::Generic(String::from("Invalid page number.")));
+    }
+    let (dead_jobs, total_count) =
+        DeadJob::paginated(current_page, DEAD_JOBS_PER_PAGE, request.db_pool()?)?;
+    let total_pages = ((total_count + DEAD_JOBS_PER_PAGE - 1) / DEAD_JOBS_PER_PAGE).max(1);
+
+    request.render(200, "admin/jobs.html", {
+        let mut ctx = Context::new();
+        ctx.insert("dead_jobs", &dead_jobs);
+        ctx.insert("total_count", &total_count);
+        ctx.insert("total_pages", &total_pages);
+        ctx.insert("current_page", &current_page);
+        ctx.insert("admin_tab", "jobs");
+        ctx
+    })
+}
+
+pub async fn retry_job(request: HttpRequest, Path(job_id): Path<Uuid>) -> Result<HttpResponse> {
+    if let Some(response) = forbid_non_admin(&request)? {
+        return Ok(response);
+    }
+    if !DeadJob::retry(job_id, request.db_pool()?)? {
+        warn!("Job {} is not dead, so it was not retried", job_id);
+    }
+    request.redirect("/admin/jobs")
+}
+
+pub async fn show_emails(
+    request: HttpRequest,
+    params: Query<EmailSearchParams>,
+) -> Result<HttpResponse> {
+    if let Some(response) = forbid_non_admin(&request)? {
+        return Ok(response);
+    }
+    let current_page = params.page.unwrap_or(1);
+    if current_page < 1 {
+        return Err(Error::Generic(String::from("Invalid page number.")));
+    }
+    let query = params.query.as_deref().unwrap_or("").trim();
+    let (emails, total_count) =
+        OutboxEmail::search(query, current_page, EMAILS_PER_PAGE, request.db_pool()?)?;
+    let total_pages = ((total_count + EMAILS_PER_PAGE - 1) / EMAILS_PER_PAGE).max(1);
+
+    request.render(200, "admin/emails.html", {
+        let mut ctx = Context::new();
+        ctx.insert("emails", &emails);
+        ctx.insert("query", query);
+        ctx.insert("total_count", &total_count);
+        ctx.insert("total_pages", &total_pages);
+        ctx.insert("current_page", &current_page);
+        ctx.insert("admin_tab", "emails");
+        ctx
+    })
+}
@@ -1,26 +1,38 @@
 use crate::package_collaborators::jobs::invite_collaborator::{SendRegisterToCollabEmail, SendCollaboratorInvitationEmail};
 use crate::package_collaborators::jobs::transfer_ownership::SendOwnershipTransferEmail;
 use crate::accounts::Account;
-use crate::api::collaborators::views::{CollaboratorJson, InvitationResponse};
+use crate::api::collaborators::views::{
+    BulkInviteJson, BulkInviteResult, CollaboratorJson, CollaboratorRoleJson, InvitationKind,
+    InvitationResponse, InviteJson, PendingInvitation,
+};
+use crate::api::package::controller as package_controller;
+use crate::organizations::models::Organization;
+use crate::package_collaborators::models::owner_approval::{ApprovalAction, OwnerApproval};
+use crate::package_collaborators::models::expiration;
 use crate::package_collaborators::models::external_invitation::ExternalInvitation;
 use crate::package_collaborators::models::owner_invitation::OwnerInvitation;
 use crate::package_collaborators::package_collaborator::{PackageCollaborator, Role};
+use crate::package_collaborators::policy::{self, Action};
 use crate::packages::Package;
 use crate::utils::request_utils;
 use diesel::result::Error as DBError;
 use diesel::Connection;
 use jelly::actix_web::web;
 use jelly::actix_web::web::Path;
+use jelly::chrono::NaiveDateTime;
 use jelly::prelude::Error::*;
 use jelly::prelude::*;
 use jelly::utils::error_constants::*;
-use jelly::Result;
+use jelly::{DieselPgConnection, DieselPgPool, Result};
 use serde_json::json;
+use std::collections::HashMap;
+
+const MAX_BULK_INVITATIONS: usize = 20;
 
 pub async fn add_collaborators(
     request: HttpRequest,
     Path(package_slug): Path<String>,
-    json: web::Json<CollaboratorJson>,
+    json: web::Json<InviteJson>,
 ) -> Result<HttpResponse> {
     if !request_utils::is_authenticated(&request)? {
         return Ok(request_utils::clear_cookie(&request));
@@ -32,32 +44,146 @@ pub async fn add_collaborators(
         .map_err(|e| ApiNotFound(MSG_PACKAGE_NOT_FOUND, Box::new(e)))?;
 
     let user = request.user().map_err(|e| ApiServerError(Box::new(e)))?;
-    PackageCollaborator::get(package.id, user.id, &conn)
-        .map_err(|e| ApiForbidden(MSG_UNAUTHORIZED_TO_ADD_COLLABORATOR, Box::new(e)))?;
+    let is_allowed = policy::can(&package, user.id, Action::Invite, &conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    if !is_allowed {
+        return Err(ApiForbidden(
+            MSG_UNAUTHORIZED_TO_ADD_COLLABORATOR,
+            Box::new(Error::Generic(format!(
+                "Not allowed to {:?} on the package. uid: {}, package id: {}",
+                Action::Invite, user.id, package.id
+            ))),
+        ));
+    }
+    let expires_at = get_expires_at(json.expiration_days)?;
+
+    let invited = invite(&request, &package, user.id, &json.user, expires_at, &conn)?;
+    if invited == Invited::External {
+        // Inviting email is not in system, return a message that will send email to them.
+        return Ok(HttpResponse::Ok().json(json!({
+            "ok": false,
+            "msg": MSG_ACCOUNT_NOT_FOUND_INVITING
+        })));
+    }
+
+    Ok(HttpResponse::Ok().json(&json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_INVITED_COLLABORATOR,
+    })))
+}
 
-    let invited_account = match Account::get_by_email_or_gh_login(&json.user, db) {
+pub async fn bulk_add_collaborators(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+    json: web::Json<BulkInviteJson>,
+) -> Result<HttpResponse> {
+    if !request_utils::is_authenticated(&request)? {
+        return Ok(request_utils::clear_cookie(&request));
+    }
+    let db = request.db_pool().map_err(|e| ApiServerError(Box::new(e)))?;
+    let conn = db.get().map_err(|e| ApiServerError(Box::new(e)))?;
+
+    let package = Package::get_by_slug(&package_slug, &conn)
+        .map_err(|e| ApiNotFound(MSG_PACKAGE_NOT_FOUND, Box::new(e)))?;
+
+    let user = request.user().map_err(|e| ApiServerError(Box::new(e)))?;
+    let is_allowed = policy::can(&package, user.id, Action::Invite, &conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    if !is_allowed {
+        return Err(ApiForbidden(
+            MSG_UNAUTHORIZED_TO_ADD_COLLABORATOR,
+            Box::new(Error::Generic(format!(
+                "Not allowed to {:?} on the package. uid: {}, package id: {}",
+                Action::Invite,
+                user.id,
+                package.id
+            ))),
+        ));
+    }
+    let expires_at = get_expires_at(json.expiration_days)?;
+
+    let mut users: Vec<&str> = vec![];
+    for invited_user in json.users.iter().map(|invited_user| invited_user.trim()) {
+        if !invited_user.is_empty() && !users.contains(&invited_user) {
+            users.push(invited_user);
+        }
+    }
+    if users.is_empty() || users.len() > MAX_BULK_INVITATIONS {
+        return Err(ApiBadRequest(
+            MSG_INVALID_BULK_INVITATION,
+            Box::new(Error::Generic(format!(
+                "Cannot invite {} users at once. package id: {}",
+                users.len(),
+                package.id
+            ))),
+        ));
+    }
+
+    // every invitation stands on its own, one failing doesn't undo the others
+    let results: Vec<BulkInviteResult> = users
+        .into_iter()
+        .map(|invited_user| {
+            match invite(&request, &package, user.id, invited_user, expires_at, &conn) {
+                Ok(Invited::Account) => BulkInviteResult {
+                    user: invited_user.to_string(),
+                    ok: true,
+                    msg: MSG_SUCCESSFULLY_INVITED_COLLABORATOR,
+                },
+                Ok(Invited::External) => BulkInviteResult {
+                    user: invited_user.to_string(),
+                    ok: true,
+                    msg: MSG_ACCOUNT_NOT_FOUND_INVITING,
+                },
+                Err(e) => BulkInviteResult {
+                    user: invited_user.to_string(),
+                    ok: false,
+                    msg: get_error_message(&e),
+                },
+            }
+        })
+        .collect();
+
+    Ok(HttpResponse::Ok().json(&json!({
+        "ok": results.iter().all(|result| result.ok),
+        "results": results,
+    })))
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Invited {
+    Account,
+    /// The email address has no Movey account yet, they are asked to register
+    External,
+}
+
+fn invite(
+    request: &HttpRequest,
+    package: &Package,
+    inviter_id: i32,
+    invited_user: &str,
+    expires_at: Option<NaiveDateTime>,
+    conn: &DieselPgConnection,
+) -> Result<Invited> {
+    let db = request.db_pool().map_err(|e| ApiServerError(Box::new(e)))?;
+    let invited_account = match Account::get_by_email_or_gh_login(invited_user, db) {
         Ok(account) => account,
         Err(e) => {
-            if matches!(e, Error::Database(DBError::NotFound)) && json.user.contains('@') {
-                ExternalInvitation::create(&json.user, user.id, package.id, &conn)
+            if matches!(e, Error::Database(DBError::NotFound)) && invited_user.contains('@') {
+                ExternalInvitation::create(invited_user, inviter_id, package.id, expires_at, conn)
                     .map_err(|e| ApiBadRequest(MSG_INVITATION_ALREADY_EXISTED, Box::new(e)))?;
                 // TODO: Handle error for this line
                 let _ = request.queue(SendRegisterToCollabEmail {
-                    to: json.user.clone(),
+                    to: invited_user.to_string(),
                     package_name: package.name.clone(),
                 });
-                // Inviting email is not in system, return a message that will send email to them.
-                return Ok(HttpResponse::Ok().json(json!({
-                    "ok": false,
-                    "msg": MSG_ACCOUNT_NOT_FOUND_INVITING
-                })));
+                return Ok(Invited::External);
             } else {
                 return Err(ApiNotFound(MSG_ACCOUNT_NOT_FOUND_DONT_INVITE, Box::new(e)));
             }
         }
     };
 
-    if PackageCollaborator::get(package.id, invited_account.id, &conn).is_ok() {
+    if PackageCollaborator::get(package.id, invited_account.id, conn).is_ok() {
         return Err(ApiBadRequest(
             MSG_COLLABORATOR_ALREADY_EXISTED,
             Box::new(Error::Generic(format!(
@@ -67,21 +193,49 @@ pub async fn add_collaborators(
         ));
     }
 
-    let invitation =
-        OwnerInvitation::create(invited_account.id, user.id, package.id, None, None, &conn)
-            .map_err(|e| ApiBadRequest(MSG_INVITATION_ALREADY_EXISTED, Box::new(e)))?;
+    let invitation = OwnerInvitation::create(
+        invited_account.id,
+        inviter_id,
+        package.id,
+        None,
+        None,
+        expires_at,
+        conn,
+    )
+    .map_err(|e| ApiBadRequest(MSG_INVITATION_ALREADY_EXISTED, Box::new(e)))?;
     if !invited_account.is_generated_email() {
         request.queue(SendCollaboratorInvitationEmail {
             to: invited_account.email,
-            package_name: package.name,
+            package_name: package.name.clone(),
             token: invitation.token,
         })?;
     }
+    Ok(Invited::Account)
+}
 
-    Ok(HttpResponse::Ok().json(&json!({
-        "ok": true,
-        "msg": MSG_SUCCESSFULLY_INVITED_COLLABORATOR,
-    })))
+// No expiry chosen falls back to the default one
+fn get_expires_at(expiration_days: Option<i64>) -> Result<Option<NaiveDateTime>> {
+    match expiration_days {
+        Some(days) if !expiration::is_valid_expiration_days(days) => Err(ApiBadRequest(
+            MSG_INVALID_EXPIRATION_DAYS,
+            Box::new(Error::Generic(format!(
+                "Invalid invitation expiration days: {}",
+                days
+            ))),
+        )),
+        Some(days) => Ok(Some(expiration::expires_in(days))),
+        None => Ok(None),
+    }
+}
+
+fn get_error_message(e: &Error) -> &'static str {
+    match e {
+        ApiBadRequest(msg, _)
+        | ApiUnauthorized(msg, _)
+        | ApiForbidden(msg, _)
+        | ApiNotFound(msg, _) => *msg,
+        _ => MSG_UNEXPECTED_ERROR,
+    }
 }
 
 pub async fn transfer_ownership(
@@ -102,49 +256,78 @@ pub async fn transfer_ownership(
         .map_err(|e| ApiNotFound(MSG_ACCOUNT_NOT_FOUND, Box::new(e)))?;
 
     let user = request.user().map_err(|e| ApiServerError(Box::new(e)))?;
-    let ids = vec![user.id, invited_account.id];
-    let collaborators = PackageCollaborator::get_in_bulk_order_by_role(package.id, ids, &conn)
-        .map_err(|e| ApiForbidden(MSG_UNAUTHORIZED_TO_ADD_COLLABORATOR, Box::new(e)))?;
     let unauthorized_error = Box::new(Error::Generic(String::from(
         "Unauthorized to transfer ownership.",
     )));
-    if collaborators.len() != 2 {
-        return Err(ApiBadRequest(
+    let is_allowed = policy::can(&package, user.id, Action::TransferOwnership, &conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    if !is_allowed {
+        return Err(ApiForbidden(
             MSG_UNAUTHORIZED_TO_TRANSFER_OWNERSHIP,
             unauthorized_error,
         ));
     }
-    if collaborators.get(0).unwrap().role != Role::Owner as i32
-        || collaborators.get(1).unwrap().role != Role::Collaborator as i32
+    // ownership goes to an existing collaborator
+    match PackageCollaborator::get_role(package.id, invited_account.id, &conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?
     {
-        return Err(ApiForbidden(
-            MSG_UNAUTHORIZED_TO_TRANSFER_OWNERSHIP,
-            unauthorized_error,
-        ));
+        Some(role) if role != Role::Owner => {}
+        _ => {
+            return Err(ApiBadRequest(
+                MSG_UNAUTHORIZED_TO_TRANSFER_OWNERSHIP,
+                unauthorized_error,
+            ))
+        }
+    }
+
+    let needs_approval =
+        policy::requires_approval(&package, &conn).map_err(|e| ApiServerError(Box::new(e)))?;
+    if needs_approval {
+        return request_owner_approval(
+            &package,
+            user.id,
+            ApprovalAction::TransferOwnership,
+            Some(invited_account.id),
+            None,
+            None,
+            &conn,
+        );
     }
+    send_ownership_transfer(&request, &package, user.id, invited_account, &conn)?;
+
+    Ok(HttpResponse::Ok().json(&json!({
+        "ok": true,
+        "msg": format!("{} {}.", MSG_SUCCESSFULLY_TRANSFER_OWNERSHIP, json.user),
+    })))
+}
 
+// The owner who invites is the one handing off the package when the invitation is accepted
+fn send_ownership_transfer(
+    request: &HttpRequest,
+    package: &Package,
+    inviter_id: i32,
+    invited_account: Account,
+    conn: &DieselPgConnection,
+) -> Result<()> {
     let invitation = OwnerInvitation::create(
         invited_account.id,
-        user.id,
+        inviter_id,
         package.id,
         Some(true),
         None,
-        &conn,
+        None,
+        conn,
     )
     .map_err(|e| ApiBadRequest(MSG_INVITATION_ALREADY_EXISTED, Box::new(e)))?;
 
     if !invited_account.is_generated_email() {
         request.queue(SendOwnershipTransferEmail {
             to: invited_account.email,
-            package_name: package.name,
+            package_name: package.name.clone(),
             token: invitation.token,
         })?;
     }
-
-    Ok(HttpResponse::Ok().json(&json!({
-        "ok": true,
-        "msg": format!("{} {}.", MSG_SUCCESSFULLY_TRANSFER_OWNERSHIP, json.user),
-    })))
+    Ok(())
 }
 
 pub async fn handle_invite(
@@ -175,9 +358,6 @@ pub async fn handle_invite(
             invitation
                 .accept(&conn)
                 .map_err(|e| ApiUnauthorized(MSG_UNEXPECTED_ERROR, Box::new(e)))?;
-            if invitation.is_transferring {
-                Package::change_owner(invitation.package_id, invitation.invited_user_id, &conn)?;
-            }
             Ok(())
         })?
     } else {
@@ -206,26 +386,64 @@ pub async fn remove_collaborator(
         .map_err(|e| ApiNotFound(MSG_PACKAGE_NOT_FOUND, Box::new(e)))?;
 
     let user = request.user().map_err(|e| ApiServerError(Box::new(e)))?;
-    PackageCollaborator::get(package.id, user.id, &conn)
-        .map_err(|e| ApiForbidden(MSG_UNAUTHORIZED_TO_ADD_COLLABORATOR, Box::new(e)))?;
+    remove_package_collaborator(&package, user.id, &json.user, db, &conn)
+}
 
-    let target_account = Account::get_by_email_or_gh_login(&json.user, db);
+/// Removes a collaborator, or revokes its invitation, on behalf of `uid`.
+/// Collaborators are only removed by a higher role, owners never are.
+pub fn remove_package_collaborator(
+    package: &Package,
+    uid: i32,
+    removed_user: &str,
+    db: &DieselPgPool,
+    conn: &DieselPgConnection,
+) -> Result<HttpResponse> {
+    let role = policy::get_role(package, uid, conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?
+        .filter(|role| role.allows(Action::RemoveCollaborator))
+        .ok_or_else(|| {
+            ApiForbidden(
+                MSG_UNAUTHORIZED_TO_REMOVE_COLLABORATOR,
+                Box::new(Error::Generic(format!(
+                    "Not allowed to {:?} on the package. uid: {}, package id: {}",
+                    Action::RemoveCollaborator,
+                    uid,
+                    package.id
+                ))),
+            )
+        })?;
+
+    let target_account = Account::get_by_email_or_gh_login(removed_user, db);
     match target_account {
         Ok(account) => {
             // if account is a PendingOwner, only delete the invitation
             let num_deleted_invitations =
-                OwnerInvitation::delete_by_id(account.id, package.id, &conn)
+                OwnerInvitation::delete_by_id(account.id, package.id, conn)
                     .map_err(|e| ApiServerError(Box::new(e)))?;
             if num_deleted_invitations == 0 {
-                let num_deleted_collaborators =
-                    PackageCollaborator::delete_collaborator_by_id(account.id, package.id, &conn)
-                        .map_err(|e| ApiServerError(Box::new(e)))?;
+                let target_role = PackageCollaborator::get_role(package.id, account.id, conn)
+                    .map_err(|e| ApiServerError(Box::new(e)))?;
+                if let Some(target_role) = target_role {
+                    if !role.outranks(target_role) {
+                        return Err(ApiForbidden(
+                            MSG_UNAUTHORIZED_TO_REMOVE_COLLABORATOR,
+                            Box::new(Error::Generic(format!(
+                                "Cannot remove a collaborator with a role as high. requester id: {}, target id: {}, package id: {}",
+                                uid, account.id, package.id
+                            ))),
+                        ));
+                    }
+                }
+                let num_deleted_collaborators = PackageCollaborator::delete_collaborator_by_id(
+                    account.id, package.id, role, conn,
+                )
+                .map_err(|e| ApiServerError(Box::new(e)))?;
                 if num_deleted_collaborators == 0 {
                     return Err(ApiNotFound(
                         MSG_COLLABORATOR_NOT_FOUND,
                         Box::new(Error::Generic(format!(
                             "Failure trying to remove collaborator from package. requester id: {}, target id: {}, package id: {}",
-                            user.id, account.id, package.id
+                            uid, account.id, package.id
                         ))),
                     ));
                 }
@@ -233,10 +451,10 @@ pub async fn remove_collaborator(
         }
         Err(e) => {
             // an external account must have a valid email address
-            if !json.user.contains('@') {
+            if !removed_user.contains('@') {
                 return Err(ApiNotFound(MSG_ACCOUNT_NOT_FOUND, Box::new(e)));
             }
-            ExternalInvitation::delete_by_id(&json.user, package.id, &conn)
+            ExternalInvitation::delete_by_id(removed_user, package.id, conn)
                 .map_err(|e| ApiNotFound(MSG_ACCOUNT_NOT_FOUND, Box::new(e)))?;
         }
     }
@@ -246,3 +464,533 @@ pub async fn remove_collaborator(
         "msg": MSG_SUCCESSFULLY_REMOVED_COLLABORATOR,
     })))
 }
+
+pub async fn list_invitations(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+) -> Result<HttpResponse> {
+    if !request_utils::is_authenticated(&request)? {
+        return Ok(request_utils::clear_cookie(&request));
+    }
+    let db = request.db_pool().map_err(|e| ApiServerError(Box::new(e)))?;
+    let conn = db.get().map_err(|e| ApiServerError(Box::new(e)))?;
+
+    let package = Package::get_by_slug(&package_slug, &conn)
+        .map_err(|e| ApiNotFound(MSG_PACKAGE_NOT_FOUND, Box::new(e)))?;
+
+    let user = request.user().map_err(|e| ApiServerError(Box::new(e)))?;
+    ensure_can_manage_invitations(&package, user.id, Action::Invite, &conn)?;
+
+    let owner_invitations = OwnerInvitation::find_all_by_package_id(package.id, &conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    let external_invitations = ExternalInvitation::find_all_by_package_id(package.id, &conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    let account_ids: Vec<i32> = owner_invitations
+        .iter()
+        .flat_map(|invitation| vec![invitation.invited_user_id, invitation.invited_by_user_id])
+        .chain(
+            external_invitations
+                .iter()
+                .map(|invitation| invitation.invited_by_user_id),
+        )
+        .collect();
+    let names: HashMap<i32, String> = Account::get_accounts(&account_ids, &conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?
+        .into_iter()
+        .map(|account| {
+            let name = if account.is_generated_email() {
+                account.github_login.clone().unwrap_or(account.email)
+            } else {
+                account.email
+            };
+            (account.id, name)
+        })
+        .collect();
+    let get_name = |account_id: i32| names.get(&account_id).cloned().unwrap_or_default();
+
+    let mut invitations: Vec<PendingInvitation> = owner_invitations
+        .iter()
+        .map(|invitation| PendingInvitation {
+            user: get_name(invitation.invited_user_id),
+            kind: if invitation.is_transferring {
+                InvitationKind::Transfer
+            } else {
+                InvitationKind::Collaborator
+            },
+            invited_by: get_name(invitation.invited_by_user_id),
+            created_at: invitation.created_at,
+            expires_at: invitation.expires_at(),
+            is_expired: invitation.is_expired(),
+        })
+        .collect();
+    invitations.extend(
+        external_invitations
+            .iter()
+            .map(|invitation| PendingInvitation {
+                user: invitation.external_user_email.clone(),
+                kind: InvitationKind::External,
+                invited_by: get_name(invitation.invited_by_user_id),
+                created_at: invitation.created_at,
+                expires_at: invitation.expires_at(),
+                is_expired: invitation.is_expired(),
+            }),
+    );
+
+    Ok(HttpResponse::Ok().json(&json!({
+        "ok": true,
+        "invitations": invitations,
+    })))
+}
+
+pub async fn resend_invitation(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+    json: web::Json<InviteJson>,
+) -> Result<HttpResponse> {
+    if !request_utils::is_authenticated(&request)? {
+        return Ok(request_utils::clear_cookie(&request));
+    }
+    let db = request.db_pool().map_err(|e| ApiServerError(Box::new(e)))?;
+    let conn = db.get().map_err(|e| ApiServerError(Box::new(e)))?;
+
+    let package = Package::get_by_slug(&package_slug, &conn)
+        .map_err(|e| ApiNotFound(MSG_PACKAGE_NOT_FOUND, Box::new(e)))?;
+
+    let user = request.user().map_err(|e| ApiServerError(Box::new(e)))?;
+    match renew_invitation(&package, user.id, &json, db, &conn)? {
+        Invitation::Account(account, invitation) => {
+            if !account.is_generated_email() {
+                if invitation.is_transferring {
+                    request.queue(SendOwnershipTransferEmail {
+                        to: account.email,
+                        package_name: package.name.clone(),
+                        token: invitation.token,
+                    })?;
+                } else {
+                    request.queue(SendCollaboratorInvitationEmail {
+                        to: account.email,
+                        package_name: package.name.clone(),
+                        token: invitation.token,
+                    })?;
+                }
+            }
+        }
+        Invitation::External(invitation) => {
+            request.queue(SendRegisterToCollabEmail {
+                to: invitation.external_user_email,
+                package_name: package.name.clone(),
+            })?;
+        }
+    }
+
+    Ok(HttpResponse::Ok().json(&json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_RESENT_INVITATION,
+    })))
+}
+
+/// Renews the pending invitation on behalf of `uid` with a new token and expiry,
+/// the caller sends it again.
+pub fn renew_invitation(
+    package: &Package,
+    uid: i32,
+    json: &InviteJson,
+    db: &DieselPgPool,
+    conn: &DieselPgConnection,
+) -> Result<Invitation> {
+    ensure_can_manage_invitations(package, uid, Action::Invite, conn)?;
+    let expires_at = get_expires_at(json.expiration_days)?;
+
+    match find_invitation(package, &json.user, db, conn)? {
+        Invitation::Account(account, invitation) => {
+            // only those who can transfer the package resend a transfer
+            if invitation.is_transferring {
+                ensure_can_manage_invitations(package, uid, Action::TransferOwnership, conn)?;
+            }
+            let invitation = invitation
+                .renew(expires_at, conn)
+                .map_err(|e| ApiServerError(Box::new(e)))?;
+            Ok(Invitation::Account(account, invitation))
+        }
+        Invitation::External(invitation) => {
+            let invitation = invitation
+                .renew(expires_at, conn)
+                .map_err(|e| ApiServerError(Box::new(e)))?;
+            Ok(Invitation::External(invitation))
+        }
+    }
+}
+
+pub async fn revoke_invitation(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+    json: web::Json<CollaboratorJson>,
+) -> Result<HttpResponse> {
+    if !request_utils::is_authenticated(&request)? {
+        return Ok(request_utils::clear_cookie(&request));
+    }
+    let db = request.db_pool().map_err(|e| ApiServerError(Box::new(e)))?;
+    let conn = db.get().map_err(|e| ApiServerError(Box::new(e)))?;
+
+    let package = Package::get_by_slug(&package_slug, &conn)
+        .map_err(|e| ApiNotFound(MSG_PACKAGE_NOT_FOUND, Box::new(e)))?;
+
+    let user = request.user().map_err(|e| ApiServerError(Box::new(e)))?;
+    delete_invitation(&package, user.id, &json.user, db, &conn)?;
+
+    Ok(HttpResponse::Ok().json(&json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_REVOKED_INVITATION,
+    })))
+}
+
+/// Revokes the pending invitation on behalf of `uid`.
+pub fn delete_invitation(
+    package: &Package,
+    uid: i32,
+    invited_user: &str,
+    db: &DieselPgPool,
+    conn: &DieselPgConnection,
+) -> Result<()> {
+    ensure_can_manage_invitations(package, uid, Action::Invite, conn)?;
+
+    match find_invitation(package, invited_user, db, conn)? {
+        Invitation::Account(_, invitation) => {
+            if invitation.is_transferring {
+                ensure_can_manage_invitations(package, uid, Action::TransferOwnership, conn)?;
+            }
+            invitation
+                .delete(conn)
+                .map_err(|e| ApiServerError(Box::new(e)))?;
+        }
+        Invitation::External(invitation) => {
+            invitation
+                .delete(conn)
+                .map_err(|e| ApiServerError(Box::new(e)))?;
+        }
+    }
+    Ok(())
+}
+
+/// A pending invitation, of an account or of an email address without one
+pub enum Invitation {
+    Account(Account, OwnerInvitation),
+    External(ExternalInvitation),
+}
+
+// The pending invitation of an account, or of an email address without one
+fn find_invitation(
+    package: &Package,
+    invited_user: &str,
+    db: &DieselPgPool,
+    conn: &DieselPgConnection,
+) -> Result<Invitation> {
+    match Account::get_by_email_or_gh_login(invited_user, db) {
+        Ok(account) => {
+            let invitation = OwnerInvitation::find_by_id(account.id, package.id, conn)
+                .map_err(|e| ApiNotFound(MSG_INVITATION_NOT_FOUND, Box::new(e)))?;
+            Ok(Invitation::Account(account, invitation))
+        }
+        Err(e) => {
+            if !invited_user.contains('@') {
+                return Err(ApiNotFound(MSG_INVITATION_NOT_FOUND, Box::new(e)));
+            }
+            let invitation = ExternalInvitation::find_by_id(invited_user, package.id, conn)
+                .map_err(|e| ApiNotFound(MSG_INVITATION_NOT_FOUND, Box::new(e)))?;
+            Ok(Invitation::External(invitation))
+        }
+    }
+}
+
+fn ensure_can_manage_invitations(
+    package: &Package,
+    uid: i32,
+    action: Action,
+    conn: &DieselPgConnection,
+) -> Result<()> {
+    let is_allowed =
+        policy::can(package, uid, action, conn).map_err(|e| ApiServerError(Box::new(e)))?;
+    if !is_allowed {
+        return Err(ApiForbidden(
+            MSG_UNAUTHORIZED_TO_MANAGE_INVITATIONS,
+            Box::new(Error::Generic(format!(
+                "Not allowed to {:?} on the package. uid: {}, package id: {}",
+                action, uid, package.id
+            ))),
+        ));
+    }
+    Ok(())
+}
+
+pub async fn change_role(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+    json: web::Json<CollaboratorRoleJson>,
+) -> Result<HttpResponse> {
+    if !request_utils::is_authenticated(&request)? {
+        return Ok(request_utils::clear_cookie(&request));
+    }
+    let db = request.db_pool().map_err(|e| ApiServerError(Box::new(e)))?;
+    let conn = db.get().map_err(|e| ApiServerError(Box::new(e)))?;
+
+    let package = Package::get_by_slug(&package_slug, &conn)
+        .map_err(|e| ApiNotFound(MSG_PACKAGE_NOT_FOUND, Box::new(e)))?;
+
+    let user = request.user().map_err(|e| ApiServerError(Box::new(e)))?;
+    update_collaborator_role(&package, user.id, &json, db, &conn)
+}
+
+/// Changes the role of a collaborator on behalf of `uid`. Demoting an owner waits for
+/// another owner when approvals are required.
+pub fn update_collaborator_role(
+    package: &Package,
+    uid: i32,
+    json: &CollaboratorRoleJson,
+    db: &DieselPgPool,
+    conn: &DieselPgConnection,
+) -> Result<HttpResponse> {
+    let is_allowed = policy::can(package, uid, Action::ChangeRole, conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    if !is_allowed {
+        return Err(ApiForbidden(
+            MSG_UNAUTHORIZED_TO_CHANGE_ROLE,
+            Box::new(Error::Generic(format!(
+                "Not allowed to {:?} on the package. uid: {}, package id: {}",
+                Action::ChangeRole,
+                uid,
+                package.id
+            ))),
+        ));
+    }
+    let account = Account::get_by_email_or_gh_login(&json.user, db)
+        .map_err(|e| ApiNotFound(MSG_ACCOUNT_NOT_FOUND, Box::new(e)))?;
+    let current_role = PackageCollaborator::get_role(package.id, account.id, conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?
+        .ok_or_else(|| {
+            ApiNotFound(
+                MSG_COLLABORATOR_NOT_FOUND,
+                Box::new(Error::Generic(format!(
+                    "Failure trying to change the role of a collaborator. requester id: {}, target id: {}, package id: {}",
+                    uid, account.id, package.id
+                ))),
+            )
+        })?;
+    // promoting a collaborator adds a co-owner, demoting an owner removes an ownership
+    if current_role == Role::Owner && json.role != Role::Owner {
+        ensure_not_last_owner(package, conn)?;
+        let needs_approval =
+            policy::requires_approval(package, conn).map_err(|e| ApiServerError(Box::new(e)))?;
+        if needs_approval {
+            return request_owner_approval(
+                package,
+                uid,
+                ApprovalAction::RemoveOwner,
+                Some(account.id),
+                Some(json.role),
+                None,
+                conn,
+            );
+        }
+    }
+    PackageCollaborator::update_role(package.id, account.id, json.role, conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+
+    Ok(HttpResponse::Ok().json(&json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_CHANGED_ROLE,
+    })))
+}
+
+/// Records a destructive action for another owner to approve, nothing changes until then.
+pub fn request_owner_approval(
+    package: &Package,
+    uid: i32,
+    action: ApprovalAction,
+    target_account_id: Option<i32>,
+    target_role: Option<Role>,
+    organization_id: Option<i32>,
+    conn: &DieselPgConnection,
+) -> Result<HttpResponse> {
+    let approval = OwnerApproval::create(
+        package.id,
+        action,
+        target_account_id,
+        target_role,
+        organization_id,
+        uid,
+        conn,
+    )
+    .map_err(|e| ApiBadRequest(MSG_OWNER_APPROVAL_ALREADY_REQUESTED, Box::new(e)))?;
+
+    Ok(HttpResponse::Ok().json(&json!({
+        "ok": true,
+        "msg": MSG_OWNER_APPROVAL_REQUESTED,
+        "pending_approval": true,
+        "approval_id": approval.id,
+    })))
+}
+
+pub async fn approve_owner_action(
+    request: HttpRequest,
+    Path((package_slug, approval_id)): Path<(String, i32)>,
+) -> Result<HttpResponse> {
+    if !request_utils::is_authenticated(&request)? {
+        return Ok(request_utils::clear_cookie(&request));
+    }
+    let db = request.db_pool().map_err(|e| ApiServerError(Box::new(e)))?;
+    let conn = db.get().map_err(|e| ApiServerError(Box::new(e)))?;
+
+    let package = Package::get_by_slug(&package_slug, &conn)
+        .map_err(|e| ApiNotFound(MSG_PACKAGE_NOT_FOUND, Box::new(e)))?;
+    let user = request.user().map_err(|e| ApiServerError(Box::new(e)))?;
+    let (approval, action) = get_approvable_action(&package, user.id, approval_id, &conn)?;
+
+    conn.transaction(|| -> Result<()> {
+        approval
+            .delete(&conn)
+            .map_err(|e| ApiServerError(Box::new(e)))?;
+        // the action runs on behalf of the owner who requested it
+        match action {
+            ApprovalAction::RemoveOwner => {
+                let target_id = approval.target_account_id.unwrap_or_default();
+                ensure_not_last_owner(&package, &conn)?;
+                let new_role = approval
+                    .target_role
+                    .and_then(Role::from_i32)
+                    .unwrap_or(Role::Collaborator);
+                PackageCollaborator::update_role(package.id, target_id, new_role, &conn)
+                    .map_err(|e| ApiServerError(Box::new(e)))?;
+            }
+            ApprovalAction::TransferOwnership => {
+                let invited_account =
+                    Account::get(approval.target_account_id.unwrap_or_default(), db)
+                        .map_err(|e| ApiNotFound(MSG_ACCOUNT_NOT_FOUND, Box::new(e)))?;
+                send_ownership_transfer(
+                    &request,
+                    &package,
+                    approval.requested_by,
+                    invited_account,
+                    &conn,
+                )?;
+            }
+            ApprovalAction::ScheduleDeletion => {
+                package_controller::schedule_deletion(&package, approval.requested_by, &conn)?;
+            }
+            ApprovalAction::TransferToOrganization => {
+                let organization =
+                    Organization::get(approval.organization_id.unwrap_or_default(), &conn)
+                        .map_err(|e| ApiNotFound(MSG_ORGANIZATION_NOT_FOUND, Box::new(e)))?;
+                package_controller::move_to_organization(
+                    &package,
+                    &organization,
+                    approval.requested_by,
+                    &conn,
+                )?;
+            }
+        }
+        Ok(())
+    })?;
+
+    Ok(HttpResponse::Ok().json(&json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_APPROVED_ACTION,
+    })))
+}
+
+/// The pending request `uid` can approve. A request whose requester is not an owner
+/// anymore is dismissed instead: nobody would stand behind it.
+pub fn get_approvable_action(
+    package: &Package,
+    uid: i32,
+    approval_id: i32,
+    conn: &DieselPgConnection,
+) -> Result<(OwnerApproval, ApprovalAction)> {
+    let approval = OwnerApproval::get(approval_id, package.id, conn)
+        .map_err(|e| ApiNotFound(MSG_OWNER_APPROVAL_NOT_FOUND, Box::new(e)))?;
+    let is_allowed = policy::can(package, uid, Action::Approve, conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    // the point is a second pair of eyes
+    if !is_allowed || approval.requested_by == uid {
+        return Err(ApiForbidden(
+            MSG_UNAUTHORIZED_TO_APPROVE,
+            Box::new(Error::Generic(format!(
+                "Not allowed to approve the request. uid: {}, approval id: {}",
+                uid, approval.id
+            ))),
+        ));
+    }
+    let requester_role = policy::get_role(package, approval.requested_by, conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    if requester_role != Some(Role::Owner) {
+        approval
+            .delete(conn)
+            .map_err(|e| ApiServerError(Box::new(e)))?;
+        return Err(ApiBadRequest(
+            MSG_OWNER_APPROVAL_OUTDATED,
+            Box::new(Error::Generic(format!(
+                "The requester is not an owner anymore. requester id: {}, approval id: {}",
+                approval.requested_by, approval.id
+            ))),
+        ));
+    }
+    let action = approval.get_action().ok_or_else(|| {
+        ApiServerError(Box::new(Error::Generic(format!(
+            "Unknown approval action: {}",
+            approval.action
+        ))))
+    })?;
+    Ok((approval, action))
+}
+
+pub async fn dismiss_owner_approval(
+    request: HttpRequest,
+    Path((package_slug, approval_id)): Path<(String, i32)>,
+) -> Result<HttpResponse> {
+    if !request_utils::is_authenticated(&request)? {
+        return Ok(request_utils::clear_cookie(&request));
+    }
+    let db = request.db_pool().map_err(|e| ApiServerError(Box::new(e)))?;
+    let conn = db.get().map_err(|e| ApiServerError(Box::new(e)))?;
+
+    let package = Package::get_by_slug(&package_slug, &conn)
+        .map_err(|e| ApiNotFound(MSG_PACKAGE_NOT_FOUND, Box::new(e)))?;
+    let approval = OwnerApproval::get(approval_id, package.id, &conn)
+        .map_err(|e| ApiNotFound(MSG_OWNER_APPROVAL_NOT_FOUND, Box::new(e)))?;
+
+    // any owner can turn a request down, its requester included
+    let user = request.user().map_err(|e| ApiServerError(Box::new(e)))?;
+    let is_allowed = policy::can(&package, user.id, Action::Approve, &conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    if !is_allowed {
+        return Err(ApiForbidden(
+            MSG_UNAUTHORIZED_TO_DISMISS_APPROVAL,
+            Box::new(Error::Generic(format!(
+                "Not allowed to dismiss the request. uid: {}, approval id: {}",
+                user.id, approval.id
+            ))),
+        ));
+    }
+    approval
+        .delete(&conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+
+    Ok(HttpResponse::Ok().json(&json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_DISMISSED_APPROVAL,
+    })))
+}
+
+fn ensure_not_last_owner(package: &Package, conn: &DieselPgConnection) -> Result<()> {
+    let owner_ids =
+        policy::get_owner_ids(package, conn).map_err(|e| ApiServerError(Box::new(e)))?;
+    if owner_ids.len() < 2 {
+        return Err(ApiBadRequest(
+            MSG_LAST_OWNER_OF_PACKAGE,
+            Box::new(Error::Generic(format!(
+                "Cannot remove the last owner of the package. package id: {}",
+                package.id
+            ))),
+        ));
+    }
+    Ok(())
+}
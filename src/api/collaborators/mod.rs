@@ -1,6 +1,8 @@
-use jelly::actix_web::web::{delete, post, resource, scope, ServiceConfig};
+use jelly::actix_web::web::{delete, get, post, put, resource, scope, ServiceConfig};
 
 pub mod controllers;
+#[cfg(test)]
+mod tests;
 pub mod views;
 
 pub fn configure(config: &mut ServiceConfig) {
@@ -13,12 +15,29 @@ pub fn configure(config: &mut ServiceConfig) {
                             post().to(controllers::add_collaborators),
                         ),
                     )
+                    .service(resource("/bulk").route(
+                        post().to(controllers::bulk_add_collaborators),
+                    ))
+                    .service(resource("/invitations")
+                        .route(get().to(controllers::list_invitations))
+                        .route(delete().to(controllers::revoke_invitation)),
+                    )
+                    .service(resource("/invitations/resend").route(
+                        post().to(controllers::resend_invitation),
+                    ))
                     .service(resource("/remove").route(
                         delete().to(controllers::remove_collaborator),
                     ))
                     .service(resource("/transfer").route(
                         post().to(controllers::transfer_ownership),
-                    )),
+                    ))
+                    .service(resource("/role").route(
+                        put().to(controllers::change_role),
+                    ))
+                    .service(resource("/approvals/{approval_id}")
+                        .route(post().to(controllers::approve_owner_action))
+                        .route(delete().to(controllers::dismiss_owner_approval)),
+                    ),
             )
             .service(resource("/handle").route(
                 post().to(controllers::handle_invite),
@@ -0,0 +1,502 @@
+use crate::api::collaborators::controllers::{
+    delete_invitation, get_approvable_action, remove_package_collaborator, renew_invitation,
+    update_collaborator_role, Invitation,
+};
+use crate::api::collaborators::views::{CollaboratorRoleJson, InviteJson};
+use crate::package_collaborators::models::expiration;
+use crate::package_collaborators::models::external_invitation::ExternalInvitation;
+use crate::package_collaborators::models::owner_approval::{ApprovalAction, OwnerApproval};
+use crate::package_collaborators::models::owner_invitation::OwnerInvitation;
+use crate::package_collaborators::package_collaborator::{PackageCollaborator, Role};
+use crate::packages::Package;
+use crate::test::util::{create_package, setup_user};
+use crate::test::{DatabaseTestContext, DB_POOL};
+
+use jelly::actix_web::http::StatusCode;
+use jelly::error::Error;
+use jelly::utils::error_constants::*;
+
+fn add_collaborator(package: &Package, email: &str, role: Role) -> i32 {
+    let conn = DB_POOL.get().unwrap();
+    let owner_id = PackageCollaborator::get_owner_ids(package.id, &conn).unwrap()[0];
+    let uid = setup_user(Some(email.to_string()), None);
+    PackageCollaborator::new_collaborator(package.id, uid, owner_id, &conn).unwrap();
+    PackageCollaborator::update_role(package.id, uid, role, &conn).unwrap();
+    uid
+}
+
+fn role_json(user: &str, role: Role) -> CollaboratorRoleJson {
+    CollaboratorRoleJson {
+        user: user.to_string(),
+        role,
+    }
+}
+
+#[actix_rt::test]
+async fn change_role_works_for_owners() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let owner_id = setup_user(Some("owner@host.com".to_string()), None);
+    let package = create_package(
+        "package1",
+        "https://github.com/owner/package1",
+        "",
+        Some(owner_id),
+    );
+    let uid = add_collaborator(&package, "collaborator@host.com", Role::Collaborator);
+    let conn = DB_POOL.get().unwrap();
+
+    let resp = update_collaborator_role(
+        &package,
+        owner_id,
+        &role_json("collaborator@host.com", Role::Maintainer),
+        &DB_POOL,
+        &conn,
+    )
+    .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        PackageCollaborator::get_role(package.id, uid, &conn).unwrap(),
+        Some(Role::Maintainer)
+    );
+
+    // Promoting adds a co-owner, who can then be demoted again
+    update_collaborator_role(
+        &package,
+        owner_id,
+        &role_json("collaborator@host.com", Role::Owner),
+        &DB_POOL,
+        &conn,
+    )
+    .unwrap();
+    assert_eq!(
+        PackageCollaborator::get_owner_ids(package.id, &conn).unwrap(),
+        vec![owner_id, uid]
+    );
+    update_collaborator_role(
+        &package,
+        owner_id,
+        &role_json("collaborator@host.com", Role::Publisher),
+        &DB_POOL,
+        &conn,
+    )
+    .unwrap();
+    assert_eq!(
+        PackageCollaborator::get_role(package.id, uid, &conn).unwrap(),
+        Some(Role::Publisher)
+    );
+}
+
+#[actix_rt::test]
+async fn change_role_is_forbidden_for_non_owners() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let owner_id = setup_user(Some("owner@host.com".to_string()), None);
+    let package = create_package(
+        "package1",
+        "https://github.com/owner/package1",
+        "",
+        Some(owner_id),
+    );
+    let maintainer_id = add_collaborator(&package, "maintainer@host.com", Role::Maintainer);
+    let uid = add_collaborator(&package, "collaborator@host.com", Role::Collaborator);
+    let outsider_id = setup_user(Some("outsider@host.com".to_string()), None);
+    let conn = DB_POOL.get().unwrap();
+
+    for requester_id in &[maintainer_id, uid, outsider_id] {
+        let result = update_collaborator_role(
+            &package,
+            *requester_id,
+            &role_json("collaborator@host.com", Role::Owner),
+            &DB_POOL,
+            &conn,
+        );
+        assert!(matches!(
+            result,
+            Err(Error::ApiForbidden(MSG_UNAUTHORIZED_TO_CHANGE_ROLE, _))
+        ));
+    }
+    assert_eq!(
+        PackageCollaborator::get_role(package.id, uid, &conn).unwrap(),
+        Some(Role::Collaborator)
+    );
+}
+
+#[actix_rt::test]
+async fn change_role_requires_a_collaborator_and_keeps_an_owner() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let owner_id = setup_user(Some("owner@host.com".to_string()), None);
+    let package = create_package(
+        "package1",
+        "https://github.com/owner/package1",
+        "",
+        Some(owner_id),
+    );
+    setup_user(Some("outsider@host.com".to_string()), None);
+    let conn = DB_POOL.get().unwrap();
+
+    let result = update_collaborator_role(
+        &package,
+        owner_id,
+        &role_json("unknown@host.com", Role::Maintainer),
+        &DB_POOL,
+        &conn,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::ApiNotFound(MSG_ACCOUNT_NOT_FOUND, _))
+    ));
+
+    let result = update_collaborator_role(
+        &package,
+        owner_id,
+        &role_json("outsider@host.com", Role::Maintainer),
+        &DB_POOL,
+        &conn,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::ApiNotFound(MSG_COLLABORATOR_NOT_FOUND, _))
+    ));
+
+    let result = update_collaborator_role(
+        &package,
+        owner_id,
+        &role_json("owner@host.com", Role::Maintainer),
+        &DB_POOL,
+        &conn,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::ApiBadRequest(MSG_LAST_OWNER_OF_PACKAGE, _))
+    ));
+    assert_eq!(
+        PackageCollaborator::get_role(package.id, owner_id, &conn).unwrap(),
+        Some(Role::Owner)
+    );
+}
+
+#[actix_rt::test]
+async fn remove_collaborator_only_removes_lower_roles() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let owner_id = setup_user(Some("owner@host.com".to_string()), None);
+    let package = create_package(
+        "package1",
+        "https://github.com/owner/package1",
+        "",
+        Some(owner_id),
+    );
+    let maintainer_id = add_collaborator(&package, "maintainer@host.com", Role::Maintainer);
+    let uid = add_collaborator(&package, "collaborator@host.com", Role::Collaborator);
+    let other_id = add_collaborator(&package, "other@host.com", Role::Collaborator);
+    let publisher_id = add_collaborator(&package, "publisher@host.com", Role::Publisher);
+    let conn = DB_POOL.get().unwrap();
+
+    let forbidden = [
+        (publisher_id, "other@host.com"),
+        (uid, "other@host.com"),
+        (uid, "maintainer@host.com"),
+        (maintainer_id, "owner@host.com"),
+    ];
+    for (requester_id, removed_user) in &forbidden {
+        let result =
+            remove_package_collaborator(&package, *requester_id, removed_user, &DB_POOL, &conn);
+        assert!(matches!(
+            result,
+            Err(Error::ApiForbidden(
+                MSG_UNAUTHORIZED_TO_REMOVE_COLLABORATOR,
+                _
+            ))
+        ));
+    }
+
+    let resp =
+        remove_package_collaborator(&package, uid, "publisher@host.com", &DB_POOL, &conn).unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp =
+        remove_package_collaborator(&package, maintainer_id, "other@host.com", &DB_POOL, &conn)
+            .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let resp =
+        remove_package_collaborator(&package, owner_id, "maintainer@host.com", &DB_POOL, &conn)
+            .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+
+    for removed_id in &[publisher_id, other_id, maintainer_id] {
+        assert_eq!(
+            PackageCollaborator::get_role(package.id, *removed_id, &conn).unwrap(),
+            None
+        );
+    }
+    assert_eq!(
+        PackageCollaborator::get_role(package.id, uid, &conn).unwrap(),
+        Some(Role::Collaborator)
+    );
+}
+
+fn request_removal(package: &Package, requested_by: i32, target_id: i32) -> OwnerApproval {
+    OwnerApproval::create(
+        package.id,
+        ApprovalAction::RemoveOwner,
+        Some(target_id),
+        Some(Role::Maintainer),
+        None,
+        requested_by,
+        &DB_POOL.get().unwrap(),
+    )
+    .unwrap()
+}
+
+#[actix_rt::test]
+async fn approvals_need_another_owner() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let owner_id = setup_user(Some("owner@host.com".to_string()), None);
+    let package = create_package(
+        "package1",
+        "https://github.com/owner/package1",
+        "",
+        Some(owner_id),
+    );
+    let co_owner_id = add_collaborator(&package, "co-owner@host.com", Role::Owner);
+    let third_owner_id = add_collaborator(&package, "third-owner@host.com", Role::Owner);
+    let maintainer_id = add_collaborator(&package, "maintainer@host.com", Role::Maintainer);
+    let conn = DB_POOL.get().unwrap();
+    let approval = request_removal(&package, owner_id, third_owner_id);
+
+    for uid in &[owner_id, maintainer_id] {
+        let result = get_approvable_action(&package, *uid, approval.id, &conn);
+        assert!(matches!(
+            result,
+            Err(Error::ApiForbidden(MSG_UNAUTHORIZED_TO_APPROVE, _))
+        ));
+    }
+    let result = get_approvable_action(&package, co_owner_id, approval.id + 1, &conn);
+    assert!(matches!(
+        result,
+        Err(Error::ApiNotFound(MSG_OWNER_APPROVAL_NOT_FOUND, _))
+    ));
+
+    let (approvable, action) =
+        get_approvable_action(&package, co_owner_id, approval.id, &conn).unwrap();
+    assert_eq!(approvable.id, approval.id);
+    assert_eq!(action, ApprovalAction::RemoveOwner);
+}
+
+#[actix_rt::test]
+async fn approvals_of_former_owners_are_dismissed() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let owner_id = setup_user(Some("owner@host.com".to_string()), None);
+    let package = create_package(
+        "package1",
+        "https://github.com/owner/package1",
+        "",
+        Some(owner_id),
+    );
+    let co_owner_id = add_collaborator(&package, "co-owner@host.com", Role::Owner);
+    let third_owner_id = add_collaborator(&package, "third-owner@host.com", Role::Owner);
+    let conn = DB_POOL.get().unwrap();
+    let approval = request_removal(&package, co_owner_id, third_owner_id);
+
+    // The requester lost its ownership before anybody approved
+    PackageCollaborator::update_role(package.id, co_owner_id, Role::Collaborator, &conn).unwrap();
+    let result = get_approvable_action(&package, owner_id, approval.id, &conn);
+    assert!(matches!(
+        result,
+        Err(Error::ApiBadRequest(MSG_OWNER_APPROVAL_OUTDATED, _))
+    ));
+    assert!(OwnerApproval::get_by_package_id(package.id, &conn)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        PackageCollaborator::get_role(package.id, third_owner_id, &conn).unwrap(),
+        Some(Role::Owner)
+    );
+}
+
+fn invite_json(user: &str, expiration_days: Option<i64>) -> InviteJson {
+    InviteJson {
+        user: user.to_string(),
+        expiration_days,
+    }
+}
+
+#[actix_rt::test]
+async fn renew_invitation_gives_a_new_token_and_expiry() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let owner_id = setup_user(Some("owner@host.com".to_string()), None);
+    let package = create_package(
+        "package1",
+        "https://github.com/owner/package1",
+        "",
+        Some(owner_id),
+    );
+    let invited_id = setup_user(Some("invited@host.com".to_string()), None);
+    let conn = DB_POOL.get().unwrap();
+    let invitation =
+        OwnerInvitation::create(invited_id, owner_id, package.id, None, None, None, &conn).unwrap();
+    ExternalInvitation::create("external@host.com", owner_id, package.id, None, &conn).unwrap();
+
+    let renewed = renew_invitation(
+        &package,
+        owner_id,
+        &invite_json("invited@host.com", Some(3)),
+        &DB_POOL,
+        &conn,
+    )
+    .unwrap();
+    match renewed {
+        Invitation::Account(account, renewed) => {
+            assert_eq!(account.id, invited_id);
+            assert_ne!(renewed.token, invitation.token);
+            let expires_at = renewed.expires_at.unwrap();
+            assert!(expires_at > expiration::expires_in(2));
+            assert!(expires_at <= expiration::expires_in(3));
+        }
+        Invitation::External(_) => panic!("expected the invitation of an account"),
+    }
+
+    let renewed = renew_invitation(
+        &package,
+        owner_id,
+        &invite_json("external@host.com", None),
+        &DB_POOL,
+        &conn,
+    )
+    .unwrap();
+    assert!(matches!(
+        renewed,
+        Invitation::External(ref invitation) if invitation.expires_at.is_none()
+    ));
+
+    let result = renew_invitation(
+        &package,
+        owner_id,
+        &invite_json("invited@host.com", Some(0)),
+        &DB_POOL,
+        &conn,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::ApiBadRequest(MSG_INVALID_EXPIRATION_DAYS, _))
+    ));
+    let result = renew_invitation(
+        &package,
+        owner_id,
+        &invite_json("nobody@host.com", None),
+        &DB_POOL,
+        &conn,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::ApiNotFound(MSG_INVITATION_NOT_FOUND, _))
+    ));
+}
+
+#[actix_rt::test]
+async fn only_owners_resend_and_revoke_transfers() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let owner_id = setup_user(Some("owner@host.com".to_string()), None);
+    let package = create_package(
+        "package1",
+        "https://github.com/owner/package1",
+        "",
+        Some(owner_id),
+    );
+    let maintainer_id = add_collaborator(&package, "maintainer@host.com", Role::Maintainer);
+    let publisher_id = add_collaborator(&package, "publisher@host.com", Role::Publisher);
+    let invited_id = setup_user(Some("invited@host.com".to_string()), None);
+    let conn = DB_POOL.get().unwrap();
+    OwnerInvitation::create(
+        invited_id,
+        owner_id,
+        package.id,
+        Some(true),
+        None,
+        None,
+        &conn,
+    )
+    .unwrap();
+
+    let result = renew_invitation(
+        &package,
+        publisher_id,
+        &invite_json("invited@host.com", None),
+        &DB_POOL,
+        &conn,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::ApiForbidden(
+            MSG_UNAUTHORIZED_TO_MANAGE_INVITATIONS,
+            _
+        ))
+    ));
+    let result = renew_invitation(
+        &package,
+        maintainer_id,
+        &invite_json("invited@host.com", None),
+        &DB_POOL,
+        &conn,
+    );
+    assert!(matches!(
+        result,
+        Err(Error::ApiForbidden(
+            MSG_UNAUTHORIZED_TO_MANAGE_INVITATIONS,
+            _
+        ))
+    ));
+    let result = delete_invitation(&package, maintainer_id, "invited@host.com", &DB_POOL, &conn);
+    assert!(matches!(
+        result,
+        Err(Error::ApiForbidden(
+            MSG_UNAUTHORIZED_TO_MANAGE_INVITATIONS,
+            _
+        ))
+    ));
+    assert!(OwnerInvitation::find_by_id(invited_id, package.id, &conn).is_ok());
+
+    delete_invitation(&package, owner_id, "invited@host.com", &DB_POOL, &conn).unwrap();
+    assert!(OwnerInvitation::find_by_id(invited_id, package.id, &conn).is_err());
+}
+
+#[actix_rt::test]
+async fn revoke_invitation_works_for_accounts_and_emails() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let owner_id = setup_user(Some("owner@host.com".to_string()), None);
+    let package = create_package(
+        "package1",
+        "https://github.com/owner/package1",
+        "",
+        Some(owner_id),
+    );
+    let maintainer_id = add_collaborator(&package, "maintainer@host.com", Role::Maintainer);
+    let invited_id = setup_user(Some("invited@host.com".to_string()), None);
+    let conn = DB_POOL.get().unwrap();
+    OwnerInvitation::create(invited_id, owner_id, package.id, None, None, None, &conn).unwrap();
+    ExternalInvitation::create("external@host.com", owner_id, package.id, None, &conn).unwrap();
+
+    delete_invitation(&package, maintainer_id, "invited@host.com", &DB_POOL, &conn).unwrap();
+    delete_invitation(
+        &package,
+        maintainer_id,
+        "external@host.com",
+        &DB_POOL,
+        &conn,
+    )
+    .unwrap();
+    assert!(OwnerInvitation::find_by_id(invited_id, package.id, &conn).is_err());
+    assert!(ExternalInvitation::find_by_id("external@host.com", package.id, &conn).is_err());
+
+    let result = delete_invitation(&package, owner_id, "external@host.com", &DB_POOL, &conn);
+    assert!(matches!(
+        result,
+        Err(Error::ApiNotFound(MSG_INVITATION_NOT_FOUND, _))
+    ));
+}
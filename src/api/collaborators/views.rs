@@ -1,12 +1,61 @@
+use jelly::chrono::NaiveDateTime;
 use serde::{Deserialize, Serialize};
 
+use crate::package_collaborators::package_collaborator::Role;
+
 #[derive(Deserialize)]
 pub struct CollaboratorJson {
     pub user: String,
 }
 
+#[derive(Deserialize)]
+pub struct CollaboratorRoleJson {
+    pub user: String,
+    pub role: Role,
+}
+
+#[derive(Deserialize)]
+pub struct InviteJson {
+    pub user: String,
+    /// `OWNERSHIP_INVITATIONS_EXPIRATION_DAYS` when not chosen
+    pub expiration_days: Option<i64>,
+}
+
+#[derive(Deserialize)]
+pub struct BulkInviteJson {
+    pub users: Vec<String>,
+    pub expiration_days: Option<i64>,
+}
+
 #[derive(Deserialize, Serialize, Debug, Copy, Clone)]
 pub struct InvitationResponse {
     pub package_id: i32,
     pub accepted: bool,
 }
+
+#[derive(Serialize, Debug, Copy, Clone, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum InvitationKind {
+    Collaborator,
+    Transfer,
+    /// Sent to an email address without a Movey account
+    External,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PendingInvitation {
+    pub user: String,
+    pub kind: InvitationKind,
+    pub invited_by: String,
+    pub created_at: NaiveDateTime,
+    pub expires_at: NaiveDateTime,
+    pub is_expired: bool,
+}
+
+/// The outcome of one invitation of a bulk invite.
+#[derive(Serialize, Debug)]
+pub struct BulkInviteResult {
+    pub user: String,
+    pub ok: bool,
+    pub msg: &'static str,
+}
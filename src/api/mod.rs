@@ -3,6 +3,7 @@
 use jelly::actix_web::web::ServiceConfig;
 
 pub mod collaborators;
+pub mod organizations;
 pub mod package;
 pub mod setting;
 
@@ -10,4 +11,5 @@ pub fn configure(config: &mut ServiceConfig) {
     package::configure(config);
     setting::configure(config);
     collaborators::configure(config);
+    organizations::configure(config);
 }
@@ -0,0 +1,256 @@
+use diesel::result::DatabaseErrorKind;
+use diesel::result::Error as DBError;
+use jelly::actix_web::web;
+use jelly::actix_web::web::Path;
+use jelly::prelude::Error::*;
+use jelly::prelude::*;
+use jelly::utils::error_constants::*;
+use jelly::{DieselPgConnection, Result};
+use serde_json::json;
+
+use crate::accounts::Account;
+use crate::api::organizations::views::{InvitationResponseJson, MemberJson, OrganizationJson};
+use crate::organizations::jobs::SendOrganizationInvitationEmail;
+use crate::organizations::models::invitation::OrganizationInvitation;
+use crate::organizations::models::{Organization, OrganizationRole};
+use crate::utils::request_utils;
+
+pub async fn create_organization(
+    request: HttpRequest,
+    json: web::Json<OrganizationJson>,
+) -> Result<HttpResponse> {
+    if !request_utils::is_authenticated(&request)? {
+        return Ok(request_utils::clear_cookie(&request));
+    }
+    let db = request.db_pool().map_err(|e| ApiServerError(Box::new(e)))?;
+    let conn = db.get().map_err(|e| ApiServerError(Box::new(e)))?;
+    let user = request.user().map_err(|e| ApiServerError(Box::new(e)))?;
+
+    if slug::slugify(json.name.trim()).is_empty() {
+        return Err(ApiBadRequest(
+            MSG_INVALID_ORGANIZATION_NAME,
+            Box::new(Error::Generic(format!(
+                "Invalid organization name: {}",
+                json.name
+            ))),
+        ));
+    }
+    let description = json.description.as_deref().unwrap_or("");
+    let organization =
+        Organization::create(&json.name, description, user.id, &conn).map_err(|e| match e {
+            Error::Database(DBError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+                ApiBadRequest(MSG_ORGANIZATION_SLUG_TAKEN, Box::new(e))
+            }
+            _ => ApiServerError(Box::new(e)),
+        })?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_CREATED_ORGANIZATION,
+        "slug": organization.slug,
+    })))
+}
+
+/// Invites the account, it joins the organization once it accepts the invitation from the
+/// email or its settings.
+pub async fn add_member(
+    request: HttpRequest,
+    Path(organization_slug): Path<String>,
+    json: web::Json<MemberJson>,
+) -> Result<HttpResponse> {
+    if !request_utils::is_authenticated(&request)? {
+        return Ok(request_utils::clear_cookie(&request));
+    }
+    let (organization, account, conn) =
+        get_administered_organization(&request, &organization_slug, &json.user)?;
+    let role = organization
+        .get_member_role(account.id, &conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    if role.is_some() {
+        return Err(ApiBadRequest(
+            MSG_MEMBER_ALREADY_EXISTED,
+            Box::new(Error::Generic(format!(
+                "Already a member of the organization. uid: {}, organization id: {}",
+                account.id, organization.id
+            ))),
+        ));
+    }
+    let user = request.user().map_err(|e| ApiServerError(Box::new(e)))?;
+    let invitation = OrganizationInvitation::create(
+        organization.id,
+        account.id,
+        user.id,
+        json.role.unwrap_or(OrganizationRole::Member),
+        &conn,
+    )
+    .map_err(|e| ApiServerError(Box::new(e)))?;
+    if !account.is_generated_email() {
+        request.queue(SendOrganizationInvitationEmail {
+            to: account.email,
+            organization_name: organization.name,
+            token: invitation.token,
+        })?;
+    }
+
+    Ok(HttpResponse::Ok().json(json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_INVITED_MEMBER,
+    })))
+}
+
+pub async fn update_member(
+    request: HttpRequest,
+    Path(organization_slug): Path<String>,
+    json: web::Json<MemberJson>,
+) -> Result<HttpResponse> {
+    if !request_utils::is_authenticated(&request)? {
+        return Ok(request_utils::clear_cookie(&request));
+    }
+    let (organization, account, conn) =
+        get_administered_organization(&request, &organization_slug, &json.user)?;
+    let role = json.role.unwrap_or(OrganizationRole::Member);
+    if role != OrganizationRole::Admin {
+        ensure_not_last_admin(&organization, account.id, &conn)?;
+    }
+    let updated_members = organization
+        .update_member_role(account.id, role, &conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    if updated_members == 0 {
+        return Err(ApiNotFound(
+            MSG_MEMBER_NOT_FOUND,
+            Box::new(Error::Generic(format!(
+                "Not a member of the organization. uid: {}, organization id: {}",
+                account.id, organization.id
+            ))),
+        ));
+    }
+
+    Ok(HttpResponse::Ok().json(json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_UPDATED_MEMBER,
+    })))
+}
+
+pub async fn remove_member(
+    request: HttpRequest,
+    Path(organization_slug): Path<String>,
+    json: web::Json<MemberJson>,
+) -> Result<HttpResponse> {
+    if !request_utils::is_authenticated(&request)? {
+        return Ok(request_utils::clear_cookie(&request));
+    }
+    let (organization, account, conn) =
+        get_administered_organization(&request, &organization_slug, &json.user)?;
+    ensure_not_last_admin(&organization, account.id, &conn)?;
+    let removed_members = organization
+        .remove_member(account.id, &conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    if removed_members == 0 {
+        return Err(ApiNotFound(
+            MSG_MEMBER_NOT_FOUND,
+            Box::new(Error::Generic(format!(
+                "Not a member of the organization. uid: {}, organization id: {}",
+                account.id, organization.id
+            ))),
+        ));
+    }
+
+    Ok(HttpResponse::Ok().json(json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_REMOVED_MEMBER,
+    })))
+}
+
+/// Accepts or declines the invitation of the signed in account to the organization.
+pub async fn handle_invitation(
+    request: HttpRequest,
+    Path(organization_slug): Path<String>,
+    json: web::Json<InvitationResponseJson>,
+) -> Result<HttpResponse> {
+    if !request_utils::is_authenticated(&request)? {
+        return Ok(request_utils::clear_cookie(&request));
+    }
+    let db = request.db_pool().map_err(|e| ApiServerError(Box::new(e)))?;
+    let conn = db.get().map_err(|e| ApiServerError(Box::new(e)))?;
+    let organization = Organization::get_by_slug(&organization_slug, &conn)
+        .map_err(|e| ApiNotFound(MSG_ORGANIZATION_NOT_FOUND, Box::new(e)))?;
+    let user = request.user().map_err(|e| ApiServerError(Box::new(e)))?;
+    let invitation = OrganizationInvitation::find_by_id(user.id, organization.id, &conn)
+        .map_err(|e| ApiNotFound(MSG_INVITATION_NOT_FOUND, Box::new(e)))?;
+    if invitation.is_expired() {
+        return Err(ApiBadRequest(
+            MSG_INVITATION_EXPIRED,
+            Box::new(Error::Generic(format!(
+                "Invitation is expired. invited id: {}, organization id: {}",
+                invitation.invited_user_id, invitation.organization_id
+            ))),
+        ));
+    }
+    let message = if json.accepted {
+        invitation
+            .accept(&conn)
+            .map_err(|e| ApiServerError(Box::new(e)))?;
+        MSG_SUCCESSFULLY_JOINED_ORGANIZATION
+    } else {
+        invitation
+            .delete(&conn)
+            .map_err(|e| ApiServerError(Box::new(e)))?;
+        MSG_SUCCESSFULLY_DECLINED_INVITATION
+    };
+
+    Ok(HttpResponse::Ok().json(json!({
+        "ok": true,
+        "msg": message,
+    })))
+}
+
+// Only admins manage the members of an organization
+fn get_administered_organization(
+    request: &HttpRequest,
+    organization_slug: &str,
+    member: &str,
+) -> Result<(Organization, Account, DieselPgConnection)> {
+    let db = request.db_pool().map_err(|e| ApiServerError(Box::new(e)))?;
+    let conn = db.get().map_err(|e| ApiServerError(Box::new(e)))?;
+    let organization = Organization::get_by_slug(organization_slug, &conn)
+        .map_err(|e| ApiNotFound(MSG_ORGANIZATION_NOT_FOUND, Box::new(e)))?;
+    let user = request.user().map_err(|e| ApiServerError(Box::new(e)))?;
+    let is_admin = organization
+        .is_admin(user.id, &conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    if !is_admin {
+        return Err(ApiForbidden(
+            MSG_UNAUTHORIZED_TO_MANAGE_ORGANIZATION,
+            Box::new(Error::Generic(format!(
+                "Not an admin of the organization. uid: {}, organization id: {}",
+                user.id, organization.id
+            ))),
+        ));
+    }
+    let account = Account::get_by_email_or_gh_login(member, db)
+        .map_err(|e| ApiNotFound(MSG_ACCOUNT_NOT_FOUND, Box::new(e)))?;
+    Ok((organization, account, conn))
+}
+
+fn ensure_not_last_admin(
+    organization: &Organization,
+    account_id: i32,
+    conn: &DieselPgConnection,
+) -> Result<()> {
+    let is_admin = organization
+        .is_admin(account_id, conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    let admins_count = organization
+        .count_admins(conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    if is_admin && admins_count <= 1 {
+        return Err(ApiBadRequest(
+            MSG_LAST_ORGANIZATION_ADMIN,
+            Box::new(Error::Generic(format!(
+                "Cannot remove the last admin of the organization. organization id: {}",
+                organization.id
+            ))),
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,21 @@
+use jelly::actix_web::web::{delete, post, put, resource, scope, ServiceConfig};
+
+pub mod controllers;
+pub mod views;
+
+pub fn configure(config: &mut ServiceConfig) {
+    config.service(
+        scope("/api/v1/organizations")
+            .service(resource("").route(post().to(controllers::create_organization)))
+            .service(
+                resource("/{organization_slug}/members")
+                    .route(post().to(controllers::add_member))
+                    .route(put().to(controllers::update_member))
+                    .route(delete().to(controllers::remove_member)),
+            )
+            .service(
+                resource("/{organization_slug}/invitation")
+                    .route(post().to(controllers::handle_invitation)),
+            ),
+    );
+}
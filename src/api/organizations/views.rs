@@ -0,0 +1,20 @@
+use serde::Deserialize;
+
+use crate::organizations::models::OrganizationRole;
+
+#[derive(Deserialize)]
+pub struct OrganizationJson {
+    pub name: String,
+    pub description: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct MemberJson {
+    pub user: String,
+    pub role: Option<OrganizationRole>,
+}
+
+#[derive(Deserialize)]
+pub struct InvitationResponseJson {
+    pub accepted: bool,
+}
@@ -5,20 +5,49 @@ use jelly::actix_web::HttpRequest;
 
 use diesel::result::DatabaseErrorKind;
 use diesel::result::Error as DBError;
+use diesel::Connection;
+use jelly::accounts::User;
+#[allow(unused_imports)]
+use jelly::actix_session::UserSession;
 use jelly::actix_web::web;
+use jelly::actix_web::web::Path;
+use jelly::anyhow::anyhow;
+use jelly::prelude::Error::*;
 use jelly::prelude::*;
-use jelly::Result;
+use jelly::utils::error_constants::*;
+use jelly::{DieselPgPool, Result};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 
 #[cfg(not(test))]
 use crate::github_service::GithubService;
+#[cfg(not(test))]
+use crate::github_service::GithubService as GithubClient;
+#[cfg(test)]
+use crate::github_service::MockGithubService as GithubClient;
 #[cfg(test)]
 use crate::test::mock::GithubService;
 
-use crate::api::package::view::PackageBadgeRespond;
+use crate::accounts::Account;
+use crate::api::collaborators::controllers::request_owner_approval;
+use crate::api::package::view::{
+    ClaimJson, ClaimMethod, DeprecationJson, OrganizationTransferJson, PackageBadgeRespond,
+    PackageDeprecationRespond, RenameJson,
+};
+use crate::constants::GITHUB_ADMIN_REPOSITORY_SESSION_KEY;
+use crate::organizations::models::Organization;
+use crate::package_collaborators::models::owner_approval::ApprovalAction;
+use crate::package_collaborators::models::package_claim::{PackageClaim, VERIFICATION_FILE};
+use crate::package_collaborators::policy::{self, Action};
+use crate::packages::docs::search::search_symbols;
 use crate::packages::Package;
-use crate::packages::models::{PackageSortField, PackageSortOrder};
+use crate::packages::models::audit_log::{PackageAuditAction, PackageAuditLog};
+use crate::packages::models::{
+    DeletionBlocker, PackageSearchFilters, PackageSortField, PackageSortOrder,
+};
+use crate::packages::platform::Platform;
 use crate::settings::models::token::ApiToken;
+use crate::utils::license::parse_license_filter;
 use crate::utils::presenter::validate_version;
 
 #[derive(Serialize, Deserialize)]
@@ -32,6 +61,14 @@ pub struct PackageRequest {
 #[derive(Serialize, Deserialize)]
 pub struct PackageSearch {
     search_query: String,
+    platform: Option<String>,
+    license: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SymbolSearch {
+    search_query: String,
+    package: Option<String>,
 }
 
 pub async fn register_package(
@@ -81,6 +118,21 @@ pub async fn register_package(
             hints.join("; ")
         )));
     }
+    // Archived packages and packages scheduled for deletion are read-only
+    if let Ok(conn) = db.get() {
+        if let Ok(package) =
+            Package::get_by_name_and_repo_url(&github_data.name, &req.github_repo_url, &conn)
+        {
+            if package.is_read_only() {
+                let domain = std::env::var("JELLY_DOMAIN").expect("JELLY_DOMAIN is not set");
+                return Ok(HttpResponse::BadRequest().body(format!(
+                    "Cannot upload package.\nThe package at {}/packages/{} is archived or \
+                    scheduled for deletion, it doesn't accept new versions.",
+                    domain, package.slug
+                )));
+            }
+        }
+    }
     let result = Package::create_from_crawled_data(
         &req.github_repo_url,
         &github_data.description.clone(),
@@ -152,12 +204,600 @@ pub async fn increase_download_count(
     if let Ok(res) =
         Package::increase_download_count(&form.url, &form.rev, &form.subdir, &service, db)
     {
-        Ok(HttpResponse::Ok().body(res.to_string()))
+        let mut response = HttpResponse::Ok();
+        let package = db
+            .get()
+            .ok()
+            .and_then(|conn| Package::get_by_download_info(&form.url, &form.subdir, &conn).ok())
+            .flatten();
+        if let Some(warning) = package.and_then(|package| package.deprecation_warning()) {
+            response.header("Warning", deprecation_warning_header(&warning));
+        }
+        Ok(response.body(res.to_string()))
     } else {
         Ok(HttpResponse::NotFound().body("Cannot find url or rev."))
     }
 }
 
+// `Warning: 299 movey "..."`, header values can only contain visible ASCII characters
+fn deprecation_warning_header(warning: &str) -> String {
+    let warning: String = warning
+        .chars()
+        .map(|c| match c {
+            '"' | '\\' => '\'',
+            c if c.is_ascii() && !c.is_ascii_control() => c,
+            _ => ' ',
+        })
+        .collect();
+    format!("299 movey \"{}\"", warning)
+}
+
+pub async fn get_deprecation(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+) -> Result<HttpResponse> {
+    let db = request.db_pool().map_err(|e| ApiServerError(Box::new(e)))?;
+    let conn = db.get().map_err(|e| ApiServerError(Box::new(e)))?;
+    let package = Package::get_by_slug(&package_slug, &conn)
+        .map_err(|e| ApiNotFound(MSG_PACKAGE_NOT_FOUND, Box::new(e)))?;
+    Ok(HttpResponse::Ok().json(PackageDeprecationRespond::from(&package)))
+}
+
+pub async fn deprecate_package(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+    json: web::Json<DeprecationJson>,
+) -> Result<HttpResponse> {
+    let (package, uid, conn) = get_authorized_package(
+        &request,
+        &package_slug,
+        Action::Deprecate,
+        MSG_UNAUTHORIZED_TO_DEPRECATE_PACKAGE,
+    )?;
+    if json.reason.trim().is_empty() {
+        return Err(ApiBadRequest(
+            MSG_DEPRECATION_REASON_REQUIRED,
+            Box::new(Error::Generic(String::from("Missing deprecation reason."))),
+        ));
+    }
+    let successor = match json.successor.as_deref().map(str::trim) {
+        Some(successor_slug) if !successor_slug.is_empty() => {
+            let successor = Package::get_by_slug(successor_slug, &conn)
+                .map_err(|e| ApiBadRequest(MSG_SUCCESSOR_NOT_FOUND, Box::new(e)))?;
+            if successor.id == package.id {
+                return Err(ApiBadRequest(
+                    MSG_SUCCESSOR_NOT_FOUND,
+                    Box::new(Error::Generic(String::from(
+                        "A package cannot succeed itself.",
+                    ))),
+                ));
+            }
+            Some(successor.slug)
+        }
+        _ => None,
+    };
+    let package = package
+        .deprecate(&json.reason, successor.as_deref(), &conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    log_package_action(
+        &package,
+        uid,
+        PackageAuditAction::Deprecate,
+        package.deprecation_reason.as_deref(),
+        &conn,
+    )?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_DEPRECATED_PACKAGE,
+        "deprecation": PackageDeprecationRespond::from(&package),
+    })))
+}
+
+pub async fn undeprecate_package(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+) -> Result<HttpResponse> {
+    let (package, uid, conn) = get_authorized_package(
+        &request,
+        &package_slug,
+        Action::Deprecate,
+        MSG_UNAUTHORIZED_TO_DEPRECATE_PACKAGE,
+    )?;
+    let package = package
+        .undeprecate(&conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    log_package_action(&package, uid, PackageAuditAction::Undeprecate, None, &conn)?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_UNDEPRECATED_PACKAGE,
+        "deprecation": PackageDeprecationRespond::from(&package),
+    })))
+}
+
+pub async fn rename_package(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+    json: web::Json<RenameJson>,
+) -> Result<HttpResponse> {
+    let (package, uid, conn) = get_authorized_package(
+        &request,
+        &package_slug,
+        Action::Administer,
+        MSG_UNAUTHORIZED_TO_RENAME_PACKAGE,
+    )?;
+    let new_slug = json.slug.trim();
+    if new_slug.is_empty() || slug::slugify(new_slug) != new_slug {
+        return Err(ApiBadRequest(
+            MSG_INVALID_PACKAGE_SLUG,
+            Box::new(Error::Generic(format!(
+                "Invalid package slug: {}",
+                new_slug
+            ))),
+        ));
+    }
+    // Old slugs of other packages keep redirecting to them
+    if let Some(owner_id) = Package::get_slug_history_owner(new_slug, &conn)? {
+        if owner_id != package.id {
+            return Err(ApiBadRequest(
+                MSG_PACKAGE_SLUG_TAKEN,
+                Box::new(Error::Generic(format!(
+                    "Package slug {} redirects to another package",
+                    new_slug
+                ))),
+            ));
+        }
+    }
+    let old_slug = package.slug.clone();
+    let package = package.rename_slug(new_slug, &conn).map_err(|e| match e {
+        Error::Database(DBError::DatabaseError(DatabaseErrorKind::UniqueViolation, _)) => {
+            ApiBadRequest(MSG_PACKAGE_SLUG_TAKEN, Box::new(e))
+        }
+        _ => ApiServerError(Box::new(e)),
+    })?;
+    log_package_action(
+        &package,
+        uid,
+        PackageAuditAction::Rename,
+        Some(&format!("{} -> {}", old_slug, package.slug)),
+        &conn,
+    )?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_RENAMED_PACKAGE,
+        "slug": package.slug,
+    })))
+}
+
+pub async fn transfer_to_organization(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+    json: web::Json<OrganizationTransferJson>,
+) -> Result<HttpResponse> {
+    let (package, uid, conn) = get_authorized_package(
+        &request,
+        &package_slug,
+        Action::Administer,
+        MSG_UNAUTHORIZED_TO_TRANSFER_TO_ORGANIZATION,
+    )?;
+    if package.organization_id.is_some() {
+        return Err(ApiBadRequest(
+            MSG_PACKAGE_ALREADY_OWNED_BY_ORGANIZATION,
+            Box::new(Error::Generic(format!(
+                "Package is already owned by an organization. package id: {}",
+                package.id
+            ))),
+        ));
+    }
+    let organization = Organization::get_by_slug(&json.organization, &conn)
+        .map_err(|e| ApiNotFound(MSG_ORGANIZATION_NOT_FOUND, Box::new(e)))?;
+    let is_admin = organization
+        .is_admin(uid, &conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    if !is_admin {
+        return Err(ApiForbidden(
+            MSG_UNAUTHORIZED_TO_TRANSFER_TO_ORGANIZATION,
+            Box::new(Error::Generic(format!(
+                "Not an admin of the organization. uid: {}, organization id: {}",
+                uid, organization.id
+            ))),
+        ));
+    }
+    if requires_approval(&package, &conn)? {
+        return request_owner_approval(
+            &package,
+            uid,
+            ApprovalAction::TransferToOrganization,
+            None,
+            None,
+            Some(organization.id),
+            &conn,
+        );
+    }
+    move_to_organization(&package, &organization, uid, &conn)?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_TRANSFERRED_TO_ORGANIZATION,
+    })))
+}
+
+/// Transfers the package once its owners agreed, also run when a second owner approves.
+pub fn move_to_organization(
+    package: &Package,
+    organization: &Organization,
+    uid: i32,
+    conn: &jelly::DieselPgConnection,
+) -> Result<()> {
+    organization
+        .transfer_package(package, conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    log_package_action(
+        package,
+        uid,
+        PackageAuditAction::TransferToOrganization,
+        Some(&organization.slug),
+        conn,
+    )
+}
+
+pub async fn archive_package(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+) -> Result<HttpResponse> {
+    let (package, uid, conn) = get_authorized_package(
+        &request,
+        &package_slug,
+        Action::Administer,
+        MSG_UNAUTHORIZED_TO_ARCHIVE_PACKAGE,
+    )?;
+    let package = conn.transaction(|| -> Result<Package> {
+        let package = package
+            .archive(&conn)
+            .map_err(|e| ApiServerError(Box::new(e)))?;
+        log_package_action(&package, uid, PackageAuditAction::Archive, None, &conn)?;
+        Ok(package)
+    })?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_ARCHIVED_PACKAGE,
+        "archived_at": package.archived_at,
+    })))
+}
+
+pub async fn unarchive_package(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+) -> Result<HttpResponse> {
+    let (package, uid, conn) = get_authorized_package(
+        &request,
+        &package_slug,
+        Action::Administer,
+        MSG_UNAUTHORIZED_TO_ARCHIVE_PACKAGE,
+    )?;
+    let package = conn.transaction(|| -> Result<Package> {
+        let package = package
+            .unarchive(&conn)
+            .map_err(|e| ApiServerError(Box::new(e)))?;
+        log_package_action(&package, uid, PackageAuditAction::Unarchive, None, &conn)?;
+        Ok(package)
+    })?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_UNARCHIVED_PACKAGE,
+        "archived_at": package.archived_at,
+    })))
+}
+
+pub async fn schedule_package_deletion(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+) -> Result<HttpResponse> {
+    let (package, uid, conn) = get_authorized_package(
+        &request,
+        &package_slug,
+        Action::Administer,
+        MSG_UNAUTHORIZED_TO_DELETE_PACKAGE,
+    )?;
+    check_deletion_blocker(&package, &conn)?;
+    if requires_approval(&package, &conn)? {
+        return request_owner_approval(
+            &package,
+            uid,
+            ApprovalAction::ScheduleDeletion,
+            None,
+            None,
+            None,
+            &conn,
+        );
+    }
+    let package = schedule_deletion(&package, uid, &conn)?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_SCHEDULED_PACKAGE_DELETION,
+        "deletion_scheduled_at": package.deletion_scheduled_at,
+    })))
+}
+
+/// Schedules the deletion once the owners agreed, also run when a second owner approves.
+pub fn schedule_deletion(
+    package: &Package,
+    uid: i32,
+    conn: &jelly::DieselPgConnection,
+) -> Result<Package> {
+    // Dependents or downloads may have come in while the deletion waited for an approval
+    check_deletion_blocker(package, conn)?;
+    conn.transaction(|| -> Result<Package> {
+        let package = package
+            .schedule_deletion(conn)
+            .map_err(|e| ApiServerError(Box::new(e)))?;
+        let details = package
+            .deletion_scheduled_at
+            .map(|scheduled_at| format!("Purged after {}", scheduled_at.to_rfc3339()));
+        log_package_action(
+            &package,
+            uid,
+            PackageAuditAction::ScheduleDeletion,
+            details.as_deref(),
+            conn,
+        )?;
+        Ok(package)
+    })
+}
+
+fn check_deletion_blocker(package: &Package, conn: &jelly::DieselPgConnection) -> Result<()> {
+    let blocker = package
+        .deletion_blocker(conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    if let Some(blocker) = blocker {
+        let message = match blocker {
+            DeletionBlocker::Dependents(_) => MSG_PACKAGE_HAS_DEPENDENTS,
+            DeletionBlocker::Downloads(_) => MSG_PACKAGE_HAS_TOO_MANY_DOWNLOADS,
+        };
+        return Err(ApiBadRequest(
+            message,
+            Box::new(Error::Generic(format!(
+                "Cannot delete package {}: {:?}",
+                package.slug, blocker
+            ))),
+        ));
+    }
+    Ok(())
+}
+
+pub async fn cancel_package_deletion(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+) -> Result<HttpResponse> {
+    let (package, uid, conn) = get_authorized_package(
+        &request,
+        &package_slug,
+        Action::Administer,
+        MSG_UNAUTHORIZED_TO_DELETE_PACKAGE,
+    )?;
+    let package = conn.transaction(|| -> Result<Package> {
+        let package = package
+            .cancel_deletion(&conn)
+            .map_err(|e| ApiServerError(Box::new(e)))?;
+        log_package_action(
+            &package,
+            uid,
+            PackageAuditAction::CancelDeletion,
+            None,
+            &conn,
+        )?;
+        Ok(package)
+    })?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_RESTORED_PACKAGE,
+        "deletion_scheduled_at": package.deletion_scheduled_at,
+    })))
+}
+
+pub async fn claim_package(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+    json: web::Json<ClaimJson>,
+) -> Result<HttpResponse> {
+    let db = request.db_pool().map_err(|e| ApiServerError(Box::new(e)))?;
+    let user = request.user().map_err(|e| ApiServerError(Box::new(e)))?;
+    let session = request.get_session();
+    let github_admin_repository = session
+        .get::<String>(GITHUB_ADMIN_REPOSITORY_SESSION_KEY)
+        .map_err(|e| ApiServerError(Box::new(e.into())))?;
+    // A GitHub sign in proves a single claim
+    if let ClaimMethod::Github = json.method {
+        session.remove(GITHUB_ADMIN_REPOSITORY_SESSION_KEY);
+    }
+    verify_and_claim(
+        &package_slug,
+        &user,
+        json.method,
+        github_admin_repository,
+        GithubService::new(),
+        db,
+    )
+    .await
+}
+
+/// Checks, during the GitHub sign in started from the claim page, whether the GitHub account
+/// administers the repository of the package. Returns the repository if so, the token is not
+/// kept anywhere.
+pub fn check_github_claim(
+    package_slug: &str,
+    user: &User,
+    github_id: i64,
+    github_token: &str,
+    service: GithubClient,
+    db: &DieselPgPool,
+) -> Result<Option<String>> {
+    if user.is_anonymous {
+        return Ok(None);
+    }
+    let conn = db.get()?;
+    let package = Package::get_by_slug(package_slug, &conn)?;
+    // Only the GitHub account linked to the signed in account proves a claim
+    if Account::get(user.id, db)?.github_id != Some(github_id) {
+        return Ok(None);
+    }
+    if service.is_repo_admin(&package.repository_url, github_token)? {
+        Ok(Some(package.repository_url))
+    } else {
+        Ok(None)
+    }
+}
+
+/// Makes `user` the owner of a package nobody owns, once the repository proves it theirs.
+/// GitHub is called on the blocking thread pool.
+pub async fn verify_and_claim(
+    package_slug: &str,
+    user: &User,
+    method: ClaimMethod,
+    github_admin_repository: Option<String>,
+    service: GithubClient,
+    db: &DieselPgPool,
+) -> Result<HttpResponse> {
+    let conn = db.get().map_err(|e| ApiServerError(Box::new(e)))?;
+    if user.is_anonymous {
+        return Err(ApiUnauthorized(
+            MSG_LOGIN_REQUIRED_TO_CLAIM,
+            Box::new(Error::Generic(String::from(
+                "Anonymous users cannot claim packages.",
+            ))),
+        ));
+    }
+    let package = Package::get_by_slug(package_slug, &conn)
+        .map_err(|e| ApiNotFound(MSG_PACKAGE_NOT_FOUND, Box::new(e)))?;
+    let already_claimed_error = || {
+        ApiBadRequest(
+            MSG_PACKAGE_ALREADY_CLAIMED,
+            Box::new(Error::Generic(format!(
+                "Package already has owners. package id: {}",
+                package.id
+            ))),
+        )
+    };
+    let is_claimable =
+        PackageClaim::is_claimable(&package, &conn).map_err(|e| ApiServerError(Box::new(e)))?;
+    if !is_claimable {
+        return Err(already_claimed_error());
+    }
+    let claim = PackageClaim::get_or_create(package.id, user.id, &conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+
+    let repository_url = package.repository_url.clone();
+    let is_verified = match method {
+        ClaimMethod::Github => {
+            let account = Account::get(user.id, db).map_err(|e| ApiServerError(Box::new(e)))?;
+            if account.github_id.is_none() {
+                return Err(ApiBadRequest(
+                    MSG_GITHUB_ACCOUNT_NOT_LINKED,
+                    Box::new(Error::Generic(format!(
+                        "No GitHub account linked. uid: {}",
+                        user.id
+                    ))),
+                ));
+            }
+            // Only a GitHub sign in started from the claim page checks the permissions
+            let github_admin_repository = github_admin_repository.ok_or_else(|| {
+                ApiBadRequest(
+                    MSG_GITHUB_SIGN_IN_REQUIRED_TO_CLAIM,
+                    Box::new(Error::Generic(format!(
+                        "No repository verified with GitHub in the session. uid: {}",
+                        user.id
+                    ))),
+                )
+            })?;
+            Ok(github_admin_repository == repository_url)
+        }
+        ClaimMethod::File => web::block(move || {
+            service
+                .fetch_source_file(&repository_url, "HEAD", VERIFICATION_FILE)
+                .map_err(|e| anyhow!("{:?}", e))
+        })
+        .await
+        .map(|content| content.map_or(false, |content| claim.matches_verification_file(&content))),
+    }
+    .map_err(|e| ApiServerError(Box::new(Error::Anyhow(anyhow!("{:?}", e)))))?;
+    if !is_verified {
+        return Err(ApiForbidden(
+            MSG_CLAIM_NOT_VERIFIED,
+            Box::new(Error::Generic(format!(
+                "Claim not verified with {:?}. uid: {}, package id: {}",
+                method, user.id, package.id
+            ))),
+        ));
+    }
+    claim
+        .complete(&package, &conn)
+        .map_err(|_| already_claimed_error())?;
+    let verified_with = match method {
+        ClaimMethod::Github => "Verified with GitHub",
+        ClaimMethod::File => "Verified with the verification file",
+    };
+    log_package_action(
+        &package,
+        user.id,
+        PackageAuditAction::Claim,
+        Some(verified_with),
+        &conn,
+    )?;
+
+    Ok(HttpResponse::Ok().json(json!({
+        "ok": true,
+        "msg": MSG_SUCCESSFULLY_CLAIMED_PACKAGE,
+    })))
+}
+
+fn log_package_action(
+    package: &Package,
+    uid: i32,
+    action: PackageAuditAction,
+    details: Option<&str>,
+    conn: &jelly::DieselPgConnection,
+) -> Result<()> {
+    PackageAuditLog::create(package, Some(uid), action, details, conn)
+        .map_err(|e| ApiServerError(Box::new(e)))?;
+    Ok(())
+}
+
+fn requires_approval(package: &Package, conn: &jelly::DieselPgConnection) -> Result<bool> {
+    policy::requires_approval(package, conn).map_err(|e| ApiServerError(Box::new(e)))
+}
+
+fn get_authorized_package(
+    request: &HttpRequest,
+    package_slug: &str,
+    action: Action,
+    forbidden_message: &'static str,
+) -> Result<(Package, i32, jelly::DieselPgConnection)> {
+    let db = request.db_pool().map_err(|e| ApiServerError(Box::new(e)))?;
+    let conn = db.get().map_err(|e| ApiServerError(Box::new(e)))?;
+    let package = Package::get_by_slug(package_slug, &conn)
+        .map_err(|e| ApiNotFound(MSG_PACKAGE_NOT_FOUND, Box::new(e)))?;
+    let user = request.user().map_err(|e| ApiServerError(Box::new(e)))?;
+    let is_allowed = !user.is_anonymous
+        && policy::can(&package, user.id, action, &conn)
+            .map_err(|e| ApiServerError(Box::new(e)))?;
+    if !is_allowed {
+        return Err(ApiForbidden(
+            forbidden_message,
+            Box::new(Error::Generic(format!(
+                "Not allowed to {:?} the package. uid: {}, package id: {}",
+                action, user.id, package.id
+            ))),
+        ));
+    }
+    Ok((package, user.id, conn))
+}
+
 pub async fn search_package(
     request: HttpRequest,
     res: web::Json<PackageSearch>,
@@ -165,15 +805,30 @@ pub async fn search_package(
     let db = request.db_pool()?;
     let field = Some(PackageSortField::MostStars);
     let order = Some(PackageSortOrder::Desc);
+    let filters = PackageSearchFilters {
+        platform: Platform::from_filter(res.platform.as_deref()),
+        license: parse_license_filter(res.license.as_deref()),
+        ..Default::default()
+    };
     let packages_result = Package::auto_complete_search(
         &res.search_query,
         field.as_ref().unwrap(),
         order.as_ref().unwrap(),
+        &filters,
         db,
     )?;
     Ok(HttpResponse::Ok().json(packages_result))
 }
 
+pub async fn search_symbol(
+    request: HttpRequest,
+    res: web::Json<SymbolSearch>,
+) -> Result<HttpResponse> {
+    let conn = request.db_pool()?.get()?;
+    let symbols = search_symbols(&res.search_query, res.package.as_deref(), &conn)?;
+    Ok(HttpResponse::Ok().json(symbols))
+}
+
 #[derive(Deserialize)]
 pub struct BadgeRequest {
     pkg_name: String,
@@ -1,4 +1,4 @@
-use jelly::actix_web::web::{get, post, resource, scope, ServiceConfig};
+use jelly::actix_web::web::{delete, get, post, put, resource, scope, ServiceConfig};
 
 pub mod controller;
 #[cfg(test)]
@@ -20,9 +20,41 @@ pub fn configure(config: &mut ServiceConfig) {
                 resource("/search")
                     .route(post().to(controller::search_package))
             )
+            .service(
+                resource("/search/symbols")
+                    .route(post().to(controller::search_symbol))
+            )
             .service(
                 resource("{package_name}/badge")
                     .route(get().to(controller::package_badge_info)),
+            )
+            .service(
+                resource("{package_slug}/deprecation")
+                    .route(get().to(controller::get_deprecation))
+                    .route(put().to(controller::deprecate_package))
+                    .route(delete().to(controller::undeprecate_package)),
+            )
+            .service(
+                resource("{package_slug}/slug")
+                    .route(put().to(controller::rename_package)),
+            )
+            .service(
+                resource("{package_slug}/organization")
+                    .route(put().to(controller::transfer_to_organization)),
+            )
+            .service(
+                resource("{package_slug}/archive")
+                    .route(put().to(controller::archive_package))
+                    .route(delete().to(controller::unarchive_package)),
+            )
+            .service(
+                resource("{package_slug}/deletion")
+                    .route(put().to(controller::schedule_package_deletion))
+                    .route(delete().to(controller::cancel_package_deletion)),
+            )
+            .service(
+                resource("{package_slug}/claim")
+                    .route(post().to(controller::claim_package)),
             ),
     );
 }
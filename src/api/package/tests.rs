@@ -1,14 +1,30 @@
 use crate::api::package::controller::{
-    increase_download_count, register_package, DownloadInfo, PackageRequest,
+    archive_package, cancel_package_deletion, check_github_claim, deprecate_package,
+    increase_download_count, register_package, rename_package, schedule_package_deletion,
+    transfer_to_organization, unarchive_package, undeprecate_package, verify_and_claim,
+    DownloadInfo, PackageRequest,
 };
-use crate::packages::{Package, PackageVersion};
-use crate::test::util::create_test_token;
+use crate::api::package::view::{
+    ClaimMethod, DeprecationJson, OrganizationTransferJson, RenameJson,
+};
+use crate::github_service::MockGithubService;
+use crate::organizations::models::{Organization, OrganizationRole};
+use crate::package_collaborators::package_collaborator::{PackageCollaborator, Role};
+use crate::packages::models::audit_log::PackageAuditLog;
+use crate::packages::platform::{ManifestDependency, Platform};
+use crate::packages::{Package, PackageVersion, PackageVersionSort};
+use crate::schema::accounts;
+use crate::test::util::{create_package, create_test_token, setup_user};
 use crate::test::{mock, DatabaseTestContext, DB_POOL};
 
+use diesel::prelude::*;
+use jelly::accounts::User;
 use jelly::actix_web::body::Body;
 use jelly::actix_web::http::StatusCode;
 use jelly::actix_web::web;
+use jelly::actix_web::web::Path;
 use jelly::error::Error;
+use jelly::utils::error_constants::*;
 
 fn init_form() -> web::Form<DownloadInfo> {
     web::Form(DownloadInfo {
@@ -27,6 +43,32 @@ fn package_request() -> web::Json<PackageRequest> {
     })
 }
 
+fn signed_in_request(uid: Option<i32>) -> mock::MockHttpRequest {
+    let mut mock_http_request = mock::MockHttpRequest::new();
+    mock_http_request
+        .expect_db_pool()
+        .returning(|| Ok(&DB_POOL));
+    mock_http_request.expect_user().returning(move || {
+        Ok(match uid {
+            Some(id) => User {
+                id,
+                name: "".to_string(),
+                is_admin: false,
+                is_anonymous: false,
+            },
+            None => User::default(),
+        })
+    });
+    mock_http_request
+}
+
+fn deprecation_json(reason: &str, successor: Option<&str>) -> web::Json<DeprecationJson> {
+    web::Json(DeprecationJson {
+        reason: reason.to_string(),
+        successor: successor.map(str::to_string),
+    })
+}
+
 #[actix_rt::test]
 async fn register_package_create_new_packages() {
     crate::test::init();
@@ -123,6 +165,32 @@ async fn increase_download_count_works() {
     assert_eq!(PackageVersion::count(&DB_POOL).unwrap(), 1);
 }
 
+#[actix_rt::test]
+async fn increase_download_count_warns_about_deprecated_packages() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let form = init_form();
+    let conn = DB_POOL.get().unwrap();
+    create_package("move", &form.url, &form.rev, None)
+        .deprecate("Use the \"move-cli\" package.", None, &conn)
+        .unwrap();
+
+    let mut mock_http_request = mock::MockHttpRequest::new();
+    mock_http_request
+        .expect_db_pool()
+        .returning(|| Ok(&DB_POOL));
+    let mut form = init_form();
+    form.subdir = "".to_string();
+    let resp = increase_download_count(mock_http_request, form)
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        resp.headers().get("Warning").unwrap(),
+        "299 movey \"Package move is deprecated: Use the 'move-cli' package.\""
+    );
+}
+
 #[actix_rt::test]
 async fn increase_download_count_returns_error_with_empty_rev() {
     crate::test::init();
@@ -212,3 +280,757 @@ async fn increase_download_count_returns_error_when_database_is_not_available()
         &Body::from("Something went wrong, please try again later.")
     );
 }
+
+#[actix_rt::test]
+async fn deprecate_package_works_for_owners() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let package = create_package("move", "https://github.com/movey/move", "", Some(uid));
+    create_package("move-cli", "https://github.com/movey/move-cli", "", None);
+
+    let resp = deprecate_package(
+        signed_in_request(Some(uid)),
+        Path(package.slug.clone()),
+        deprecation_json("Unmaintained", Some("move-cli")),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let package = Package::get(package.id, &DB_POOL).unwrap();
+    assert!(package.deprecated_at.is_some());
+    assert_eq!(package.deprecation_reason.unwrap(), "Unmaintained");
+    assert_eq!(package.deprecation_successor.unwrap(), "move-cli");
+
+    let resp = undeprecate_package(signed_in_request(Some(uid)), Path(package.slug.clone()))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let package = Package::get(package.id, &DB_POOL).unwrap();
+    assert!(package.deprecated_at.is_none());
+
+    let conn = DB_POOL.get().unwrap();
+    let logs = PackageAuditLog::get_by_package_id(package.id, &conn).unwrap();
+    assert_eq!(logs.len(), 2);
+    assert_eq!(logs[0].action, "undeprecate");
+    assert_eq!(logs[1].action, "deprecate");
+    assert_eq!(logs[1].account_id, Some(uid));
+}
+
+#[actix_rt::test]
+async fn deprecate_package_is_forbidden_for_non_owners() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let other_uid = setup_user(Some("other@host.com".to_string()), None);
+    let package = create_package("move", "https://github.com/movey/move", "", Some(uid));
+
+    for uid in &[Some(other_uid), None] {
+        let result = deprecate_package(
+            signed_in_request(*uid),
+            Path(package.slug.clone()),
+            deprecation_json("Unmaintained", None),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(Error::ApiForbidden(
+                MSG_UNAUTHORIZED_TO_DEPRECATE_PACKAGE,
+                _
+            ))
+        ));
+    }
+    let result = undeprecate_package(
+        signed_in_request(Some(other_uid)),
+        Path(package.slug.clone()),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(Error::ApiForbidden(
+            MSG_UNAUTHORIZED_TO_DEPRECATE_PACKAGE,
+            _
+        ))
+    ));
+
+    let package = Package::get(package.id, &DB_POOL).unwrap();
+    assert!(package.deprecated_at.is_none());
+    let conn = DB_POOL.get().unwrap();
+    assert!(PackageAuditLog::get_by_package_id(package.id, &conn)
+        .unwrap()
+        .is_empty());
+}
+
+#[actix_rt::test]
+async fn deprecate_package_returns_not_found_for_unknown_packages() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+
+    let result = deprecate_package(
+        signed_in_request(Some(uid)),
+        Path("not-a-package".to_string()),
+        deprecation_json("Unmaintained", None),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(Error::ApiNotFound(MSG_PACKAGE_NOT_FOUND, _))
+    ));
+}
+
+#[actix_rt::test]
+async fn deprecate_package_requires_a_reason_and_a_valid_successor() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let package = create_package("move", "https://github.com/movey/move", "", Some(uid));
+
+    let result = deprecate_package(
+        signed_in_request(Some(uid)),
+        Path(package.slug.clone()),
+        deprecation_json("  ", None),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(Error::ApiBadRequest(MSG_DEPRECATION_REASON_REQUIRED, _))
+    ));
+
+    for successor in &["not-a-package", "move"] {
+        let result = deprecate_package(
+            signed_in_request(Some(uid)),
+            Path(package.slug.clone()),
+            deprecation_json("Unmaintained", Some(successor)),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(Error::ApiBadRequest(MSG_SUCCESSOR_NOT_FOUND, _))
+        ));
+    }
+
+    let package = Package::get(package.id, &DB_POOL).unwrap();
+    assert!(package.deprecated_at.is_none());
+}
+
+fn rename_json(slug: &str) -> web::Json<RenameJson> {
+    web::Json(RenameJson {
+        slug: slug.to_string(),
+    })
+}
+
+#[actix_rt::test]
+async fn rename_package_works_for_owners() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let package = create_package("move", "https://github.com/movey/move", "", Some(uid));
+
+    let resp = rename_package(
+        signed_in_request(Some(uid)),
+        Path(package.slug.clone()),
+        rename_json("move-lang"),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        Package::get(package.id, &DB_POOL).unwrap().slug,
+        "move-lang"
+    );
+
+    let conn = DB_POOL.get().unwrap();
+    let logs = PackageAuditLog::get_by_package_id(package.id, &conn).unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].action, "rename");
+    assert_eq!(logs[0].details.as_deref(), Some("move -> move-lang"));
+}
+
+#[actix_rt::test]
+async fn rename_package_is_forbidden_for_non_owners() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let collaborator_uid = setup_user(Some("collaborator@host.com".to_string()), None);
+    let package = create_package("move", "https://github.com/movey/move", "", Some(uid));
+    let conn = DB_POOL.get().unwrap();
+    PackageCollaborator::new_collaborator(package.id, collaborator_uid, uid, &conn).unwrap();
+
+    for uid in &[Some(collaborator_uid), None] {
+        let result = rename_package(
+            signed_in_request(*uid),
+            Path(package.slug.clone()),
+            rename_json("move-lang"),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(Error::ApiForbidden(MSG_UNAUTHORIZED_TO_RENAME_PACKAGE, _))
+        ));
+    }
+    assert_eq!(Package::get(package.id, &DB_POOL).unwrap().slug, "move");
+}
+
+#[actix_rt::test]
+async fn rename_package_rejects_invalid_and_taken_slugs() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let package = create_package("move", "https://github.com/movey/move", "", Some(uid));
+    create_package("move-cli", "https://github.com/movey/move-cli", "", None);
+    let conn = DB_POOL.get().unwrap();
+    create_package("move-std", "https://github.com/movey/move-std", "", None)
+        .rename_slug("move-stdlib", &conn)
+        .unwrap();
+
+    for slug in &["", "Move Lang", "move/lang"] {
+        let result = rename_package(
+            signed_in_request(Some(uid)),
+            Path(package.slug.clone()),
+            rename_json(slug),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(Error::ApiBadRequest(MSG_INVALID_PACKAGE_SLUG, _))
+        ));
+    }
+    // Taken by another package, or still redirecting to it
+    for slug in &["move-cli", "move-std"] {
+        let result = rename_package(
+            signed_in_request(Some(uid)),
+            Path(package.slug.clone()),
+            rename_json(slug),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(Error::ApiBadRequest(MSG_PACKAGE_SLUG_TAKEN, _))
+        ));
+    }
+
+    assert_eq!(Package::get(package.id, &DB_POOL).unwrap().slug, "move");
+    assert!(PackageAuditLog::get_by_package_id(package.id, &conn)
+        .unwrap()
+        .is_empty());
+}
+
+#[actix_rt::test]
+async fn archive_package_works_for_owners() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let package = create_package("move", "https://github.com/movey/move", "", Some(uid));
+
+    let resp = archive_package(signed_in_request(Some(uid)), Path(package.slug.clone()))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(Package::get(package.id, &DB_POOL)
+        .unwrap()
+        .archived_at
+        .is_some());
+
+    let resp = unarchive_package(signed_in_request(Some(uid)), Path(package.slug.clone()))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(Package::get(package.id, &DB_POOL)
+        .unwrap()
+        .archived_at
+        .is_none());
+
+    let conn = DB_POOL.get().unwrap();
+    let logs = PackageAuditLog::get_by_package_id(package.id, &conn).unwrap();
+    assert_eq!(logs.len(), 2);
+    assert_eq!(logs[0].action, "unarchive");
+    assert_eq!(logs[1].action, "archive");
+    assert_eq!(logs[1].account_id, Some(uid));
+}
+
+#[actix_rt::test]
+async fn schedule_package_deletion_works_for_owners() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let package = create_package("move", "https://github.com/movey/move", "", Some(uid));
+
+    let resp = schedule_package_deletion(signed_in_request(Some(uid)), Path(package.slug.clone()))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(Package::get(package.id, &DB_POOL)
+        .unwrap()
+        .deletion_scheduled_at
+        .is_some());
+
+    let resp = cancel_package_deletion(signed_in_request(Some(uid)), Path(package.slug.clone()))
+        .await
+        .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert!(Package::get(package.id, &DB_POOL)
+        .unwrap()
+        .deletion_scheduled_at
+        .is_none());
+
+    let conn = DB_POOL.get().unwrap();
+    let logs = PackageAuditLog::get_by_package_id(package.id, &conn).unwrap();
+    assert_eq!(logs.len(), 2);
+    assert_eq!(logs[0].action, "cancel_deletion");
+    assert_eq!(logs[1].action, "schedule_deletion");
+}
+
+#[actix_rt::test]
+async fn archive_and_deletion_are_forbidden_for_non_owners() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let maintainer_uid = setup_user(Some("maintainer@host.com".to_string()), None);
+    let package = create_package("move", "https://github.com/movey/move", "", Some(uid));
+    let conn = DB_POOL.get().unwrap();
+    PackageCollaborator::new_collaborator(package.id, maintainer_uid, uid, &conn).unwrap();
+    PackageCollaborator::update_role(package.id, maintainer_uid, Role::Maintainer, &conn).unwrap();
+
+    for uid in &[Some(maintainer_uid), None] {
+        let result = archive_package(signed_in_request(*uid), Path(package.slug.clone())).await;
+        assert!(matches!(
+            result,
+            Err(Error::ApiForbidden(MSG_UNAUTHORIZED_TO_ARCHIVE_PACKAGE, _))
+        ));
+        let result = unarchive_package(signed_in_request(*uid), Path(package.slug.clone())).await;
+        assert!(matches!(
+            result,
+            Err(Error::ApiForbidden(MSG_UNAUTHORIZED_TO_ARCHIVE_PACKAGE, _))
+        ));
+        let result =
+            schedule_package_deletion(signed_in_request(*uid), Path(package.slug.clone())).await;
+        assert!(matches!(
+            result,
+            Err(Error::ApiForbidden(MSG_UNAUTHORIZED_TO_DELETE_PACKAGE, _))
+        ));
+        let result =
+            cancel_package_deletion(signed_in_request(*uid), Path(package.slug.clone())).await;
+        assert!(matches!(
+            result,
+            Err(Error::ApiForbidden(MSG_UNAUTHORIZED_TO_DELETE_PACKAGE, _))
+        ));
+    }
+
+    let package = Package::get(package.id, &DB_POOL).unwrap();
+    assert!(package.archived_at.is_none());
+    assert!(package.deletion_scheduled_at.is_none());
+    assert!(PackageAuditLog::get_by_package_id(package.id, &conn)
+        .unwrap()
+        .is_empty());
+}
+
+#[actix_rt::test]
+async fn schedule_package_deletion_is_blocked_by_downloads_and_dependents() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let conn = DB_POOL.get().unwrap();
+    let popular_id = Package::create_test_package_with_downloads(
+        &"popular".to_string(),
+        &"https://github.com/movey/popular".to_string(),
+        &"".to_string(),
+        10_000,
+        0,
+        0,
+        &DB_POOL,
+    )
+    .unwrap();
+    PackageCollaborator::new_owner(popular_id, uid, uid, &conn).unwrap();
+    let popular = Package::get(popular_id, &DB_POOL).unwrap();
+
+    let result = schedule_package_deletion(signed_in_request(Some(uid)), Path(popular.slug)).await;
+    assert!(matches!(
+        result,
+        Err(Error::ApiBadRequest(MSG_PACKAGE_HAS_TOO_MANY_DOWNLOADS, _))
+    ));
+
+    let package = create_package("move", "https://github.com/movey/move", "", Some(uid));
+    let dependent = create_package("move-cli", "https://github.com/movey/move-cli", "", None);
+    PackageVersion::from_package_id(dependent.id, &PackageVersionSort::Latest, &DB_POOL)
+        .unwrap()
+        .remove(0)
+        .update_platform(
+            Platform::Unknown,
+            &[ManifestDependency {
+                name: "Move".to_string(),
+                git: Some("https://github.com/movey/move.git".to_string()),
+                rev: Some("main".to_string()),
+                ..Default::default()
+            }],
+            &conn,
+        )
+        .unwrap();
+
+    let result =
+        schedule_package_deletion(signed_in_request(Some(uid)), Path(package.slug.clone())).await;
+    assert!(matches!(
+        result,
+        Err(Error::ApiBadRequest(MSG_PACKAGE_HAS_DEPENDENTS, _))
+    ));
+    assert!(Package::get(package.id, &DB_POOL)
+        .unwrap()
+        .deletion_scheduled_at
+        .is_none());
+    assert!(Package::get(popular_id, &DB_POOL)
+        .unwrap()
+        .deletion_scheduled_at
+        .is_none());
+}
+
+fn transfer_json(organization: &str) -> web::Json<OrganizationTransferJson> {
+    web::Json(OrganizationTransferJson {
+        organization: organization.to_string(),
+    })
+}
+
+#[actix_rt::test]
+async fn transfer_to_organization_works_for_owners_administering_it() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let package = create_package("move", "https://github.com/movey/move", "", Some(uid));
+    let conn = DB_POOL.get().unwrap();
+    let organization = Organization::create("Move Labs", "", uid, &conn).unwrap();
+
+    let resp = transfer_to_organization(
+        signed_in_request(Some(uid)),
+        Path(package.slug.clone()),
+        transfer_json(&organization.slug),
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    assert_eq!(
+        Package::get(package.id, &DB_POOL).unwrap().organization_id,
+        Some(organization.id)
+    );
+    let logs = PackageAuditLog::get_by_package_id(package.id, &conn).unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].action, "transfer_to_organization");
+    assert_eq!(logs[0].details, Some(organization.slug.clone()));
+
+    let result = transfer_to_organization(
+        signed_in_request(Some(uid)),
+        Path(package.slug.clone()),
+        transfer_json(&organization.slug),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(Error::ApiBadRequest(
+            MSG_PACKAGE_ALREADY_OWNED_BY_ORGANIZATION,
+            _
+        ))
+    ));
+}
+
+#[actix_rt::test]
+async fn transfer_to_organization_is_forbidden_for_non_owners() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let maintainer_uid = setup_user(Some("maintainer@host.com".to_string()), None);
+    let package = create_package("move", "https://github.com/movey/move", "", Some(uid));
+    let conn = DB_POOL.get().unwrap();
+    PackageCollaborator::new_collaborator(package.id, maintainer_uid, uid, &conn).unwrap();
+    PackageCollaborator::update_role(package.id, maintainer_uid, Role::Maintainer, &conn).unwrap();
+    let organization = Organization::create("Move Labs", "", maintainer_uid, &conn).unwrap();
+
+    for uid in &[Some(maintainer_uid), None] {
+        let result = transfer_to_organization(
+            signed_in_request(*uid),
+            Path(package.slug.clone()),
+            transfer_json(&organization.slug),
+        )
+        .await;
+        assert!(matches!(
+            result,
+            Err(Error::ApiForbidden(
+                MSG_UNAUTHORIZED_TO_TRANSFER_TO_ORGANIZATION,
+                _
+            ))
+        ));
+    }
+    assert_eq!(
+        Package::get(package.id, &DB_POOL).unwrap().organization_id,
+        None
+    );
+}
+
+#[actix_rt::test]
+async fn transfer_to_organization_requires_an_organization_the_owner_administers() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let admin_uid = setup_user(Some("admin@host.com".to_string()), None);
+    let package = create_package("move", "https://github.com/movey/move", "", Some(uid));
+    let conn = DB_POOL.get().unwrap();
+    let organization = Organization::create("Move Labs", "", admin_uid, &conn).unwrap();
+
+    let result = transfer_to_organization(
+        signed_in_request(Some(uid)),
+        Path(package.slug.clone()),
+        transfer_json("unknown-organization"),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(Error::ApiNotFound(MSG_ORGANIZATION_NOT_FOUND, _))
+    ));
+
+    let result = transfer_to_organization(
+        signed_in_request(Some(uid)),
+        Path(package.slug.clone()),
+        transfer_json(&organization.slug),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(Error::ApiForbidden(
+            MSG_UNAUTHORIZED_TO_TRANSFER_TO_ORGANIZATION,
+            _
+        ))
+    ));
+
+    // Plain members cannot move packages into the organization either
+    organization
+        .add_member(uid, OrganizationRole::Member, &conn)
+        .unwrap();
+    let result = transfer_to_organization(
+        signed_in_request(Some(uid)),
+        Path(package.slug.clone()),
+        transfer_json(&organization.slug),
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(Error::ApiForbidden(
+            MSG_UNAUTHORIZED_TO_TRANSFER_TO_ORGANIZATION,
+            _
+        ))
+    ));
+    assert_eq!(
+        Package::get(package.id, &DB_POOL).unwrap().organization_id,
+        None
+    );
+}
+
+fn link_github(uid: i32) -> User {
+    let conn = DB_POOL.get().unwrap();
+    diesel::update(accounts::table.find(uid))
+        .set((
+            accounts::github_id.eq(uid as i64),
+            accounts::github_login.eq(format!("github-user-{}", uid)),
+        ))
+        .execute(&conn)
+        .unwrap();
+    User {
+        id: uid,
+        name: "".to_string(),
+        is_admin: false,
+        is_anonymous: false,
+    }
+}
+
+fn github_service(is_admin: bool) -> MockGithubService {
+    let mut service = MockGithubService::new();
+    service
+        .expect_is_repo_admin()
+        .times(1)
+        .returning(move |repo_url, user_token| {
+            Ok(is_admin
+                && repo_url == "https://github.com/movey/move"
+                && user_token == "user-token")
+        });
+    service
+}
+
+fn no_github_calls() -> MockGithubService {
+    let mut service = MockGithubService::new();
+    service.expect_is_repo_admin().never();
+    service
+}
+
+#[actix_rt::test]
+async fn check_github_claim_only_keeps_administered_repositories() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let user = link_github(uid);
+    let package = create_package("move", "https://github.com/movey/move", "", None);
+
+    let repository = check_github_claim(
+        &package.slug,
+        &user,
+        uid as i64,
+        "user-token",
+        github_service(true),
+        &DB_POOL,
+    )
+    .unwrap();
+    assert_eq!(repository.as_deref(), Some("https://github.com/movey/move"));
+    let repository = check_github_claim(
+        &package.slug,
+        &user,
+        uid as i64,
+        "user-token",
+        github_service(false),
+        &DB_POOL,
+    )
+    .unwrap();
+    assert!(repository.is_none());
+
+    // Another GitHub account than the linked one, or no account, proves nothing
+    for (user, github_id) in &[(user, uid as i64 + 1), (User::default(), uid as i64)] {
+        let repository = check_github_claim(
+            &package.slug,
+            user,
+            *github_id,
+            "user-token",
+            no_github_calls(),
+            &DB_POOL,
+        )
+        .unwrap();
+        assert!(repository.is_none());
+    }
+}
+
+#[actix_rt::test]
+async fn claim_package_works_for_repo_admins() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let user = link_github(uid);
+    let package = create_package("move", "https://github.com/movey/move", "", None);
+
+    let resp = verify_and_claim(
+        &package.slug,
+        &user,
+        ClaimMethod::Github,
+        Some("https://github.com/movey/move".to_string()),
+        no_github_calls(),
+        &DB_POOL,
+    )
+    .await
+    .unwrap();
+    assert_eq!(resp.status(), StatusCode::OK);
+    let conn = DB_POOL.get().unwrap();
+    assert_eq!(
+        PackageCollaborator::get_role(package.id, uid, &conn).unwrap(),
+        Some(Role::Owner)
+    );
+    let logs = PackageAuditLog::get_by_package_id(package.id, &conn).unwrap();
+    assert_eq!(logs.len(), 1);
+    assert_eq!(logs[0].action, "claim");
+
+    // Once claimed, GitHub is not even asked
+    let other_uid = setup_user(Some("other@host.com".to_string()), None);
+    let result = verify_and_claim(
+        &package.slug,
+        &link_github(other_uid),
+        ClaimMethod::Github,
+        Some("https://github.com/movey/move".to_string()),
+        no_github_calls(),
+        &DB_POOL,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(Error::ApiBadRequest(MSG_PACKAGE_ALREADY_CLAIMED, _))
+    ));
+}
+
+#[actix_rt::test]
+async fn claim_package_is_refused_without_admin_permission() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let user = link_github(uid);
+    let package = create_package("move", "https://github.com/movey/move", "", None);
+
+    let result = verify_and_claim(
+        &package.slug,
+        &user,
+        ClaimMethod::Github,
+        Some("https://github.com/movey/other".to_string()),
+        no_github_calls(),
+        &DB_POOL,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(Error::ApiForbidden(MSG_CLAIM_NOT_VERIFIED, _))
+    ));
+    let conn = DB_POOL.get().unwrap();
+    assert_eq!(
+        PackageCollaborator::get_role(package.id, uid, &conn).unwrap(),
+        None
+    );
+}
+
+#[actix_rt::test]
+async fn claim_package_requires_a_github_sign_in() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let package = create_package("move", "https://github.com/movey/move", "", None);
+    let user = User {
+        id: uid,
+        name: "".to_string(),
+        is_admin: false,
+        is_anonymous: false,
+    };
+
+    let result = verify_and_claim(
+        &package.slug,
+        &user,
+        ClaimMethod::Github,
+        Some("https://github.com/movey/move".to_string()),
+        no_github_calls(),
+        &DB_POOL,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(Error::ApiBadRequest(MSG_GITHUB_ACCOUNT_NOT_LINKED, _))
+    ));
+
+    // Linked, but not verified with GitHub from the claim page
+    let user = link_github(uid);
+    let result = verify_and_claim(
+        &package.slug,
+        &user,
+        ClaimMethod::Github,
+        None,
+        no_github_calls(),
+        &DB_POOL,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(Error::ApiBadRequest(
+            MSG_GITHUB_SIGN_IN_REQUIRED_TO_CLAIM,
+            _
+        ))
+    ));
+
+    let result = verify_and_claim(
+        &package.slug,
+        &User::default(),
+        ClaimMethod::Github,
+        None,
+        no_github_calls(),
+        &DB_POOL,
+    )
+    .await;
+    assert!(matches!(
+        result,
+        Err(Error::ApiUnauthorized(MSG_LOGIN_REQUIRED_TO_CLAIM, _))
+    ));
+}
@@ -1,5 +1,8 @@
+use jelly::chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 
+use crate::packages::Package;
+
 #[derive(Serialize, Deserialize)]
 pub struct PackageBadgeRespond {
     package_name: String,
@@ -37,3 +40,53 @@ impl From<Vec<(String, i32, String, i32)>> for PackageBadgeRespond {
         shield_respond
     }
 }
+
+#[derive(Deserialize)]
+pub struct DeprecationJson {
+    pub reason: String,
+    pub successor: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct RenameJson {
+    pub slug: String,
+}
+
+#[derive(Deserialize)]
+pub struct OrganizationTransferJson {
+    pub organization: String,
+}
+
+#[derive(Deserialize)]
+pub struct ClaimJson {
+    pub method: ClaimMethod,
+}
+
+/// How a claim is proven
+#[derive(Clone, Copy, Debug, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ClaimMethod {
+    /// The linked GitHub account owns or administers the repository
+    Github,
+    /// The verification file is committed to the repository
+    File,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct PackageDeprecationRespond {
+    pub deprecated: bool,
+    pub deprecated_at: Option<DateTime<Utc>>,
+    pub reason: Option<String>,
+    pub successor: Option<String>,
+}
+
+impl From<&Package> for PackageDeprecationRespond {
+    fn from(package: &Package) -> Self {
+        PackageDeprecationRespond {
+            deprecated: package.deprecated_at.is_some(),
+            deprecated_at: package.deprecated_at,
+            reason: package.deprecation_reason.clone(),
+            successor: package.deprecation_successor.clone(),
+        }
+    }
+}
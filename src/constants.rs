@@ -1,3 +1,8 @@
 pub const REMEMBER_ME_TOKEN_INVALIDATE: &str =
     "remember_me_token=\"\"; path=/; expires=Thu, 01 Jan 1970 00:00:00 GMT";
 pub const DEEP_AI_URL: &str = "https://api.deepai.org/api/summarization";
+/// The package whose claim the next GitHub sign in verifies.
+pub const CLAIM_PACKAGE_SESSION_KEY: &str = "claim_package";
+/// The repository the GitHub account administers, checked during the sign in started from the
+/// claim page. The OAuth token itself is never kept.
+pub const GITHUB_ADMIN_REPOSITORY_SESSION_KEY: &str = "github_admin_repository";
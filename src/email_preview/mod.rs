@@ -0,0 +1,99 @@
+//! Development-only pages to preview the email templates and send them for real,
+//! compiled in with the `email-preview` feature. Tests always compile it so every
+//! template is checked to render.
+
+use jelly::actix_web::web::{get, post, resource, scope, ServiceConfig};
+use jelly::tera::{Context, Tera};
+use std::env;
+
+use crate::accounts::jobs::{
+    build_odd_registration_attempt_context, build_reset_password_context, build_verify_context,
+};
+
+pub mod views;
+
+pub fn configure(config: &mut ServiceConfig) {
+    config.service(
+        scope("/dev/emails")
+            .service(resource("").route(get().to(views::list_templates)))
+            .service(
+                resource("/{template}")
+                    .route(get().to(views::show_template))
+                    .route(post().to(views::send_template)),
+            ),
+    );
+}
+
+/// Names of the templates in `templates/email` that have both an html and a text version.
+pub fn template_names(engine: &Tera) -> Vec<String> {
+    let mut names: Vec<String> = engine
+        .get_template_names()
+        .filter_map(|name| name.strip_prefix("email/")?.strip_suffix(".html"))
+        .filter(|name| *name != "layout")
+        .filter(|name| {
+            engine
+                .get_template_names()
+                .any(|other| other == format!("email/{}.txt", name))
+        })
+        .map(String::from)
+        .collect();
+    names.sort();
+    names
+}
+
+/// Context to render a template with, using the same builders as the jobs sending it
+/// when there is one, and sample values for everything else.
+pub fn sample_context(template: &str) -> Context {
+    let domain = env::var("JELLY_DOMAIN").unwrap_or_else(|_| "http://localhost:17002".to_string());
+    let action_url = format!("{}/accounts/reset/MQ-sample-token", domain);
+    let mut context = Context::new();
+    context.insert("name", "Jane Doe");
+    context.insert("username", "jane");
+    context.insert("email", "jane@example.com");
+    context.insert("category", "Feedback");
+    context.insert("description", "This is a sample message.");
+    context.insert("package_name", "sample-package");
+    context.insert("organization_name", "Move Labs");
+    context.insert("invited_email", "invited@example.com");
+    context.insert("token_name", "ci-token");
+    context.insert("unused_days", &365);
+    context.insert("notice_days", &14);
+    context.insert("help_url", &format!("{}/docs", domain));
+    context.insert("action_url", &action_url);
+    match template {
+        "verify-account" => context.extend(build_verify_context("jane", &action_url)),
+        "reset-password" => context.extend(build_reset_password_context(&action_url)),
+        "odd-registration-attempt" => {
+            context.extend(build_odd_registration_attempt_context("Jane Doe"))
+        }
+        _ => {}
+    }
+    context
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jelly::email::Email;
+    use jelly::error::TERA;
+
+    #[test]
+    fn every_template_renders_with_the_sample_context() {
+        crate::test::init();
+        let names = template_names(&TERA.read().unwrap());
+        assert!(names.contains(&"verify-account".to_string()));
+
+        for name in names {
+            let email = Email::new(
+                &format!("email/{}", name),
+                &["jane@example.com".to_string()],
+                &name,
+                sample_context(&name),
+                TERA.clone(),
+            )
+            .unwrap_or_else(|e| panic!("Cannot render email/{}: {:#}", name, e));
+            assert!(!email.body_html.is_empty(), "email/{}.html is empty", name);
+            assert!(!email.body.is_empty(), "email/{}.txt is empty", name);
+        }
+    }
+}
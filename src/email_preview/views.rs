@@ -0,0 +1,130 @@
+use std::sync::{Arc, RwLock};
+
+use jelly::actix_web::web::{Form, Path};
+use jelly::email::Email;
+use jelly::prelude::*;
+use jelly::request::DatabasePool;
+use jelly::tera::Tera;
+use jelly::Result;
+use serde::Deserialize;
+
+use super::{sample_context, template_names};
+
+#[derive(Deserialize)]
+pub struct SendTemplateForm {
+    pub to: String,
+}
+
+fn templates(request: &HttpRequest) -> Result<Arc<RwLock<Tera>>> {
+    request
+        .app_data::<Arc<RwLock<Tera>>>()
+        .cloned()
+        .ok_or_else(|| Error::Generic("Unable to locate Templates cache".to_string()))
+}
+
+fn find_template(request: &HttpRequest, template: &str) -> Result<Arc<RwLock<Tera>>> {
+    let templates = templates(request)?;
+    let exists = {
+        let engine = templates
+            .read()
+            .map_err(|e| Error::Generic(format!("Error acquiring template read lock: {:?}", e)))?;
+        template_names(&engine).iter().any(|name| name == template)
+    };
+    if exists {
+        Ok(templates)
+    } else {
+        Err(Error::Generic(format!(
+            "No email template named {}",
+            template
+        )))
+    }
+}
+
+/// Sending reaches real inboxes, so only admins may do it, even in development.
+/// The peer address can't tell a local request from a proxied one.
+fn can_send(request: &HttpRequest) -> Result<bool> {
+    Ok(request.user()?.is_admin)
+}
+
+/// Renders the template the way it would be sent, or returns the rendering error.
+fn render_email(
+    template: &str,
+    to: &str,
+    templates: Arc<RwLock<Tera>>,
+) -> std::result::Result<Email, String> {
+    Email::new(
+        &format!("email/{}", template),
+        &[to.to_string()],
+        &format!("[Preview] {}", template),
+        sample_context(template),
+        templates,
+    )
+    .map_err(|e| format!("{:#}", e))
+}
+
+fn render_preview(
+    request: &HttpRequest,
+    template: &str,
+    to: &str,
+    mut ctx: Context,
+) -> Result<HttpResponse> {
+    let templates = find_template(request, template)?;
+    match render_email(template, to, templates) {
+        Ok(email) => ctx.insert("email", &email),
+        Err(e) => ctx.insert("render_error", &e),
+    }
+    ctx.insert("template", template);
+    ctx.insert("to", to);
+    ctx.insert("can_send", &can_send(request)?);
+    request.render(200, "email_preview/show.html", ctx)
+}
+
+pub async fn list_templates(request: HttpRequest) -> Result<HttpResponse> {
+    let names = {
+        let templates = templates(&request)?;
+        let engine = templates
+            .read()
+            .map_err(|e| Error::Generic(format!("Error acquiring template read lock: {:?}", e)))?;
+        template_names(&engine)
+    };
+
+    request.render(200, "email_preview/index.html", {
+        let mut ctx = Context::new();
+        ctx.insert("templates", &names);
+        ctx
+    })
+}
+
+pub async fn show_template(
+    request: HttpRequest,
+    Path(template): Path<String>,
+) -> Result<HttpResponse> {
+    render_preview(&request, &template, "", Context::new())
+}
+
+pub async fn send_template(
+    request: HttpRequest,
+    Path(template): Path<String>,
+    form: Form<SendTemplateForm>,
+) -> Result<HttpResponse> {
+    if !can_send(&request)? {
+        return request.render(404, "404.html", Context::new());
+    }
+    let to = form.into_inner().to.trim().to_string();
+    let templates = find_template(&request, &template)?;
+    let mut ctx = Context::new();
+    match render_email(&template, &to, templates) {
+        Ok(email) => match email.deliver(request.db_pool()?) {
+            Ok(()) => ctx.insert(
+                "send_result",
+                &format!(
+                    "Sent to {}, see /admin/emails for the provider response.",
+                    to
+                ),
+            ),
+            Err(e) => ctx.insert("send_error", &format!("{:#}", e)),
+        },
+        Err(e) => ctx.insert("send_error", &e),
+    }
+    render_preview(&request, &template, &to, ctx)
+}
@@ -1,11 +1,14 @@
 use jelly::error::Error;
 use jelly::error::Error::Generic;
-use reqwest::blocking::{multipart, Response};
+use reqwest::blocking::Response;
 use reqwest::header;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::env;
 use std::hash::{Hash, Hasher};
 
+use crate::packages::platform::{self, ManifestDependency, Platform};
+
 #[derive(Deserialize, Serialize)]
 struct MoveToml {
     package: PackageToml,
@@ -15,13 +18,16 @@ struct MoveToml {
 struct PackageToml {
     name: String,
     version: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    license: Option<String>,
 }
 
-#[derive(Clone, Debug, Eq, Deserialize)]
+#[derive(Clone, Debug, Default, Eq, Deserialize)]
 pub struct GithubRepoData {
     pub name: String,
     pub version: String,
     pub readme_content: String,
+    pub readme_html: String,
     pub license: String,
     pub description: String,
     pub size: i32,
@@ -29,6 +35,19 @@ pub struct GithubRepoData {
     pub forks_count: i32,
     pub url: String,
     pub rev: String,
+    // sha256 of the normalized Move.toml, identical manifests are copies of the same package
+    pub manifest_hash: String,
+    // url of the repository this one was forked from
+    pub fork_source_url: Option<String>,
+    pub changelog_content: String,
+    pub license_content: String,
+    // detected from the dependencies of the Move.toml, see `packages::platform`
+    pub platform: String,
+    pub dependencies: Vec<ManifestDependency>,
+    // `license` of the Move.toml, the license name returned by GitHub is in `license`
+    pub manifest_license: String,
+    // SPDX expression of the manifest or GitHub license, empty when not recognized
+    pub license_spdx: String,
 }
 
 impl PartialEq for GithubRepoData {
@@ -52,6 +71,23 @@ pub struct GithubRepoInfo {
     pub forks_count: i32,
     pub default_branch: String,
     pub license: Option<GithubLicenseInfo>,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub fork: bool,
+    pub source: Option<GithubRepoSource>,
+    /// Only returned to the account the token belongs to
+    pub permissions: Option<GithubRepoPermissions>,
+}
+
+#[derive(Clone, Default, Deserialize)]
+pub struct GithubRepoSource {
+    pub html_url: String,
+}
+
+#[derive(Clone, Default, Deserialize)]
+pub struct GithubRepoPermissions {
+    pub admin: bool,
 }
 
 #[derive(Clone, Default, Deserialize)]
@@ -59,6 +95,39 @@ pub struct GithubLicenseInfo {
     pub key: String,
     pub name: String,
     pub url: String,
+    // `NOASSERTION` for licenses GitHub doesn't recognize
+    #[serde(default)]
+    pub spdx_id: Option<String>,
+}
+
+impl GithubLicenseInfo {
+    /// The SPDX expression of the manifest license, or else of this license.
+    pub fn resolve_spdx(&self, manifest_license: Option<&str>) -> Option<String> {
+        license::resolve_license(&[manifest_license, self.spdx_id.as_deref(), Some(&self.name)])
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+pub struct GithubTreeEntry {
+    pub path: String,
+    #[serde(rename = "type")]
+    pub entry_type: String,
+    #[serde(default)]
+    pub size: i32,
+}
+
+#[derive(Deserialize)]
+struct GithubTree {
+    tree: Vec<GithubTreeEntry>,
+    #[serde(default)]
+    truncated: bool,
+}
+
+#[derive(Deserialize)]
+struct GithubContentEntry {
+    name: String,
+    #[serde(rename = "type")]
+    entry_type: String,
 }
 
 #[derive(Deserialize)]
@@ -66,9 +135,16 @@ pub struct GithubRepoCommit {
     pub sha: String,
 }
 
+const README_FILE_NAMES: [&str; 4] = ["README.md", "readme.md", "Readme.md", "README"];
+const CHANGELOG_FILE_NAMES: [&str; 4] =
+    ["CHANGELOG.md", "changelog.md", "Changelog.md", "CHANGELOG"];
+const LICENSE_FILE_NAMES: [&str; 4] = ["LICENSE", "LICENSE.md", "LICENSE.txt", "license"];
+
 pub static APP_USER_AGENT: &str = concat!(env!("CARGO_PKG_NAME"), "/", env!("CARGO_PKG_VERSION"),);
 
-use crate::constants::DEEP_AI_URL;
+use crate::summarizer;
+use crate::utils::license;
+use crate::utils::markdown::{self, MarkdownLinks};
 #[cfg(test)]
 use mockall::{automock, predicate::*};
 use oauth2::http::StatusCode;
@@ -109,66 +185,73 @@ impl GithubService {
         }
         let rev = rev.unwrap();
 
-        // example readme url:
+        // example raw file url:
         // https://raw.githubusercontent.com/move-language/move/main/README.md
-        let readme_url = format!(
-            "{}/{}/README.md",
+        let raw_url = format!(
+            "{}/{}",
             repo_url.replace("https://github.com", "https://raw.githubusercontent.com"),
             rev
         );
+        // files of a package in a subdir are looked up in the subdir first, then in the repository root
+        let mut file_dirs = vec![];
+        if let Some(subdir) = path
+            .as_deref()
+            .and_then(|path| path.strip_suffix("Move.toml"))
+            .map(|subdir| subdir.trim_end_matches('/'))
+            .filter(|subdir| !subdir.is_empty())
+        {
+            file_dirs.push(subdir.to_string());
+        }
+        file_dirs.push("".to_string());
+        // each directory is listed once instead of probing every possible file name
+        let file_dirs = file_dirs
+            .into_iter()
+            .map(|dir| {
+                let file_names = list_dir_files(repo_url, &rev, &dir);
+                (dir, file_names)
+            })
+            .collect::<Vec<(String, Vec<String>)>>();
+
         let mut readme_content = "".to_string();
-        let response = call_github_api(&readme_url)?;
-        if response.status() != StatusCode::NOT_FOUND {
-            match response.text() {
-                Ok(content) => {
-                    // generate description from readme if not existed
-                    if github_info.description.is_none() {
-                        let mut description = call_deep_ai_api(content.clone(), None)?;
-                        if description.chars().count() > 100 {
-                            let deepai_summary = call_deep_ai_api(description.clone(), None)?;
-                            if !deepai_summary.is_empty() {
-                                description = deepai_summary;
-                            }
-                        }
-                        description = strip_markdown::strip_markdown(&description);
-
-                        if description.is_empty() {
-                            description = strip_markdown::strip_markdown(&content);
-                        }
-                        description = description.chars().take(100).collect();
-                        github_info.description = Some(description);
-                    }
-
-                    let formatted_readme_src_http =
-                        format!("src=\"{}", readme_url.replace("README.md", ""),);
-                    let formatted_readme_src_markdown =
-                        format!("]({}", readme_url.replace("README.md", ""),);
-
-                    // Add prefix url for linkable content in readme file such as: images, link...
-                    let formatted_readme_content = content
-                        .replace("src=\"", &formatted_readme_src_http)
-                        .replace(&format!("{}http", &formatted_readme_src_http), "src=\"http")
-                        .replace("](", &formatted_readme_src_markdown)
-                        .replace(&format!("{}http", &formatted_readme_src_markdown), "](http");
-
-                    readme_content = formatted_readme_content
-                }
-                _ => {
-                    warn!("Error getting README.md content. url: {}", readme_url);
-                }
+        let mut readme_html = "".to_string();
+        if let Some((readme_dir, content)) =
+            fetch_first_file(&raw_url, &file_dirs, &README_FILE_NAMES)
+        {
+            // generate description from readme if not existed
+            if github_info.description.is_none() {
+                let description =
+                    summarizer::generate_description(summarizer::from_env().as_ref(), &content);
+                github_info.description = Some(description);
             }
+
+            // relative links and images in the readme point to files of the same rev
+            readme_html = markdown::render_markdown(
+                &content,
+                &MarkdownLinks {
+                    raw_base: raw_url.clone(),
+                    blob_base: format!("{}/blob/{}", repo_url, rev),
+                    dir: readme_dir,
+                },
+            );
+            readme_content = content;
         }
+        let changelog_content = fetch_first_file(&raw_url, &file_dirs, &CHANGELOG_FILE_NAMES)
+            .map(|(_, content)| content)
+            .unwrap_or_default();
+        let license_content = fetch_first_file(&raw_url, &file_dirs, &LICENSE_FILE_NAMES)
+            .map(|(_, content)| content)
+            .unwrap_or_default();
 
         let move_url = match path {
             // example Move.toml url with subdir:
             // https://raw.githubusercontent.com/move-language/move/main/language/evm/hardhat-examples/contracts/ABIStruct/Move.toml
             Some(path) => {
-                format!("{}/{}", readme_url.replace("/README.md", ""), path)
+                format!("{}/{}", raw_url, path)
             }
             None => {
                 // Move.toml in top directory:
                 // https://raw.githubusercontent.com/taoheorg/taohe/master/Move.toml
-                readme_url.replace("README.md", "Move.toml")
+                format!("{}/Move.toml", raw_url)
             }
         };
 
@@ -183,16 +266,29 @@ impl GithubService {
             }
         };
 
-        let license = match github_info.license {
-            Some(license) => license.name,
+        let github_license = github_info.license;
+        let license = match &github_license {
+            Some(license) => license.name.clone(),
             None => "".to_string(),
         };
+        let resolve_spdx = |manifest_license: Option<&str>| match &github_license {
+            Some(github_license) => github_license.resolve_spdx(manifest_license),
+            None => license::resolve_license(&[manifest_license]),
+        };
+        let fork_source_url = match github_info.source {
+            Some(source) if github_info.fork => Some(source.html_url),
+            _ => None,
+        };
 
         match toml::from_str::<MoveToml>(&move_toml_content) {
             Ok(move_toml) => Ok(GithubRepoData {
+                license_spdx: resolve_spdx(move_toml.package.license.as_deref())
+                    .unwrap_or_default(),
+                manifest_license: move_toml.package.license.unwrap_or_default(),
                 name: move_toml.package.name,
                 version: move_toml.package.version,
                 readme_content,
+                readme_html,
                 license,
                 description: github_info.description.unwrap_or_else(|| "".to_string()),
                 size: github_info.size,
@@ -201,6 +297,14 @@ impl GithubService {
                 // this field is overwritten in the crawler, modified this to save default branch
                 url: github_info.default_branch,
                 rev,
+                manifest_hash: manifest_hash(&move_toml_content),
+                fork_source_url,
+                changelog_content,
+                license_content,
+                platform: platform::detect_platform(&move_toml_content)
+                    .as_str()
+                    .to_string(),
+                dependencies: platform::parse_dependencies(&move_toml_content),
             }),
             Err(error) => {
                 warn!(
@@ -211,6 +315,7 @@ impl GithubService {
                     name: String::from(""),
                     version: String::from(""),
                     readme_content,
+                    readme_html,
                     license,
                     description: github_info.description.unwrap_or_else(|| "".to_string()),
                     size: github_info.size,
@@ -219,63 +324,266 @@ impl GithubService {
                     // this field is overwritten in the crawler, modified this to save default branch
                     url: github_info.default_branch,
                     rev,
+                    manifest_hash: String::new(),
+                    fork_source_url,
+                    changelog_content,
+                    license_content,
+                    platform: Platform::Unknown.as_str().to_string(),
+                    dependencies: vec![],
+                    manifest_license: String::new(),
+                    license_spdx: resolve_spdx(None).unwrap_or_default(),
                 })
             }
         }
     }
+
+    /// Fetches the current repository metadata, `None` means the repository
+    /// has been deleted or made private.
+    pub fn fetch_repo_metadata(&self, repo_url: &str) -> Result<Option<GithubRepoInfo>, Error> {
+        let url = repo_api_url(repo_url);
+        let response = call_github_api(&url)?;
+        if response.status() == StatusCode::NOT_FOUND || response.status() == StatusCode::GONE {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(Generic(format!(
+                "Error getting repo metadata. url: {:?}, status: {}",
+                url,
+                response.status()
+            )));
+        }
+        match response.json::<GithubRepoInfo>() {
+            Ok(info) => Ok(Some(info)),
+            Err(error) => Err(Generic(format!(
+                "Error getting repo metadata. url: {:?}, error: {}",
+                url, error
+            ))),
+        }
+    }
+
+    /// Whether the GitHub account the OAuth token belongs to administers the repository,
+    /// its owner included. The server token would only tell about the server account.
+    pub fn is_repo_admin(&self, repo_url: &str, user_token: &str) -> Result<bool, Error> {
+        let url = repo_api_url(repo_url);
+        let response = call_github_api_with_token(&url, user_token)?;
+        // The repository is private and the account cannot see it, or it is gone
+        if response.status() == StatusCode::NOT_FOUND || response.status() == StatusCode::GONE {
+            return Ok(false);
+        }
+        if !response.status().is_success() {
+            return Err(Generic(format!(
+                "Error getting repo permissions. url: {:?}, status: {}",
+                url,
+                response.status()
+            )));
+        }
+        match response.json::<GithubRepoInfo>() {
+            Ok(info) => Ok(info
+                .permissions
+                .map_or(false, |permissions| permissions.admin)),
+            Err(error) => Err(Generic(format!(
+                "Error getting repo permissions. url: {:?}, error: {}",
+                url, error
+            ))),
+        }
+    }
+
+    /// Lists the files of a package directory at the given rev, with paths relative to `subdir`.
+    pub fn fetch_source_tree(
+        &self,
+        repo_url: &str,
+        rev: &str,
+        subdir: &str,
+    ) -> Result<Vec<GithubTreeEntry>, Error> {
+        let url = format!("{}/git/trees/{}?recursive=1", repo_api_url(repo_url), rev);
+        let response = call_github_api(&url)?;
+        if !response.status().is_success() {
+            return Err(Generic(format!(
+                "Error getting source tree. url: {:?}, status: {}",
+                url,
+                response.status()
+            )));
+        }
+        let tree = response.json::<GithubTree>().map_err(|error| {
+            Generic(format!(
+                "Error getting source tree. url: {:?}, error: {}",
+                url, error
+            ))
+        })?;
+        if tree.truncated {
+            warn!("Source tree is truncated. url: {:?}", url);
+        }
+
+        let prefix = if subdir.is_empty() {
+            "".to_string()
+        } else {
+            format!("{}/", subdir.trim_end_matches('/'))
+        };
+        let mut files = tree
+            .tree
+            .into_iter()
+            .filter(|entry| entry.entry_type == "blob")
+            .filter_map(|entry| {
+                let path = entry.path.strip_prefix(&prefix)?.to_string();
+                Some(GithubTreeEntry { path, ..entry })
+            })
+            .collect::<Vec<GithubTreeEntry>>();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    /// Fetches a file at the given rev, `path` is relative to the repository root.
+    pub fn fetch_source_file(
+        &self,
+        repo_url: &str,
+        rev: &str,
+        path: &str,
+    ) -> Result<Option<String>, Error> {
+        let url = format!(
+            "{}/{}/{}",
+            repo_root_url(repo_url)
+                .replace("https://github.com", "https://raw.githubusercontent.com"),
+            rev,
+            path
+        );
+        let response = call_github_api(&url)?;
+        if response.status() == StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(Generic(format!(
+                "Error getting source file. url: {:?}, status: {}",
+                url,
+                response.status()
+            )));
+        }
+        Ok(Some(response.text()?))
+    }
+}
+
+// Names of the files directly in a directory of the repository, a directory
+// that can't be listed is considered empty
+fn list_dir_files(repo_url: &str, rev: &str, dir: &str) -> Vec<String> {
+    let url = if dir.is_empty() {
+        format!("{}/contents?ref={}", repo_api_url(repo_url), rev)
+    } else {
+        format!("{}/contents/{}?ref={}", repo_api_url(repo_url), dir, rev)
+    };
+    let response = match call_github_api(&url) {
+        Ok(response) if response.status().is_success() => response,
+        Ok(response) => {
+            warn!(
+                "Error listing directory. url: {}, status: {}",
+                url,
+                response.status()
+            );
+            return vec![];
+        }
+        Err(error) => {
+            warn!("Error listing directory. url: {}, error: {}", url, error);
+            return vec![];
+        }
+    };
+    match response.json::<Vec<GithubContentEntry>>() {
+        Ok(entries) => entries
+            .into_iter()
+            .filter(|entry| entry.entry_type == "file")
+            .map(|entry| entry.name)
+            .collect(),
+        Err(error) => {
+            warn!("Error listing directory. url: {}, error: {}", url, error);
+            vec![]
+        }
+    }
+}
+
+// Returns the directory and content of the first listed file, directories are
+// relative to the repository root and tried in order. A file that can't be
+// fetched is skipped like a missing one.
+fn fetch_first_file(
+    raw_url: &str,
+    dirs: &[(String, Vec<String>)],
+    file_names: &[&str],
+) -> Option<(String, String)> {
+    for (dir, dir_files) in dirs {
+        for file_name in file_names {
+            if !dir_files.iter().any(|name| name == file_name) {
+                continue;
+            }
+            let url = if dir.is_empty() {
+                format!("{}/{}", raw_url, file_name)
+            } else {
+                format!("{}/{}/{}", raw_url, dir, file_name)
+            };
+            let response = match call_github_api(&url) {
+                Ok(response) if response.status().is_success() => response,
+                Ok(response) => {
+                    warn!(
+                        "Error getting file content. url: {}, status: {}",
+                        url,
+                        response.status()
+                    );
+                    continue;
+                }
+                Err(error) => {
+                    warn!("Error getting file content. url: {}, error: {}", url, error);
+                    continue;
+                }
+            };
+            match response.text() {
+                Ok(content) => return Some((dir.clone(), content)),
+                Err(error) => {
+                    warn!("Error getting file content. url: {}, error: {}", url, error);
+                }
+            }
+        }
+    }
+    None
+}
+
+// Comments and formatting don't make a copied Move.toml a different package
+fn manifest_hash(move_toml_content: &str) -> String {
+    let normalized = move_toml_content
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or("").trim())
+        .filter(|line| !line.is_empty())
+        .collect::<Vec<&str>>()
+        .join("\n");
+    format!("{:x}", Sha256::digest(normalized.as_bytes()))
+}
+
+// Package urls may point to a subdir, e.g: https://github.com/move-language/move/tree/main/language/move-stdlib,
+// only the owner and repository name are kept
+fn repo_root_url(repo_url: &str) -> String {
+    match repo_url.strip_prefix("https://github.com/") {
+        Some(path) => format!(
+            "https://github.com/{}",
+            path.split('/').take(2).collect::<Vec<&str>>().join("/")
+        ),
+        None => repo_url.to_string(),
+    }
+}
+
+fn repo_api_url(repo_url: &str) -> String {
+    repo_root_url(repo_url).replace("https://github.com/", "https://api.github.com/repos/")
 }
 
 fn call_github_api(url: &str) -> Result<Response, Error> {
     let access_token = env::var("GITHUB_ACCESS_TOKEN").expect("Unable to pull GITHUB_ACCESS_TOKEN");
+    call_github_api_with_token(url, &access_token)
+}
+
+fn call_github_api_with_token(url: &str, access_token: &str) -> Result<Response, Error> {
     let client = reqwest::blocking::Client::builder()
         .user_agent(APP_USER_AGENT)
         .build()?;
     let res = client
         .get(url)
-        .header(header::AUTHORIZATION, format!("token {}", &access_token))
+        .header(header::AUTHORIZATION, format!("token {}", access_token))
         .send()?;
     Ok(res)
 }
 
-// url param is only used in testing
-fn call_deep_ai_api(content: String, url: Option<&str>) -> Result<String, Error> {
-    let access_token = env::var("DEEP_AI_API_KEY").expect("Unable to pull DEEP_AI_API_KEY");
-    // not be able to mock both get and post func of Client at the moment,
-    // use full path to avoid using MockClient
-    let client = reqwest::blocking::Client::builder()
-        .user_agent(APP_USER_AGENT)
-        .build()?;
-    let form = multipart::Form::new().text("text", content);
-    let url = url.unwrap_or(DEEP_AI_URL);
-    let response = client
-        .post(url)
-        .header("api-key", access_token)
-        .multipart(form)
-        .send()
-        .ok();
-    if response.is_none() {
-        return Ok(String::new());
-    };
-
-    #[derive(Deserialize)]
-    struct DeepApiResponse {
-        output: String,
-    }
-
-    match response.unwrap().json::<DeepApiResponse>() {
-        Ok(response) => {
-            if response.output.is_empty() {
-                return Ok(String::new());
-            }
-            Ok(response.output)
-        }
-        Err(error) => {
-            error!("Error getting response from deepai.org. error: {}", error);
-            Ok(String::new())
-        }
-    }
-}
-
 fn get_repo_description_and_size(repo_url: &str) -> Result<GithubRepoInfo, Error> {
     let url = repo_url.replace("https://github.com/", "https://api.github.com/repos/");
     let response = call_github_api(&url)?;
@@ -379,123 +687,218 @@ mod tests {
     }
 
     #[test]
-    fn get_repo_latest_commit_sha_works() {
+    fn fetch_repo_metadata_works() {
         crate::test::init();
 
-        let access_token = env::var("GITHUB_ACCESS_TOKEN").unwrap();
         let server = MockServer::start();
         let server_mock = server.mock(|when, then| {
-            when.method(GET)
-                .path("/commits")
-                .header("User-Agent", APP_USER_AGENT)
-                .header("authorization", format!("token {}", &access_token));
-            then.status(200).json_body(json!([
-                { "sha": "test sha" }
-            ]));
+            when.method(GET).header("User-Agent", APP_USER_AGENT);
+            then.status(200).json_body(json!({
+                "description": "test description",
+                "size": 1,
+                "stargazers_count": 2,
+                "forks_count": 3,
+                "default_branch": "main",
+                "archived": true,
+            }));
         });
 
-        let result = get_repo_latest_commit_sha(&server.base_url());
+        let result = GithubService::new()
+            .fetch_repo_metadata(&server.base_url())
+            .unwrap()
+            .unwrap();
         server_mock.assert();
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "test sha");
+        assert_eq!(result.stargazers_count, 2);
+        assert_eq!(result.forks_count, 3);
+        assert!(result.archived);
     }
 
     #[test]
-    fn get_repo_latest_commit_sha_returns_err_if_response_body_is_empty() {
+    fn fetch_repo_metadata_returns_none_if_repo_not_found() {
         crate::test::init();
 
-        let access_token = env::var("GITHUB_ACCESS_TOKEN").unwrap();
         let server = MockServer::start();
         let server_mock = server.mock(|when, then| {
-            when.method(GET)
-                .path("/commits")
-                .header("User-Agent", APP_USER_AGENT)
-                .header("authorization", format!("token {}", &access_token));
-            then.status(200);
+            when.method(GET).header("User-Agent", APP_USER_AGENT);
+            then.status(404);
         });
 
-        let result = get_repo_latest_commit_sha(&server.base_url());
+        let result = GithubService::new()
+            .fetch_repo_metadata(&server.base_url())
+            .unwrap();
         server_mock.assert();
-        assert!(result.is_err());
+        assert!(result.is_none());
     }
 
     #[test]
-    fn call_deep_ai_api_works() {
+    fn is_repo_admin_asks_github_with_the_user_token() {
         crate::test::init();
 
-        let access_token = env::var("DEEP_AI_API_KEY").expect("Unable to pull DEEP_AI_API_KEY");
         let server = MockServer::start();
-        // TODO: check if the request content type is multipart
-        // https://github.com/alexliesenfeld/httpmock/tree/master/tests/examples
-        let server_mock = server.mock(|when, then| {
-            when.method(POST)
-                .path("/api/summarization")
-                .header("User-Agent", APP_USER_AGENT)
-                .header("api-key", access_token);
-            then.status(200)
-                .json_body(json!({ "output":"summarized text" }));
+        let repo = |admin: bool| {
+            json!({
+                "size": 1,
+                "stargazers_count": 2,
+                "forks_count": 3,
+                "default_branch": "main",
+                "permissions": { "admin": admin, "push": true, "pull": true },
+            })
+        };
+        let admin_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/")
+                .header("authorization", "token admin-token");
+            then.status(200).json_body(repo(true));
+        });
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/")
+                .header("authorization", "token writer-token");
+            then.status(200).json_body(repo(false));
+        });
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/")
+                .header("authorization", "token outsider-token");
+            then.status(404);
         });
 
-        let result = call_deep_ai_api(
-            "original text".to_string(),
-            Some(&format!("{}/api/summarization", &server.base_url())),
+        let service = GithubService::new();
+        assert!(service
+            .is_repo_admin(&server.base_url(), "admin-token")
+            .unwrap());
+        assert!(!service
+            .is_repo_admin(&server.base_url(), "writer-token")
+            .unwrap());
+        assert!(!service
+            .is_repo_admin(&server.base_url(), "outsider-token")
+            .unwrap());
+        admin_mock.assert();
+    }
+
+    #[test]
+    fn manifest_hash_ignores_comments_and_formatting() {
+        let original = "[package]\nname = \"MoveStdlib\"\nversion = \"1.5.0\"\n";
+        let copy =
+            "# vendored\n[package]\n  name = \"MoveStdlib\"\n\nversion = \"1.5.0\" # pinned\n";
+        let other = "[package]\nname = \"MoveStdlib\"\nversion = \"1.6.0\"\n";
+        assert_eq!(manifest_hash(original), manifest_hash(copy));
+        assert_ne!(manifest_hash(original), manifest_hash(other));
+    }
+
+    #[test]
+    fn repo_api_url_strips_subdir() {
+        assert_eq!(
+            repo_api_url("https://github.com/move-language/move/tree/main/language/move-stdlib"),
+            "https://api.github.com/repos/move-language/move"
+        );
+        assert_eq!(
+            repo_api_url("https://github.com/taoheorg/taohe"),
+            "https://api.github.com/repos/taoheorg/taohe"
         );
+    }
+
+    #[test]
+    fn fetch_source_tree_lists_files_of_package_subdir() {
+        crate::test::init();
+
+        let server = MockServer::start();
+        let server_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/EastAgile/ea-movey/git/trees/rev")
+                .query_param("recursive", "1");
+            then.status(200).json_body(json!({
+                "tree": [
+                    { "path": "pkg/sources", "type": "tree" },
+                    { "path": "pkg/sources/coin.move", "type": "blob", "size": 120 },
+                    { "path": "pkg/Move.toml", "type": "blob", "size": 60 },
+                    { "path": "README.md", "type": "blob", "size": 10 },
+                ],
+                "truncated": false,
+            }));
+        });
+
+        let repo_url = format!("{}/EastAgile/ea-movey", server.base_url());
+        let files = GithubService::new()
+            .fetch_source_tree(&repo_url, "rev", "pkg")
+            .unwrap();
         server_mock.assert();
-        assert!(result.is_ok());
-        assert_eq!(result.unwrap(), "summarized text");
+        let paths = files
+            .iter()
+            .map(|file| file.path.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(paths, vec!["Move.toml", "sources/coin.move"]);
+        assert_eq!(files[1].size, 120);
     }
 
     #[test]
-    fn call_deep_ai_api_returns_empty_string_if_deep_ai_response_body_is_empty() {
+    fn fetch_source_file_returns_none_if_file_not_found() {
         crate::test::init();
 
-        let access_token = env::var("DEEP_AI_API_KEY").expect("Unable to pull DEEP_AI_API_KEY");
         let server = MockServer::start();
-        // TODO: check if the request content type is multipart
+        let found_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/EastAgile/ea-movey/rev/sources/coin.move");
+            then.status(200).body("module 0x1::coin {}");
+        });
+
+        let repo_url = format!("{}/EastAgile/ea-movey", server.base_url());
+        let service = GithubService::new();
+        let content = service
+            .fetch_source_file(&repo_url, "rev", "sources/coin.move")
+            .unwrap();
+        found_mock.assert();
+        assert_eq!(content, Some("module 0x1::coin {}".to_string()));
+
+        let content = service
+            .fetch_source_file(&repo_url, "rev", "sources/missing.move")
+            .unwrap();
+        assert!(content.is_none());
+    }
+
+    #[test]
+    fn get_repo_latest_commit_sha_works() {
+        crate::test::init();
+
+        let access_token = env::var("GITHUB_ACCESS_TOKEN").unwrap();
+        let server = MockServer::start();
         let server_mock = server.mock(|when, then| {
-            when.method(POST)
-                .path("/api/summarization")
+            when.method(GET)
+                .path("/commits")
                 .header("User-Agent", APP_USER_AGENT)
-                .header("api-key", access_token);
-            then.status(200).json_body(json!({ "output":"" }));
+                .header("authorization", format!("token {}", &access_token));
+            then.status(200).json_body(json!([
+                { "sha": "test sha" }
+            ]));
         });
 
-        let result = call_deep_ai_api(
-            "original text".to_string(),
-            Some(&format!("{}/api/summarization", &server.base_url())),
-        );
+        let result = get_repo_latest_commit_sha(&server.base_url());
         server_mock.assert();
         assert!(result.is_ok());
-        assert!(result.unwrap().is_empty());
+        assert_eq!(result.unwrap(), "test sha");
     }
 
     #[test]
-    fn call_deep_ai_api_returns_empty_string_if_out_of_free_credit() {
+    fn get_repo_latest_commit_sha_returns_err_if_response_body_is_empty() {
         crate::test::init();
 
-        let access_token = env::var("DEEP_AI_API_KEY").expect("Unable to pull DEEP_AI_API_KEY");
+        let access_token = env::var("GITHUB_ACCESS_TOKEN").unwrap();
         let server = MockServer::start();
-        // TODO: check if the request content type is multipart
         let server_mock = server.mock(|when, then| {
-            when.method(POST)
-                .path("/api/summarization")
+            when.method(GET)
+                .path("/commits")
                 .header("User-Agent", APP_USER_AGENT)
-                .header("api-key", access_token);
-            then.status(401).json_body(json!({
-                "status":"Out of free credits \
-                    - please enter payment info in your dashboard: https://deepai.org/dashboard"
-            }));
+                .header("authorization", format!("token {}", &access_token));
+            then.status(200);
         });
 
-        let result = call_deep_ai_api(
-            "original text".to_string(),
-            Some(&format!("{}/api/summarization", &server.base_url())),
-        );
+        let result = get_repo_latest_commit_sha(&server.base_url());
         server_mock.assert();
-        assert!(result.is_ok());
-        assert!(result.unwrap().is_empty());
+        assert!(result.is_err());
     }
 
+    const README_CONTENT: &str = "test readme content - <img src=\"one.png\" /> - <img src=\"http://two.png\" /> - [three](three) - [four](http://four)";
+
     #[test]
     fn fetch_repo_data_works() {
         crate::test::init();
@@ -515,18 +918,40 @@ mod tests {
                 "default_branch": "test-default-branch",
             }));
         });
+        let contents_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/EastAgile/ea-movey/contents")
+                .query_param("ref", "rev");
+            then.status(200).json_body(json!([
+                { "name": "README.md", "type": "file" },
+                { "name": "CHANGELOG.md", "type": "file" },
+                { "name": "LICENSE", "type": "file" },
+                { "name": "Move.toml", "type": "file" },
+                { "name": "sources", "type": "dir" },
+            ]));
+        });
         let readme_mock = server.mock(|when, then| {
             when.method(GET)
                 .path("/EastAgile/ea-movey/rev/README.md")
                 .header("User-Agent", APP_USER_AGENT)
                 .header("authorization", format!("token {}", &access_token));
-            then.status(200).body("test readme content - <img src=\"one\" /> - <img src=\"http://two\" /> - [three](three) - [four](http://four)");
+            then.status(200).body(README_CONTENT);
+        });
+        let changelog_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/EastAgile/ea-movey/rev/CHANGELOG.md");
+            then.status(200).body("test changelog content");
+        });
+        let license_mock = server.mock(|when, then| {
+            when.method(GET).path("/EastAgile/ea-movey/rev/LICENSE");
+            then.status(200).body("test license content");
         });
 
         let move_toml = MoveToml {
             package: PackageToml {
                 name: "test package name".to_string(),
                 version: "0.0.0".to_string(),
+                license: Some("mit or apache-2.0".to_string()),
             },
         };
         let move_toml_mock = server.mock(|when, then| {
@@ -543,19 +968,165 @@ mod tests {
             .fetch_repo_data(&repo_url, None, Some("rev".to_string()))
             .unwrap();
 
-        let expected_readme_content = format!("test readme content - <img src=\"{}/rev/one\" /> - <img src=\"http://two\" /> - [three]({}/rev/three) - [four](http://four)", &repo_url, &repo_url);
-
         description_and_size_mock.assert();
+        contents_mock.assert();
         readme_mock.assert();
+        changelog_mock.assert();
+        license_mock.assert();
         move_toml_mock.assert();
         assert_eq!(gh_repo_data.name, "test package name");
         assert_eq!(gh_repo_data.version, "0.0.0");
-        assert_eq!(gh_repo_data.readme_content, expected_readme_content);
+        assert_eq!(gh_repo_data.readme_content, README_CONTENT);
+        assert!(gh_repo_data
+            .readme_html
+            .contains(&format!("<img src=\"{}/rev/one.png\">", &repo_url)));
+        assert!(gh_repo_data
+            .readme_html
+            .contains("<img src=\"http://two.png\">"));
+        assert!(gh_repo_data
+            .readme_html
+            .contains(&format!("<a href=\"{}/blob/rev/three\"", &repo_url)));
+        assert!(gh_repo_data.readme_html.contains("<a href=\"http://four\""));
         assert_eq!(gh_repo_data.description, "test description");
         assert_eq!(gh_repo_data.size, 10);
         assert_eq!(gh_repo_data.stars_count, 20);
         assert_eq!(gh_repo_data.forks_count, 30);
         assert_eq!(gh_repo_data.url, "test-default-branch");
         assert_eq!(gh_repo_data.rev, "rev");
+        assert!(!gh_repo_data.manifest_hash.is_empty());
+        assert_eq!(gh_repo_data.fork_source_url, None);
+        assert_eq!(gh_repo_data.changelog_content, "test changelog content");
+        assert_eq!(gh_repo_data.license_content, "test license content");
+        assert_eq!(gh_repo_data.platform, "unknown");
+        assert_eq!(gh_repo_data.manifest_license, "mit or apache-2.0");
+        assert_eq!(gh_repo_data.license_spdx, "MIT OR Apache-2.0");
+    }
+
+    #[test]
+    fn fetch_repo_data_prefers_files_in_package_subdir() {
+        crate::test::init();
+
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/EastAgile/ea-movey");
+            then.status(200).json_body(json!({
+                "description": "test description",
+                "size": 10,
+                "stargazers_count": 20,
+                "forks_count": 30,
+                "default_branch": "main",
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/EastAgile/ea-movey/contents")
+                .query_param("ref", "rev");
+            then.status(200).json_body(json!([
+                { "name": "README.md", "type": "file" },
+                { "name": "LICENSE", "type": "file" },
+                { "name": "pkg", "type": "dir" },
+            ]));
+        });
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/EastAgile/ea-movey/contents/pkg")
+                .query_param("ref", "rev");
+            then.status(200).json_body(json!([
+                { "name": "readme.md", "type": "file" },
+                { "name": "Move.toml", "type": "file" },
+            ]));
+        });
+        let root_readme_mock = server.mock(|when, then| {
+            when.method(GET).path("/EastAgile/ea-movey/rev/README.md");
+            then.status(200).body("root readme");
+        });
+        let subdir_readme_mock = server.mock(|when, then| {
+            when.method(GET)
+                .path("/EastAgile/ea-movey/rev/pkg/readme.md");
+            then.status(200).body("subdir readme - [link](doc.md)");
+        });
+        let root_license_mock = server.mock(|when, then| {
+            when.method(GET).path("/EastAgile/ea-movey/rev/LICENSE");
+            then.status(200).body("root license");
+        });
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/EastAgile/ea-movey/rev/pkg/Move.toml");
+            then.status(200)
+                .body("[package]\nname = \"pkg\"\nversion = \"0.1.0\"\n");
+        });
+
+        let repo_url = format!("{}/EastAgile/ea-movey", server.base_url());
+        let gh_repo_data = GithubService::new()
+            .fetch_repo_data(
+                &repo_url,
+                Some("pkg/Move.toml".to_string()),
+                Some("rev".to_string()),
+            )
+            .unwrap();
+
+        subdir_readme_mock.assert();
+        root_readme_mock.assert_hits(0);
+        root_license_mock.assert();
+        assert_eq!(gh_repo_data.name, "pkg");
+        assert_eq!(
+            gh_repo_data.readme_content,
+            "subdir readme - [link](doc.md)"
+        );
+        assert!(gh_repo_data
+            .readme_html
+            .contains(&format!("<a href=\"{}/blob/rev/pkg/doc.md\"", &repo_url)));
+        assert_eq!(gh_repo_data.license_content, "root license");
+        assert_eq!(gh_repo_data.changelog_content, "");
+    }
+
+    #[test]
+    fn fetch_repo_data_treats_unreadable_files_as_missing() {
+        crate::test::init();
+
+        let server = MockServer::start();
+        server.mock(|when, then| {
+            when.method(GET).path("/EastAgile/ea-movey");
+            then.status(200).json_body(json!({
+                "description": "test description",
+                "size": 10,
+                "stargazers_count": 20,
+                "forks_count": 30,
+                "default_branch": "main",
+            }));
+        });
+        server.mock(|when, then| {
+            when.method(GET)
+                .path("/EastAgile/ea-movey/contents")
+                .query_param("ref", "rev");
+            then.status(200).json_body(json!([
+                { "name": "LICENSE", "type": "file" },
+                { "name": "Move.toml", "type": "file" },
+            ]));
+        });
+        let readme_mock = server.mock(|when, then| {
+            when.method(GET).path("/EastAgile/ea-movey/rev/README.md");
+            then.status(200).body("unlisted readme");
+        });
+        let license_mock = server.mock(|when, then| {
+            when.method(GET).path("/EastAgile/ea-movey/rev/LICENSE");
+            then.status(500);
+        });
+        server.mock(|when, then| {
+            when.method(GET).path("/EastAgile/ea-movey/rev/Move.toml");
+            then.status(200)
+                .body("[package]\nname = \"pkg\"\nversion = \"0.1.0\"\n");
+        });
+
+        let repo_url = format!("{}/EastAgile/ea-movey", server.base_url());
+        let gh_repo_data = GithubService::new()
+            .fetch_repo_data(&repo_url, None, Some("rev".to_string()))
+            .unwrap();
+
+        readme_mock.assert_hits(0);
+        license_mock.assert();
+        assert_eq!(gh_repo_data.name, "pkg");
+        assert_eq!(gh_repo_data.readme_content, "");
+        assert_eq!(gh_repo_data.license_content, "");
     }
 }
@@ -1,21 +1,36 @@
 use crate::github_service::{GithubRepoData, APP_USER_AGENT};
 use crate::utils::presenter::validate_version;
 use core::time::Duration;
+use diesel::result::{DatabaseErrorKind, Error as DBError};
 use jelly::actix_web::http::header;
 use jelly::actix_web::rt::time::delay_for;
+use jelly::anyhow::Error;
+use jelly::chrono::Utc;
+use jelly::error::Error as JellyError;
+use jelly::jobs::{Job, JobState};
 use jelly::DieselPgPool;
 use mockall_double::double;
 use rayon::prelude::*;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use std::env;
+use std::future::Future;
 use std::iter::FromIterator;
+use std::pin::Pin;
 use std::sync::Mutex;
 
 #[double]
 use crate::github_service::GithubService;
 use crate::packages::Package;
 
+pub mod models;
+
+use models::{CrawlCursor, CrawlMode, CrawlRun, CrawlStats};
+
+pub const FULL_CRAWL_CURSOR: &str = "full";
+// Github API response is unstable, need to query it multiple times to get all packages
+const FULL_CRAWL_PAGES: i32 = 119;
+
 #[derive(Deserialize)]
 pub struct GithubSearchRepository {
     pub html_url: String,
@@ -34,33 +49,157 @@ pub struct GithubSearchResult {
     pub items: Vec<GithubSearchItem>,
 }
 
+#[derive(Debug, PartialEq, Eq)]
+pub struct CrawlFailure {
+    pub url: String,
+    pub message: String,
+}
+
 pub struct GithubCrawler {
     pub repo_urls: Vec<GithubSearchItem>,
     pub repos_data: Mutex<Vec<GithubRepoData>>,
     pub pool: DieselPgPool,
 }
 
+/// Crawls Github for packages. A full crawl is done whenever the previous one has not
+/// completed yet or is older than `FULL_CRAWL_INTERVAL_DAYS`, otherwise only recently
+/// indexed files are crawled.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CrawlPackages;
+
+impl Job for CrawlPackages {
+    type State = JobState;
+    type Future = Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
+
+    const NAME: &'static str = "CrawlPackages";
+
+    fn run(self, state: JobState) -> Self::Future {
+        Box::pin(async move {
+            // The schedule's lease is held while a run is in progress, and only lapses when
+            // the process holding it stops renewing it, most likely because it died. A run
+            // still marked as running then was cut short.
+            match CrawlRun::interrupt_unfinished(&state.pool) {
+                Ok(count) if count > 0 => {
+                    warn!("Marked {} unfinished crawl runs as interrupted", count)
+                }
+                Ok(_) => {}
+                Err(e) => error!("Cannot clean up unfinished crawl runs: {:?}", e),
+            }
+            let mode = next_crawl_mode(&state.pool);
+            let gh_crawler = GithubCrawler {
+                repo_urls: vec![],
+                repos_data: Mutex::new(vec![]),
+                pool: state.pool,
+            };
+            gh_crawler.run(mode).await;
+            Ok(())
+        })
+    }
+}
+
+fn next_crawl_mode(pool: &DieselPgPool) -> CrawlMode {
+    let full_crawl_interval_days = env::var("FULL_CRAWL_INTERVAL_DAYS")
+        .ok()
+        .and_then(|days| days.parse::<i64>().ok())
+        .unwrap_or(7);
+    let cursor = match CrawlCursor::get_or_create(FULL_CRAWL_CURSOR, pool) {
+        Ok(cursor) => cursor,
+        Err(e) => {
+            error!("Cannot load crawl cursor: {:?}", e);
+            return CrawlMode::Incremental;
+        }
+    };
+    match cursor.completed_at {
+        Some(completed_at)
+            if cursor.next_page == 1
+                && (Utc::now() - completed_at).num_days() < full_crawl_interval_days =>
+        {
+            CrawlMode::Incremental
+        }
+        _ => CrawlMode::Full,
+    }
+}
+
 impl GithubCrawler {
-    pub async fn run(mut self) {
-        let mut order = "desc";
-        // Github API response is unstable, need to query it multiple times to get all packages
-        for page in 1..120 {
-            if page == 61 {
-                order = "asc";
+    pub async fn run(mut self, mode: CrawlMode) {
+        let crawl_run = match CrawlRun::start(mode, &self.pool) {
+            Ok(crawl_run) => crawl_run,
+            Err(e) => {
+                error!("Cannot start crawl run: {:?}", e);
+                return;
             }
-            // Github API only allows us to query first ten pages
-            let url = format!(
-                "https://api.github.com/search/code?q=\
-                package%20in:file%20extension:toml%20filename:Move%20language:TOML&per_page=100&page={}&order={}&sort=indexed",
-                (page % 10) + 1,
-                order
-            );
-            self.find_new_repos(&url);
-            let gh_service = GithubService::new();
-            self.scrape(&gh_service);
-            self.save_to_db();
-            if page != 50 {
-                delay_for(Duration::from_secs(60)).await;
+        };
+        info!("Starting {} crawl run #{}", mode.as_str(), crawl_run.id);
+        match mode {
+            CrawlMode::Full => {
+                let cursor = match CrawlCursor::get_or_create(FULL_CRAWL_CURSOR, &self.pool) {
+                    Ok(cursor) => cursor,
+                    Err(e) => {
+                        error!("Cannot load crawl cursor: {:?}", e);
+                        return;
+                    }
+                };
+                for page in cursor.next_page.max(1)..=FULL_CRAWL_PAGES {
+                    // Second half of the pages is queried in ascending order to reach
+                    // files that never make it into the first ten pages
+                    let order = if page > FULL_CRAWL_PAGES / 2 {
+                        "asc"
+                    } else {
+                        "desc"
+                    };
+                    // Github API only allows us to query first ten pages
+                    self.crawl_page(&crawl_run, (page % 10) + 1, order);
+                    if let Err(e) = cursor.advance(page + 1, &self.pool) {
+                        error!("Cannot save crawl cursor: {:?}", e);
+                    }
+                    if page != FULL_CRAWL_PAGES {
+                        delay_for(Duration::from_secs(60)).await;
+                    }
+                }
+                if let Err(e) = cursor.complete(&self.pool) {
+                    error!("Cannot complete crawl cursor: {:?}", e);
+                }
+            }
+            CrawlMode::Incremental => {
+                let incremental_pages = env::var("CRAWL_INCREMENTAL_PAGES")
+                    .ok()
+                    .and_then(|pages| pages.parse::<i32>().ok())
+                    .unwrap_or(2);
+                for page in 1..=incremental_pages {
+                    self.crawl_page(&crawl_run, page, "desc");
+                    if page != incremental_pages {
+                        delay_for(Duration::from_secs(60)).await;
+                    }
+                }
+            }
+        }
+        if let Err(e) = crawl_run.finish(&self.pool) {
+            error!("Cannot finish crawl run: {:?}", e);
+        }
+        info!("Finished {} crawl run #{}", mode.as_str(), crawl_run.id);
+    }
+
+    fn crawl_page(&mut self, crawl_run: &CrawlRun, search_page: i32, order: &str) {
+        let url = format!(
+            "https://api.github.com/search/code?q=\
+            package%20in:file%20extension:toml%20filename:Move%20language:TOML&per_page=100&page={}&order={}&sort=indexed",
+            search_page,
+            order
+        );
+        self.find_new_repos(&url);
+        let gh_service = GithubService::new();
+        let mut failures = self.scrape(&gh_service);
+        let (mut stats, save_failures) = self.save_to_db();
+        failures.extend(save_failures);
+        stats.found = self.repo_urls.len() as i32;
+        stats.failed = failures.len() as i32;
+
+        if let Err(e) = crawl_run.record(&stats, &self.pool) {
+            error!("Cannot record crawl run stats: {:?}", e);
+        }
+        for failure in failures.iter() {
+            if let Err(e) = crawl_run.record_error(&failure.url, &failure.message, &self.pool) {
+                error!("Cannot record crawl error: {:?}", e);
             }
         }
     }
@@ -97,8 +236,9 @@ impl GithubCrawler {
         info!("Found {} packages", self.repo_urls.len());
     }
 
-    fn scrape(&mut self, github_service: &GithubService) {
+    fn scrape(&mut self, github_service: &GithubService) -> Vec<CrawlFailure> {
         self.repos_data = Mutex::new(vec![]);
+        let failures = Mutex::new(vec![]);
         (0..self.repo_urls.len()).into_par_iter().for_each(|index| {
             if let Some(item) = self.repo_urls.get(index) {
                 let mut no_of_trial = 3;
@@ -151,31 +291,64 @@ impl GithubCrawler {
                             no_of_trial -= 1;
                             if no_of_trial == 0 {
                                 error!(
-                                    "Cannot get package info. url: {}, error: {}",
+                                    "Cannot get package info. url: {}, error: {:?}",
                                     &item.repository.html_url, e
                                 );
+                                if let Ok(mut failures) = failures.lock() {
+                                    failures.push(CrawlFailure {
+                                        url: item.repository.html_url.clone(),
+                                        message: format!("{:?}", e),
+                                    });
+                                }
+                                break;
                             };
                         }
                     }
                 }
             };
         });
+        failures.into_inner().unwrap_or_default()
     }
 
-    fn save_to_db(&self) {
+    /// Saves scraped packages, returning how many packages were created or got a new
+    /// version along with the packages that could not be saved.
+    fn save_to_db(&self) -> (CrawlStats, Vec<CrawlFailure>) {
+        let mut stats = CrawlStats::default();
         let guard = match self.repos_data.lock() {
             Ok(guard) => guard,
             Err(e) => {
                 error!("Error acquiring guard to save repo: {:?}", e);
-                return;
+                return (stats, vec![]);
             }
         };
         let repos_data: HashSet<GithubRepoData> = HashSet::from_iter(guard.iter().cloned());
-        repos_data.par_iter().for_each(|repo_data| {
-            let invalid_name_or_version =
-                validate_version(&repo_data.version);
-            if invalid_name_or_version.is_empty() {
-                let _ = Package::create_from_crawled_data(
+        let results = repos_data
+            .par_iter()
+            .map(|repo_data| {
+                let invalid_name_or_version = validate_version(&repo_data.version);
+                if !invalid_name_or_version.is_empty() {
+                    error!(
+                        "Crawler: either {} is not a valid name or {} is not a valid version. url: {}",
+                        repo_data.name, repo_data.version, repo_data.url
+                    );
+                    return Err(CrawlFailure {
+                        url: repo_data.url.clone(),
+                        message: format!("Invalid version: {}", repo_data.version),
+                    });
+                }
+                let package_exists = match self.pool.get() {
+                    Ok(conn) => {
+                        Package::get_by_name_and_repo_url(&repo_data.name, &repo_data.url, &conn)
+                            .is_ok()
+                    }
+                    Err(e) => {
+                        return Err(CrawlFailure {
+                            url: repo_data.url.clone(),
+                            message: e.to_string(),
+                        })
+                    }
+                };
+                match Package::create_from_crawled_data(
                     &repo_data.url,
                     &repo_data.description,
                     &repo_data.rev,
@@ -184,14 +357,42 @@ impl GithubCrawler {
                     None,
                     repo_data.clone(),
                     &self.pool,
-                );
-            } else {
-                error!(
-                    "Crawler: either {} is not a valid name or {} is not a valid version. url: {}",
-                    repo_data.name, repo_data.version, repo_data.url
-                );
+                ) {
+                    Ok(_) => Ok((true, package_exists)),
+                    // Existing versions and packages published by their owners are rejected
+                    // by `create_from_crawled_data`, they are neither new nor updated
+                    Err(JellyError::Database(DBError::DatabaseError(
+                        DatabaseErrorKind::UniqueViolation
+                        | DatabaseErrorKind::ForeignKeyViolation,
+                        _,
+                    ))) => Ok((false, package_exists)),
+                    Err(e) => {
+                        error!(
+                            "Crawler: cannot save package {}. url: {}, error: {:?}",
+                            repo_data.name, repo_data.url, e
+                        );
+                        Err(CrawlFailure {
+                            url: repo_data.url.clone(),
+                            message: e.to_string(),
+                        })
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut failures = vec![];
+        for result in results {
+            match result {
+                Ok((true, false)) => stats.new += 1,
+                Ok((true, true)) => stats.updated += 1,
+                Ok((false, _)) => {}
+                Err(failure) => {
+                    stats.failed += 1;
+                    failures.push(failure);
+                }
             }
-        });
+        }
+        (stats, failures)
     }
 }
 
@@ -199,12 +400,224 @@ impl GithubCrawler {
 mod tests {
     use super::*;
     use crate::packages::Package;
+    use crate::schema::{jelly_jobs, jelly_schedules};
     use crate::test::{DatabaseTestContext, DB_POOL};
+    use diesel::prelude::*;
     use httpmock::prelude::GET;
     use httpmock::MockServer;
+    use jelly::chrono::{Duration, Utc};
     use jelly::database;
+    use jelly::error::Error;
+    use jelly::jobs::cron::Cron;
+    use jelly::jobs::schedule::{Schedule, ScheduleRecord};
+    use jelly::jobs::storage::{
+        DeadJob, PgStorage, JOB_STATUS_DEAD, JOB_STATUS_PENDING, JOB_STATUS_RUNNING,
+    };
+    use jelly::uuid::Uuid;
     use serde_json::json;
 
+    fn insert_job(status: &str, attempts: i32, started_minutes_ago: Option<i64>) -> Uuid {
+        let id = Uuid::new_v4();
+        let now = Utc::now();
+        diesel::insert_into(jelly_jobs::table)
+            .values((
+                jelly_jobs::id.eq(id),
+                jelly_jobs::queue.eq("default"),
+                jelly_jobs::name.eq("TestJob"),
+                jelly_jobs::info.eq(json!({ "name": "TestJob", "args": { "id": id } })),
+                jelly_jobs::status.eq(status),
+                jelly_jobs::attempts.eq(attempts),
+                jelly_jobs::run_at.eq(now - Duration::minutes(1)),
+                jelly_jobs::started_at
+                    .eq(started_minutes_ago.map(|minutes| now - Duration::minutes(minutes))),
+                jelly_jobs::created_at.eq(now),
+                jelly_jobs::updated_at.eq(now),
+            ))
+            .execute(&DB_POOL.get().unwrap())
+            .unwrap();
+        id
+    }
+
+    fn job_status(id: Uuid) -> (String, i32, Option<String>) {
+        jelly_jobs::table
+            .find(id)
+            .select((
+                jelly_jobs::status,
+                jelly_jobs::attempts,
+                jelly_jobs::last_error,
+            ))
+            .first(&DB_POOL.get().unwrap())
+            .unwrap()
+    }
+
+    #[actix_rt::test]
+    async fn claim_next_job_skips_jobs_locked_by_other_workers() {
+        crate::test::init();
+        let _ctx = DatabaseTestContext::new();
+        let storage = PgStorage::new(DB_POOL.clone());
+        let locked = insert_job(JOB_STATUS_PENDING, 0, None);
+        let free = insert_job(JOB_STATUS_PENDING, 0, None);
+
+        let conn = DB_POOL.get().unwrap();
+        conn.transaction(|| -> QueryResult<()> {
+            jelly_jobs::table
+                .find(locked)
+                .select(jelly_jobs::id)
+                .for_update()
+                .first::<Uuid>(&conn)?;
+            let (claimed, _) = storage.claim_next_job("default").unwrap().unwrap();
+            assert_eq!(claimed, free);
+            assert!(storage.claim_next_job("default").unwrap().is_none());
+            Ok(())
+        })
+        .unwrap();
+
+        let (claimed, _) = storage.claim_next_job("default").unwrap().unwrap();
+        assert_eq!(claimed, locked);
+        assert_eq!(
+            job_status(locked),
+            (JOB_STATUS_RUNNING.to_string(), 1, None)
+        );
+    }
+
+    #[actix_rt::test]
+    async fn failed_jobs_are_queued_again_after_a_backoff() {
+        crate::test::init();
+        let _ctx = DatabaseTestContext::new();
+        let storage = PgStorage::new(DB_POOL.clone());
+        let id = insert_job(JOB_STATUS_PENDING, 0, None);
+
+        storage.claim_next_job("default").unwrap().unwrap();
+        storage.fail_job(id, "Cannot send email").unwrap();
+        assert_eq!(
+            job_status(id),
+            (
+                JOB_STATUS_PENDING.to_string(),
+                1,
+                Some("Cannot send email".to_string())
+            )
+        );
+        let run_at = jelly_jobs::table
+            .find(id)
+            .select(jelly_jobs::run_at)
+            .first::<jelly::chrono::DateTime<Utc>>(&DB_POOL.get().unwrap())
+            .unwrap();
+        assert!(run_at > Utc::now() + Duration::seconds(20));
+        assert!(storage.claim_next_job("default").unwrap().is_none());
+    }
+
+    #[actix_rt::test]
+    async fn jobs_are_dead_once_they_used_up_their_attempts() {
+        crate::test::init();
+        let _ctx = DatabaseTestContext::new();
+        let storage = PgStorage::new(DB_POOL.clone());
+        let id = insert_job(JOB_STATUS_RUNNING, 5, Some(1));
+
+        storage.fail_job(id, "Cannot send email").unwrap();
+        assert_eq!(
+            job_status(id),
+            (
+                JOB_STATUS_DEAD.to_string(),
+                5,
+                Some("Cannot send email".to_string())
+            )
+        );
+        assert!(storage.claim_next_job("default").unwrap().is_none());
+        let (dead_jobs, total) = DeadJob::paginated(1, 10, &DB_POOL).unwrap();
+        assert_eq!(total, 1);
+        assert_eq!(dead_jobs[0].id, id);
+    }
+
+    #[actix_rt::test]
+    async fn dead_jobs_are_retried_with_fresh_attempts() {
+        crate::test::init();
+        let _ctx = DatabaseTestContext::new();
+        let storage = PgStorage::new(DB_POOL.clone());
+        let dead = insert_job(JOB_STATUS_DEAD, 5, Some(10));
+        let pending = insert_job(JOB_STATUS_PENDING, 1, None);
+
+        assert!(!DeadJob::retry(pending, &DB_POOL).unwrap());
+        assert!(DeadJob::retry(dead, &DB_POOL).unwrap());
+        assert_eq!(job_status(dead).0, JOB_STATUS_PENDING);
+        assert_eq!(job_status(dead).1, 0);
+
+        let mut claimed = vec![
+            storage.claim_next_job("default").unwrap().unwrap().0,
+            storage.claim_next_job("default").unwrap().unwrap().0,
+        ];
+        claimed.sort();
+        let mut expected = vec![dead, pending];
+        expected.sort();
+        assert_eq!(claimed, expected);
+    }
+
+    #[actix_rt::test]
+    async fn abandoned_jobs_are_picked_up_again_until_they_used_up_their_attempts() {
+        crate::test::init();
+        let _ctx = DatabaseTestContext::new();
+        let storage = PgStorage::new(DB_POOL.clone());
+        let abandoned = insert_job(JOB_STATUS_RUNNING, 1, Some(31));
+        let exhausted = insert_job(JOB_STATUS_RUNNING, 5, Some(31));
+        let running = insert_job(JOB_STATUS_RUNNING, 1, Some(5));
+
+        let (claimed, _) = storage.claim_next_job("default").unwrap().unwrap();
+        assert_eq!(claimed, abandoned);
+        assert_eq!(
+            job_status(abandoned),
+            (JOB_STATUS_RUNNING.to_string(), 2, None)
+        );
+        assert_eq!(
+            job_status(exhausted),
+            (
+                JOB_STATUS_DEAD.to_string(),
+                5,
+                Some("Abandoned while running".to_string())
+            )
+        );
+        assert_eq!(
+            job_status(running),
+            (JOB_STATUS_RUNNING.to_string(), 1, None)
+        );
+        assert!(storage.claim_next_job("default").unwrap().is_none());
+    }
+
+    fn set_schedule_due(locked_for: Duration) {
+        let now = Utc::now();
+        diesel::update(jelly_schedules::table)
+            .set((
+                jelly_schedules::next_run_at.eq(now - Duration::minutes(1)),
+                jelly_schedules::locked_until.eq(now + locked_for),
+            ))
+            .execute(&DB_POOL.get().unwrap())
+            .unwrap();
+    }
+
+    #[actix_rt::test]
+    async fn schedule_is_claimed_once_until_its_lease_expires() {
+        crate::test::init();
+        let _ctx = DatabaseTestContext::new();
+        let conn = DB_POOL.get().unwrap();
+        let schedule = Schedule::new(Cron::parse("0 3 * * *").unwrap(), || CrawlPackages);
+        ScheduleRecord::register(&schedule, &conn).unwrap();
+
+        // not due yet
+        assert!(!ScheduleRecord::claim(&schedule, &conn).unwrap());
+
+        set_schedule_due(Duration::minutes(-1));
+        assert!(ScheduleRecord::claim(&schedule, &conn).unwrap());
+        let record = &ScheduleRecord::all(&DB_POOL).unwrap()[0];
+        assert!(record.next_run_at > Utc::now());
+        assert!(record.locked_until.unwrap() > Utc::now());
+
+        // another process cannot claim it while the lease is held
+        set_schedule_due(Duration::minutes(4));
+        assert!(!ScheduleRecord::claim(&schedule, &conn).unwrap());
+
+        // but does once the lease ran out
+        set_schedule_due(Duration::seconds(-1));
+        assert!(ScheduleRecord::claim(&schedule, &conn).unwrap());
+    }
+
     #[actix_rt::test]
     async fn save_to_db() {
         crate::test::init();
@@ -225,6 +638,7 @@ mod tests {
                     forks_count: 0,
                     url: "".to_string(),
                     rev: "".to_string(),
+                    ..Default::default()
                 },
                 GithubRepoData {
                     name: "name2".to_string(),
@@ -237,11 +651,14 @@ mod tests {
                     forks_count: 0,
                     url: "".to_string(),
                     rev: "".to_string(),
+                    ..Default::default()
                 },
             ]),
             pool: pool.clone(),
         };
-        crawler.save_to_db();
+        let (stats, failures) = crawler.save_to_db();
+        assert_eq!(stats.new, 2);
+        assert!(failures.is_empty());
         assert_eq!(Package::count(&DB_POOL).unwrap(), 2);
 
         let crawler = GithubCrawler {
@@ -258,6 +675,7 @@ mod tests {
                     forks_count: 0,
                     url: "".to_string(),
                     rev: "".to_string(),
+                    ..Default::default()
                 },
                 GithubRepoData {
                     name: "name3".to_string(),
@@ -270,32 +688,53 @@ mod tests {
                     forks_count: 0,
                     url: "".to_string(),
                     rev: "".to_string(),
+                    ..Default::default()
                 },
             ]),
             pool: pool.clone(),
         };
-        crawler.save_to_db();
+        let (stats, _) = crawler.save_to_db();
+        assert_eq!(stats.new, 1);
         assert_eq!(Package::count(&DB_POOL).unwrap(), 3);
 
         let crawler = GithubCrawler {
             repo_urls: vec![],
-            repos_data: Mutex::new(vec![
-                GithubRepoData {
-                    name: "valid-package-name".to_string(),
-                    version: "invalid_version".to_string(),
-                    readme_content: "readme1".to_string(),
-                    license: "license1".to_string(),
-                    description: "".to_string(),
-                    size: 0,
-                    stars_count: 0,
-                    forks_count: 0,
-                    url: "".to_string(),
-                    rev: "".to_string(),
-                },
-            ]),
+            repos_data: Mutex::new(vec![GithubRepoData {
+                name: "valid-package-name".to_string(),
+                version: "invalid_version".to_string(),
+                readme_content: "readme1".to_string(),
+                license: "license1".to_string(),
+                description: "".to_string(),
+                size: 0,
+                stars_count: 0,
+                forks_count: 0,
+                url: "".to_string(),
+                rev: "".to_string(),
+                ..Default::default()
+            }]),
+            pool: pool.clone(),
+        };
+        let (stats, failures) = crawler.save_to_db();
+        assert_eq!(stats.failed, 1);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(Package::count(&DB_POOL).unwrap(), 3);
+
+        // Packages that can't be saved are failures, not skipped duplicates
+        let crawler = GithubCrawler {
+            repo_urls: vec![],
+            repos_data: Mutex::new(vec![GithubRepoData {
+                name: "".to_string(),
+                version: "0.0.0".to_string(),
+                url: "https://github.com/movey/unnamed".to_string(),
+                ..Default::default()
+            }]),
             pool,
         };
-        crawler.save_to_db();
+        let (stats, failures) = crawler.save_to_db();
+        assert_eq!(stats.new, 0);
+        assert_eq!(stats.failed, 1);
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].url, "https://github.com/movey/unnamed");
         assert_eq!(Package::count(&DB_POOL).unwrap(), 3);
     }
 
@@ -331,9 +770,11 @@ mod tests {
                     forks_count: 0,
                     url: "".to_string(),
                     rev: "".to_string(),
+                    ..Default::default()
                 })
             });
-        crawler.scrape(&mock_github_service);
+        let failures = crawler.scrape(&mock_github_service);
+        assert!(failures.is_empty());
         let repo_data = crawler.repos_data.lock().unwrap();
         assert_eq!(repo_data.len(), 1);
 
@@ -348,10 +789,44 @@ mod tests {
             forks_count: 0,
             url: "".to_string(),
             rev: "".to_string(),
+            ..Default::default()
         };
         assert_eq!(repo_data[0], stub1);
     }
 
+    #[actix_rt::test]
+    async fn scrape_gives_up_after_three_failed_attempts() {
+        dotenv::dotenv().ok();
+        let _ctx = DatabaseTestContext::new();
+        let pool = database::init_database();
+        let mut crawler = GithubCrawler {
+            repo_urls: vec![GithubSearchItem {
+                repository: GithubSearchRepository {
+                    html_url: "repo_url1".to_string(),
+                },
+                url: "".to_string(),
+                path: "".to_string(),
+                html_url: "".to_string(),
+            }],
+            repos_data: Mutex::new(vec![]),
+            pool,
+        };
+        let mut mock_github_service = GithubService::new();
+        mock_github_service
+            .expect_fetch_repo_data()
+            .times(3)
+            .returning(|_, _, _| Err(Error::Generic("rate limited".to_string())));
+        let failures = crawler.scrape(&mock_github_service);
+        assert_eq!(crawler.repos_data.lock().unwrap().len(), 0);
+        assert_eq!(
+            failures,
+            vec![CrawlFailure {
+                url: "repo_url1".to_string(),
+                message: format!("{:?}", Error::Generic("rate limited".to_string())),
+            }]
+        );
+    }
+
     #[actix_rt::test]
     async fn scrape_beautify_git_url() {
         dotenv::dotenv().ok();
@@ -384,6 +859,7 @@ mod tests {
                     rev: "".to_string(),
                     stars_count: 0,
                     forks_count: 0,
+                    ..Default::default()
                 })
             });
         crawler.scrape(&mock_github_service);
@@ -403,6 +879,7 @@ mod tests {
             "https://github.com/alinush/aptos-core/tree/master/aptos-move/framework/move-stdlib"
                 .to_string(),
             rev: "".to_string(),
+            ..Default::default()
         };
         assert_eq!(repo_data[0], stub1);
         assert_eq!(repo_data[0].readme_content, stub1.readme_content);
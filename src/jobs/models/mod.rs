@@ -0,0 +1,211 @@
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel::{Identifiable, Insertable, Queryable};
+use jelly::chrono::{DateTime, Utc};
+use jelly::serde::Serialize;
+use jelly::DieselPgPool;
+use jelly::Result;
+
+use crate::schema::crawl_cursors;
+use crate::schema::crawl_errors;
+use crate::schema::crawl_runs;
+use crate::utils::paginate::LoadPaginated;
+
+#[cfg(test)]
+mod tests;
+
+pub const CRAWL_RUNS_PER_PAGE: i64 = 20;
+
+pub const RUN_STATUS_RUNNING: &str = "running";
+pub const RUN_STATUS_FINISHED: &str = "finished";
+pub const RUN_STATUS_INTERRUPTED: &str = "interrupted";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CrawlMode {
+    /// Walks every page of the Github code search, resuming from the saved cursor.
+    Full,
+    /// Only looks at the most recently indexed Move.toml files.
+    Incremental,
+}
+
+impl CrawlMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CrawlMode::Full => "full",
+            CrawlMode::Incremental => "incremental",
+        }
+    }
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CrawlStats {
+    pub found: i32,
+    pub new: i32,
+    pub updated: i32,
+    pub failed: i32,
+}
+
+#[derive(Clone, Debug, Identifiable, Queryable, Serialize)]
+pub struct CrawlRun {
+    pub id: i32,
+    pub mode: String,
+    pub status: String,
+    pub found_count: i32,
+    pub new_count: i32,
+    pub updated_count: i32,
+    pub failed_count: i32,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Clone, Debug, Identifiable, Queryable, Serialize)]
+pub struct CrawlError {
+    pub id: i32,
+    pub crawl_run_id: i32,
+    pub url: String,
+    pub message: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "crawl_errors"]
+struct NewCrawlError<'a> {
+    crawl_run_id: i32,
+    url: &'a str,
+    message: &'a str,
+}
+
+#[derive(Clone, Debug, Identifiable, Queryable, Serialize)]
+#[primary_key(name)]
+pub struct CrawlCursor {
+    pub name: String,
+    pub next_page: i32,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl CrawlRun {
+    pub fn start(mode: CrawlMode, pool: &DieselPgPool) -> Result<Self> {
+        let conn = pool.get()?;
+        let record = diesel::insert_into(crawl_runs::table)
+            .values((
+                crawl_runs::mode.eq(mode.as_str()),
+                crawl_runs::status.eq(RUN_STATUS_RUNNING),
+            ))
+            .get_result::<CrawlRun>(&conn)?;
+        Ok(record)
+    }
+
+    pub fn get(run_id: i32, pool: &DieselPgPool) -> Result<Self> {
+        let conn = pool.get()?;
+        let record = crawl_runs::table.find(run_id).first::<CrawlRun>(&conn)?;
+        Ok(record)
+    }
+
+    /// Adds the statistics of a crawled page to the run, so that an interrupted
+    /// run still reports what it managed to do.
+    pub fn record(&self, stats: &CrawlStats, pool: &DieselPgPool) -> Result<()> {
+        let conn = pool.get()?;
+        diesel::update(crawl_runs::table.find(self.id))
+            .set((
+                crawl_runs::found_count.eq(crawl_runs::found_count + stats.found),
+                crawl_runs::new_count.eq(crawl_runs::new_count + stats.new),
+                crawl_runs::updated_count.eq(crawl_runs::updated_count + stats.updated),
+                crawl_runs::failed_count.eq(crawl_runs::failed_count + stats.failed),
+            ))
+            .execute(&conn)?;
+        Ok(())
+    }
+
+    pub fn record_error(&self, url: &str, message: &str, pool: &DieselPgPool) -> Result<()> {
+        let conn = pool.get()?;
+        diesel::insert_into(crawl_errors::table)
+            .values(NewCrawlError {
+                crawl_run_id: self.id,
+                url,
+                message,
+            })
+            .execute(&conn)?;
+        Ok(())
+    }
+
+    pub fn finish(&self, pool: &DieselPgPool) -> Result<()> {
+        let conn = pool.get()?;
+        diesel::update(crawl_runs::table.find(self.id))
+            .set((
+                crawl_runs::status.eq(RUN_STATUS_FINISHED),
+                crawl_runs::finished_at.eq(now),
+            ))
+            .execute(&conn)?;
+        Ok(())
+    }
+
+    /// Marks runs left in `running` state by a previous process as interrupted.
+    pub fn interrupt_unfinished(pool: &DieselPgPool) -> Result<usize> {
+        let conn = pool.get()?;
+        let count =
+            diesel::update(crawl_runs::table.filter(crawl_runs::status.eq(RUN_STATUS_RUNNING)))
+                .set((
+                    crawl_runs::status.eq(RUN_STATUS_INTERRUPTED),
+                    crawl_runs::finished_at.eq(now),
+                ))
+                .execute(&conn)?;
+        Ok(count)
+    }
+
+    pub fn all_paginated(page: Option<i64>, pool: &DieselPgPool) -> Result<(Vec<Self>, i64, i64)> {
+        let conn = pool.get()?;
+        let result = crawl_runs::table
+            .order_by(crawl_runs::id.desc())
+            .load_with_pagination(&conn, page, Some(CRAWL_RUNS_PER_PAGE))?;
+        Ok(result)
+    }
+
+    pub fn errors(&self, pool: &DieselPgPool) -> Result<Vec<CrawlError>> {
+        let conn = pool.get()?;
+        let records = crawl_errors::table
+            .filter(crawl_errors::crawl_run_id.eq(self.id))
+            .order_by(crawl_errors::id.asc())
+            .load::<CrawlError>(&conn)?;
+        Ok(records)
+    }
+}
+
+impl CrawlCursor {
+    /// Returns the cursor with the given name, creating it at page 1 if missing.
+    pub fn get_or_create(cursor_name: &str, pool: &DieselPgPool) -> Result<Self> {
+        let conn = pool.get()?;
+        diesel::insert_into(crawl_cursors::table)
+            .values(crawl_cursors::name.eq(cursor_name))
+            .on_conflict_do_nothing()
+            .execute(&conn)?;
+        let record = crawl_cursors::table
+            .find(cursor_name)
+            .first::<CrawlCursor>(&conn)?;
+        Ok(record)
+    }
+
+    pub fn advance(&self, page: i32, pool: &DieselPgPool) -> Result<()> {
+        let conn = pool.get()?;
+        diesel::update(crawl_cursors::table.find(&self.name))
+            .set((
+                crawl_cursors::next_page.eq(page),
+                crawl_cursors::updated_at.eq(now),
+            ))
+            .execute(&conn)?;
+        Ok(())
+    }
+
+    /// Resets the cursor to the first page once every page has been crawled.
+    pub fn complete(&self, pool: &DieselPgPool) -> Result<()> {
+        let conn = pool.get()?;
+        diesel::update(crawl_cursors::table.find(&self.name))
+            .set((
+                crawl_cursors::next_page.eq(1),
+                crawl_cursors::completed_at.eq(now),
+                crawl_cursors::updated_at.eq(now),
+            ))
+            .execute(&conn)?;
+        Ok(())
+    }
+}
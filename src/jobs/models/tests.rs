@@ -0,0 +1,80 @@
+use crate::jobs::models::*;
+use crate::test::{DatabaseTestContext, DB_POOL};
+
+#[actix_rt::test]
+async fn crawl_run_records_stats_and_errors() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+
+    let run = CrawlRun::start(CrawlMode::Incremental, &DB_POOL).unwrap();
+    assert_eq!(run.status, RUN_STATUS_RUNNING);
+    assert_eq!(run.mode, "incremental");
+
+    let stats = CrawlStats {
+        found: 5,
+        new: 2,
+        updated: 1,
+        failed: 1,
+    };
+    run.record(&stats, &DB_POOL).unwrap();
+    run.record(&stats, &DB_POOL).unwrap();
+    run.record_error("https://github.com/a/b", "timeout", &DB_POOL)
+        .unwrap();
+    run.finish(&DB_POOL).unwrap();
+
+    let run = CrawlRun::get(run.id, &DB_POOL).unwrap();
+    assert_eq!(run.status, RUN_STATUS_FINISHED);
+    assert_eq!(run.found_count, 10);
+    assert_eq!(run.new_count, 4);
+    assert_eq!(run.updated_count, 2);
+    assert_eq!(run.failed_count, 2);
+    assert!(run.finished_at.is_some());
+
+    let errors = run.errors(&DB_POOL).unwrap();
+    assert_eq!(errors.len(), 1);
+    assert_eq!(errors[0].url, "https://github.com/a/b");
+    assert_eq!(errors[0].message, "timeout");
+}
+
+#[actix_rt::test]
+async fn interrupt_unfinished_only_touches_running_runs() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+
+    let finished = CrawlRun::start(CrawlMode::Full, &DB_POOL).unwrap();
+    finished.finish(&DB_POOL).unwrap();
+    let running = CrawlRun::start(CrawlMode::Full, &DB_POOL).unwrap();
+
+    assert_eq!(CrawlRun::interrupt_unfinished(&DB_POOL).unwrap(), 1);
+    assert_eq!(
+        CrawlRun::get(running.id, &DB_POOL).unwrap().status,
+        RUN_STATUS_INTERRUPTED
+    );
+    assert_eq!(
+        CrawlRun::get(finished.id, &DB_POOL).unwrap().status,
+        RUN_STATUS_FINISHED
+    );
+
+    let (runs, total_count, _) = CrawlRun::all_paginated(None, &DB_POOL).unwrap();
+    assert_eq!(total_count, 2);
+    assert_eq!(runs[0].id, running.id);
+}
+
+#[actix_rt::test]
+async fn crawl_cursor_advances_and_resets() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+
+    let cursor = CrawlCursor::get_or_create("full", &DB_POOL).unwrap();
+    assert_eq!(cursor.next_page, 1);
+    assert!(cursor.completed_at.is_none());
+
+    cursor.advance(42, &DB_POOL).unwrap();
+    let cursor = CrawlCursor::get_or_create("full", &DB_POOL).unwrap();
+    assert_eq!(cursor.next_page, 42);
+
+    cursor.complete(&DB_POOL).unwrap();
+    let cursor = CrawlCursor::get_or_create("full", &DB_POOL).unwrap();
+    assert_eq!(cursor.next_page, 1);
+    assert!(cursor.completed_at.is_some());
+}
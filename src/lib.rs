@@ -4,8 +4,6 @@ use jelly::actix_web::dev;
 #[cfg(not(feature = "test"))]
 use std::env;
 use std::io;
-#[cfg(not(feature = "test"))]
-use std::sync::Mutex;
 
 #[macro_use]
 extern crate diesel;
@@ -20,12 +18,15 @@ extern crate diesel_migrations;
 extern crate core;
 
 pub mod accounts;
+pub mod admin;
 pub mod api;
 pub mod github_service;
+pub mod organizations;
 pub mod packages;
 pub mod pages;
 pub mod policy;
 pub mod settings;
+pub mod summarizer;
 pub mod users;
 mod utils;
 
@@ -33,11 +34,17 @@ mod utils;
 pub mod schema;
 
 pub mod constants;
+#[cfg(any(feature = "email-preview", test))]
+pub mod email_preview;
 pub mod jobs;
+pub mod maintenance;
 pub mod package_collaborators;
 pub mod sql;
 pub mod test;
 
+#[cfg(all(feature = "email-preview", feature = "production"))]
+compile_error!("The email-preview feature must never be enabled in production");
+
 use jelly::{DieselPgPool, Server};
 
 #[cfg(not(feature = "test"))]
@@ -58,18 +65,7 @@ pub async fn main() -> io::Result<()> {
             ..Default::default()
         },
     ));
-    let (server, pool) = start_server().await?;
-    let is_crawling = env::var("CRAWLING").unwrap_or_else(|_| "".to_string());
-    if is_crawling.to_lowercase() == "true" {
-        actix_rt::spawn(async {
-            let gh_crawler = jobs::GithubCrawler {
-                repo_urls: vec![],
-                repos_data: Mutex::new(vec![]),
-                pool,
-            };
-            gh_crawler.run().await;
-        });
-    }
+    let (server, _) = start_server().await?;
     server.await
 }
 
@@ -80,7 +76,7 @@ pub async fn main() -> io::Result<()> {
 }
 
 async fn start_server() -> io::Result<(dev::Server, DieselPgPool)> {
-    Server::new()
+    let server = Server::new()
         .register_service(package_collaborators::configure)
         .register_jobs(package_collaborators::jobs::configure)
         .register_service(pages::configure)
@@ -91,7 +87,61 @@ async fn start_server() -> io::Result<(dev::Server, DieselPgPool)> {
         .register_service(api::configure)
         .register_service(settings::configure)
         .register_service(users::configure)
+        .register_service(organizations::configure)
+        .register_jobs(organizations::jobs::configure)
         .register_service(policy::configure)
-        .run()
-        .await
+        .register_service(admin::configure);
+    #[cfg(not(feature = "test"))]
+    let server = register_schedules(server);
+    #[cfg(feature = "email-preview")]
+    let server = server.register_service(email_preview::configure);
+    server.run().await
+}
+
+/// Recurring background work, each enabled by its own environment variable and run
+/// on the cron schedule of its `*_SCHEDULE` variable.
+#[cfg(not(feature = "test"))]
+fn register_schedules(mut server: Server) -> Server {
+    if is_enabled("CRAWLING") {
+        let cron = schedule_or("CRAWL_SCHEDULE", "0 3 * * *");
+        server = server.register_schedule(&cron, || jobs::CrawlPackages);
+    }
+    if is_enabled("METADATA_REFRESH") {
+        let cron = schedule_or("METADATA_REFRESH_SCHEDULE", "0 * * * *");
+        server =
+            server.register_schedule(&cron, || packages::jobs::scheduled::RefreshPackagesMetadata);
+    }
+    if is_enabled("DOCS_GENERATION") {
+        let cron = schedule_or("DOCS_GENERATION_SCHEDULE", "*/30 * * * *");
+        server =
+            server.register_schedule(&cron, || packages::jobs::scheduled::GeneratePackagesDocs);
+    }
+    if is_enabled("QUALITY_SCORE") {
+        let cron = schedule_or("QUALITY_SCORE_SCHEDULE", "0 */6 * * *");
+        server =
+            server.register_schedule(&cron, || packages::jobs::scheduled::RefreshQualityScores);
+    }
+    if is_enabled("PACKAGE_PURGE") {
+        let cron = schedule_or("PACKAGE_PURGE_SCHEDULE", "15 * * * *");
+        server =
+            server.register_schedule(&cron, || packages::jobs::scheduled::PurgeDeletedPackages);
+    }
+    if is_enabled("MAINTENANCE") {
+        let cron = schedule_or("MAINTENANCE_SCHEDULE", "0 4 * * *");
+        server = server.register_schedule(&cron, || maintenance::RunMaintenance);
+    }
+    server
+}
+
+#[cfg(not(feature = "test"))]
+fn is_enabled(key: &str) -> bool {
+    env::var(key)
+        .unwrap_or_else(|_| "".to_string())
+        .to_lowercase()
+        == "true"
+}
+
+#[cfg(not(feature = "test"))]
+fn schedule_or(key: &str, default: &str) -> String {
+    env::var(key).unwrap_or_else(|_| default.to_string())
 }
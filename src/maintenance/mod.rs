@@ -0,0 +1,208 @@
+use jelly::anyhow::{anyhow, Error};
+use jelly::email::Email;
+use jelly::error::TERA;
+use jelly::jobs::{Job, JobState};
+use jelly::serde::{Deserialize, Serialize};
+use jelly::tera::Context;
+use jelly::{DieselPgConnection, DieselPgPool, Result};
+use std::env;
+use std::future::Future;
+use std::pin::Pin;
+
+use crate::accounts::Account;
+use crate::package_collaborators::models::external_invitation::ExternalInvitation;
+use crate::package_collaborators::models::owner_invitation::OwnerInvitation;
+use crate::packages::Package;
+use crate::settings::models::token::ApiToken;
+
+pub mod models;
+
+#[cfg(test)]
+mod tests;
+
+use models::{MaintenanceRun, MaintenanceStats};
+
+/// Cleans up stale data, see `run_maintenance`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RunMaintenance;
+
+impl Job for RunMaintenance {
+    type State = JobState;
+    type Future = Pin<Box<dyn Future<Output = std::result::Result<(), Error>> + Send>>;
+
+    const NAME: &'static str = "RunMaintenance";
+
+    fn run(self, state: JobState) -> Self::Future {
+        Box::pin(async move {
+            // The schedule's lease is held while a run is in progress, and only lapses when
+            // the process holding it stops renewing it, most likely because it died. A run
+            // still marked as running then was cut short.
+            match MaintenanceRun::interrupt_unfinished(&state.pool) {
+                Ok(count) if count > 0 => warn!(
+                    "Marked {} unfinished maintenance runs as interrupted",
+                    count
+                ),
+                Ok(_) => {}
+                Err(e) => error!("Cannot clean up unfinished maintenance runs: {:?}", e),
+            }
+            let stats = run_maintenance(&state.pool)
+                .map_err(|e| anyhow!("Error running maintenance: {:?}", e))?;
+            info!("Ran maintenance: {:?}", stats);
+            Ok(())
+        })
+    }
+}
+
+/// Purges expired invitations, expires accounts left unverified for
+/// `UNVERIFIED_ACCOUNT_EXPIRATION_DAYS` and prunes api tokens unused for
+/// `API_TOKEN_UNUSED_DAYS`, `API_TOKEN_PRUNE_NOTICE_DAYS` after warning their owners.
+pub fn run_maintenance(pool: &DieselPgPool) -> Result<MaintenanceStats> {
+    let run = MaintenanceRun::start(pool)?;
+    let conn = pool.get()?;
+    let mut stats = MaintenanceStats::default();
+    purge_expired_invitations(&mut stats, pool, &conn)?;
+    expire_unverified_accounts(&mut stats, &conn)?;
+    prune_unused_api_tokens(&mut stats, pool, &conn)?;
+    run.finish(&stats, pool)?;
+    Ok(stats)
+}
+
+fn purge_expired_invitations(
+    stats: &mut MaintenanceStats,
+    pool: &DieselPgPool,
+    conn: &DieselPgConnection,
+) -> Result<()> {
+    for invitation in OwnerInvitation::delete_expired(conn)? {
+        stats.expired_invitations += 1;
+        let invited_email = Account::get(invitation.invited_user_id, pool)
+            .map(|account| account.email)
+            .unwrap_or_default();
+        notify_inviter(
+            invitation.invited_by_user_id,
+            invitation.package_id,
+            &invited_email,
+            stats,
+            pool,
+        );
+    }
+    for invitation in ExternalInvitation::delete_expired(conn)? {
+        stats.expired_invitations += 1;
+        notify_inviter(
+            invitation.invited_by_user_id,
+            invitation.package_id,
+            &invitation.external_user_email,
+            stats,
+            pool,
+        );
+    }
+    Ok(())
+}
+
+fn notify_inviter(
+    inviter_id: i32,
+    package_id: i32,
+    invited_email: &str,
+    stats: &mut MaintenanceStats,
+    pool: &DieselPgPool,
+) {
+    let sent = Account::get(inviter_id, pool).and_then(|inviter| {
+        let package = Package::get(package_id, pool)?;
+        let domain = env::var("JELLY_DOMAIN").expect("No JELLY_DOMAIN value set!");
+        let mut context = Context::new();
+        context.insert("package_name", &package.name);
+        context.insert("invited_email", invited_email);
+        context.insert(
+            "action_url",
+            &format!("{}/packages/{}/collaborators", domain, package.slug),
+        );
+        send_email(
+            "email/invitation-expired",
+            inviter.email,
+            &format!("Your invitation to {} has expired", package.name),
+            context,
+            pool,
+        )
+    });
+    if let Err(e) = sent {
+        warn!(
+            "Cannot tell account {} that its invitation expired. error: {:?}",
+            inviter_id, e
+        );
+        stats.failed += 1;
+    }
+}
+
+fn expire_unverified_accounts(
+    stats: &mut MaintenanceStats,
+    conn: &DieselPgConnection,
+) -> Result<()> {
+    let expiration_days = env_number_or("UNVERIFIED_ACCOUNT_EXPIRATION_DAYS", 30);
+    for uid in Account::get_expired_unverified(expiration_days, conn)? {
+        match Account::delete_unverified(uid, conn) {
+            Ok(count) => stats.expired_accounts += count as i32,
+            Err(e) => {
+                warn!("Cannot expire account {}. error: {:?}", uid, e);
+                stats.failed += 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn prune_unused_api_tokens(
+    stats: &mut MaintenanceStats,
+    pool: &DieselPgPool,
+    conn: &DieselPgConnection,
+) -> Result<()> {
+    let unused_days = env_number_or("API_TOKEN_UNUSED_DAYS", 365);
+    let notice_days = env_number_or("API_TOKEN_PRUNE_NOTICE_DAYS", 14);
+    stats.pruned_tokens += ApiToken::prune_warned(notice_days, conn)? as i32;
+    for token in ApiToken::find_unused(unused_days, conn)? {
+        let sent = Account::get(token.account_id, pool).and_then(|owner| {
+            let domain = env::var("JELLY_DOMAIN").expect("No JELLY_DOMAIN value set!");
+            let mut context = Context::new();
+            context.insert("token_name", &token.name);
+            context.insert("unused_days", &unused_days);
+            context.insert("notice_days", &notice_days);
+            context.insert("action_url", &format!("{}/settings/tokens", domain));
+            send_email(
+                "email/api-token-unused",
+                owner.email,
+                &format!("Your unused API token {} will be deleted", token.name),
+                context,
+                pool,
+            )
+        });
+        // the notice only starts once the owner was told, a token whose warning could not
+        // be sent is picked up again on the next run
+        match sent.and_then(|_| token.mark_prune_warned(conn)) {
+            Ok(()) => stats.warned_tokens += 1,
+            Err(e) => {
+                warn!(
+                    "Cannot warn about unused api token {}. error: {:?}",
+                    token.id, e
+                );
+                stats.failed += 1;
+            }
+        }
+    }
+    Ok(())
+}
+
+fn send_email(
+    template: &str,
+    to: String,
+    subject: &str,
+    context: Context,
+    pool: &DieselPgPool,
+) -> Result<()> {
+    Email::new(template, &[to], subject, context, TERA.clone())?.deliver(pool)?;
+    Ok(())
+}
+
+fn env_number_or(key: &str, default: i64) -> i64 {
+    env::var(key)
+        .ok()
+        .and_then(|number| number.parse::<i64>().ok())
+        .unwrap_or(default)
+}
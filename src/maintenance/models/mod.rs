@@ -0,0 +1,94 @@
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel::{Identifiable, Queryable};
+use jelly::chrono::{DateTime, Utc};
+use jelly::serde::Serialize;
+use jelly::DieselPgPool;
+use jelly::Result;
+
+use crate::jobs::models::{RUN_STATUS_FINISHED, RUN_STATUS_INTERRUPTED, RUN_STATUS_RUNNING};
+use crate::schema::maintenance_runs;
+use crate::utils::paginate::LoadPaginated;
+
+#[cfg(test)]
+mod tests;
+
+pub const MAINTENANCE_RUNS_PER_PAGE: i64 = 20;
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MaintenanceStats {
+    pub expired_invitations: i32,
+    pub expired_accounts: i32,
+    pub warned_tokens: i32,
+    pub pruned_tokens: i32,
+    pub failed: i32,
+}
+
+#[derive(Clone, Debug, Identifiable, Queryable, Serialize)]
+pub struct MaintenanceRun {
+    pub id: i32,
+    pub status: String,
+    pub expired_invitations_count: i32,
+    pub expired_accounts_count: i32,
+    pub warned_tokens_count: i32,
+    pub pruned_tokens_count: i32,
+    pub failed_count: i32,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+impl MaintenanceRun {
+    pub fn start(pool: &DieselPgPool) -> Result<Self> {
+        let conn = pool.get()?;
+        let record = diesel::insert_into(maintenance_runs::table)
+            .values(maintenance_runs::status.eq(RUN_STATUS_RUNNING))
+            .get_result::<MaintenanceRun>(&conn)?;
+        Ok(record)
+    }
+
+    pub fn get(run_id: i32, pool: &DieselPgPool) -> Result<Self> {
+        let conn = pool.get()?;
+        let record = maintenance_runs::table
+            .find(run_id)
+            .first::<MaintenanceRun>(&conn)?;
+        Ok(record)
+    }
+
+    pub fn finish(&self, stats: &MaintenanceStats, pool: &DieselPgPool) -> Result<()> {
+        let conn = pool.get()?;
+        diesel::update(maintenance_runs::table.find(self.id))
+            .set((
+                maintenance_runs::status.eq(RUN_STATUS_FINISHED),
+                maintenance_runs::expired_invitations_count.eq(stats.expired_invitations),
+                maintenance_runs::expired_accounts_count.eq(stats.expired_accounts),
+                maintenance_runs::warned_tokens_count.eq(stats.warned_tokens),
+                maintenance_runs::pruned_tokens_count.eq(stats.pruned_tokens),
+                maintenance_runs::failed_count.eq(stats.failed),
+                maintenance_runs::finished_at.eq(now),
+            ))
+            .execute(&conn)?;
+        Ok(())
+    }
+
+    /// Marks runs left in `running` state by a previous process as interrupted.
+    pub fn interrupt_unfinished(pool: &DieselPgPool) -> Result<usize> {
+        let conn = pool.get()?;
+        let count = diesel::update(
+            maintenance_runs::table.filter(maintenance_runs::status.eq(RUN_STATUS_RUNNING)),
+        )
+        .set((
+            maintenance_runs::status.eq(RUN_STATUS_INTERRUPTED),
+            maintenance_runs::finished_at.eq(now),
+        ))
+        .execute(&conn)?;
+        Ok(count)
+    }
+
+    pub fn all_paginated(page: Option<i64>, pool: &DieselPgPool) -> Result<(Vec<Self>, i64, i64)> {
+        let conn = pool.get()?;
+        let result = maintenance_runs::table
+            .order_by(maintenance_runs::id.desc())
+            .load_with_pagination(&conn, page, Some(MAINTENANCE_RUNS_PER_PAGE))?;
+        Ok(result)
+    }
+}
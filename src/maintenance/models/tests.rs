@@ -0,0 +1,56 @@
+use crate::jobs::models::{RUN_STATUS_FINISHED, RUN_STATUS_INTERRUPTED, RUN_STATUS_RUNNING};
+use crate::maintenance::models::*;
+use crate::test::{DatabaseTestContext, DB_POOL};
+
+#[actix_rt::test]
+async fn maintenance_run_records_stats() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+
+    let run = MaintenanceRun::start(&DB_POOL).unwrap();
+    assert_eq!(run.status, RUN_STATUS_RUNNING);
+
+    let stats = MaintenanceStats {
+        expired_invitations: 3,
+        expired_accounts: 2,
+        warned_tokens: 1,
+        pruned_tokens: 4,
+        failed: 1,
+    };
+    run.finish(&stats, &DB_POOL).unwrap();
+
+    let run = MaintenanceRun::get(run.id, &DB_POOL).unwrap();
+    assert_eq!(run.status, RUN_STATUS_FINISHED);
+    assert_eq!(run.expired_invitations_count, 3);
+    assert_eq!(run.expired_accounts_count, 2);
+    assert_eq!(run.warned_tokens_count, 1);
+    assert_eq!(run.pruned_tokens_count, 4);
+    assert_eq!(run.failed_count, 1);
+    assert!(run.finished_at.is_some());
+}
+
+#[actix_rt::test]
+async fn interrupt_unfinished_only_touches_running_runs() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+
+    let finished = MaintenanceRun::start(&DB_POOL).unwrap();
+    finished
+        .finish(&MaintenanceStats::default(), &DB_POOL)
+        .unwrap();
+    let running = MaintenanceRun::start(&DB_POOL).unwrap();
+
+    assert_eq!(MaintenanceRun::interrupt_unfinished(&DB_POOL).unwrap(), 1);
+    assert_eq!(
+        MaintenanceRun::get(finished.id, &DB_POOL).unwrap().status,
+        RUN_STATUS_FINISHED
+    );
+    assert_eq!(
+        MaintenanceRun::get(running.id, &DB_POOL).unwrap().status,
+        RUN_STATUS_INTERRUPTED
+    );
+
+    let (runs, total, _) = MaintenanceRun::all_paginated(None, &DB_POOL).unwrap();
+    assert_eq!(total, 2);
+    assert_eq!(runs[0].id, running.id);
+}
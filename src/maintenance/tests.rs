@@ -0,0 +1,106 @@
+use crate::accounts::Account;
+use crate::maintenance::models::MaintenanceRun;
+use crate::maintenance::run_maintenance;
+use crate::package_collaborators::models::expiration;
+use crate::package_collaborators::models::owner_invitation::OwnerInvitation;
+use crate::packages::Package;
+use crate::schema::{accounts, api_tokens};
+use crate::settings::models::token::ApiToken;
+use crate::test::util::setup_user;
+use crate::test::{DatabaseTestContext, DB_POOL};
+use diesel::prelude::*;
+use jelly::chrono::{Duration, NaiveDateTime, Utc};
+
+fn setup_expired_invitation() -> OwnerInvitation {
+    let invited_uid = setup_user(Some("invited@host.com".to_string()), None);
+    let invited_by_uid = setup_user(None, None);
+    let pid = Package::create_test_package(
+        &"package1".to_string(),
+        &"".to_string(),
+        &"".to_string(),
+        &"1.0.0".to_string(),
+        &"".to_string(),
+        &"".to_string(),
+        &"".to_string(),
+        -1,
+        -1,
+        0,
+        0,
+        Some(invited_by_uid),
+        &DB_POOL,
+    )
+    .unwrap();
+    OwnerInvitation::create(
+        invited_uid,
+        invited_by_uid,
+        pid,
+        None,
+        None,
+        Some(expiration::expires_in(-1)),
+        &DB_POOL.get().unwrap(),
+    )
+    .unwrap()
+}
+
+fn backdate_token(token_id: i32, days: i64) {
+    diesel::update(api_tokens::table.find(token_id))
+        .set((
+            api_tokens::created_at.eq(Utc::now().naive_utc() - Duration::days(days)),
+            api_tokens::last_used_at.eq(None::<NaiveDateTime>),
+        ))
+        .execute(&DB_POOL.get().unwrap())
+        .unwrap();
+}
+
+#[actix_rt::test]
+async fn run_maintenance_cleans_up_stale_data() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let conn = DB_POOL.get().unwrap();
+
+    let invitation = setup_expired_invitation();
+    let unverified_uid = setup_user(Some("unverified@host.com".to_string()), None);
+    diesel::update(accounts::table.find(unverified_uid))
+        .set((
+            accounts::has_verified_email.eq(false),
+            accounts::created.eq(Utc::now() - Duration::days(31)),
+        ))
+        .execute(&conn)
+        .unwrap();
+    let owner = Account::get(invitation.invited_by_user_id, &DB_POOL).unwrap();
+    let unused = ApiToken::insert(&owner, "unused", &DB_POOL).unwrap();
+    let used = ApiToken::insert(&owner, "used", &DB_POOL).unwrap();
+    backdate_token(unused.model.id, 366);
+
+    let stats = run_maintenance(&DB_POOL).unwrap();
+    assert_eq!(stats.expired_invitations, 1);
+    assert_eq!(stats.expired_accounts, 1);
+    assert_eq!(stats.warned_tokens, 1);
+    assert_eq!(stats.pruned_tokens, 0);
+    assert!(
+        OwnerInvitation::find_all_by_package_id(invitation.package_id, &conn)
+            .unwrap()
+            .is_empty()
+    );
+    assert!(Account::get(unverified_uid, &DB_POOL).is_err());
+    assert!(Account::get(owner.id, &DB_POOL).is_ok());
+
+    // Past the notice, only the token still unused gets pruned
+    diesel::update(api_tokens::table)
+        .set(api_tokens::prune_warned_at.eq(Utc::now().naive_utc() - Duration::days(15)))
+        .execute(&conn)
+        .unwrap();
+    backdate_token(used.model.id, 366);
+    ApiToken::associated_account(&used.plaintext, &DB_POOL).unwrap();
+
+    let stats = run_maintenance(&DB_POOL).unwrap();
+    assert_eq!(stats.pruned_tokens, 1);
+    assert_eq!(stats.warned_tokens, 0);
+    assert!(ApiToken::associated_account(&unused.plaintext, &DB_POOL).is_err());
+    assert!(ApiToken::associated_account(&used.plaintext, &DB_POOL).is_ok());
+
+    let (runs, total, _) = MaintenanceRun::all_paginated(None, &DB_POOL).unwrap();
+    assert_eq!(total, 2);
+    assert_eq!(runs[1].expired_accounts_count, 1);
+    assert_eq!(runs[0].pruned_tokens_count, 1);
+}
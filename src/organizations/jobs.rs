@@ -0,0 +1,55 @@
+use std::env;
+use std::future::Future;
+use std::pin::Pin;
+
+use jelly::anyhow::Error;
+use jelly::email::Email;
+use jelly::jobs::{Job, JobState, RegisterJob, WorkerConfig};
+use jelly::serde::{Deserialize, Serialize};
+use jelly::tera::Context;
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SendOrganizationInvitationEmail {
+    pub to: String,
+    pub organization_name: String,
+    pub token: String,
+}
+
+impl Job for SendOrganizationInvitationEmail {
+    type State = JobState;
+    type Future = Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
+
+    const NAME: &'static str = "SendOrganizationInvitationEmail";
+
+    fn run(self, state: JobState) -> Self::Future {
+        Box::pin(async move {
+            let domain = env::var("JELLY_DOMAIN").expect("No JELLY_DOMAIN value set!");
+
+            let invitation_url = format!("{}/organizations/accept/{}", domain, self.token);
+
+            let email = Email::new(
+                "email/invite-organization-member",
+                &[self.to],
+                &format!(
+                    "You have been invited to join {} on Movey",
+                    self.organization_name
+                ),
+                {
+                    let mut context = Context::new();
+                    context.insert("action_url", &invitation_url);
+                    context.insert("organization_name", &self.organization_name);
+                    context
+                },
+                state.templates,
+            );
+
+            email?.deliver(&state.pool)?;
+
+            Ok(())
+        })
+    }
+}
+
+pub fn configure(config: WorkerConfig<JobState>) -> WorkerConfig<JobState> {
+    config.register_job::<SendOrganizationInvitationEmail>()
+}
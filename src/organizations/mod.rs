@@ -0,0 +1,13 @@
+pub mod jobs;
+pub mod models;
+pub mod views;
+
+use jelly::actix_web::web::{get, resource, scope, ServiceConfig};
+
+pub fn configure(config: &mut ServiceConfig) {
+    config.service(
+        scope("/organizations")
+            .service(resource("/accept/{token}").route(get().to(views::accept_invitation)))
+            .service(resource("/{organization_slug}").route(get().to(views::get_public_profile))),
+    );
+}
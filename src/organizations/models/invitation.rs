@@ -0,0 +1,119 @@
+//! Invitations to join an organization, an account only becomes a member once it accepts.
+
+use diesel::prelude::*;
+use diesel::{Identifiable, Queryable};
+use jelly::chrono::{NaiveDateTime, Utc};
+use jelly::DieselPgConnection;
+use jelly::Result;
+
+use super::{Organization, OrganizationRole};
+use crate::package_collaborators::models::expiration;
+use crate::schema::{organization_invitations, organizations};
+use crate::utils::token::SecureToken;
+
+#[derive(Clone, Debug, Identifiable, Queryable)]
+#[primary_key(invited_user_id, organization_id)]
+pub struct OrganizationInvitation {
+    pub organization_id: i32,
+    pub invited_user_id: i32,
+    pub invited_by_user_id: i32,
+    pub role: i32,
+    pub token: String,
+    pub created_at: NaiveDateTime,
+}
+
+impl OrganizationInvitation {
+    /// Invites the account, or sends its pending invitation again with a new token and role.
+    /// The returned invitation holds the plaintext token, only its hash is stored.
+    pub fn create(
+        organization_id: i32,
+        invited_user_id: i32,
+        invited_by_user_id: i32,
+        role: OrganizationRole,
+        conn: &DieselPgConnection,
+    ) -> Result<Self> {
+        let secure_token = SecureToken::generate();
+        let created_at = Utc::now().naive_utc();
+        let mut invitation: Self = diesel::insert_into(organization_invitations::table)
+            .values((
+                organization_invitations::organization_id.eq(organization_id),
+                organization_invitations::invited_user_id.eq(invited_user_id),
+                organization_invitations::invited_by_user_id.eq(invited_by_user_id),
+                organization_invitations::role.eq(role as i32),
+                organization_invitations::token.eq(&secure_token.inner.sha256),
+                organization_invitations::created_at.eq(created_at),
+            ))
+            .on_conflict((
+                organization_invitations::invited_user_id,
+                organization_invitations::organization_id,
+            ))
+            .do_update()
+            .set((
+                organization_invitations::invited_by_user_id.eq(invited_by_user_id),
+                organization_invitations::role.eq(role as i32),
+                organization_invitations::token.eq(&secure_token.inner.sha256),
+                organization_invitations::created_at.eq(created_at),
+            ))
+            .get_result(conn)?;
+
+        invitation.token = secure_token.plaintext;
+        Ok(invitation)
+    }
+
+    pub fn find_by_token(token: &str, conn: &DieselPgConnection) -> Result<Self> {
+        let hashed_token = SecureToken::hash(token);
+        Ok(organization_invitations::table
+            .filter(organization_invitations::token.eq(hashed_token))
+            .first::<Self>(conn)?)
+    }
+
+    pub fn find_by_id(
+        invited_user_id: i32,
+        organization_id: i32,
+        conn: &DieselPgConnection,
+    ) -> Result<Self> {
+        Ok(organization_invitations::table
+            .find((invited_user_id, organization_id))
+            .first::<Self>(conn)?)
+    }
+
+    /// Invitations the account can still accept, with the organization inviting it.
+    pub fn find_by_invited_account(
+        invited_user_id: i32,
+        conn: &DieselPgConnection,
+    ) -> Result<Vec<(Self, Organization)>> {
+        let invitations = organization_invitations::table
+            .inner_join(organizations::table)
+            .filter(organization_invitations::invited_user_id.eq(invited_user_id))
+            .order_by(organization_invitations::created_at.asc())
+            .load::<(Self, Organization)>(conn)?;
+        Ok(invitations
+            .into_iter()
+            .filter(|(invitation, _)| !invitation.is_expired())
+            .collect())
+    }
+
+    pub fn delete(&self, conn: &DieselPgConnection) -> Result<()> {
+        diesel::delete(self).execute(conn)?;
+        Ok(())
+    }
+
+    /// Adds the invited account to the organization with the role it was invited with.
+    pub fn accept(&self, conn: &DieselPgConnection) -> Result<()> {
+        let role = OrganizationRole::from_i32(self.role).unwrap_or(OrganizationRole::Member);
+        conn.transaction(|| -> Result<()> {
+            Organization::get(self.organization_id, conn)?.add_member(
+                self.invited_user_id,
+                role,
+                conn,
+            )?;
+            self.delete(conn)
+        })
+    }
+
+    /// Organization invitations expire like package ones, after
+    /// `OWNERSHIP_INVITATIONS_EXPIRATION_DAYS`.
+    pub fn is_expired(&self) -> bool {
+        expiration::expires_at(self.created_at, None) <= Utc::now().naive_utc()
+    }
+}
@@ -0,0 +1,260 @@
+extern crate slug;
+
+use diesel::prelude::*;
+use diesel::{Identifiable, Insertable, Queryable};
+use jelly::chrono::{DateTime, Utc};
+use jelly::error::Error;
+use jelly::serde::{Deserialize, Serialize};
+use jelly::DieselPgConnection;
+use jelly::Result;
+
+use crate::package_collaborators::package_collaborator::Role;
+use crate::packages::Package;
+use crate::schema::{
+    accounts, organization_members, organizations, package_collaborators, packages,
+};
+use crate::utils::presenter::censor_email;
+
+pub mod invitation;
+#[cfg(test)]
+mod tests;
+
+/// A group of accounts owning packages together. Its admins are the owners of its packages,
+/// its members are collaborators.
+#[derive(Debug, Serialize, Deserialize, Queryable, Identifiable)]
+pub struct Organization {
+    pub id: i32,
+    pub name: String,
+    pub slug: String,
+    pub description: String,
+    pub created_by: Option<i32>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "organizations"]
+struct NewOrganization {
+    name: String,
+    slug: String,
+    description: String,
+    created_by: Option<i32>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OrganizationRole {
+    Admin = 0,
+    Member = 1,
+}
+
+impl OrganizationRole {
+    pub fn from_i32(role: i32) -> Option<Self> {
+        match role {
+            0 => Some(OrganizationRole::Admin),
+            1 => Some(OrganizationRole::Member),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Queryable)]
+pub struct OrganizationMember {
+    pub organization_id: i32,
+    pub account_id: i32,
+    pub role: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A member as listed on the organization pages.
+#[derive(Debug, Serialize, Queryable)]
+pub struct OrganizationMemberAccount {
+    pub account_id: i32,
+    pub name: String,
+    pub email: String,
+    pub github_login: Option<String>,
+    pub slug: Option<String>,
+    pub role: i32,
+}
+
+impl OrganizationMemberAccount {
+    /// The name shown on the public page of the organization, which never shows a full email.
+    pub fn display_name(&self) -> String {
+        if !self.name.is_empty() {
+            self.name.clone()
+        } else if let Some(github_login) = &self.github_login {
+            github_login.clone()
+        } else {
+            censor_email(&self.email).unwrap_or_default()
+        }
+    }
+}
+
+impl Organization {
+    /// Creates the organization with `created_by` as its first admin.
+    pub fn create(
+        organization_name: &str,
+        organization_description: &str,
+        created_by: i32,
+        conn: &DieselPgConnection,
+    ) -> Result<Self> {
+        let organization_name = organization_name.trim();
+        let organization_slug = slug::slugify(organization_name);
+        if organization_slug.is_empty() {
+            return Err(Error::Generic(format!(
+                "Invalid organization name: {}",
+                organization_name
+            )));
+        }
+        conn.transaction(|| -> Result<Self> {
+            let organization = diesel::insert_into(organizations::table)
+                .values(NewOrganization {
+                    name: organization_name.to_string(),
+                    slug: organization_slug,
+                    description: organization_description.trim().to_string(),
+                    created_by: Some(created_by),
+                })
+                .get_result::<Organization>(conn)?;
+            organization.add_member(created_by, OrganizationRole::Admin, conn)?;
+            Ok(organization)
+        })
+    }
+
+    pub fn get(organization_id: i32, conn: &DieselPgConnection) -> Result<Self> {
+        Ok(organizations::table
+            .find(organization_id)
+            .first::<Self>(conn)?)
+    }
+
+    pub fn get_by_slug(organization_slug: &str, conn: &DieselPgConnection) -> Result<Self> {
+        Ok(organizations::table
+            .filter(organizations::slug.eq(organization_slug))
+            .first::<Self>(conn)?)
+    }
+
+    /// Organizations of an account, with its role in each of them.
+    pub fn get_by_member(account_id: i32, conn: &DieselPgConnection) -> Result<Vec<(Self, i32)>> {
+        Ok(organizations::table
+            .inner_join(organization_members::table)
+            .filter(organization_members::account_id.eq(account_id))
+            .select((organizations::all_columns, organization_members::role))
+            .order(organizations::name.asc())
+            .load::<(Self, i32)>(conn)?)
+    }
+
+    pub fn get_members(&self, conn: &DieselPgConnection) -> Result<Vec<OrganizationMemberAccount>> {
+        Ok(organization_members::table
+            .inner_join(accounts::table)
+            .filter(organization_members::organization_id.eq(self.id))
+            .select((
+                accounts::id,
+                accounts::name,
+                accounts::email,
+                accounts::github_login,
+                accounts::slug,
+                organization_members::role,
+            ))
+            // Admins first
+            .order((
+                organization_members::role.asc(),
+                organization_members::created_at.asc(),
+            ))
+            .load::<OrganizationMemberAccount>(conn)?)
+    }
+
+    pub fn get_member_role(
+        &self,
+        account_id: i32,
+        conn: &DieselPgConnection,
+    ) -> Result<Option<OrganizationRole>> {
+        OrganizationMember::get_role(self.id, account_id, conn)
+    }
+
+    pub fn is_admin(&self, account_id: i32, conn: &DieselPgConnection) -> Result<bool> {
+        Ok(self.get_member_role(account_id, conn)? == Some(OrganizationRole::Admin))
+    }
+
+    pub fn add_member(
+        &self,
+        account_id: i32,
+        role: OrganizationRole,
+        conn: &DieselPgConnection,
+    ) -> Result<()> {
+        diesel::insert_into(organization_members::table)
+            .values((
+                organization_members::organization_id.eq(self.id),
+                organization_members::account_id.eq(account_id),
+                organization_members::role.eq(role as i32),
+            ))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Returns the number of updated members, 0 when the account is not a member.
+    pub fn update_member_role(
+        &self,
+        account_id: i32,
+        role: OrganizationRole,
+        conn: &DieselPgConnection,
+    ) -> Result<usize> {
+        Ok(
+            diesel::update(organization_members::table.find((self.id, account_id)))
+                .set(organization_members::role.eq(role as i32))
+                .execute(conn)?,
+        )
+    }
+
+    /// Returns the number of removed members, 0 when the account is not a member.
+    pub fn remove_member(&self, account_id: i32, conn: &DieselPgConnection) -> Result<usize> {
+        Ok(
+            diesel::delete(organization_members::table.find((self.id, account_id)))
+                .execute(conn)?,
+        )
+    }
+
+    pub fn count_admins(&self, conn: &DieselPgConnection) -> Result<i64> {
+        Ok(organization_members::table
+            .filter(
+                organization_members::organization_id
+                    .eq(self.id)
+                    .and(organization_members::role.eq(OrganizationRole::Admin as i32)),
+            )
+            .count()
+            .get_result::<i64>(conn)?)
+    }
+
+    /// Moves the ownership of a package to the organization. Its previous owners stay on as
+    /// collaborators, the admins of the organization become its owners.
+    pub fn transfer_package(&self, package: &Package, conn: &DieselPgConnection) -> Result<()> {
+        conn.transaction(|| -> Result<()> {
+            diesel::update(packages::table.find(package.id))
+                .set(packages::organization_id.eq(self.id))
+                .execute(conn)?;
+            diesel::update(
+                package_collaborators::table.filter(
+                    package_collaborators::package_id
+                        .eq(package.id)
+                        .and(package_collaborators::role.eq(Role::Owner as i32)),
+                ),
+            )
+            .set(package_collaborators::role.eq(Role::Collaborator as i32))
+            .execute(conn)?;
+            Ok(())
+        })
+    }
+}
+
+impl OrganizationMember {
+    pub fn get_role(
+        organization_id: i32,
+        account_id: i32,
+        conn: &DieselPgConnection,
+    ) -> Result<Option<OrganizationRole>> {
+        let role = organization_members::table
+            .find((organization_id, account_id))
+            .select(organization_members::role)
+            .first::<i32>(conn)
+            .optional()?;
+        Ok(role.and_then(OrganizationRole::from_i32))
+    }
+}
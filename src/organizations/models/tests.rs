@@ -0,0 +1,234 @@
+use diesel::prelude::*;
+use jelly::chrono::{Duration, Utc};
+
+use crate::organizations::models::invitation::OrganizationInvitation;
+use crate::organizations::models::{
+    Organization, OrganizationMember, OrganizationMemberAccount, OrganizationRole,
+};
+use crate::package_collaborators::package_collaborator::{PackageCollaborator, Role};
+use crate::package_collaborators::policy;
+use crate::packages::models::{PackageSortField, PackageSortOrder};
+use crate::packages::Package;
+use crate::schema::organization_invitations;
+use crate::test::util::{create_package, setup_user};
+use crate::test::{DatabaseTestContext, DB_POOL};
+
+#[actix_rt::test]
+async fn create_makes_creator_an_admin() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let conn = DB_POOL.get().unwrap();
+
+    let organization = Organization::create(" Move Labs ", "", uid, &conn).unwrap();
+    assert_eq!(organization.name, "Move Labs");
+    assert_eq!(organization.slug, "move-labs");
+    assert!(organization.is_admin(uid, &conn).unwrap());
+    assert_eq!(organization.count_admins(&conn).unwrap(), 1);
+    let organizations = Organization::get_by_member(uid, &conn).unwrap();
+    assert_eq!(organizations.len(), 1);
+    assert_eq!(organizations[0].1, OrganizationRole::Admin as i32);
+
+    assert!(Organization::create("Move labs", "", uid, &conn).is_err());
+    assert!(Organization::create("???", "", uid, &conn).is_err());
+}
+
+#[actix_rt::test]
+async fn members_can_be_added_updated_and_removed() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let admin_id = setup_user(None, None);
+    let member_id = setup_user(Some("member@host.com".to_string()), None);
+    let conn = DB_POOL.get().unwrap();
+    let organization = Organization::create("Move Labs", "", admin_id, &conn).unwrap();
+
+    organization
+        .add_member(member_id, OrganizationRole::Member, &conn)
+        .unwrap();
+    assert!(organization
+        .add_member(member_id, OrganizationRole::Admin, &conn)
+        .is_err());
+    assert_eq!(
+        OrganizationMember::get_role(organization.id, member_id, &conn).unwrap(),
+        Some(OrganizationRole::Member)
+    );
+    let members = organization.get_members(&conn).unwrap();
+    assert_eq!(
+        members
+            .iter()
+            .map(|member| member.account_id)
+            .collect::<Vec<i32>>(),
+        vec![admin_id, member_id]
+    );
+
+    assert_eq!(
+        organization
+            .update_member_role(member_id, OrganizationRole::Admin, &conn)
+            .unwrap(),
+        1
+    );
+    assert_eq!(organization.count_admins(&conn).unwrap(), 2);
+    assert_eq!(organization.remove_member(member_id, &conn).unwrap(), 1);
+    assert_eq!(organization.remove_member(member_id, &conn).unwrap(), 0);
+    assert_eq!(
+        organization.get_member_role(member_id, &conn).unwrap(),
+        None
+    );
+}
+
+#[actix_rt::test]
+async fn transfer_package_makes_admins_owners() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let owner_id = setup_user(None, None);
+    let admin_id = setup_user(Some("admin@host.com".to_string()), None);
+    let member_id = setup_user(Some("member@host.com".to_string()), None);
+    let outsider_id = setup_user(Some("outsider@host.com".to_string()), None);
+    let conn = DB_POOL.get().unwrap();
+    let package = create_package(
+        "package1",
+        "https://github.com/owner/package1",
+        "",
+        Some(owner_id),
+    );
+    let package_id = package.id;
+    let organization = Organization::create("Move Labs", "", admin_id, &conn).unwrap();
+    organization
+        .add_member(member_id, OrganizationRole::Member, &conn)
+        .unwrap();
+
+    organization.transfer_package(&package, &conn).unwrap();
+    let package = Package::get(package_id, &DB_POOL).unwrap();
+    assert_eq!(package.organization_id, Some(organization.id));
+
+    assert_eq!(
+        policy::get_role(&package, admin_id, &conn).unwrap(),
+        Some(Role::Owner)
+    );
+    assert_eq!(
+        policy::get_role(&package, member_id, &conn).unwrap(),
+        Some(Role::Maintainer)
+    );
+    assert_eq!(
+        policy::get_role(&package, outsider_id, &conn).unwrap(),
+        None
+    );
+    // The previous owner keeps access as a collaborator
+    assert_eq!(
+        PackageCollaborator::get(package_id, owner_id, &conn)
+            .unwrap()
+            .role,
+        Role::Collaborator as i32
+    );
+
+    let (packages, total_count, _) = Package::get_by_organization_paginated(
+        organization.id,
+        &PackageSortField::Name,
+        &PackageSortOrder::Asc,
+        None,
+        None,
+        &DB_POOL,
+    )
+    .unwrap();
+    assert_eq!(total_count, 1);
+    assert_eq!(packages[0].id, package_id);
+}
+
+#[actix_rt::test]
+async fn invited_accounts_become_members_once_they_accept() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let admin_id = setup_user(None, None);
+    let invited_id = setup_user(Some("invited@host.com".to_string()), None);
+    let conn = DB_POOL.get().unwrap();
+    let organization = Organization::create("Move Labs", "", admin_id, &conn).unwrap();
+
+    let first = OrganizationInvitation::create(
+        organization.id,
+        invited_id,
+        admin_id,
+        OrganizationRole::Member,
+        &conn,
+    )
+    .unwrap();
+    assert_eq!(
+        organization.get_member_role(invited_id, &conn).unwrap(),
+        None
+    );
+
+    // Inviting again renews the token and the role
+    let invitation = OrganizationInvitation::create(
+        organization.id,
+        invited_id,
+        admin_id,
+        OrganizationRole::Admin,
+        &conn,
+    )
+    .unwrap();
+    assert!(OrganizationInvitation::find_by_token(&first.token, &conn).is_err());
+    let found = OrganizationInvitation::find_by_token(&invitation.token, &conn).unwrap();
+    assert_eq!(found.role, OrganizationRole::Admin as i32);
+    assert_eq!(
+        OrganizationInvitation::find_by_invited_account(invited_id, &conn)
+            .unwrap()
+            .len(),
+        1
+    );
+
+    found.accept(&conn).unwrap();
+    assert_eq!(
+        organization.get_member_role(invited_id, &conn).unwrap(),
+        Some(OrganizationRole::Admin)
+    );
+    assert!(OrganizationInvitation::find_by_id(invited_id, organization.id, &conn).is_err());
+}
+
+#[actix_rt::test]
+async fn expired_invitations_are_not_listed() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let admin_id = setup_user(None, None);
+    let invited_id = setup_user(Some("invited@host.com".to_string()), None);
+    let conn = DB_POOL.get().unwrap();
+    let organization = Organization::create("Move Labs", "", admin_id, &conn).unwrap();
+    OrganizationInvitation::create(
+        organization.id,
+        invited_id,
+        admin_id,
+        OrganizationRole::Member,
+        &conn,
+    )
+    .unwrap();
+
+    diesel::update(organization_invitations::table)
+        .set(organization_invitations::created_at.eq(Utc::now().naive_utc() - Duration::days(365)))
+        .execute(&conn)
+        .unwrap();
+    let invitation =
+        OrganizationInvitation::find_by_id(invited_id, organization.id, &conn).unwrap();
+    assert!(invitation.is_expired());
+    assert!(
+        OrganizationInvitation::find_by_invited_account(invited_id, &conn)
+            .unwrap()
+            .is_empty()
+    );
+}
+
+#[actix_rt::test]
+async fn display_name_never_shows_a_full_email() {
+    let mut member = OrganizationMemberAccount {
+        account_id: 1,
+        name: "".to_string(),
+        email: "member@host.com".to_string(),
+        github_login: None,
+        slug: None,
+        role: OrganizationRole::Member as i32,
+    };
+    assert_eq!(member.display_name(), "m***@host.com");
+
+    member.github_login = Some("member-gh".to_string());
+    assert_eq!(member.display_name(), "member-gh");
+
+    member.name = "Jane Doe".to_string();
+    assert_eq!(member.display_name(), "Jane Doe");
+}
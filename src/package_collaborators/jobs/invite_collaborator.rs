@@ -43,7 +43,7 @@ impl Job for SendCollaboratorInvitationEmail {
                 state.templates,
             );
 
-            email?.send()?;
+            email?.deliver(&state.pool)?;
 
             Ok(())
         })
@@ -83,7 +83,7 @@ impl Job for SendRegisterToCollabEmail {
                 state.templates,
             );
 
-            email?.send()?;
+            email?.deliver(&state.pool)?;
 
             Ok(())
         })
@@ -1,4 +1,4 @@
-use jelly::jobs::{JobState, WorkerConfig};
+use jelly::jobs::{JobState, RegisterJob, WorkerConfig};
 
 use {
     invite_collaborator::{SendCollaboratorInvitationEmail, SendRegisterToCollabEmail},
@@ -9,7 +9,7 @@ pub mod invite_collaborator;
 pub mod transfer_ownership;
 
 pub fn configure(config: WorkerConfig<JobState>) -> WorkerConfig<JobState> {
-    let mut config = config.register::<SendCollaboratorInvitationEmail>();
-    config = config.register::<SendRegisterToCollabEmail>();
-    config.register::<SendOwnershipTransferEmail>()
+    let mut config = config.register_job::<SendCollaboratorInvitationEmail>();
+    config = config.register_job::<SendRegisterToCollabEmail>();
+    config.register_job::<SendOwnershipTransferEmail>()
 }
@@ -47,7 +47,7 @@ impl Job for SendOwnershipTransferEmail {
                 state.templates,
             );
 
-            email?.send()?;
+            email?.deliver(&state.pool)?;
 
             Ok(())
         })
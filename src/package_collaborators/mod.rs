@@ -1,6 +1,7 @@
 pub mod models;
 pub mod views;
 pub mod jobs;
+pub mod policy;
 
 use jelly::actix_web::web::{get, resource, scope, ServiceConfig};
 pub use models::package_collaborator;
@@ -0,0 +1,79 @@
+use jelly::chrono;
+use jelly::chrono::{NaiveDateTime, Utc};
+use std::env;
+
+/// Used when OWNERSHIP_INVITATIONS_EXPIRATION_DAYS is not set.
+pub const DEFAULT_EXPIRATION_DAYS: i64 = 10;
+/// The longest an inviter can keep an invitation open.
+pub const MAX_EXPIRATION_DAYS: i64 = 30;
+
+/// Days an invitation stays valid when the inviter didn't choose. A misconfigured
+/// OWNERSHIP_INVITATIONS_EXPIRATION_DAYS falls back to the default instead of failing requests.
+pub fn default_expiration_days() -> i64 {
+    let expiration_days = match env::var("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS") {
+        Ok(expiration_days) => expiration_days,
+        Err(_) => return DEFAULT_EXPIRATION_DAYS,
+    };
+    match expiration_days.parse::<i64>() {
+        Ok(days) if days >= 0 => days,
+        _ => {
+            warn!(
+                "OWNERSHIP_INVITATIONS_EXPIRATION_DAYS must be a non-negative integer, using {} days instead of {:?}",
+                DEFAULT_EXPIRATION_DAYS, expiration_days
+            );
+            DEFAULT_EXPIRATION_DAYS
+        }
+    }
+}
+
+/// The chosen expiry of an invitation, or the default one counted from its creation.
+pub fn expires_at(created_at: NaiveDateTime, expires_at: Option<NaiveDateTime>) -> NaiveDateTime {
+    expires_at.unwrap_or_else(|| created_at + chrono::Duration::days(default_expiration_days()))
+}
+
+/// The expiry of an invitation sent now that stays valid for the given days.
+pub fn expires_in(days: i64) -> NaiveDateTime {
+    Utc::now().naive_utc() + chrono::Duration::days(days)
+}
+
+pub fn is_valid_expiration_days(days: i64) -> bool {
+    (1..=MAX_EXPIRATION_DAYS).contains(&days)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[actix_rt::test]
+    async fn expires_at_prefers_the_chosen_expiry() {
+        env::set_var("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS", "1");
+        let created_at = Utc::now().naive_utc();
+        let chosen = created_at + chrono::Duration::days(5);
+
+        assert_eq!(expires_at(created_at, Some(chosen)), chosen);
+        assert_eq!(
+            expires_at(created_at, None),
+            created_at + chrono::Duration::days(1)
+        );
+    }
+
+    #[actix_rt::test]
+    async fn default_expiration_days_falls_back_on_invalid_values() {
+        env::set_var("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS", "3");
+        assert_eq!(default_expiration_days(), 3);
+        env::set_var("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS", "-1");
+        assert_eq!(default_expiration_days(), DEFAULT_EXPIRATION_DAYS);
+        env::set_var("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS", "invalid-integer");
+        assert_eq!(default_expiration_days(), DEFAULT_EXPIRATION_DAYS);
+        env::remove_var("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS");
+        assert_eq!(default_expiration_days(), DEFAULT_EXPIRATION_DAYS);
+    }
+
+    #[actix_rt::test]
+    async fn is_valid_expiration_days_works() {
+        assert!(!is_valid_expiration_days(0));
+        assert!(is_valid_expiration_days(1));
+        assert!(is_valid_expiration_days(MAX_EXPIRATION_DAYS));
+        assert!(!is_valid_expiration_days(MAX_EXPIRATION_DAYS + 1));
+    }
+}
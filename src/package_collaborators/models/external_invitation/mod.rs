@@ -1,14 +1,14 @@
 #[cfg(test)]
 mod tests;
 
+use crate::package_collaborators::models::expiration;
 use crate::schema;
 use crate::schema::external_invitations;
 use diesel::prelude::*;
 use diesel::{Identifiable, Insertable, Queryable};
 use jelly::chrono::{NaiveDateTime, Utc};
+use jelly::DieselPgConnection;
 use jelly::Result;
-use jelly::{chrono, DieselPgConnection};
-use std::env;
 
 #[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable)]
 #[primary_key(external_user_email, package_id)]
@@ -17,6 +17,8 @@ pub struct ExternalInvitation {
     pub invited_by_user_id: i32,
     pub package_id: i32,
     pub created_at: NaiveDateTime,
+    /// Chosen by the inviter, `OWNERSHIP_INVITATIONS_EXPIRATION_DAYS` applies otherwise
+    pub expires_at: Option<NaiveDateTime>,
 }
 
 #[derive(Insertable, Clone, Debug)]
@@ -25,6 +27,7 @@ struct NewRecord {
     external_user_email: String,
     invited_by_user_id: i32,
     package_id: i32,
+    expires_at: Option<NaiveDateTime>,
 }
 
 impl ExternalInvitation {
@@ -32,6 +35,7 @@ impl ExternalInvitation {
         external_user_email: &str,
         invited_by_user_id: i32,
         package_id: i32,
+        expires_at: Option<NaiveDateTime>,
         conn: &DieselPgConnection,
     ) -> Result<Self> {
         conn.transaction(|| -> Result<()> {
@@ -53,6 +57,7 @@ impl ExternalInvitation {
                 external_user_email: String::from(external_user_email),
                 invited_by_user_id,
                 package_id,
+                expires_at,
             })
             .on_conflict_do_nothing()
             .get_result(conn)?;
@@ -79,6 +84,20 @@ impl ExternalInvitation {
             .get_results::<Self>(conn)?)
     }
 
+    /// Restarts the invitation, for its email to be sent again.
+    pub fn renew(
+        &self,
+        expires_at: Option<NaiveDateTime>,
+        conn: &DieselPgConnection,
+    ) -> Result<Self> {
+        Ok(diesel::update(self)
+            .set((
+                external_invitations::created_at.eq(Utc::now().naive_utc()),
+                external_invitations::expires_at.eq(expires_at),
+            ))
+            .get_result(conn)?)
+    }
+
     pub fn delete(&self, conn: &DieselPgConnection) -> Result<()> {
         diesel::delete(self).execute(conn)?;
         Ok(())
@@ -101,27 +120,35 @@ impl ExternalInvitation {
         Ok(no_deleted_rows)
     }
 
-    pub fn find_by_package_id(package_id: i32, conn: &DieselPgConnection) -> Result<Vec<String>> {
+    /// Pending invitations of a package, expired ones included.
+    pub fn find_all_by_package_id(package_id: i32, conn: &DieselPgConnection) -> Result<Vec<Self>> {
         Ok(external_invitations::table
             .filter(external_invitations::package_id.eq(package_id))
-            .select(external_invitations::external_user_email)
-            .load::<String>(conn)?)
+            .order_by(external_invitations::created_at.asc())
+            .load::<Self>(conn)?)
+    }
+
+    /// Deletes the invitations past their expiry, returning them for the inviters to be told.
+    pub fn delete_expired(conn: &DieselPgConnection) -> Result<Vec<Self>> {
+        conn.transaction(|| -> Result<Vec<Self>> {
+            let expired: Vec<Self> = external_invitations::table
+                .for_update()
+                .load::<Self>(conn)?
+                .into_iter()
+                .filter(|invitation| invitation.is_expired())
+                .collect();
+            for invitation in expired.iter() {
+                invitation.delete(conn)?;
+            }
+            Ok(expired)
+        })
     }
 
     pub fn is_expired(&self) -> bool {
         self.expires_at() <= Utc::now().naive_utc()
     }
 
-    fn expires_at(&self) -> NaiveDateTime {
-        let expiration_days = env::var("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS")
-            .expect("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS not set!");
-        let no_days = expiration_days
-            .parse::<i64>()
-            .expect("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS is not an integer!");
-        if no_days < 0 {
-            panic!("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS cannot be less than 0")
-        }
-        let days = chrono::Duration::days(no_days);
-        self.created_at + days
+    pub fn expires_at(&self) -> NaiveDateTime {
+        expiration::expires_at(self.created_at, self.expires_at)
     }
 }
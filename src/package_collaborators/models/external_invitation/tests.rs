@@ -1,3 +1,4 @@
+use crate::package_collaborators::models::expiration;
 use crate::package_collaborators::models::external_invitation::ExternalInvitation;
 use crate::packages::Package;
 use crate::test::{DatabaseTestContext, DB_POOL};
@@ -24,7 +25,7 @@ fn setup_external_invitation() -> ExternalInvitation {
         &DB_POOL,
     )
     .unwrap();
-    ExternalInvitation::create(&outside_email, uid, pid, &DB_POOL.get().unwrap()).unwrap()
+    ExternalInvitation::create(&outside_email, uid, pid, None, &DB_POOL.get().unwrap()).unwrap()
 }
 
 #[actix_rt::test]
@@ -87,25 +88,23 @@ async fn is_expired_works() {
 }
 
 #[actix_rt::test]
-#[should_panic]
-async fn is_expired_panics_if_expiration_days_is_less_than_0() {
+async fn is_expired_uses_default_if_expiration_days_is_less_than_0() {
     crate::test::init();
     let _ctx = DatabaseTestContext::new();
 
     let external_invitation = setup_external_invitation();
     env::set_var("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS", "-1");
-    external_invitation.is_expired();
+    assert!(!external_invitation.is_expired());
 }
 
 #[actix_rt::test]
-#[should_panic]
-async fn is_expired_panics_if_expiration_days_is_not_an_integer() {
+async fn is_expired_uses_default_if_expiration_days_is_not_an_integer() {
     crate::test::init();
     let _ctx = DatabaseTestContext::new();
 
     let external_invitation = setup_external_invitation();
     env::set_var("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS", "invalid-integer");
-    external_invitation.is_expired();
+    assert!(!external_invitation.is_expired());
 }
 
 #[actix_rt::test]
@@ -135,6 +134,7 @@ async fn create_new_invitation_if_existing_one_is_expired() {
         &external1.external_user_email,
         external1.invited_by_user_id,
         external1.package_id,
+        None,
         &conn,
     )
     .unwrap();
@@ -154,6 +154,7 @@ async fn not_create_new_invitation_if_it_already_exists() {
         &external.external_user_email,
         external.invited_by_user_id,
         external.package_id,
+        None,
         &conn,
     )
     .unwrap();
@@ -206,3 +207,50 @@ async fn external_invitation_delete_by_id_works() {
         panic!()
     }
 }
+
+#[actix_rt::test]
+async fn renew_restarts_the_invitation() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let conn = DB_POOL.get().unwrap();
+
+    let external = setup_external_invitation();
+    env::set_var("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS", "0");
+    assert!(external.is_expired());
+
+    let expires_at = expiration::expires_in(3);
+    let renewed = external.renew(Some(expires_at), &conn).unwrap();
+    assert!(!renewed.is_expired());
+    assert_eq!(renewed.expires_at(), expires_at);
+    assert_ne!(renewed.created_at, external.created_at);
+    assert_eq!(
+        ExternalInvitation::find_all_by_package_id(external.package_id, &conn)
+            .unwrap()
+            .len(),
+        1
+    );
+}
+
+#[actix_rt::test]
+async fn delete_expired_only_deletes_expired_invitations() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let conn = DB_POOL.get().unwrap();
+
+    env::set_var("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS", "1");
+    let external = setup_external_invitation();
+    assert!(ExternalInvitation::delete_expired(&conn)
+        .unwrap()
+        .is_empty());
+
+    let expired = external
+        .renew(Some(expiration::expires_in(-1)), &conn)
+        .unwrap();
+    let deleted = ExternalInvitation::delete_expired(&conn).unwrap();
+    assert_eq!(deleted, vec![expired]);
+    assert!(
+        ExternalInvitation::find_all_by_package_id(external.package_id, &conn)
+            .unwrap()
+            .is_empty()
+    );
+}
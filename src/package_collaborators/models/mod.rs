@@ -1,3 +1,6 @@
+pub mod expiration;
 pub mod external_invitation;
+pub mod owner_approval;
 pub mod owner_invitation;
+pub mod package_claim;
 pub mod package_collaborator;
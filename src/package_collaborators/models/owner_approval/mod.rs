@@ -0,0 +1,135 @@
+use diesel::prelude::*;
+use diesel::{Insertable, Queryable};
+use jelly::chrono::{DateTime, Utc};
+use jelly::error::Error;
+use jelly::serde::Serialize;
+use jelly::{DieselPgConnection, Result};
+
+use crate::package_collaborators::package_collaborator::Role;
+use crate::schema::owner_approvals;
+
+#[cfg(test)]
+mod tests;
+
+/// Destructive actions an owner requests and a second owner approves.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ApprovalAction {
+    RemoveOwner,
+    TransferOwnership,
+    ScheduleDeletion,
+    TransferToOrganization,
+}
+
+impl ApprovalAction {
+    pub fn from_str(action: &str) -> Option<Self> {
+        match action {
+            "remove_owner" => Some(ApprovalAction::RemoveOwner),
+            "transfer_ownership" => Some(ApprovalAction::TransferOwnership),
+            "schedule_deletion" => Some(ApprovalAction::ScheduleDeletion),
+            "transfer_to_organization" => Some(ApprovalAction::TransferToOrganization),
+            _ => None,
+        }
+    }
+}
+
+impl std::fmt::Display for ApprovalAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let action = match self {
+            ApprovalAction::RemoveOwner => "remove_owner",
+            ApprovalAction::TransferOwnership => "transfer_ownership",
+            ApprovalAction::ScheduleDeletion => "schedule_deletion",
+            ApprovalAction::TransferToOrganization => "transfer_to_organization",
+        };
+        write!(f, "{}", action)
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Serialize)]
+pub struct OwnerApproval {
+    pub id: i32,
+    pub package_id: i32,
+    pub action: String,
+    /// The owner to remove, or the collaborator receiving the ownership
+    pub target_account_id: Option<i32>,
+    /// The role a removed owner keeps
+    pub target_role: Option<i32>,
+    pub organization_id: Option<i32>,
+    pub requested_by: i32,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "owner_approvals"]
+struct NewOwnerApproval {
+    package_id: i32,
+    action: String,
+    target_account_id: Option<i32>,
+    target_role: Option<i32>,
+    organization_id: Option<i32>,
+    requested_by: i32,
+}
+
+impl OwnerApproval {
+    /// Records a pending action, an identical pending request is an error.
+    pub fn create(
+        package_id: i32,
+        action: ApprovalAction,
+        target_account_id: Option<i32>,
+        target_role: Option<Role>,
+        organization_id: Option<i32>,
+        requested_by: i32,
+        conn: &DieselPgConnection,
+    ) -> Result<Self> {
+        let target_role = target_role.map(|role| role as i32);
+        let action = action.to_string();
+        let is_pending = Self::get_by_package_id(package_id, conn)?
+            .iter()
+            .any(|approval| {
+                approval.action == action
+                    && approval.target_account_id == target_account_id
+                    && approval.organization_id == organization_id
+            });
+        if is_pending {
+            return Err(Error::Generic(format!(
+                "An approval for {} is already pending. package id: {}",
+                action, package_id
+            )));
+        }
+        let record = diesel::insert_into(owner_approvals::table)
+            .values(NewOwnerApproval {
+                package_id,
+                action,
+                target_account_id,
+                target_role,
+                organization_id,
+                requested_by,
+            })
+            .get_result::<Self>(conn)?;
+        Ok(record)
+    }
+
+    pub fn get(id: i32, package_id: i32, conn: &DieselPgConnection) -> Result<Self> {
+        Ok(owner_approvals::table
+            .filter(
+                owner_approvals::id
+                    .eq(id)
+                    .and(owner_approvals::package_id.eq(package_id)),
+            )
+            .first::<Self>(conn)?)
+    }
+
+    pub fn get_by_package_id(package_id: i32, conn: &DieselPgConnection) -> Result<Vec<Self>> {
+        Ok(owner_approvals::table
+            .filter(owner_approvals::package_id.eq(package_id))
+            .order_by(owner_approvals::id.asc())
+            .load::<Self>(conn)?)
+    }
+
+    pub fn get_action(&self) -> Option<ApprovalAction> {
+        ApprovalAction::from_str(&self.action)
+    }
+
+    pub fn delete(&self, conn: &DieselPgConnection) -> Result<usize> {
+        Ok(diesel::delete(owner_approvals::table.find(self.id)).execute(conn)?)
+    }
+}
@@ -0,0 +1,72 @@
+use crate::package_collaborators::models::owner_approval::{ApprovalAction, OwnerApproval};
+use crate::package_collaborators::package_collaborator::Role;
+use crate::test::util::{create_package, setup_user};
+use crate::test::{DatabaseTestContext, DB_POOL};
+
+#[actix_rt::test]
+async fn create_rejects_duplicated_requests() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let owner_id = setup_user(None, None);
+    let co_owner_id = setup_user(Some("co-owner@host.com".to_string()), None);
+    let conn = DB_POOL.get().unwrap();
+    let package_id = create_package(
+        "package1",
+        "https://github.com/owner/package1",
+        "",
+        Some(owner_id),
+    )
+    .id;
+
+    let approval = OwnerApproval::create(
+        package_id,
+        ApprovalAction::RemoveOwner,
+        Some(co_owner_id),
+        Some(Role::Maintainer),
+        None,
+        owner_id,
+        &conn,
+    )
+    .unwrap();
+    assert_eq!(approval.get_action(), Some(ApprovalAction::RemoveOwner));
+    assert_eq!(approval.target_role, Some(Role::Maintainer as i32));
+    assert!(OwnerApproval::create(
+        package_id,
+        ApprovalAction::RemoveOwner,
+        Some(co_owner_id),
+        Some(Role::Collaborator),
+        None,
+        owner_id,
+        &conn,
+    )
+    .is_err());
+    OwnerApproval::create(
+        package_id,
+        ApprovalAction::ScheduleDeletion,
+        None,
+        None,
+        None,
+        co_owner_id,
+        &conn,
+    )
+    .unwrap();
+
+    let approvals = OwnerApproval::get_by_package_id(package_id, &conn).unwrap();
+    assert_eq!(approvals.len(), 2);
+    assert_eq!(approvals[1].action, "schedule_deletion");
+    assert_eq!(
+        OwnerApproval::get(approval.id, package_id, &conn)
+            .unwrap()
+            .requested_by,
+        owner_id
+    );
+    assert!(OwnerApproval::get(approval.id, package_id + 1, &conn).is_err());
+
+    assert_eq!(approval.delete(&conn).unwrap(), 1);
+    assert_eq!(
+        OwnerApproval::get_by_package_id(package_id, &conn)
+            .unwrap()
+            .len(),
+        1
+    );
+}
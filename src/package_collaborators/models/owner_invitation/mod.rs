@@ -1,19 +1,19 @@
 #[cfg(test)]
 mod tests;
 
+use crate::package_collaborators::models::expiration;
 use crate::package_collaborators::package_collaborator::{PackageCollaborator, Role};
 use crate::schema;
-use crate::schema::{accounts, owner_invitations, package_collaborators, packages};
+use crate::schema::{accounts, owner_invitations, packages};
 use crate::utils::token::SecureToken;
 use diesel::dsl::now;
 use diesel::pg::expression::extensions::IntervalDsl;
 use diesel::prelude::*;
 use diesel::{Identifiable, Insertable, Queryable};
 use jelly::chrono::{NaiveDateTime, Utc};
+use jelly::DieselPgConnection;
 use jelly::Result;
-use jelly::{chrono, DieselPgConnection};
 use serde::Serialize;
-use std::env;
 
 #[derive(Clone, Debug, Eq, Identifiable, Queryable)]
 #[primary_key(invited_user_id, package_id)]
@@ -24,6 +24,8 @@ pub struct OwnerInvitation {
     pub token: String,
     pub is_transferring: bool,
     pub created_at: NaiveDateTime,
+    /// Chosen by the inviter, `OWNERSHIP_INVITATIONS_EXPIRATION_DAYS` applies otherwise
+    pub expires_at: Option<NaiveDateTime>,
 }
 
 #[derive(Clone, Debug, Eq, Queryable, Serialize)]
@@ -64,6 +66,7 @@ struct NewRecord {
     token: String,
     is_transferring: bool,
     created_at: Option<NaiveDateTime>,
+    expires_at: Option<NaiveDateTime>,
 }
 
 impl OwnerInvitation {
@@ -73,6 +76,7 @@ impl OwnerInvitation {
         package_id: i32,
         is_transferring: Option<bool>,
         created_at: Option<NaiveDateTime>,
+        expires_at: Option<NaiveDateTime>,
         conn: &DieselPgConnection,
     ) -> Result<Self> {
         // Before actually creating the invite, check if an expired invitation already exists
@@ -104,6 +108,7 @@ impl OwnerInvitation {
                 token: secure_token.inner.sha256,
                 is_transferring: is_transferring.unwrap_or(false),
                 created_at,
+                expires_at,
             })
             // The ON CONFLICT DO NOTHING clause results in not creating the invite if another one
             // already exists. This does not cause problems with expired invitation as those are
@@ -132,21 +137,28 @@ impl OwnerInvitation {
             .first::<Self>(conn)?)
     }
 
-    pub fn find_by_package_id(package_id: i32, conn: &DieselPgConnection) -> Result<Vec<i32>> {
+    /// Pending invitations of a package, expired ones included.
+    pub fn find_all_by_package_id(package_id: i32, conn: &DieselPgConnection) -> Result<Vec<Self>> {
         Ok(owner_invitations::table
             .filter(owner_invitations::package_id.eq(package_id))
-            .select(owner_invitations::invited_user_id)
-            .load::<i32>(conn)?)
+            .order_by(owner_invitations::created_at.asc())
+            .load::<Self>(conn)?)
     }
 
     pub fn find_by_invited_account(
         invited_user_id: i32,
         conn: &DieselPgConnection,
     ) -> Result<Vec<OwnerInvitationQuery>> {
-        let expiration_days = Self::get_expiration_days();
+        let expiration_days = expiration::default_expiration_days();
         Ok(owner_invitations::table
             .filter(owner_invitations::invited_user_id.eq(invited_user_id))
-            .filter(owner_invitations::created_at.gt(now - expiration_days.days()))
+            .filter(
+                owner_invitations::expires_at
+                    .gt(now)
+                    .or(owner_invitations::expires_at
+                        .is_null()
+                        .and(owner_invitations::created_at.gt(now - expiration_days.days()))),
+            )
             .inner_join(packages::table.on(owner_invitations::package_id.eq(packages::id)))
             .inner_join(accounts::table.on(owner_invitations::invited_by_user_id.eq(accounts::id)))
             .select((
@@ -160,6 +172,25 @@ impl OwnerInvitation {
             .load::<OwnerInvitationQuery>(conn)?)
     }
 
+    /// Sends the invitation again with a new token, the old link stops working.
+    pub fn renew(
+        &self,
+        expires_at: Option<NaiveDateTime>,
+        conn: &DieselPgConnection,
+    ) -> Result<Self> {
+        let secure_token = SecureToken::generate();
+        let mut res: OwnerInvitation = diesel::update(self)
+            .set((
+                owner_invitations::token.eq(secure_token.inner.sha256),
+                owner_invitations::created_at.eq(Utc::now().naive_utc()),
+                owner_invitations::expires_at.eq(expires_at),
+            ))
+            .get_result(conn)?;
+
+        res.token = secure_token.plaintext;
+        Ok(res)
+    }
+
     pub fn delete(&self, conn: &DieselPgConnection) -> Result<()> {
         diesel::delete(self).execute(conn)?;
         Ok(())
@@ -184,14 +215,19 @@ impl OwnerInvitation {
 
     pub fn accept(&self, conn: &DieselPgConnection) -> Result<()> {
         if self.is_transferring {
-            diesel::update(package_collaborators::table)
-                .set(package_collaborators::role.eq(Role::Collaborator as i32))
-                .filter(package_collaborators::account_id.eq(self.invited_by_user_id))
-                .execute(conn)?;
-            diesel::update(package_collaborators::table)
-                .set(package_collaborators::role.eq(Role::Owner as i32))
-                .filter(package_collaborators::account_id.eq(self.invited_user_id))
-                .execute(conn)?;
+            // Only the owner handing off steps down, co-owners keep their ownership
+            PackageCollaborator::update_role(
+                self.package_id,
+                self.invited_by_user_id,
+                Role::Collaborator,
+                conn,
+            )?;
+            PackageCollaborator::update_role(
+                self.package_id,
+                self.invited_user_id,
+                Role::Owner,
+                conn,
+            )?;
             self.delete(conn)?
         } else {
             PackageCollaborator::new_collaborator(
@@ -205,24 +241,27 @@ impl OwnerInvitation {
         Ok(())
     }
 
-    pub fn is_expired(&self) -> bool {
-        self.expires_at() <= Utc::now().naive_utc()
+    /// Deletes the invitations past their expiry, returning them for the inviters to be told.
+    pub fn delete_expired(conn: &DieselPgConnection) -> Result<Vec<Self>> {
+        conn.transaction(|| -> Result<Vec<Self>> {
+            let expired: Vec<Self> = owner_invitations::table
+                .for_update()
+                .load::<Self>(conn)?
+                .into_iter()
+                .filter(|invitation| invitation.is_expired())
+                .collect();
+            for invitation in expired.iter() {
+                invitation.delete(conn)?;
+            }
+            Ok(expired)
+        })
     }
 
-    fn expires_at(&self) -> NaiveDateTime {
-        let expiration_days = Self::get_expiration_days();
-        if expiration_days < 0 {
-            panic!("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS cannot be less than 0")
-        }
-        let days = chrono::Duration::days(expiration_days);
-        self.created_at + days
+    pub fn is_expired(&self) -> bool {
+        self.expires_at() <= Utc::now().naive_utc()
     }
 
-    fn get_expiration_days() -> i64 {
-        let expiration_days = env::var("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS")
-            .expect("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS not set!");
-        expiration_days
-            .parse::<i64>()
-            .expect("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS is not an integer!")
+    pub fn expires_at(&self) -> NaiveDateTime {
+        expiration::expires_at(self.created_at, self.expires_at)
     }
 }
@@ -1,3 +1,4 @@
+use crate::package_collaborators::models::expiration;
 use crate::package_collaborators::models::owner_invitation::OwnerInvitation;
 use crate::package_collaborators::package_collaborator::{PackageCollaborator, Role};
 use crate::packages::Package;
@@ -32,6 +33,7 @@ fn setup_invitation(is_transferring: Option<bool>) -> OwnerInvitation {
         pid,
         is_transferring,
         None,
+        None,
         &DB_POOL.get().unwrap(),
     )
     .unwrap()
@@ -150,6 +152,81 @@ async fn accept_owner_works() {
     assert_eq!(package_collaborator.role, Role::Owner as i32);
 }
 
+#[actix_rt::test]
+async fn accept_owner_only_changes_the_transferred_package() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let conn = DB_POOL.get().unwrap();
+
+    let owner_invitation = setup_invitation(Some(true));
+    let co_owner_id = setup_user(Some("email2@mail.com".to_string()), None);
+    let other_package_id = Package::create_test_package(
+        &"package2".to_string(),
+        &"".to_string(),
+        &"".to_string(),
+        &"1.0.0".to_string(),
+        &"".to_string(),
+        &"".to_string(),
+        &"".to_string(),
+        -1,
+        -1,
+        0,
+        0,
+        Some(owner_invitation.invited_by_user_id),
+        &DB_POOL,
+    )
+    .unwrap();
+    for (package_id, account_id) in [
+        (
+            owner_invitation.package_id,
+            owner_invitation.invited_user_id,
+        ),
+        (owner_invitation.package_id, co_owner_id),
+        (other_package_id, owner_invitation.invited_user_id),
+    ] {
+        PackageCollaborator::new_collaborator(
+            package_id,
+            account_id,
+            owner_invitation.invited_by_user_id,
+            &conn,
+        )
+        .unwrap();
+    }
+    PackageCollaborator::update_role(owner_invitation.package_id, co_owner_id, Role::Owner, &conn)
+        .unwrap();
+
+    owner_invitation.accept(&conn).unwrap();
+
+    let role = |package_id: i32, account_id: i32| {
+        PackageCollaborator::get_role(package_id, account_id, &conn)
+            .unwrap()
+            .unwrap()
+    };
+    assert_eq!(
+        role(
+            owner_invitation.package_id,
+            owner_invitation.invited_user_id
+        ),
+        Role::Owner
+    );
+    assert_eq!(
+        role(
+            owner_invitation.package_id,
+            owner_invitation.invited_by_user_id
+        ),
+        Role::Collaborator
+    );
+    assert_eq!(role(owner_invitation.package_id, co_owner_id), Role::Owner);
+    assert_eq!(
+        role(other_package_id, owner_invitation.invited_by_user_id),
+        Role::Owner
+    );
+    assert_eq!(
+        role(other_package_id, owner_invitation.invited_user_id),
+        Role::Collaborator
+    );
+}
+
 #[actix_rt::test]
 async fn delete_works() {
     crate::test::init();
@@ -180,25 +257,23 @@ async fn is_expired_works() {
 }
 
 #[actix_rt::test]
-#[should_panic]
-async fn is_expired_panics_if_expiration_days_is_less_than_0() {
+async fn is_expired_uses_default_if_expiration_days_is_less_than_0() {
     crate::test::init();
     let _ctx = DatabaseTestContext::new();
 
     let owner_invitation = setup_invitation(None);
     env::set_var("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS", "-1");
-    owner_invitation.is_expired();
+    assert!(!owner_invitation.is_expired());
 }
 
 #[actix_rt::test]
-#[should_panic]
-async fn is_expired_panics_if_expiration_days_is_not_an_integer() {
+async fn is_expired_uses_default_if_expiration_days_is_not_an_integer() {
     crate::test::init();
     let _ctx = DatabaseTestContext::new();
 
     let owner_invitation = setup_invitation(None);
     env::set_var("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS", "invalid-integer");
-    owner_invitation.is_expired();
+    assert!(!owner_invitation.is_expired());
 }
 
 #[actix_rt::test]
@@ -228,6 +303,7 @@ async fn create_new_invitation_if_existing_one_is_expired() {
         owner_invitation.package_id,
         None,
         None,
+        None,
         &conn,
     )
     .unwrap();
@@ -250,6 +326,7 @@ async fn not_create_new_invitation_if_it_already_exists() {
         owner_invitation.package_id,
         None,
         None,
+        None,
         &conn,
     )
     .unwrap();
@@ -323,3 +400,70 @@ async fn delete_by_id_works() {
         panic!()
     }
 }
+
+#[actix_rt::test]
+async fn chosen_expiry_overrides_the_default_one() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let conn = DB_POOL.get().unwrap();
+
+    let owner_invitation = setup_invitation(None);
+    owner_invitation.delete(&conn).unwrap();
+    env::set_var("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS", "0");
+    let owner_invitation = OwnerInvitation::create(
+        owner_invitation.invited_user_id,
+        owner_invitation.invited_by_user_id,
+        owner_invitation.package_id,
+        None,
+        None,
+        Some(expiration::expires_in(3)),
+        &conn,
+    )
+    .unwrap();
+    assert!(!owner_invitation.is_expired());
+
+    let invitations =
+        OwnerInvitation::find_by_invited_account(owner_invitation.invited_user_id, &conn).unwrap();
+    assert_eq!(invitations.len(), 1);
+}
+
+#[actix_rt::test]
+async fn renew_replaces_the_token() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let conn = DB_POOL.get().unwrap();
+
+    let owner_invitation = setup_invitation(Some(true));
+    let renewed = owner_invitation.renew(None, &conn).unwrap();
+    assert_ne!(renewed.token, owner_invitation.token);
+    assert!(renewed.is_transferring);
+    assert!(OwnerInvitation::find_by_token(&owner_invitation.token, &conn).is_err());
+    assert!(OwnerInvitation::find_by_token(&renewed.token, &conn).is_ok());
+
+    let invitations =
+        OwnerInvitation::find_all_by_package_id(owner_invitation.package_id, &conn).unwrap();
+    assert_eq!(invitations.len(), 1);
+    assert_eq!(invitations[0].created_at, renewed.created_at);
+}
+
+#[actix_rt::test]
+async fn delete_expired_only_deletes_expired_invitations() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let conn = DB_POOL.get().unwrap();
+
+    env::set_var("OWNERSHIP_INVITATIONS_EXPIRATION_DAYS", "1");
+    let owner_invitation = setup_invitation(None);
+    assert!(OwnerInvitation::delete_expired(&conn).unwrap().is_empty());
+
+    let expired = owner_invitation
+        .renew(Some(expiration::expires_in(-1)), &conn)
+        .unwrap();
+    let deleted = OwnerInvitation::delete_expired(&conn).unwrap();
+    assert_eq!(deleted, vec![expired]);
+    assert!(
+        OwnerInvitation::find_all_by_package_id(owner_invitation.package_id, &conn)
+            .unwrap()
+            .is_empty()
+    );
+}
@@ -0,0 +1,91 @@
+use diesel::prelude::*;
+use diesel::{Identifiable, Insertable, Queryable};
+use jelly::chrono::{DateTime, Utc};
+use jelly::error::Error;
+use jelly::serde::Serialize;
+use jelly::{DieselPgConnection, Result};
+
+use crate::package_collaborators::package_collaborator::PackageCollaborator;
+use crate::packages::Package;
+use crate::schema::package_claims;
+use crate::utils::token::generate_secure_alphanumeric_string;
+
+#[cfg(test)]
+mod tests;
+
+/// File committed at the root of the repository to prove a claim.
+pub const VERIFICATION_FILE: &str = ".movey-verification";
+const TOKEN_LENGTH: usize = 32;
+
+/// An account claiming the ownership of a crawled package.
+#[derive(Clone, Debug, Identifiable, Queryable, Serialize)]
+#[primary_key(package_id, account_id)]
+pub struct PackageClaim {
+    pub package_id: i32,
+    pub account_id: i32,
+    /// Content of the verification file
+    pub token: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "package_claims"]
+struct NewPackageClaim {
+    package_id: i32,
+    account_id: i32,
+    token: String,
+}
+
+impl PackageClaim {
+    /// The claim of the account, started with a new token the first time.
+    pub fn get_or_create(
+        package_id: i32,
+        account_id: i32,
+        conn: &DieselPgConnection,
+    ) -> Result<Self> {
+        diesel::insert_into(package_claims::table)
+            .values(NewPackageClaim {
+                package_id,
+                account_id,
+                token: generate_secure_alphanumeric_string(TOKEN_LENGTH),
+            })
+            .on_conflict_do_nothing()
+            .execute(conn)?;
+        Ok(package_claims::table
+            .find((package_id, account_id))
+            .first::<Self>(conn)?)
+    }
+
+    /// Only crawled packages, which nobody owns yet, can be claimed.
+    pub fn is_claimable(package: &Package, conn: &DieselPgConnection) -> Result<bool> {
+        Ok(package.organization_id.is_none()
+            && PackageCollaborator::get_by_package_id(package.id, conn)?.is_empty())
+    }
+
+    pub fn matches_verification_file(&self, content: &str) -> bool {
+        content.trim() == self.token
+    }
+
+    /// Makes the claiming account the owner of the package, the claims of others are dropped.
+    pub fn complete(&self, package: &Package, conn: &DieselPgConnection) -> Result<()> {
+        conn.transaction(|| -> Result<()> {
+            if !Self::is_claimable(package, conn)? {
+                return Err(Error::Generic(format!(
+                    "Package is already claimed. package id: {}",
+                    self.package_id
+                )));
+            }
+            PackageCollaborator::new_owner(
+                self.package_id,
+                self.account_id,
+                self.account_id,
+                conn,
+            )?;
+            diesel::delete(
+                package_claims::table.filter(package_claims::package_id.eq(self.package_id)),
+            )
+            .execute(conn)?;
+            Ok(())
+        })
+    }
+}
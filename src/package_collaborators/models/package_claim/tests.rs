@@ -0,0 +1,47 @@
+use crate::package_collaborators::models::package_claim::PackageClaim;
+use crate::package_collaborators::package_collaborator::{PackageCollaborator, Role};
+use crate::test::util::{create_package, setup_user};
+use crate::test::{DatabaseTestContext, DB_POOL};
+
+#[actix_rt::test]
+async fn get_or_create_keeps_the_token() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let conn = DB_POOL.get().unwrap();
+    let package = create_package("package1", "https://github.com/owner/package1", "", None);
+
+    let claim = PackageClaim::get_or_create(package.id, uid, &conn).unwrap();
+    assert_eq!(claim.token.len(), 32);
+    let same_claim = PackageClaim::get_or_create(package.id, uid, &conn).unwrap();
+    assert_eq!(claim.token, same_claim.token);
+
+    assert!(claim.matches_verification_file(&format!("{}\n", claim.token)));
+    assert!(!claim.matches_verification_file("another token"));
+}
+
+#[actix_rt::test]
+async fn complete_makes_the_claimer_owner() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    let other_uid = setup_user(Some("other@host.com".to_string()), None);
+    let conn = DB_POOL.get().unwrap();
+    let package = create_package("package1", "https://github.com/owner/package1", "", None);
+    assert!(PackageClaim::is_claimable(&package, &conn).unwrap());
+
+    let claim = PackageClaim::get_or_create(package.id, uid, &conn).unwrap();
+    let other_claim = PackageClaim::get_or_create(package.id, other_uid, &conn).unwrap();
+    claim.complete(&package, &conn).unwrap();
+
+    assert_eq!(
+        PackageCollaborator::get_role(package.id, uid, &conn).unwrap(),
+        Some(Role::Owner)
+    );
+    assert!(!PackageClaim::is_claimable(&package, &conn).unwrap());
+    assert!(other_claim.complete(&package, &conn).is_err());
+    assert_eq!(
+        PackageCollaborator::get_role(package.id, other_uid, &conn).unwrap(),
+        None
+    );
+}
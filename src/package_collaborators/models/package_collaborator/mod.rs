@@ -26,10 +26,29 @@ pub struct PackageCollaborator {
     pub created_at: DateTime<Utc>,
 }
 
-#[derive(Debug, Deserialize, Serialize)]
+/// From highest to lowest: owner, maintainer, collaborator and publisher.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Role {
     Owner = 0,
+    /// Publishes versions, invites collaborators and removes publishers
     Collaborator = 1,
+    /// Publishes versions, invites collaborators and removes collaborators and publishers
+    Maintainer = 2,
+    /// Publishes versions
+    Publisher = 3,
+}
+
+impl Role {
+    pub fn from_i32(role: i32) -> Option<Self> {
+        match role {
+            0 => Some(Role::Owner),
+            1 => Some(Role::Collaborator),
+            2 => Some(Role::Maintainer),
+            3 => Some(Role::Publisher),
+            _ => None,
+        }
+    }
 }
 
 #[derive(Insertable)]
@@ -84,12 +103,71 @@ impl PackageCollaborator {
             .first::<Self>(conn)?)
     }
 
+    pub fn get_role(
+        package_id: i32,
+        account_id: i32,
+        conn: &DieselPgConnection,
+    ) -> Result<Option<Role>> {
+        let role = package_collaborators::table
+            .find((package_id, account_id))
+            .select(package_collaborators::role)
+            .first::<i32>(conn)
+            .optional()?;
+        Ok(role.and_then(Role::from_i32))
+    }
+
+    /// Collaborators of a package with their roles, owners first.
+    pub fn get_all_by_package_id(package_id: i32, conn: &DieselPgConnection) -> Result<Vec<Self>> {
+        Ok(package_collaborators::table
+            .filter(package_collaborators::package_id.eq(package_id))
+            .order((
+                package_collaborators::role.asc(),
+                package_collaborators::created_at.asc(),
+            ))
+            .load::<Self>(conn)?)
+    }
+
+    /// Owners of a package, the earliest first.
+    pub fn get_owner_ids(package_id: i32, conn: &DieselPgConnection) -> Result<Vec<i32>> {
+        Ok(package_collaborators::table
+            .filter(
+                package_collaborators::package_id
+                    .eq(package_id)
+                    .and(package_collaborators::role.eq(Role::Owner as i32)),
+            )
+            .select(package_collaborators::account_id)
+            .order(package_collaborators::created_at.asc())
+            .load::<i32>(conn)?)
+    }
+
+    /// Changes the role of a collaborator. Promoting to `Role::Owner` adds a co-owner, callers
+    /// make sure a package never loses its last owner.
+    pub fn update_role(
+        package_id: i32,
+        account_id: i32,
+        new_role: Role,
+        conn: &DieselPgConnection,
+    ) -> Result<usize> {
+        Ok(diesel::update(
+            package_collaborators::table.filter(
+                package_collaborators::package_id
+                    .eq(package_id)
+                    .and(package_collaborators::account_id.eq(account_id)),
+            ),
+        )
+        .set(package_collaborators::role.eq(new_role as i32))
+        .execute(conn)?)
+    }
+
     pub fn get_by_package_id(package_id: i32, conn: &DieselPgConnection) -> Result<Vec<i32>> {
         Ok(package_collaborators::table
             .filter(package_collaborators::package_id.eq(package_id))
             .select(package_collaborators::account_id)
-            // First element is the owner of package
-            .order(package_collaborators::role.asc())
+            // Owners first, the earliest one leading
+            .order((
+                package_collaborators::role.asc(),
+                package_collaborators::created_at.asc(),
+            ))
             .load::<i32>(conn)?)
     }
 
@@ -108,18 +186,25 @@ impl PackageCollaborator {
             .load::<Self>(conn)?)
     }
 
+    /// Removes the collaborator only if its role is lower than `removed_by`.
     pub fn delete_collaborator_by_id(
         account_id_: i32,
         package_id_: i32,
+        removed_by: Role,
         conn: &DieselPgConnection,
     ) -> Result<usize> {
         use schema::package_collaborators::dsl::*;
+        let lower_roles: Vec<i32> = removed_by
+            .lower_roles()
+            .into_iter()
+            .map(|lower_role| lower_role as i32)
+            .collect();
         let no_deleted_rows = diesel::delete(
             package_collaborators.filter(
                 account_id
                     .eq(account_id_)
                     .and(package_id.eq(package_id_))
-                    .and(role.eq(Role::Collaborator as i32)),
+                    .and(role.eq_any(lower_roles)),
             ),
         )
         .execute(conn)?;
@@ -1,4 +1,4 @@
-use crate::package_collaborators::package_collaborator::PackageCollaborator;
+use crate::package_collaborators::package_collaborator::{PackageCollaborator, Role};
 use crate::packages::Package;
 use crate::test::util::setup_user;
 use crate::test::{DatabaseTestContext, DB_POOL};
@@ -72,14 +72,19 @@ async fn delete_collaborator_by_id_works() {
 
     let db = &DB_POOL;
     let conn = db.get().unwrap();
-    let res = PackageCollaborator::delete_collaborator_by_id(1, 1, &conn).unwrap();
+    let res = PackageCollaborator::delete_collaborator_by_id(1, 1, Role::Owner, &conn).unwrap();
     assert_eq!(res, 0);
 
     let (pid, uid) = setup_collaborator();
     let collaborator = PackageCollaborator::get(pid, uid, &DB_POOL.get().unwrap());
     assert!(collaborator.is_ok());
 
-    let res = PackageCollaborator::delete_collaborator_by_id(uid, pid, &conn).unwrap();
+    // Collaborators don't remove each other
+    let res = PackageCollaborator::delete_collaborator_by_id(uid, pid, Role::Collaborator, &conn)
+        .unwrap();
+    assert_eq!(res, 0);
+    let res =
+        PackageCollaborator::delete_collaborator_by_id(uid, pid, Role::Maintainer, &conn).unwrap();
     assert_eq!(res, 1);
 
     let not_found = PackageCollaborator::get(pid, uid, &DB_POOL.get().unwrap());
@@ -90,6 +95,7 @@ async fn delete_collaborator_by_id_works() {
     }
 
     let owner_id = collaborator.unwrap().created_by;
-    let res = PackageCollaborator::delete_collaborator_by_id(owner_id, pid, &conn).unwrap();
+    let res =
+        PackageCollaborator::delete_collaborator_by_id(owner_id, pid, Role::Owner, &conn).unwrap();
     assert_eq!(res, 0);
 }
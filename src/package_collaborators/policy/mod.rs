@@ -0,0 +1,132 @@
+//! Who can do what on a package. Every authorization decision on a package goes through
+//! `can`, controllers only map a refusal to their own error message.
+
+use jelly::DieselPgConnection;
+use jelly::Result;
+use std::env;
+
+use crate::organizations::models::{Organization, OrganizationMember, OrganizationRole};
+use crate::package_collaborators::package_collaborator::{PackageCollaborator, Role};
+use crate::packages::Package;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Action {
+    Publish,
+    /// Yanking a published version, versions can't be yanked yet
+    Yank,
+    Invite,
+    RemoveCollaborator,
+    ChangeRole,
+    TransferOwnership,
+    Deprecate,
+    /// Renaming, archiving, deleting or moving the package to an organization
+    Administer,
+    /// Approving or dismissing a destructive action another owner requested
+    Approve,
+}
+
+/// Lowest role first, see `Role::outranks`.
+const HIERARCHY: [Role; 4] = [
+    Role::Publisher,
+    Role::Collaborator,
+    Role::Maintainer,
+    Role::Owner,
+];
+
+impl Role {
+    fn rank(self) -> usize {
+        HIERARCHY.iter().position(|role| *role == self).unwrap_or(0)
+    }
+
+    /// Roles are a strict hierarchy: a role is allowed everything the roles below it are,
+    /// and only removes collaborators with a lower role.
+    pub fn outranks(self, other: Role) -> bool {
+        self.rank() > other.rank()
+    }
+
+    /// The roles of the collaborators this role can remove.
+    pub fn lower_roles(self) -> Vec<Role> {
+        HIERARCHY
+            .iter()
+            .copied()
+            .filter(|role| self.outranks(*role))
+            .collect()
+    }
+
+    pub fn allows(self, action: Action) -> bool {
+        let lowest_allowed = match action {
+            Action::Publish => Role::Publisher,
+            Action::Invite | Action::RemoveCollaborator => Role::Collaborator,
+            Action::Yank => Role::Maintainer,
+            Action::ChangeRole
+            | Action::TransferOwnership
+            | Action::Deprecate
+            | Action::Administer
+            | Action::Approve => Role::Owner,
+        };
+        !lowest_allowed.outranks(self)
+    }
+}
+
+/// The role an account has on a package. Admins of the organization owning the package are
+/// its owners, and its members are maintainers.
+pub fn get_role(
+    package: &Package,
+    account_id: i32,
+    conn: &DieselPgConnection,
+) -> Result<Option<Role>> {
+    let role = PackageCollaborator::get_role(package.id, account_id, conn)?;
+    let organization_role = match package.organization_id {
+        Some(organization_id) => OrganizationMember::get_role(organization_id, account_id, conn)?,
+        None => None,
+    };
+    Ok(match (role, organization_role) {
+        (Some(Role::Owner), _) | (_, Some(OrganizationRole::Admin)) => Some(Role::Owner),
+        (_, Some(OrganizationRole::Member)) => Some(Role::Maintainer),
+        (role, None) => role,
+    })
+}
+
+pub fn can(
+    package: &Package,
+    account_id: i32,
+    action: Action,
+    conn: &DieselPgConnection,
+) -> Result<bool> {
+    // Packages of an organization change hands through its membership
+    if action == Action::TransferOwnership && package.organization_id.is_some() {
+        return Ok(false);
+    }
+    Ok(get_role(package, account_id, conn)?
+        .map(|role| role.allows(action))
+        .unwrap_or(false))
+}
+
+/// Owners of a package, admins of the organization owning it included.
+pub fn get_owner_ids(package: &Package, conn: &DieselPgConnection) -> Result<Vec<i32>> {
+    let mut owner_ids = PackageCollaborator::get_owner_ids(package.id, conn)?;
+    if let Some(organization_id) = package.organization_id {
+        let organization = Organization::get(organization_id, conn)?;
+        for member in organization.get_members(conn)? {
+            if member.role == OrganizationRole::Admin as i32
+                && !owner_ids.contains(&member.account_id)
+            {
+                owner_ids.push(member.account_id);
+            }
+        }
+    }
+    Ok(owner_ids)
+}
+
+/// Removing an owner, transferring or deleting the package waits for a second owner when
+/// `OWNER_APPROVAL_REQUIRED` is set. A package with a single owner has nobody to ask.
+pub fn requires_approval(package: &Package, conn: &DieselPgConnection) -> Result<bool> {
+    let is_required = env::var("OWNER_APPROVAL_REQUIRED").unwrap_or_else(|_| "".to_string());
+    if is_required.to_lowercase() != "true" {
+        return Ok(false);
+    }
+    Ok(get_owner_ids(package, conn)?.len() > 1)
+}
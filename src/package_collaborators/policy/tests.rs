@@ -0,0 +1,153 @@
+use crate::organizations::models::{Organization, OrganizationRole};
+use crate::package_collaborators::package_collaborator::{PackageCollaborator, Role};
+use crate::package_collaborators::policy::{self, Action};
+use crate::packages::Package;
+use crate::test::util::{create_package, setup_user};
+use crate::test::{DatabaseTestContext, DB_POOL};
+
+#[test]
+fn roles_allow_their_actions_only() {
+    assert!(Role::Owner.allows(Action::Administer));
+    assert!(Role::Owner.allows(Action::ChangeRole));
+
+    assert!(Role::Owner.allows(Action::Yank));
+
+    assert!(Role::Maintainer.allows(Action::Publish));
+    assert!(Role::Maintainer.allows(Action::Yank));
+    assert!(Role::Maintainer.allows(Action::Invite));
+    assert!(Role::Maintainer.allows(Action::RemoveCollaborator));
+    assert!(!Role::Maintainer.allows(Action::Deprecate));
+
+    assert!(Role::Publisher.allows(Action::Publish));
+    assert!(!Role::Publisher.allows(Action::Yank));
+    assert!(!Role::Publisher.allows(Action::Invite));
+    assert!(!Role::Publisher.allows(Action::RemoveCollaborator));
+
+    assert!(Role::Collaborator.allows(Action::Publish));
+    assert!(Role::Collaborator.allows(Action::Invite));
+    assert!(Role::Collaborator.allows(Action::RemoveCollaborator));
+    assert!(!Role::Collaborator.allows(Action::Yank));
+    assert!(!Role::Collaborator.allows(Action::TransferOwnership));
+}
+
+#[test]
+fn roles_only_remove_lower_roles() {
+    assert_eq!(
+        Role::Owner.lower_roles(),
+        vec![Role::Publisher, Role::Collaborator, Role::Maintainer]
+    );
+    assert_eq!(
+        Role::Maintainer.lower_roles(),
+        vec![Role::Publisher, Role::Collaborator]
+    );
+    assert_eq!(Role::Collaborator.lower_roles(), vec![Role::Publisher]);
+    assert!(Role::Publisher.lower_roles().is_empty());
+
+    assert!(Role::Maintainer.outranks(Role::Collaborator));
+    assert!(!Role::Collaborator.outranks(Role::Collaborator));
+    assert!(!Role::Maintainer.outranks(Role::Owner));
+}
+
+#[actix_rt::test]
+async fn get_role_works_for_collaborators() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let owner_id = setup_user(None, None);
+    let publisher_id = setup_user(Some("publisher@host.com".to_string()), None);
+    let outsider_id = setup_user(Some("outsider@host.com".to_string()), None);
+    let conn = DB_POOL.get().unwrap();
+    let package = create_package(
+        "package1",
+        "https://github.com/owner/package1",
+        "",
+        Some(owner_id),
+    );
+    PackageCollaborator::new_collaborator(package.id, publisher_id, owner_id, &conn).unwrap();
+    PackageCollaborator::update_role(package.id, publisher_id, Role::Publisher, &conn).unwrap();
+
+    assert_eq!(
+        policy::get_role(&package, owner_id, &conn).unwrap(),
+        Some(Role::Owner)
+    );
+    assert_eq!(
+        policy::get_role(&package, publisher_id, &conn).unwrap(),
+        Some(Role::Publisher)
+    );
+    assert_eq!(
+        policy::get_role(&package, outsider_id, &conn).unwrap(),
+        None
+    );
+
+    assert!(policy::can(&package, publisher_id, Action::Publish, &conn).unwrap());
+    assert!(!policy::can(&package, publisher_id, Action::Invite, &conn).unwrap());
+    assert!(!policy::can(&package, outsider_id, Action::Publish, &conn).unwrap());
+}
+
+#[actix_rt::test]
+async fn co_owners_need_each_other_approval() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let owner_id = setup_user(None, None);
+    let co_owner_id = setup_user(Some("co-owner@host.com".to_string()), None);
+    let conn = DB_POOL.get().unwrap();
+    let package = create_package(
+        "package1",
+        "https://github.com/owner/package1",
+        "",
+        Some(owner_id),
+    );
+    PackageCollaborator::new_collaborator(package.id, co_owner_id, owner_id, &conn).unwrap();
+    std::env::set_var("OWNER_APPROVAL_REQUIRED", "true");
+
+    assert_eq!(
+        policy::get_owner_ids(&package, &conn).unwrap(),
+        vec![owner_id]
+    );
+    assert!(!policy::requires_approval(&package, &conn).unwrap());
+
+    PackageCollaborator::update_role(package.id, co_owner_id, Role::Owner, &conn).unwrap();
+    assert_eq!(
+        policy::get_owner_ids(&package, &conn).unwrap(),
+        vec![owner_id, co_owner_id]
+    );
+    assert!(policy::can(&package, co_owner_id, Action::Administer, &conn).unwrap());
+    assert!(policy::requires_approval(&package, &conn).unwrap());
+
+    std::env::remove_var("OWNER_APPROVAL_REQUIRED");
+    assert!(!policy::requires_approval(&package, &conn).unwrap());
+}
+
+#[actix_rt::test]
+async fn get_role_works_for_organization_members() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let owner_id = setup_user(None, None);
+    let admin_id = setup_user(Some("admin@host.com".to_string()), None);
+    let member_id = setup_user(Some("member@host.com".to_string()), None);
+    let conn = DB_POOL.get().unwrap();
+    let package = create_package(
+        "package1",
+        "https://github.com/owner/package1",
+        "",
+        Some(owner_id),
+    );
+    let organization = Organization::create("Move Labs", "", admin_id, &conn).unwrap();
+    organization
+        .add_member(member_id, OrganizationRole::Member, &conn)
+        .unwrap();
+    organization.transfer_package(&package, &conn).unwrap();
+    let package = Package::get(package.id, &DB_POOL).unwrap();
+
+    assert_eq!(
+        policy::get_role(&package, admin_id, &conn).unwrap(),
+        Some(Role::Owner)
+    );
+    assert_eq!(
+        policy::get_role(&package, member_id, &conn).unwrap(),
+        Some(Role::Maintainer)
+    );
+    assert_eq!(
+        policy::get_role(&package, owner_id, &conn).unwrap(),
+        Some(Role::Collaborator)
+    );
+}
@@ -0,0 +1,312 @@
+//! API documentation extracted from the `.move` sources of package versions.
+
+use diesel::dsl::now;
+use diesel::prelude::*;
+use diesel::{Identifiable, Insertable, Queryable};
+use jelly::chrono::{DateTime, Utc};
+use jelly::serde::Serialize;
+use jelly::DieselPgConnection;
+use jelly::Result;
+use mockall_double::double;
+
+#[double]
+use crate::github_service::GithubService;
+use crate::packages::models::source_file::PackageVersionFile;
+use crate::packages::PackageVersion;
+use crate::schema::move_functions;
+use crate::schema::move_modules;
+use crate::schema::move_structs;
+use crate::schema::package_versions;
+use crate::schema::packages;
+use crate::utils::presenter;
+
+pub mod parser;
+pub mod search;
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Clone, Debug, Identifiable, Queryable, Serialize)]
+pub struct MoveModule {
+    pub id: i32,
+    pub package_version_id: i32,
+    pub address: String,
+    pub name: String,
+    pub doc: String,
+    pub file_path: String,
+    /// Modules imported with `use`, as `address::module`.
+    pub used_modules: Vec<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Insertable)]
+#[table_name = "move_modules"]
+struct NewMoveModule<'a> {
+    package_version_id: i32,
+    address: &'a str,
+    name: &'a str,
+    doc: &'a str,
+    file_path: &'a str,
+    used_modules: &'a [String],
+}
+
+#[derive(Clone, Debug, Identifiable, Queryable, Serialize)]
+pub struct MoveStruct {
+    pub id: i32,
+    pub move_module_id: i32,
+    pub name: String,
+    pub signature: String,
+    pub doc: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "move_structs"]
+struct NewMoveStruct<'a> {
+    move_module_id: i32,
+    name: &'a str,
+    signature: &'a str,
+    doc: &'a str,
+}
+
+#[derive(Clone, Debug, Identifiable, Queryable, Serialize)]
+pub struct MoveFunction {
+    pub id: i32,
+    pub move_module_id: i32,
+    pub name: String,
+    pub visibility: String,
+    pub is_entry: bool,
+    pub signature: String,
+    pub doc: String,
+}
+
+#[derive(Insertable)]
+#[table_name = "move_functions"]
+struct NewMoveFunction<'a> {
+    move_module_id: i32,
+    name: &'a str,
+    visibility: &'a str,
+    is_entry: bool,
+    signature: &'a str,
+    doc: &'a str,
+}
+
+/// A module used by a documented module, with the registry package declaring it if any.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct ModuleDependency {
+    pub path: String,
+    pub address: String,
+    pub name: String,
+    pub package_slug: Option<String>,
+    pub version: Option<String>,
+}
+
+impl MoveModule {
+    pub fn get_by_package_version(version_id: i32, conn: &DieselPgConnection) -> Result<Vec<Self>> {
+        let records = move_modules::table
+            .filter(move_modules::package_version_id.eq(version_id))
+            .order_by((move_modules::address, move_modules::name))
+            .load::<MoveModule>(conn)?;
+        Ok(records)
+    }
+
+    /// Modules of different addresses may share a name in the same version.
+    pub fn get_by_package_version_and_path(
+        version_id: i32,
+        module_address: &str,
+        module_name: &str,
+        conn: &DieselPgConnection,
+    ) -> Result<Option<Self>> {
+        let record = move_modules::table
+            .filter(move_modules::package_version_id.eq(version_id))
+            .filter(move_modules::address.eq(module_address))
+            .filter(move_modules::name.eq(module_name))
+            .first::<MoveModule>(conn)
+            .optional()?;
+        Ok(record)
+    }
+
+    pub fn get_structs(&self, conn: &DieselPgConnection) -> Result<Vec<MoveStruct>> {
+        let records = move_structs::table
+            .filter(move_structs::move_module_id.eq(self.id))
+            .order_by(move_structs::id)
+            .load::<MoveStruct>(conn)?;
+        Ok(records)
+    }
+
+    pub fn get_functions(&self, conn: &DieselPgConnection) -> Result<Vec<MoveFunction>> {
+        let records = move_functions::table
+            .filter(move_functions::move_module_id.eq(self.id))
+            .order_by(move_functions::id)
+            .load::<MoveFunction>(conn)?;
+        Ok(records)
+    }
+
+    /// Resolves the used modules to the packages declaring them. Modules of the same version
+    /// win, then the most recently published version of any other package.
+    pub fn get_dependencies(&self, conn: &DieselPgConnection) -> Result<Vec<ModuleDependency>> {
+        let mut dependencies = vec![];
+        for used_module in &self.used_modules {
+            let (module_address, module_name) = match used_module.split_once("::") {
+                Some(parts) => parts,
+                None => continue,
+            };
+            let resolved = move_modules::table
+                .inner_join(package_versions::table.inner_join(packages::table))
+                .filter(move_modules::address.eq(module_address))
+                .filter(move_modules::name.eq(module_name))
+                .order_by((
+                    move_modules::package_version_id
+                        .eq(self.package_version_id)
+                        .desc(),
+                    package_versions::id.desc(),
+                ))
+                .select((packages::slug, package_versions::version))
+                .first::<(String, String)>(conn)
+                .optional()?;
+            let (package_slug, version) = match resolved {
+                Some((slug, version)) => (Some(slug), Some(version)),
+                None => (None, None),
+            };
+            dependencies.push(ModuleDependency {
+                path: used_module.clone(),
+                address: module_address.to_string(),
+                name: module_name.to_string(),
+                package_slug,
+                version,
+            });
+        }
+        Ok(dependencies)
+    }
+}
+
+/// Returns the versions whose docs haven't been generated yet, along with the repository
+/// url of their package. Latest versions first.
+pub fn get_versions_without_docs(
+    batch_size: i64,
+    conn: &DieselPgConnection,
+) -> Result<Vec<(PackageVersion, String)>> {
+    let records = package_versions::table
+        .inner_join(packages::table)
+        .filter(package_versions::docs_generated_at.is_null())
+        .order_by(package_versions::id.desc())
+        .limit(batch_size)
+        .select((package_versions::all_columns, packages::repository_url))
+        .load::<(PackageVersion, String)>(conn)?;
+    Ok(records)
+}
+
+// Test sources and build outputs, which contain copies of the dependencies, aren't documented
+fn is_documented_source(path: &str) -> bool {
+    path.ends_with(".move")
+        && !path
+            .split('/')
+            .any(|segment| segment == "tests" || segment == "build")
+}
+
+/// Parses the `.move` files of a version at its rev and replaces its documented modules.
+/// Returns the number of modules found.
+pub fn generate_version_docs(
+    package_version: &PackageVersion,
+    repository_url: &str,
+    github_service: &GithubService,
+    conn: &DieselPgConnection,
+) -> Result<usize> {
+    let mut modules = vec![];
+    let rev = package_version.rev.as_deref().unwrap_or("");
+    // versions created without a rev can't be pinned to their sources
+    if !rev.is_empty() {
+        let (repo_url, subdir) = presenter::make_package_repo_and_subdir(repository_url);
+        let files = github_service.fetch_source_tree(&repo_url, rev, &subdir)?;
+        // the source tab lists the same files
+        PackageVersionFile::cache_tree(package_version.id, &files, conn)?;
+        let full_path = |path: &str| {
+            if subdir.is_empty() {
+                path.to_string()
+            } else {
+                format!("{}/{}", subdir, path)
+            }
+        };
+        for file in files.iter().filter(|file| is_documented_source(&file.path)) {
+            let repo_path = full_path(&file.path);
+            if let Some(content) = github_service.fetch_source_file(&repo_url, rev, &repo_path)? {
+                PackageVersionFile::cache_content(package_version.id, &file.path, &content, conn)?;
+                match parser::parse_move_source(&content) {
+                    Ok(parsed_modules) => {
+                        for module in parsed_modules {
+                            modules.push((file.path.clone(), module));
+                        }
+                    }
+                    // the other files of the version are still documented
+                    Err(e) => warn!("Cannot parse {}: {}", repo_path, e),
+                }
+            }
+        }
+    }
+
+    conn.transaction::<_, diesel::result::Error, _>(|| {
+        diesel::delete(
+            move_modules::table.filter(move_modules::package_version_id.eq(package_version.id)),
+        )
+        .execute(conn)?;
+        for (file_path, module) in &modules {
+            let module_id = diesel::insert_into(move_modules::table)
+                .values(NewMoveModule {
+                    package_version_id: package_version.id,
+                    address: &module.address,
+                    name: &module.name,
+                    doc: &module.doc,
+                    file_path,
+                    used_modules: &module.used_modules,
+                })
+                .returning(move_modules::id)
+                .get_result::<i32>(conn)?;
+            let structs = module
+                .structs
+                .iter()
+                .map(|item| NewMoveStruct {
+                    move_module_id: module_id,
+                    name: &item.name,
+                    signature: &item.signature,
+                    doc: &item.doc,
+                })
+                .collect::<Vec<NewMoveStruct>>();
+            if !structs.is_empty() {
+                diesel::insert_into(move_structs::table)
+                    .values(&structs)
+                    .execute(conn)?;
+            }
+            let functions = module
+                .functions
+                .iter()
+                .map(|item| NewMoveFunction {
+                    move_module_id: module_id,
+                    name: &item.name,
+                    visibility: &item.visibility,
+                    is_entry: item.is_entry,
+                    signature: &item.signature,
+                    doc: &item.doc,
+                })
+                .collect::<Vec<NewMoveFunction>>();
+            if !functions.is_empty() {
+                diesel::insert_into(move_functions::table)
+                    .values(&functions)
+                    .execute(conn)?;
+            }
+        }
+        mark_docs_generated(package_version.id, conn)?;
+        Ok(())
+    })?;
+    Ok(modules.len())
+}
+
+/// Also used when generation fails, so that a broken version doesn't block the others.
+/// Resetting `docs_generated_at` to NULL queues the version again.
+pub fn mark_docs_generated(
+    version_id: i32,
+    conn: &DieselPgConnection,
+) -> std::result::Result<(), diesel::result::Error> {
+    diesel::update(package_versions::table.find(version_id))
+        .set(package_versions::docs_generated_at.eq(now))
+        .execute(conn)?;
+    Ok(())
+}
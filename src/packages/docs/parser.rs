@@ -0,0 +1,743 @@
+//! A lightweight parser extracting the documented items of Move modules. It only understands
+//! declarations, function bodies and other blocks are skipped by matching braces.
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ParsedModule {
+    pub address: String,
+    pub name: String,
+    pub doc: String,
+    /// Modules imported with `use`, as `address::module`.
+    pub used_modules: Vec<String>,
+    pub structs: Vec<ParsedStruct>,
+    pub functions: Vec<ParsedFunction>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ParsedStruct {
+    pub name: String,
+    pub signature: String,
+    pub doc: String,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct ParsedFunction {
+    pub name: String,
+    pub visibility: String,
+    pub is_entry: bool,
+    pub signature: String,
+    pub doc: String,
+}
+
+#[derive(Debug, PartialEq)]
+pub enum ParseError {
+    /// The source ends in the middle of the named item.
+    UnexpectedEnd(String),
+}
+
+impl std::fmt::Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ParseError::UnexpectedEnd(item) => write!(f, "unexpected end of source in {}", item),
+        }
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum TokenKind {
+    Word,
+    Punct,
+    Doc,
+}
+
+#[derive(Debug)]
+struct Token<'a> {
+    kind: TokenKind,
+    text: &'a str,
+    start: usize,
+    end: usize,
+}
+
+fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let bytes = source.as_bytes();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start = i;
+        if c.is_ascii_whitespace() {
+            i += 1;
+        } else if source[i..].starts_with("///") {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+            tokens.push(Token {
+                kind: TokenKind::Doc,
+                text: source[start + 3..i].trim(),
+                start,
+                end: i,
+            });
+        } else if source[i..].starts_with("//") {
+            while i < bytes.len() && bytes[i] != b'\n' {
+                i += 1;
+            }
+        } else if source[i..].starts_with("/*") {
+            i = match source[i + 2..].find("*/") {
+                Some(index) => i + 2 + index + 2,
+                None => bytes.len(),
+            };
+        } else if c == b'"' {
+            // byte strings b"..." and x"...", the prefix has already been read as a word
+            i += 1;
+            while i < bytes.len() && bytes[i] != b'"' {
+                if bytes[i] == b'\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            i = (i + 1).min(bytes.len());
+        } else if c.is_ascii_alphanumeric() || c == b'_' {
+            while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                i += 1;
+            }
+            tokens.push(Token {
+                kind: TokenKind::Word,
+                text: &source[start..i],
+                start,
+                end: i,
+            });
+        } else {
+            // multi-byte characters only appear in comments and strings of valid Move code
+            i += source[i..].chars().next().map_or(1, char::len_utf8);
+            tokens.push(Token {
+                kind: TokenKind::Punct,
+                text: &source[start..i],
+                start,
+                end: i,
+            });
+        }
+    }
+    tokens
+}
+
+struct Parser<'a> {
+    source: &'a str,
+    tokens: Vec<Token<'a>>,
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    fn peek(&self) -> Option<&Token<'a>> {
+        self.tokens.get(self.position)
+    }
+
+    fn peek_text(&self, offset: usize) -> &str {
+        self.tokens
+            .get(self.position + offset)
+            .map_or("", |token| token.text)
+    }
+
+    fn next(&mut self) -> Option<&Token<'a>> {
+        let token = self.tokens.get(self.position);
+        if token.is_some() {
+            self.position += 1;
+        }
+        token
+    }
+
+    // Skips a balanced group, the current token being the opening one.
+    // Returns false if the source ends before the group is closed.
+    fn skip_group(&mut self, open: &str, close: &str) -> bool {
+        let mut depth = 0;
+        while let Some(token) = self.next() {
+            if token.kind != TokenKind::Punct {
+                continue;
+            }
+            if token.text == open {
+                depth += 1;
+            } else if token.text == close {
+                depth -= 1;
+                if depth == 0 {
+                    return true;
+                }
+            }
+        }
+        false
+    }
+
+    // Skips to the end of the current item: a `;` or a balanced `{ ... }` block
+    fn skip_item(&mut self) {
+        while let Some(token) = self.peek() {
+            match token.text {
+                ";" => {
+                    self.position += 1;
+                    return;
+                }
+                "{" => {
+                    self.skip_group("{", "}");
+                    return;
+                }
+                "}" => return,
+                _ => self.position += 1,
+            }
+        }
+    }
+
+    fn take_doc(&mut self) -> String {
+        let mut lines = vec![];
+        while let Some(token) = self.peek() {
+            if token.kind != TokenKind::Doc {
+                break;
+            }
+            lines.push(token.text);
+            self.position += 1;
+        }
+        lines.join("\n")
+    }
+
+    // Returns true if the attributes mark the next item as test only
+    fn skip_attributes(&mut self) -> bool {
+        let mut is_test = false;
+        while self.peek_text(0) == "#" {
+            self.position += 1;
+            // a stray `#` is not an attribute, consuming it keeps the parser moving
+            if self.peek_text(0) != "[" {
+                continue;
+            }
+            let start = self.position;
+            self.skip_group("[", "]");
+            is_test |= self.tokens[start..self.position]
+                .iter()
+                .any(|token| token.text == "test" || token.text == "test_only");
+        }
+        is_test
+    }
+
+    // Source lines of an item without comments, e.g struct fields keep their own line
+    fn source_lines(&self, start: usize, end: usize) -> Vec<String> {
+        let mut text = self.source[start..end].to_string();
+        while let Some(index) = text.find("/*") {
+            let comment_end = text[index..]
+                .find("*/")
+                .map_or(text.len(), |offset| index + offset + 2);
+            text.replace_range(index..comment_end, " ");
+        }
+        text.lines()
+            .map(|line| line.split("//").next().unwrap_or("").trim().to_string())
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+
+    fn function_signature(&self, start: usize, end: usize) -> String {
+        self.source_lines(start, end)
+            .join(" ")
+            .split_whitespace()
+            .collect::<Vec<&str>>()
+            .join(" ")
+            .replace("( ", "(")
+            .replace(" )", ")")
+            .replace(",)", ")")
+            .replace(" ,", ",")
+    }
+
+    fn struct_signature(&self, start: usize, end: usize) -> String {
+        let lines = self.source_lines(start, end);
+        let last = lines.len().saturating_sub(1);
+        lines
+            .iter()
+            .enumerate()
+            .map(|(index, line)| {
+                if index == 0 || (index == last && line.starts_with('}')) {
+                    line.clone()
+                } else {
+                    format!("    {}", line)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn parse_file(&mut self) -> Result<Vec<ParsedModule>, ParseError> {
+        let mut modules = vec![];
+        let mut address = String::new();
+        let mut address_depth = None;
+        let mut depth = 0;
+        while let Some(token) = self.peek() {
+            match token.text {
+                "#" => {
+                    if self.skip_attributes() {
+                        self.take_doc();
+                        self.skip_module_or_item();
+                    }
+                }
+                "address" if depth == 0 => {
+                    // legacy syntax: address 0x1 { module vector { ... } }
+                    address = self.peek_text(1).to_string();
+                    self.position += 2;
+                    if self.peek_text(0) == "{" {
+                        self.position += 1;
+                        depth += 1;
+                        address_depth = Some(depth);
+                    }
+                }
+                "module" => {
+                    let doc = self.preceding_doc();
+                    if let Some(module) = self.parse_module(&address, doc)? {
+                        modules.push(module);
+                    }
+                }
+                "spec" | "script" => {
+                    self.position += 1;
+                    self.skip_item();
+                }
+                "}" => {
+                    self.position += 1;
+                    if address_depth == Some(depth) {
+                        address.clear();
+                        address_depth = None;
+                    }
+                    depth -= 1;
+                }
+                _ => self.position += 1,
+            }
+        }
+        Ok(modules)
+    }
+
+    fn skip_module_or_item(&mut self) {
+        while let Some(token) = self.peek() {
+            if token.text == "{" || token.text == ";" {
+                break;
+            }
+            self.position += 1;
+        }
+        self.skip_item();
+    }
+
+    fn preceding_doc(&self) -> String {
+        let mut lines = vec![];
+        let mut index = self.position;
+        while index > 0 && self.tokens[index - 1].kind == TokenKind::Doc {
+            index -= 1;
+            lines.insert(0, self.tokens[index].text);
+        }
+        lines.join("\n")
+    }
+
+    fn parse_module(
+        &mut self,
+        address: &str,
+        doc: String,
+    ) -> Result<Option<ParsedModule>, ParseError> {
+        // module 0x1::vector { ... }, module vector { ... } inside an address block
+        // or module 0x1::vector; for the rest of the file
+        self.position += 1;
+        let mut module = ParsedModule {
+            doc,
+            ..Default::default()
+        };
+        let first = match self.next() {
+            Some(token) => token.text.to_string(),
+            None => return Ok(None),
+        };
+        if self.peek_text(0) == ":" && self.peek_text(1) == ":" {
+            self.position += 2;
+            module.address = first;
+            module.name = match self.next() {
+                Some(token) => token.text.to_string(),
+                None => return Ok(None),
+            };
+        } else {
+            module.address = address.to_string();
+            module.name = first;
+        }
+        match self.next().map(|token| token.text) {
+            Some("{") | Some(";") => {}
+            _ => return Ok(None),
+        }
+
+        let mut pending_doc = String::new();
+        let mut is_test = false;
+        while let Some(token) = self.peek() {
+            match token.kind {
+                TokenKind::Doc => {
+                    pending_doc = self.take_doc();
+                    continue;
+                }
+                TokenKind::Punct if token.text == "}" => {
+                    self.position += 1;
+                    break;
+                }
+                TokenKind::Punct if token.text == "#" => {
+                    is_test |= self.skip_attributes();
+                    continue;
+                }
+                _ => {}
+            }
+            let doc = std::mem::take(&mut pending_doc);
+            let skip = std::mem::replace(&mut is_test, false);
+            match token.text {
+                "use" if skip => self.skip_item(),
+                "use" => self.parse_use(&mut module.used_modules),
+                "module" => break,
+                _ => self.parse_item(&mut module, doc, skip)?,
+            }
+        }
+        Ok(Some(module))
+    }
+
+    fn parse_use(&mut self, used_modules: &mut Vec<String>) {
+        let start = self.position + 1;
+        self.skip_item();
+        let mut path = String::new();
+        let mut previous_kind = &TokenKind::Punct;
+        let tokens = self
+            .tokens
+            .get(start..self.position.saturating_sub(1))
+            .unwrap_or(&[]);
+        for token in tokens {
+            if token.kind == TokenKind::Word && *previous_kind == TokenKind::Word {
+                path.push(' ');
+            }
+            path.push_str(token.text);
+            previous_kind = &token.kind;
+        }
+        // use std::vector; use std::vector::{Self, push_back}; use aptos_framework::{coin, account as acc};
+        // method aliases (use fun ...) don't import modules
+        if path.starts_with("fun ") {
+            return;
+        }
+        let mut segments = path.splitn(2, "::");
+        let address = segments.next().unwrap_or("");
+        let rest = segments.next().unwrap_or("");
+        let modules = match rest.strip_prefix('{') {
+            Some(group) => split_top_level(group.strip_suffix('}').unwrap_or(group)),
+            None => vec![rest],
+        };
+        for item in modules {
+            let name = item
+                .split("::")
+                .next()
+                .and_then(|name| name.split_whitespace().next())
+                .unwrap_or("");
+            if address.is_empty() || name.is_empty() || name == "Self" {
+                continue;
+            }
+            let used_module = format!("{}::{}", address, name);
+            if !used_modules.contains(&used_module) {
+                used_modules.push(used_module);
+            }
+        }
+    }
+
+    fn parse_item(
+        &mut self,
+        module: &mut ParsedModule,
+        doc: String,
+        is_test: bool,
+    ) -> Result<(), ParseError> {
+        let start = self.tokens[self.position].start;
+        let mut visibility = String::new();
+        let mut is_entry = false;
+        loop {
+            match self.peek_text(0) {
+                "public" => {
+                    visibility.push_str("public");
+                    self.position += 1;
+                    if self.peek_text(0) == "(" {
+                        let group_start = self.position;
+                        self.skip_group("(", ")");
+                        visibility.push_str(
+                            &self.tokens[group_start..self.position]
+                                .iter()
+                                .map(|token| token.text)
+                                .collect::<String>(),
+                        );
+                    }
+                }
+                "entry" => {
+                    is_entry = true;
+                    self.position += 1;
+                }
+                "native" | "inline" | "macro" => self.position += 1,
+                _ => break,
+            }
+        }
+
+        match self.peek_text(0) {
+            "fun" => {
+                let name = self.peek_text(1).to_string();
+                let end = self.item_header_end();
+                self.skip_item();
+                let is_documented = !visibility.is_empty() || is_entry;
+                if is_documented && !is_test {
+                    module.functions.push(ParsedFunction {
+                        name,
+                        visibility,
+                        is_entry,
+                        signature: self.function_signature(start, end),
+                        doc,
+                    });
+                }
+            }
+            "struct" => {
+                let name = self.peek_text(1).to_string();
+                // fields are part of the documented signature
+                while let Some(token) = self.peek() {
+                    if token.text == "{" || token.text == ";" {
+                        break;
+                    }
+                    self.position += 1;
+                }
+                let end = if let Some(token) = self.peek().filter(|token| token.text == ";") {
+                    // the `;` of structs without fields isn't part of the signature
+                    let end = token.start;
+                    self.position += 1;
+                    end
+                } else if self.peek().is_some() && self.skip_group("{", "}") {
+                    // the closing brace of the fields
+                    self.tokens[self.position - 1].end
+                } else {
+                    return Err(ParseError::UnexpectedEnd(format!("struct {}", name)));
+                };
+                if !is_test {
+                    module.structs.push(ParsedStruct {
+                        name,
+                        signature: self.struct_signature(start, end),
+                        doc,
+                    });
+                }
+            }
+            _ => self.skip_item(),
+        }
+        Ok(())
+    }
+
+    // End of a function signature: before the body or the `;` of native functions
+    fn item_header_end(&self) -> usize {
+        let mut index = self.position;
+        while let Some(token) = self.tokens.get(index) {
+            if token.text == "{" || token.text == ";" {
+                return token.start;
+            }
+            index += 1;
+        }
+        self.source.len()
+    }
+}
+
+// Splits `a, b::{c, d}` on the commas outside of braces
+fn split_top_level(group: &str) -> Vec<&str> {
+    let mut items = vec![];
+    let mut depth = 0;
+    let mut item_start = 0;
+    for (index, c) in group.char_indices() {
+        match c {
+            '{' => depth += 1,
+            '}' => depth -= 1,
+            ',' if depth == 0 => {
+                items.push(group[item_start..index].trim());
+                item_start = index + 1;
+            }
+            _ => {}
+        }
+    }
+    items.push(group[item_start..].trim());
+    items
+}
+
+/// Extracts the modules declared in a `.move` file, with their structs, public and entry
+/// functions. Test only items are left out.
+pub fn parse_move_source(source: &str) -> Result<Vec<ParsedModule>, ParseError> {
+    let mut parser = Parser {
+        source,
+        tokens: tokenize(source),
+        position: 0,
+    };
+    parser.parse_file()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const COIN_SOURCE: &str = r#"
+/// A simple coin.
+module 0xCAFE::coin {
+    use std::signer;
+    use std::vector::{Self, push_back};
+    use aptos_framework::{account, event as events, assets::{Self, Asset}};
+    #[test_only]
+    use std::debug;
+
+    /// Error when the balance is too low.
+    const EINSUFFICIENT_BALANCE: u64 = 1;
+
+    /// The coin, stored under the owner account.
+    struct Coin<phantom T> has key, store {
+        /// Amount of coins.
+        value: u64,
+    }
+
+    struct Marker has drop;
+
+    /// Mints `amount` coins.
+    /// Only callable by the admin.
+    public fun mint<T>(
+        admin: &signer,
+        amount: u64,
+    ): Coin<T> acquires Coin {
+        let s = b"{ not a brace";
+        if (amount > 0) { abort 1 };
+        Coin { value: amount }
+    }
+
+    public(friend) fun burn<T>(coin: Coin<T>) {
+        let Coin { value: _ } = coin;
+    }
+
+    public entry fun transfer(from: &signer, to: address, amount: u64) {}
+
+    entry fun claim(account: &signer) {}
+
+    fun private_helper(): u64 { 1 }
+
+    native public fun native_value(): u64;
+
+    #[test_only]
+    public fun init_for_test() {}
+
+    #[test]
+    fun test_mint() {}
+
+    spec mint {
+        aborts_if false;
+    }
+}
+"#;
+
+    #[test]
+    fn parse_move_source_extracts_module_items() {
+        let modules = parse_move_source(COIN_SOURCE).unwrap();
+        assert_eq!(modules.len(), 1);
+        let module = &modules[0];
+        assert_eq!(module.address, "0xCAFE");
+        assert_eq!(module.name, "coin");
+        assert_eq!(module.doc, "A simple coin.");
+        assert_eq!(
+            module.used_modules,
+            vec![
+                "std::signer",
+                "std::vector",
+                "aptos_framework::account",
+                "aptos_framework::event",
+                "aptos_framework::assets"
+            ]
+        );
+
+        let struct_names = module
+            .structs
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(struct_names, vec!["Coin", "Marker"]);
+        assert_eq!(
+            module.structs[0].signature,
+            "struct Coin<phantom T> has key, store {\n    value: u64,\n}"
+        );
+        assert_eq!(
+            module.structs[0].doc,
+            "The coin, stored under the owner account."
+        );
+        assert_eq!(module.structs[1].signature, "struct Marker has drop");
+
+        let function_names = module
+            .functions
+            .iter()
+            .map(|item| item.name.as_str())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            function_names,
+            vec!["mint", "burn", "transfer", "claim", "native_value"]
+        );
+        let mint = &module.functions[0];
+        assert_eq!(mint.visibility, "public");
+        assert!(!mint.is_entry);
+        assert_eq!(
+            mint.signature,
+            "public fun mint<T>(admin: &signer, amount: u64): Coin<T> acquires Coin"
+        );
+        assert_eq!(
+            mint.doc,
+            "Mints `amount` coins.\nOnly callable by the admin."
+        );
+        assert_eq!(module.functions[1].visibility, "public(friend)");
+        assert!(module.functions[2].is_entry);
+        assert_eq!(module.functions[3].visibility, "");
+        assert!(module.functions[3].is_entry);
+        assert_eq!(
+            module.functions[4].signature,
+            "native public fun native_value(): u64"
+        );
+    }
+
+    #[test]
+    fn parse_move_source_supports_address_blocks() {
+        let source = "address 0x1 {\nmodule vector {\npublic fun empty<T>(): vector<T> { }\n}\nmodule option {\n}\n}\nmodule 0x2::other {}";
+        let modules = parse_move_source(source).unwrap();
+        let names = modules
+            .iter()
+            .map(|module| format!("{}::{}", module.address, module.name))
+            .collect::<Vec<String>>();
+        assert_eq!(names, vec!["0x1::vector", "0x1::option", "0x2::other"]);
+        assert_eq!(modules[0].functions[0].name, "empty");
+    }
+
+    #[test]
+    fn parse_move_source_skips_test_only_modules() {
+        let source = "#[test_only]\nmodule 0x1::coin_tests {\npublic fun helper() {}\n}\nmodule 0x1::coin {}";
+        let modules = parse_move_source(source).unwrap();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].name, "coin");
+    }
+
+    #[test]
+    fn parse_move_source_rejects_truncated_structs() {
+        for source in [
+            "module 0x1::m { struct Foo",
+            "module 0x1::m { struct Foo has key",
+            "module 0x1::m { struct Foo has key { value: u64",
+        ] {
+            assert_eq!(
+                parse_move_source(source),
+                Err(ParseError::UnexpectedEnd("struct Foo".to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn parse_move_source_stops_at_the_end_of_truncated_sources() {
+        for source in [
+            "module 0x1::m { #[test",
+            "module 0x1::m { use",
+            "module 0x1::m { public(",
+            "module 0x1::m { public fun f(",
+            "module 0x1::",
+        ] {
+            assert!(parse_move_source(source).is_ok(), "{}", source);
+        }
+    }
+
+    #[test]
+    fn parse_move_source_skips_stray_hashes() {
+        let modules = parse_move_source("module 0x1::m { # }").unwrap();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].name, "m");
+
+        let modules = parse_move_source("# stray\nmodule 0x1::m { # public fun f() {} }").unwrap();
+        assert_eq!(modules.len(), 1);
+        assert_eq!(modules[0].name, "m");
+
+        assert_eq!(parse_move_source("module 0x1::m {} #").unwrap().len(), 1);
+        assert!(parse_move_source("module 0x1::m { #").is_ok());
+    }
+}
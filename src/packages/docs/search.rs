@@ -0,0 +1,298 @@
+//! Search of modules and functions by name, e.g `coin`, `coin::transfer` or `0x1::coin::transfer`.
+
+use diesel::dsl::sql;
+use diesel::prelude::*;
+use diesel::sql_types::{Nullable, Text};
+use jelly::serde::Serialize;
+use jelly::DieselPgConnection;
+use jelly::Result;
+
+use crate::schema::move_functions;
+use crate::schema::move_modules;
+use crate::schema::package_versions;
+use crate::schema::packages;
+use crate::sql::lower;
+
+/// Maximum number of symbols returned by a search.
+pub const MAX_SYMBOL_RESULTS: usize = 100;
+// Rows loaded per kind of symbol before grouping the versions of a package together
+const MAX_SYMBOL_ROWS: i64 = 1000;
+
+/// A module, or a function if `function_name` is set, with the versions of the package
+/// defining it, latest first.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+pub struct SymbolSearchResult {
+    pub package_name: String,
+    pub package_slug: String,
+    pub module_address: String,
+    pub module_name: String,
+    pub function_name: Option<String>,
+    pub signature: Option<String>,
+    pub versions: Vec<String>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct SymbolQuery {
+    address: Option<String>,
+    module_name: Option<String>,
+    function_name: Option<String>,
+}
+
+// `a` matches modules and functions named `a`, `a::b` modules `b` of address `a` and
+// functions `b` of modules `a`, `a::b::c` functions `c` of module `a::b`
+fn parse_symbol_queries(query: &str) -> Vec<SymbolQuery> {
+    let segments = query
+        .trim()
+        .split("::")
+        .map(|segment| segment.trim().to_lowercase())
+        .collect::<Vec<String>>();
+    if segments.iter().any(|segment| segment.is_empty()) {
+        return vec![];
+    }
+    match segments.as_slice() {
+        [name] => vec![
+            SymbolQuery {
+                module_name: Some(name.clone()),
+                ..Default::default()
+            },
+            SymbolQuery {
+                function_name: Some(name.clone()),
+                ..Default::default()
+            },
+        ],
+        [first, second] => vec![
+            SymbolQuery {
+                address: Some(first.clone()),
+                module_name: Some(second.clone()),
+                ..Default::default()
+            },
+            SymbolQuery {
+                module_name: Some(first.clone()),
+                function_name: Some(second.clone()),
+                ..Default::default()
+            },
+        ],
+        [address, module_name, function_name] => vec![SymbolQuery {
+            address: Some(address.clone()),
+            module_name: Some(module_name.clone()),
+            function_name: Some(function_name.clone()),
+        }],
+        _ => vec![],
+    }
+}
+
+fn escape_like(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('%', "\\%")
+        .replace('_', "\\_")
+}
+
+type SymbolRow = (
+    String,
+    String,
+    String,
+    String,
+    String,
+    Option<String>,
+    Option<String>,
+);
+
+// The last segment of the query matches by prefix, to find symbols while typing
+fn load_module_rows(
+    symbol_query: &SymbolQuery,
+    module_name: &str,
+    package: Option<&str>,
+    conn: &DieselPgConnection,
+) -> Result<Vec<SymbolRow>> {
+    let mut modules = move_modules::table
+        .inner_join(package_versions::table.inner_join(packages::table))
+        .filter(packages::archived_at.is_null())
+        .filter(packages::deletion_scheduled_at.is_null())
+        .into_boxed();
+    if let Some(address) = &symbol_query.address {
+        modules = modules.filter(lower(move_modules::address).eq(address.clone()));
+    }
+    if let Some(package) = package {
+        modules = modules.filter(
+            packages::slug
+                .eq(package.to_string())
+                .or(lower(packages::name).eq(package.to_lowercase())),
+        );
+    }
+    let rows = modules
+        .filter(lower(move_modules::name).like(format!("{}%", escape_like(module_name))))
+        .select((
+            packages::name,
+            packages::slug,
+            package_versions::version,
+            move_modules::address,
+            move_modules::name,
+            sql::<Nullable<Text>>("NULL"),
+            sql::<Nullable<Text>>("NULL"),
+        ))
+        .order_by((
+            lower(move_modules::name).eq(module_name.to_string()).desc(),
+            packages::name,
+            package_versions::id.desc(),
+        ))
+        .limit(MAX_SYMBOL_ROWS)
+        .load::<SymbolRow>(conn)?;
+    Ok(rows)
+}
+
+fn load_function_rows(
+    symbol_query: &SymbolQuery,
+    function_name: &str,
+    package: Option<&str>,
+    conn: &DieselPgConnection,
+) -> Result<Vec<SymbolRow>> {
+    let mut functions = move_functions::table
+        .inner_join(
+            move_modules::table.inner_join(package_versions::table.inner_join(packages::table)),
+        )
+        .filter(packages::archived_at.is_null())
+        .filter(packages::deletion_scheduled_at.is_null())
+        .into_boxed();
+    if let Some(address) = &symbol_query.address {
+        functions = functions.filter(lower(move_modules::address).eq(address.clone()));
+    }
+    if let Some(module_name) = &symbol_query.module_name {
+        functions = functions.filter(lower(move_modules::name).eq(module_name.clone()));
+    }
+    if let Some(package) = package {
+        functions = functions.filter(
+            packages::slug
+                .eq(package.to_string())
+                .or(lower(packages::name).eq(package.to_lowercase())),
+        );
+    }
+    let rows = functions
+        .filter(lower(move_functions::name).like(format!("{}%", escape_like(function_name))))
+        .select((
+            packages::name,
+            packages::slug,
+            package_versions::version,
+            move_modules::address,
+            move_modules::name,
+            move_functions::name.nullable(),
+            move_functions::signature.nullable(),
+        ))
+        .order_by((
+            lower(move_functions::name)
+                .eq(function_name.to_string())
+                .desc(),
+            packages::name,
+            package_versions::id.desc(),
+        ))
+        .limit(MAX_SYMBOL_ROWS)
+        .load::<SymbolRow>(conn)?;
+    Ok(rows)
+}
+
+/// Finds the modules and public functions matching `query` in every published version,
+/// optionally restricted to the package with the given slug or name. An empty package
+/// means every package. Archived packages and packages pending deletion are left out.
+pub fn search_symbols(
+    query: &str,
+    package: Option<&str>,
+    conn: &DieselPgConnection,
+) -> Result<Vec<SymbolSearchResult>> {
+    let package = package
+        .map(|package| package.trim())
+        .filter(|package| !package.is_empty());
+    let mut results: Vec<SymbolSearchResult> = vec![];
+    for symbol_query in parse_symbol_queries(query) {
+        let rows = match (&symbol_query.module_name, &symbol_query.function_name) {
+            (_, Some(function_name)) => {
+                load_function_rows(&symbol_query, function_name, package, conn)?
+            }
+            (Some(module_name), None) => {
+                load_module_rows(&symbol_query, module_name, package, conn)?
+            }
+            (None, None) => vec![],
+        };
+        for row in rows {
+            let (
+                package_name,
+                package_slug,
+                version,
+                address,
+                module_name,
+                function_name,
+                signature,
+            ) = row;
+            // rows of a symbol are sorted latest version first, the signature of the
+            // latest version is shown
+            let existing = results.iter_mut().find(|result| {
+                result.package_slug == package_slug
+                    && result.module_address == address
+                    && result.module_name == module_name
+                    && result.function_name == function_name
+            });
+            match existing {
+                Some(result) => {
+                    if !result.versions.contains(&version) {
+                        result.versions.push(version);
+                    }
+                }
+                None if results.len() < MAX_SYMBOL_RESULTS => results.push(SymbolSearchResult {
+                    package_name,
+                    package_slug,
+                    module_address: address,
+                    module_name,
+                    function_name,
+                    signature,
+                    versions: vec![version],
+                }),
+                None => {}
+            }
+        }
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_symbol_queries_works() {
+        assert_eq!(
+            parse_symbol_queries("Coin"),
+            vec![
+                SymbolQuery {
+                    module_name: Some("coin".to_string()),
+                    ..Default::default()
+                },
+                SymbolQuery {
+                    function_name: Some("coin".to_string()),
+                    ..Default::default()
+                },
+            ]
+        );
+        assert_eq!(
+            parse_symbol_queries("coin::transfer")[1],
+            SymbolQuery {
+                module_name: Some("coin".to_string()),
+                function_name: Some("transfer".to_string()),
+                ..Default::default()
+            }
+        );
+        assert_eq!(
+            parse_symbol_queries("0x1::coin::transfer"),
+            vec![SymbolQuery {
+                address: Some("0x1".to_string()),
+                module_name: Some("coin".to_string()),
+                function_name: Some("transfer".to_string()),
+            }]
+        );
+        assert!(parse_symbol_queries("coin::").is_empty());
+        assert!(parse_symbol_queries("a::b::c::d").is_empty());
+    }
+
+    #[test]
+    fn escape_like_escapes_wildcards() {
+        assert_eq!(escape_like("coin_%"), "coin\\_\\%");
+    }
+}
@@ -0,0 +1,242 @@
+use crate::github_service::GithubTreeEntry;
+use crate::packages::docs::*;
+use crate::packages::{Package, PackageVersionSort};
+use crate::test::util::create_package;
+use crate::test::{DatabaseTestContext, DB_POOL};
+
+const COIN_SOURCE: &str = "/// Coins.\nmodule 0xCAFE::coin {\n    use 0x1::vector;\n    use 0xCAFE::unknown;\n\n    /// A coin.\n    struct Coin has store { value: u64 }\n\n    /// Mints a coin.\n    public fun mint(value: u64): Coin { Coin { value } }\n\n    fun helper() {}\n}\n";
+const VECTOR_SOURCE: &str =
+    "module 0x1::vector {\n    native public fun empty<Element>(): vector<Element>;\n}\n";
+
+fn create_version(package_name: &str, repo_url: &str, rev: &str) -> PackageVersion {
+    let package = create_package(package_name, repo_url, rev, None);
+    PackageVersion::from_package_id(package.id, &PackageVersionSort::Latest, &DB_POOL).unwrap()[0]
+        .clone()
+}
+
+fn tree_entry(path: &str) -> GithubTreeEntry {
+    GithubTreeEntry {
+        path: path.to_string(),
+        entry_type: "blob".to_string(),
+        size: 100,
+    }
+}
+
+fn mock_github_service() -> GithubService {
+    let mut mock_github_service = GithubService::new();
+    mock_github_service
+        .expect_fetch_source_tree()
+        .returning(|repo_url, _, _| match repo_url {
+            "https://github.com/owner/coin" => Ok(vec![
+                tree_entry("Move.toml"),
+                tree_entry("sources/coin.move"),
+                tree_entry("tests/coin_tests.move"),
+            ]),
+            _ => Ok(vec![tree_entry("sources/vector.move")]),
+        });
+    mock_github_service
+        .expect_fetch_source_file()
+        .returning(|_, _, path| match path {
+            "packages/coin/sources/coin.move" => Ok(Some(COIN_SOURCE.to_string())),
+            "sources/vector.move" => Ok(Some(VECTOR_SOURCE.to_string())),
+            _ => Ok(None),
+        });
+    mock_github_service
+}
+
+#[actix_rt::test]
+async fn generate_version_docs_stores_modules_and_resolves_dependencies() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let conn = DB_POOL.get().unwrap();
+    let coin_version = create_version(
+        "coin",
+        "https://github.com/owner/coin/tree/main/packages/coin",
+        "rev",
+    );
+    let std_version = create_version("std", "https://github.com/owner/std", "rev");
+    let github_service = mock_github_service();
+
+    let modules_count = generate_version_docs(
+        &coin_version,
+        "https://github.com/owner/coin/tree/main/packages/coin",
+        &github_service,
+        &conn,
+    )
+    .unwrap();
+    assert_eq!(modules_count, 1);
+    generate_version_docs(
+        &std_version,
+        "https://github.com/owner/std",
+        &github_service,
+        &conn,
+    )
+    .unwrap();
+
+    let modules = MoveModule::get_by_package_version(coin_version.id, &conn).unwrap();
+    assert_eq!(modules.len(), 1);
+    let module = &modules[0];
+    assert_eq!(module.name, "coin");
+    assert_eq!(module.doc, "Coins.");
+    assert_eq!(module.file_path, "sources/coin.move");
+
+    // the source tab lists the cached files
+    let files = PackageVersionFile::get_by_package_version(coin_version.id, &conn).unwrap();
+    assert_eq!(files.len(), 3);
+    assert_eq!(files[1].path, "sources/coin.move");
+    assert_eq!(files[1].content.as_deref(), Some(COIN_SOURCE));
+
+    let structs = module.get_structs(&conn).unwrap();
+    assert_eq!(structs.len(), 1);
+    assert_eq!(structs[0].doc, "A coin.");
+    let functions = module.get_functions(&conn).unwrap();
+    assert_eq!(functions.len(), 1);
+    assert_eq!(functions[0].signature, "public fun mint(value: u64): Coin");
+
+    let dependencies = module.get_dependencies(&conn).unwrap();
+    assert_eq!(
+        dependencies,
+        vec![
+            ModuleDependency {
+                path: "0x1::vector".to_string(),
+                address: "0x1".to_string(),
+                name: "vector".to_string(),
+                package_slug: Some("std".to_string()),
+                version: Some("0.1.0".to_string()),
+            },
+            ModuleDependency {
+                path: "0xCAFE::unknown".to_string(),
+                address: "0xCAFE".to_string(),
+                name: "unknown".to_string(),
+                package_slug: None,
+                version: None,
+            },
+        ]
+    );
+    assert!(get_versions_without_docs(10, &conn).unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn generate_version_docs_replaces_previous_docs() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let conn = DB_POOL.get().unwrap();
+    let version = create_version("std", "https://github.com/owner/std", "rev");
+    let github_service = mock_github_service();
+
+    generate_version_docs(
+        &version,
+        "https://github.com/owner/std",
+        &github_service,
+        &conn,
+    )
+    .unwrap();
+    generate_version_docs(
+        &version,
+        "https://github.com/owner/std",
+        &github_service,
+        &conn,
+    )
+    .unwrap();
+
+    let modules = MoveModule::get_by_package_version(version.id, &conn).unwrap();
+    assert_eq!(modules.len(), 1);
+    assert!(
+        MoveModule::get_by_package_version_and_path(version.id, "0x1", "vector", &conn)
+            .unwrap()
+            .is_some()
+    );
+    assert!(
+        MoveModule::get_by_package_version_and_path(version.id, "0x2", "vector", &conn)
+            .unwrap()
+            .is_none()
+    );
+}
+
+#[actix_rt::test]
+async fn generate_version_docs_marks_versions_without_rev() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let conn = DB_POOL.get().unwrap();
+    let version = create_version("no-rev", "https://github.com/owner/no-rev", "");
+
+    let pending = get_versions_without_docs(10, &conn).unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].1, "https://github.com/owner/no-rev");
+
+    let modules_count = generate_version_docs(
+        &version,
+        "https://github.com/owner/no-rev",
+        &GithubService::new(),
+        &conn,
+    )
+    .unwrap();
+    assert_eq!(modules_count, 0);
+    assert!(get_versions_without_docs(10, &conn).unwrap().is_empty());
+}
+
+#[actix_rt::test]
+async fn search_symbols_finds_modules_and_functions() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let conn = DB_POOL.get().unwrap();
+    let coin_version = create_version(
+        "coin",
+        "https://github.com/owner/coin/tree/main/packages/coin",
+        "rev",
+    );
+    let std_version = create_version("std", "https://github.com/owner/std", "rev");
+    let github_service = mock_github_service();
+    for (version, repo_url) in [
+        (
+            &coin_version,
+            "https://github.com/owner/coin/tree/main/packages/coin",
+        ),
+        (&std_version, "https://github.com/owner/std"),
+    ] {
+        generate_version_docs(version, repo_url, &github_service, &conn).unwrap();
+    }
+
+    let results = search::search_symbols("coin", None, &conn).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].package_slug, "coin");
+    assert_eq!(results[0].module_name, "coin");
+    assert_eq!(results[0].function_name, None);
+    assert_eq!(results[0].versions, vec!["0.1.0"]);
+
+    let results = search::search_symbols("coin::mint", None, &conn).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].function_name, Some("mint".to_string()));
+    assert_eq!(
+        results[0].signature,
+        Some("public fun mint(value: u64): Coin".to_string())
+    );
+
+    let results = search::search_symbols("0x1::vector::emp", None, &conn).unwrap();
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].package_slug, "std");
+
+    assert!(search::search_symbols("vector", Some("coin"), &conn)
+        .unwrap()
+        .is_empty());
+    assert_eq!(
+        search::search_symbols("vector", Some("std"), &conn)
+            .unwrap()
+            .len(),
+        1
+    );
+
+    let std_package = Package::get_by_slug("std", &conn).unwrap();
+    let std_package = std_package.archive(&conn).unwrap();
+    assert!(search::search_symbols("vector", None, &conn)
+        .unwrap()
+        .is_empty());
+    std_package
+        .unarchive(&conn)
+        .unwrap()
+        .schedule_deletion(&conn)
+        .unwrap();
+    assert!(search::search_symbols("vector", None, &conn)
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,202 @@
+use diesel::Connection;
+use jelly::chrono::Utc;
+use jelly::Result;
+use jelly::{DieselPgConnection, DieselPgPool};
+use mockall_double::double;
+
+#[double]
+use crate::github_service::GithubService;
+use crate::packages::docs;
+use crate::packages::models::audit_log::{PackageAuditAction, PackageAuditLog};
+use crate::packages::platform;
+use crate::packages::quality::compute_quality;
+use crate::packages::{Package, PackageVersion};
+use crate::utils::presenter;
+
+pub mod scheduled;
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DocsGenerationStats {
+    pub generated: i32,
+    pub modules: i32,
+    pub failed: i32,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct QualityScoreStats {
+    pub scored: i32,
+    pub changed: i32,
+    pub failed: i32,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct PackagePurgeStats {
+    pub purged: i32,
+    pub failed: i32,
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct MetadataRefreshStats {
+    pub refreshed: i32,
+    pub archived: i32,
+    pub missing: i32,
+    pub failed: i32,
+    pub platforms_detected: i32,
+}
+
+pub fn refresh_packages_metadata(
+    batch_size: i64,
+    github_service: &GithubService,
+    pool: &DieselPgPool,
+) -> Result<MetadataRefreshStats> {
+    let mut stats = MetadataRefreshStats::default();
+    let conn = pool.get()?;
+    PackageVersion::normalize_unresolved_licenses(&conn)?;
+    stats.platforms_detected = detect_pending_platforms(batch_size, github_service, &conn)?;
+    for package in Package::get_for_metadata_refresh(batch_size, pool)? {
+        let repo_info = match github_service.fetch_repo_metadata(&package.repository_url) {
+            Ok(repo_info) => repo_info,
+            Err(e) => {
+                warn!(
+                    "Cannot refresh metadata of package {}. error: {:?}",
+                    package.slug, e
+                );
+                // Keep the current metadata, but move the package to the back of the queue
+                // so that a broken repository url doesn't block the others
+                package.touch_metadata_refreshed_at(&conn)?;
+                stats.failed += 1;
+                continue;
+            }
+        };
+        match &repo_info {
+            Some(info) if info.archived => stats.archived += 1,
+            Some(_) => {}
+            None => stats.missing += 1,
+        }
+        package.refresh_metadata(repo_info.as_ref(), &conn)?;
+        stats.refreshed += 1;
+    }
+    Ok(stats)
+}
+
+/// Classifies the versions crawled before platform detection from the Move.toml at their rev.
+/// Each version is only looked at once. Returns the number of versions with a Move.toml.
+fn detect_pending_platforms(
+    batch_size: i64,
+    github_service: &GithubService,
+    conn: &DieselPgConnection,
+) -> Result<i32> {
+    let mut detected = 0;
+    for (package_version, repository_url) in
+        PackageVersion::get_without_detected_platform(batch_size, conn)?
+    {
+        let rev = package_version.rev.as_deref().unwrap_or("");
+        let (repo_url, subdir) = presenter::make_package_repo_and_subdir(&repository_url);
+        let manifest_path = if subdir.is_empty() {
+            "Move.toml".to_string()
+        } else {
+            format!("{}/Move.toml", subdir)
+        };
+        // versions created without a rev can't be pinned to their manifest
+        let move_toml = if rev.is_empty() {
+            None
+        } else {
+            match github_service.fetch_source_file(&repo_url, rev, &manifest_path) {
+                Ok(move_toml) => move_toml,
+                // like the docs generation, a broken repository doesn't block the others
+                Err(e) => {
+                    warn!(
+                        "Cannot detect the platform of package version {}. error: {:?}",
+                        package_version.id, e
+                    );
+                    None
+                }
+            }
+        };
+        match move_toml {
+            Some(move_toml) => {
+                package_version.update_platform(
+                    platform::detect_platform(&move_toml),
+                    &platform::parse_dependencies(&move_toml),
+                    conn,
+                )?;
+                detected += 1;
+            }
+            None => package_version.mark_platform_detected(conn)?,
+        }
+    }
+    Ok(detected)
+}
+
+pub fn generate_pending_docs(
+    batch_size: i64,
+    github_service: &GithubService,
+    pool: &DieselPgPool,
+) -> Result<DocsGenerationStats> {
+    let mut stats = DocsGenerationStats::default();
+    let conn = pool.get()?;
+    for (package_version, repository_url) in docs::get_versions_without_docs(batch_size, &conn)? {
+        match docs::generate_version_docs(&package_version, &repository_url, github_service, &conn)
+        {
+            Ok(modules) => {
+                stats.generated += 1;
+                stats.modules += modules as i32;
+            }
+            Err(e) => {
+                warn!(
+                    "Cannot generate docs of package version {}. error: {:?}",
+                    package_version.id, e
+                );
+                docs::mark_docs_generated(package_version.id, &conn)?;
+                stats.failed += 1;
+            }
+        }
+    }
+    Ok(stats)
+}
+
+pub fn refresh_quality_scores(pool: &DieselPgPool) -> Result<QualityScoreStats> {
+    let mut stats = QualityScoreStats::default();
+    let conn = pool.get()?;
+    let scored_at = Utc::now();
+    for package in Package::get_all(pool)? {
+        let score = match package.quality_inputs(&conn) {
+            Ok(inputs) => compute_quality(&inputs, scored_at),
+            Err(e) => {
+                warn!(
+                    "Cannot compute quality score of package {}. error: {:?}",
+                    package.slug, e
+                );
+                stats.failed += 1;
+                continue;
+            }
+        };
+        if package.update_quality_score(score.total, &conn)? {
+            stats.changed += 1;
+        }
+        stats.scored += 1;
+    }
+    Ok(stats)
+}
+
+pub fn purge_deleted_packages(pool: &DieselPgPool) -> Result<PackagePurgeStats> {
+    let mut stats = PackagePurgeStats::default();
+    let conn = pool.get()?;
+    for package in Package::get_due_for_purge(&conn)? {
+        let result = conn.transaction(|| -> Result<()> {
+            package.purge(&conn)?;
+            PackageAuditLog::create(&package, None, PackageAuditAction::Purge, None, &conn)?;
+            Ok(())
+        });
+        match result {
+            Ok(()) => stats.purged += 1,
+            Err(e) => {
+                warn!("Cannot purge package {}. error: {:?}", package.slug, e);
+                stats.failed += 1;
+            }
+        }
+    }
+    Ok(stats)
+}
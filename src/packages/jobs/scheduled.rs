@@ -0,0 +1,107 @@
+use std::env;
+use std::future::Future;
+use std::pin::Pin;
+
+use jelly::anyhow::{anyhow, Error};
+use jelly::jobs::{Job, JobState};
+use jelly::serde::{Deserialize, Serialize};
+use mockall_double::double;
+
+#[double]
+use crate::github_service::GithubService;
+use crate::packages::jobs::{
+    generate_pending_docs, purge_deleted_packages, refresh_packages_metadata,
+    refresh_quality_scores,
+};
+
+/// Refreshes the metadata of the `METADATA_REFRESH_BATCH_SIZE` most out of date packages,
+/// so that every package is revisited in turn.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshPackagesMetadata;
+
+impl Job for RefreshPackagesMetadata {
+    type State = JobState;
+    type Future = Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
+
+    const NAME: &'static str = "RefreshPackagesMetadata";
+
+    fn run(self, state: JobState) -> Self::Future {
+        Box::pin(async move {
+            let batch_size = env::var("METADATA_REFRESH_BATCH_SIZE")
+                .ok()
+                .and_then(|size| size.parse::<i64>().ok())
+                .unwrap_or(100);
+            let gh_service = GithubService::new();
+            let stats = refresh_packages_metadata(batch_size, &gh_service, &state.pool)
+                .map_err(|e| anyhow!("Error refreshing packages metadata: {:?}", e))?;
+            info!("Refreshed packages metadata: {:?}", stats);
+            Ok(())
+        })
+    }
+}
+
+/// Generates the API docs of up to `DOCS_GENERATION_BATCH_SIZE` versions without docs.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GeneratePackagesDocs;
+
+impl Job for GeneratePackagesDocs {
+    type State = JobState;
+    type Future = Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
+
+    const NAME: &'static str = "GeneratePackagesDocs";
+
+    fn run(self, state: JobState) -> Self::Future {
+        Box::pin(async move {
+            let batch_size = env::var("DOCS_GENERATION_BATCH_SIZE")
+                .ok()
+                .and_then(|size| size.parse::<i64>().ok())
+                .unwrap_or(50);
+            let gh_service = GithubService::new();
+            let stats = generate_pending_docs(batch_size, &gh_service, &state.pool)
+                .map_err(|e| anyhow!("Error generating package docs: {:?}", e))?;
+            info!("Generated package docs: {:?}", stats);
+            Ok(())
+        })
+    }
+}
+
+/// Recomputes the quality score of every package, as downloads, stars and dependents
+/// change without the package being updated.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RefreshQualityScores;
+
+impl Job for RefreshQualityScores {
+    type State = JobState;
+    type Future = Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
+
+    const NAME: &'static str = "RefreshQualityScores";
+
+    fn run(self, state: JobState) -> Self::Future {
+        Box::pin(async move {
+            let stats = refresh_quality_scores(&state.pool)
+                .map_err(|e| anyhow!("Error refreshing package quality scores: {:?}", e))?;
+            info!("Refreshed package quality scores: {:?}", stats);
+            Ok(())
+        })
+    }
+}
+
+/// Deletes the packages whose deletion grace period is over.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PurgeDeletedPackages;
+
+impl Job for PurgeDeletedPackages {
+    type State = JobState;
+    type Future = Pin<Box<dyn Future<Output = Result<(), Error>> + Send>>;
+
+    const NAME: &'static str = "PurgeDeletedPackages";
+
+    fn run(self, state: JobState) -> Self::Future {
+        Box::pin(async move {
+            let stats = purge_deleted_packages(&state.pool)
+                .map_err(|e| anyhow!("Error purging deleted packages: {:?}", e))?;
+            info!("Purged deleted packages: {:?}", stats);
+            Ok(())
+        })
+    }
+}
@@ -0,0 +1,306 @@
+use crate::github_service::{GithubLicenseInfo, GithubRepoInfo};
+use crate::packages::jobs::*;
+use crate::packages::models::audit_log::PackageAuditLog;
+use crate::packages::models::{
+    REPOSITORY_STATUS_ACTIVE, REPOSITORY_STATUS_ARCHIVED, REPOSITORY_STATUS_MISSING,
+};
+use crate::packages::platform::{ManifestDependency, Platform};
+use crate::packages::{PackageVersion, PackageVersionSort};
+use crate::schema::{package_versions, packages};
+use crate::test::util::create_package;
+use crate::test::{DatabaseTestContext, DB_POOL};
+use diesel::prelude::*;
+use jelly::chrono::{Duration, Utc};
+use jelly::error::Error;
+
+const COIN_MANIFEST: &str = "[package]\nname = \"coin\"\nversion = \"0.1.0\"\n\n[dependencies]\nAptosFramework = { git = \"https://github.com/aptos-labs/aptos-core.git\", rev = \"main\" }\n";
+
+// A package with the metadata it was crawled with
+fn create_crawled_package(package_name: &str, repo_url: &str) -> i32 {
+    let package = create_package(package_name, repo_url, "", None);
+    let conn = DB_POOL.get().unwrap();
+    diesel::update(packages::table.find(package.id))
+        .set((
+            packages::description.eq("old description"),
+            packages::stars_count.eq(1),
+            packages::forks_count.eq(1),
+        ))
+        .execute(&conn)
+        .unwrap();
+    package.id
+}
+
+#[actix_rt::test]
+async fn refresh_packages_metadata_updates_packages() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let active_id = create_crawled_package("active", "https://github.com/owner/active");
+    let archived_id = create_crawled_package("archived", "https://github.com/owner/archived");
+    let missing_id = create_crawled_package("missing", "https://github.com/owner/missing");
+    let updated_at = Package::get(active_id, &DB_POOL).unwrap().updated_at;
+    let archived_updated_at = Package::get(archived_id, &DB_POOL).unwrap().updated_at;
+
+    let mut mock_github_service = GithubService::new();
+    mock_github_service
+        .expect_fetch_repo_metadata()
+        .returning(|repo_url| match repo_url {
+            "https://github.com/owner/active" => Ok(Some(GithubRepoInfo {
+                description: Some("new description".to_string()),
+                stargazers_count: 10,
+                forks_count: 20,
+                license: Some(GithubLicenseInfo {
+                    key: "mit".to_string(),
+                    name: "MIT License".to_string(),
+                    url: "".to_string(),
+                    spdx_id: Some("MIT".to_string()),
+                }),
+                ..Default::default()
+            })),
+            "https://github.com/owner/archived" => Ok(Some(GithubRepoInfo {
+                archived: true,
+                ..Default::default()
+            })),
+            _ => Ok(None),
+        });
+
+    let stats = refresh_packages_metadata(10, &mock_github_service, &DB_POOL).unwrap();
+    assert_eq!(
+        stats,
+        MetadataRefreshStats {
+            refreshed: 3,
+            archived: 1,
+            missing: 1,
+            failed: 0,
+            platforms_detected: 0,
+        }
+    );
+
+    let active = Package::get(active_id, &DB_POOL).unwrap();
+    assert_eq!(active.stars_count, 10);
+    assert_eq!(active.forks_count, 20);
+    assert_eq!(active.description, "new description");
+    assert_eq!(active.repository_status, REPOSITORY_STATUS_ACTIVE);
+    assert!(active.metadata_refreshed_at.is_some());
+    // a new description is an update for the 'Recently Updated' sort order
+    assert!(active.updated_at > updated_at);
+    let versions =
+        PackageVersion::from_package_id(active_id, &PackageVersionSort::Latest, &DB_POOL);
+    assert_eq!(
+        versions.unwrap()[0].license,
+        Some("MIT License".to_string())
+    );
+    assert_eq!(active.license_spdx, Some("MIT".to_string()));
+
+    let archived = Package::get(archived_id, &DB_POOL).unwrap();
+    assert_eq!(archived.repository_status, REPOSITORY_STATUS_ARCHIVED);
+    // descriptions are kept when the repository doesn't have one
+    assert_eq!(archived.description, "old description");
+    // status changes and metadata are not
+    assert_eq!(archived.updated_at, archived_updated_at);
+
+    let missing = Package::get(missing_id, &DB_POOL).unwrap();
+    assert_eq!(missing.repository_status, REPOSITORY_STATUS_MISSING);
+    assert_eq!(missing.stars_count, 1);
+}
+
+#[actix_rt::test]
+async fn refresh_packages_metadata_visits_least_recently_refreshed_first() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let first_id = create_crawled_package("first", "https://github.com/owner/first");
+    let second_id = create_crawled_package("second", "https://github.com/owner/second");
+
+    let mut mock_github_service = GithubService::new();
+    mock_github_service
+        .expect_fetch_repo_metadata()
+        .returning(|_| Err(Error::Generic("rate limited".to_string())));
+
+    let stats = refresh_packages_metadata(1, &mock_github_service, &DB_POOL).unwrap();
+    assert_eq!(stats.failed, 1);
+    assert!(Package::get(first_id, &DB_POOL)
+        .unwrap()
+        .metadata_refreshed_at
+        .is_some());
+    assert!(Package::get(second_id, &DB_POOL)
+        .unwrap()
+        .metadata_refreshed_at
+        .is_none());
+
+    let batch = Package::get_for_metadata_refresh(1, &DB_POOL).unwrap();
+    assert_eq!(batch[0].id, second_id);
+}
+
+#[actix_rt::test]
+async fn refresh_packages_metadata_detects_platforms_once() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let mut package_ids = vec![];
+    for (package_name, repo_url) in &[
+        (
+            "coin",
+            "https://github.com/owner/coin/tree/main/packages/coin",
+        ),
+        ("no-manifest", "https://github.com/owner/no-manifest"),
+    ] {
+        package_ids.push(create_package(package_name, repo_url, "rev", None).id);
+    }
+    let conn = DB_POOL.get().unwrap();
+    // crawled before platform detection
+    diesel::update(package_versions::table)
+        .set(package_versions::platform_detected.eq(false))
+        .execute(&conn)
+        .unwrap();
+
+    let mut mock_github_service = GithubService::new();
+    mock_github_service
+        .expect_fetch_repo_metadata()
+        .returning(|_| Ok(Some(GithubRepoInfo::default())));
+    mock_github_service
+        .expect_fetch_source_file()
+        .times(2)
+        .returning(|repo_url, rev, path| match (repo_url, rev, path) {
+            ("https://github.com/owner/coin", "rev", "packages/coin/Move.toml") => {
+                Ok(Some(COIN_MANIFEST.to_string()))
+            }
+            _ => Ok(None),
+        });
+
+    let stats = refresh_packages_metadata(10, &mock_github_service, &DB_POOL).unwrap();
+    assert_eq!(stats.platforms_detected, 1);
+    let coin = Package::get(package_ids[0], &DB_POOL).unwrap();
+    assert_eq!(coin.platform, "aptos");
+    let coin_version =
+        PackageVersion::from_package_id(coin.id, &PackageVersionSort::Latest, &DB_POOL)
+            .unwrap()
+            .remove(0);
+    assert_eq!(coin_version.platform, "aptos");
+    assert!(coin_version.platform_detected);
+    assert_eq!(coin_version.get_dependencies(&conn).unwrap().len(), 1);
+    // the docs of the version are left alone
+    assert!(coin_version.docs_generated_at.is_none());
+    let other_version =
+        PackageVersion::from_package_id(package_ids[1], &PackageVersionSort::Latest, &DB_POOL)
+            .unwrap()
+            .remove(0);
+    assert_eq!(other_version.platform, Platform::Unknown.as_str());
+    assert!(other_version.platform_detected);
+
+    // versions are not looked at again
+    let stats = refresh_packages_metadata(10, &mock_github_service, &DB_POOL).unwrap();
+    assert_eq!(stats.platforms_detected, 0);
+}
+
+#[actix_rt::test]
+async fn generate_pending_docs_marks_failed_versions() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    create_crawled_package("no-rev", "https://github.com/owner/no-rev");
+    let failing_id = create_package("failing", "https://github.com/owner/failing", "rev", None).id;
+
+    let mut mock_github_service = GithubService::new();
+    mock_github_service
+        .expect_fetch_source_tree()
+        .returning(|_, _, _| Err(Error::Generic("rate limited".to_string())));
+
+    let stats = generate_pending_docs(10, &mock_github_service, &DB_POOL).unwrap();
+    assert_eq!(
+        stats,
+        DocsGenerationStats {
+            generated: 1,
+            modules: 0,
+            failed: 1,
+        }
+    );
+    let versions =
+        PackageVersion::from_package_id(failing_id, &PackageVersionSort::Latest, &DB_POOL).unwrap();
+    assert!(versions[0].docs_generated_at.is_some());
+}
+
+#[actix_rt::test]
+async fn refresh_quality_scores_counts_dependents() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let coin_id = create_crawled_package("coin", "https://github.com/owner/coin");
+    let wallet_id = create_crawled_package("wallet", "https://github.com/owner/wallet");
+    let conn = DB_POOL.get().unwrap();
+    let updated_at = Package::get(coin_id, &DB_POOL).unwrap().updated_at;
+    let wallet_version =
+        PackageVersion::from_package_id(wallet_id, &PackageVersionSort::Latest, &DB_POOL)
+            .unwrap()
+            .remove(0);
+    wallet_version
+        .update_platform(
+            Platform::Unknown,
+            &[ManifestDependency {
+                name: "Coin".to_string(),
+                git: Some("https://github.com/Owner/coin.git".to_string()),
+                rev: Some("main".to_string()),
+                ..Default::default()
+            }],
+            &conn,
+        )
+        .unwrap();
+
+    let stats = refresh_quality_scores(&DB_POOL).unwrap();
+    assert_eq!(
+        stats,
+        QualityScoreStats {
+            scored: 2,
+            changed: 2,
+            failed: 0,
+        }
+    );
+    let coin = Package::get(coin_id, &DB_POOL).unwrap();
+    assert_eq!(coin.count_dependents(&conn).unwrap(), 1);
+    // description, versions, recent activity, one star and one dependent
+    assert_eq!(coin.quality_score, 5 + 10 + 15 + 2 + 2);
+    assert_eq!(coin.updated_at, updated_at);
+    let wallet = Package::get(wallet_id, &DB_POOL).unwrap();
+    assert_eq!(wallet.quality_score, 5 + 10 + 15 + 2);
+
+    let stats = refresh_quality_scores(&DB_POOL).unwrap();
+    assert_eq!(stats.changed, 0);
+}
+
+#[actix_rt::test]
+async fn purge_deleted_packages_waits_for_grace_period() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let coin_id = create_crawled_package("coin", "https://github.com/owner/coin");
+    let wallet_id = create_crawled_package("wallet", "https://github.com/owner/wallet");
+    let conn = DB_POOL.get().unwrap();
+    let coin = Package::get(coin_id, &DB_POOL)
+        .unwrap()
+        .schedule_deletion(&conn)
+        .unwrap();
+    assert!(coin.deletion_scheduled_at.unwrap() > Utc::now());
+    Package::get(wallet_id, &DB_POOL)
+        .unwrap()
+        .schedule_deletion(&conn)
+        .unwrap();
+    diesel::update(packages::table.find(coin_id))
+        .set(packages::deletion_scheduled_at.eq(Utc::now() - Duration::minutes(1)))
+        .execute(&conn)
+        .unwrap();
+
+    let stats = purge_deleted_packages(&DB_POOL).unwrap();
+    assert_eq!(
+        stats,
+        PackagePurgeStats {
+            purged: 1,
+            failed: 0,
+        }
+    );
+    assert!(Package::get(coin_id, &DB_POOL).is_err());
+    assert!(Package::get(wallet_id, &DB_POOL).is_ok());
+    assert!(PackageVersion::from_package_id(coin_id, &PackageVersionSort::Latest, &DB_POOL)
+        .unwrap()
+        .is_empty());
+    let logs = PackageAuditLog::get_by_package_id(coin_id, &conn).unwrap();
+    assert_eq!(logs[0].action, "purge");
+    assert_eq!(logs[0].package_slug, "coin");
+    assert_eq!(logs[0].account_id, None);
+
+    let stats = purge_deleted_packages(&DB_POOL).unwrap();
+    assert_eq!(stats.purged, 0);
+}
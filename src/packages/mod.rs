@@ -1,10 +1,17 @@
 use jelly::actix_web::web::{get, resource, scope, ServiceConfig};
 
+pub mod docs;
+pub mod jobs;
 pub mod models;
+pub mod platform;
+pub mod quality;
+pub mod source;
 pub mod views;
 
 pub use models::{NewPackage, NewPackageVersion, Package, PackageVersion, PackageVersionSort};
 
+use crate::utils::new_auth;
+
 pub fn configure(config: &mut ServiceConfig) {
     config.service(
         scope("/packages")
@@ -13,11 +20,49 @@ pub fn configure(config: &mut ServiceConfig) {
                 resource("/{package_slug}/versions")
                     .route(get().to(views::controller::show_package_versions)),
             )
+            .service(
+                resource("/{package_slug}/changelog")
+                    .route(get().to(views::controller::show_package_changelog)),
+            )
+            .service(
+                resource("/{package_slug}/license")
+                    .route(get().to(views::controller::show_package_license)),
+            )
+            .service(
+                resource("/{package_slug}/source")
+                    .route(get().to(views::controller::show_package_source)),
+            )
+            .service(
+                resource("/{package_slug}/source/{file_path:.*}")
+                    .route(get().to(views::controller::show_package_source_file)),
+            )
+            .service(
+                resource("/{package_slug}/docs")
+                    .route(get().to(views::controller::show_package_docs)),
+            )
+            .service(
+                resource("/{package_slug}/docs/{module_address}/{module_name}")
+                    .route(get().to(views::controller::show_package_module_docs)),
+            )
             .service(
                 resource("/{package_slug}/collaborators")
                     .route(get().to(views::controller::show_package_settings)),
             )
+            .service(
+                resource("/{package_slug}/claim")
+                    .wrap(new_auth())
+                    .route(get().to(views::controller::show_package_claim)),
+            )
+            .service(
+                resource("/{package_slug}/claim/github")
+                    .wrap(new_auth())
+                    .route(get().to(views::controller::start_github_claim)),
+            )
             .service(resource("/search").route(get().to(views::controller::show_search_results)))
+            .service(
+                resource("/search/symbols")
+                    .route(get().to(views::controller::show_symbol_search_results)),
+            )
             .service(resource("/owned").route(get().to(views::controller::show_owned_packages)))
             .service(resource("/{package_slug}").route(get().to(views::controller::show_package)))
             .service(resource("").route(get().to(views::controller::packages_index))),
@@ -0,0 +1,118 @@
+use diesel::prelude::*;
+use diesel::{Insertable, Queryable};
+use jelly::chrono::{DateTime, Utc};
+use jelly::serde::Serialize;
+use jelly::{DieselPgConnection, Result};
+
+use crate::packages::Package;
+use crate::schema::{accounts, package_audit_logs};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PackageAuditAction {
+    Archive,
+    Unarchive,
+    ScheduleDeletion,
+    CancelDeletion,
+    Purge,
+    Deprecate,
+    Undeprecate,
+    Rename,
+    TransferToOrganization,
+    Claim,
+}
+
+impl std::fmt::Display for PackageAuditAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let action = match self {
+            PackageAuditAction::Archive => "archive",
+            PackageAuditAction::Unarchive => "unarchive",
+            PackageAuditAction::ScheduleDeletion => "schedule_deletion",
+            PackageAuditAction::CancelDeletion => "cancel_deletion",
+            PackageAuditAction::Purge => "purge",
+            PackageAuditAction::Deprecate => "deprecate",
+            PackageAuditAction::Undeprecate => "undeprecate",
+            PackageAuditAction::Rename => "rename",
+            PackageAuditAction::TransferToOrganization => "transfer_to_organization",
+            PackageAuditAction::Claim => "claim",
+        };
+        write!(f, "{}", action)
+    }
+}
+
+#[derive(Clone, Debug, Queryable, Serialize)]
+pub struct PackageAuditLog {
+    pub id: i32,
+    pub package_id: i32,
+    pub package_slug: String,
+    pub account_id: Option<i32>,
+    pub action: String,
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// An audit log with the email of the account behind it, as listed on the package settings page.
+#[derive(Clone, Debug, Queryable, Serialize)]
+pub struct PackageAuditLogEntry {
+    pub action: String,
+    pub details: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub account_email: Option<String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "package_audit_logs"]
+struct NewPackageAuditLog {
+    package_id: i32,
+    package_slug: String,
+    account_id: Option<i32>,
+    action: String,
+    details: Option<String>,
+}
+
+impl PackageAuditLog {
+    /// Records an action on the package, `account_id` is `None` for background jobs.
+    pub fn create(
+        package: &Package,
+        account_id: Option<i32>,
+        action: PackageAuditAction,
+        details: Option<&str>,
+        conn: &DieselPgConnection,
+    ) -> Result<Self> {
+        let record = diesel::insert_into(package_audit_logs::table)
+            .values(NewPackageAuditLog {
+                package_id: package.id,
+                package_slug: package.slug.clone(),
+                account_id,
+                action: action.to_string(),
+                details: details.map(str::to_string),
+            })
+            .get_result::<PackageAuditLog>(conn)?;
+        Ok(record)
+    }
+
+    pub fn get_by_package_id(package_id: i32, conn: &DieselPgConnection) -> Result<Vec<Self>> {
+        let result = package_audit_logs::table
+            .filter(package_audit_logs::package_id.eq(package_id))
+            .order_by(package_audit_logs::id.desc())
+            .load::<PackageAuditLog>(conn)?;
+        Ok(result)
+    }
+
+    pub fn get_entries_by_package_id(
+        package_id: i32,
+        conn: &DieselPgConnection,
+    ) -> Result<Vec<PackageAuditLogEntry>> {
+        let result = package_audit_logs::table
+            .left_join(accounts::table)
+            .filter(package_audit_logs::package_id.eq(package_id))
+            .order_by(package_audit_logs::id.desc())
+            .select((
+                package_audit_logs::action,
+                package_audit_logs::details,
+                package_audit_logs::created_at,
+                accounts::email.nullable(),
+            ))
+            .load::<PackageAuditLogEntry>(conn)?;
+        Ok(result)
+    }
+}
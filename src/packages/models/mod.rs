@@ -1,44 +1,66 @@
 extern crate slug;
 
-use crate::sql::lower;
+use crate::sql::{concat, lower, translate};
 
 use diesel::dsl::{count, now, sum};
 use diesel::prelude::*;
-use diesel::sql_types::{Integer, Text, Timestamptz};
+use diesel::sql_types::{Integer, Nullable, Text, Timestamptz};
 use diesel::{AsChangeset, Identifiable, Insertable, Queryable};
 
 use diesel_full_text_search::{plainto_tsquery, TsVectorExtensions};
 
 use diesel::result::Error::NotFound;
 use diesel::result::{DatabaseErrorKind, Error as DBError};
-use jelly::chrono::{DateTime, NaiveDateTime, Utc};
+use jelly::chrono::{DateTime, Duration, NaiveDateTime, Utc};
 use jelly::error::Error;
 use jelly::serde::{Deserialize, Serialize};
 use jelly::{DieselPgConnection, DieselPgPool};
 
-use crate::github_service::GithubRepoData;
+use crate::github_service::{GithubRepoData, GithubRepoInfo};
 use crate::package_collaborators::package_collaborator::{PackageCollaborator, Role};
+use crate::package_collaborators::policy::{self, Action};
 use jelly::Result;
 use mockall_double::double;
 use rayon::prelude::*;
 
+pub mod audit_log;
+pub mod source_file;
 #[cfg(test)]
 mod tests;
 
 #[double]
 use crate::github_service::GithubService;
+use crate::packages::platform::{ManifestDependency, Platform};
+use crate::packages::quality::QualityInputs;
 use crate::schema::package_collaborators;
+use crate::schema::package_slug_histories;
+use crate::schema::package_version_dependencies;
 use crate::schema::package_versions;
 use crate::schema::package_versions::dsl::*;
 use crate::schema::packages;
 use crate::schema::packages::dsl::*;
+use crate::utils::license::{normalize_license, resolve_license, UNKNOWN_LICENSE_FILTER};
 use crate::utils::paginate::LoadPaginated;
+use crate::utils::presenter::make_package_repo_and_subdir;
 use crate::utils::token::generate_secure_alphanumeric_string;
 
 use super::views::serializer::slugify_package_name;
 
 pub const PACKAGES_PER_PAGE: i64 = 10;
 
+pub const REPOSITORY_STATUS_ACTIVE: &str = "active";
+pub const REPOSITORY_STATUS_ARCHIVED: &str = "archived";
+pub const REPOSITORY_STATUS_MISSING: &str = "missing";
+
+/// Why an owner can't delete a package, they can still archive it.
+#[derive(Debug, PartialEq, Eq)]
+pub enum DeletionBlocker {
+    /// More packages depend on it than `DELETION_MAX_DEPENDENTS`.
+    Dependents(i64),
+    /// It was downloaded more than `DELETION_MAX_DOWNLOADS` times.
+    Downloads(i32),
+}
+
 #[derive(Debug, Serialize, Deserialize, Queryable, Identifiable, AsChangeset, QueryableByName)]
 #[table_name = "packages"]
 pub struct Package {
@@ -52,6 +74,18 @@ pub struct Package {
     pub slug: String,
     pub stars_count: i32,
     pub forks_count: i32,
+    pub repository_status: String,
+    pub metadata_refreshed_at: Option<DateTime<Utc>>,
+    pub upstream_package_id: Option<i32>,
+    pub platform: String,
+    pub license_spdx: Option<String>,
+    pub quality_score: i32,
+    pub deprecated_at: Option<DateTime<Utc>>,
+    pub deprecation_reason: Option<String>,
+    pub deprecation_successor: Option<String>,
+    pub archived_at: Option<DateTime<Utc>>,
+    pub deletion_scheduled_at: Option<DateTime<Utc>>,
+    pub organization_id: Option<i32>,
 }
 
 type PackageColumns = (
@@ -65,6 +99,18 @@ type PackageColumns = (
     packages::slug,
     packages::stars_count,
     packages::forks_count,
+    packages::repository_status,
+    packages::metadata_refreshed_at,
+    packages::upstream_package_id,
+    packages::platform,
+    packages::license_spdx,
+    packages::quality_score,
+    packages::deprecated_at,
+    packages::deprecation_reason,
+    packages::deprecation_successor,
+    packages::archived_at,
+    packages::deletion_scheduled_at,
+    packages::organization_id,
 );
 
 pub const PACKAGE_COLUMNS: PackageColumns = (
@@ -78,6 +124,18 @@ pub const PACKAGE_COLUMNS: PackageColumns = (
     packages::slug,
     packages::stars_count,
     packages::forks_count,
+    packages::repository_status,
+    packages::metadata_refreshed_at,
+    packages::upstream_package_id,
+    packages::platform,
+    packages::license_spdx,
+    packages::quality_score,
+    packages::deprecated_at,
+    packages::deprecation_reason,
+    packages::deprecation_successor,
+    packages::archived_at,
+    packages::deletion_scheduled_at,
+    packages::organization_id,
 );
 
 #[derive(Debug, Serialize, Deserialize, QueryableByName, Queryable)]
@@ -101,6 +159,12 @@ pub struct PackageSearchResult {
     #[sql_type = "Integer"]
     pub forks_count: i32,
     #[sql_type = "Text"]
+    pub platform: String,
+    #[sql_type = "Nullable<Text>"]
+    pub license_spdx: Option<String>,
+    #[sql_type = "Nullable<Timestamptz>"]
+    pub deprecated_at: Option<NaiveDateTime>,
+    #[sql_type = "Text"]
     pub version: String,
 }
 
@@ -114,6 +178,7 @@ pub struct NewPackage {
     pub slug: String,
     pub stars_count: i32,
     pub forks_count: i32,
+    pub upstream_package_id: Option<i32>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -132,6 +197,8 @@ pub enum PackageSortField {
     MostStars,
     #[serde(alias = "most_forks")]
     MostForks,
+    #[serde(alias = "quality")]
+    Quality,
 }
 
 // Convert to a value used in view template
@@ -145,6 +212,7 @@ impl std::fmt::Display for PackageSortField {
             PackageSortField::RecentlyUpdated => "recently_updated",
             PackageSortField::MostStars => "most_stars",
             PackageSortField::MostForks => "most_forks",
+            PackageSortField::Quality => "quality",
         };
         write!(f, "{}", enum_name)
     }
@@ -161,10 +229,57 @@ impl PackageSortField {
             PackageSortField::RecentlyUpdated => "updated_at",
             PackageSortField::MostStars => "stars_count",
             PackageSortField::MostForks => "forks_count",
+            PackageSortField::Quality => "quality_score",
         })
     }
 }
 
+/// Narrows down the packages listed by `search` and `all_packages`.
+#[derive(Debug, Default)]
+pub struct PackageSearchFilters {
+    /// Also list forks and copies of other packages.
+    pub include_forks: bool,
+    /// Only list packages whose latest version targets this platform.
+    pub platform: Option<Platform>,
+    /// Only list packages whose license expression includes this SPDX id, or
+    /// without a recognized license for `UNKNOWN_LICENSE_FILTER`.
+    pub license: Option<String>,
+}
+
+impl PackageSearchFilters {
+    /// Query string that keeps the filters when sorting or paginating.
+    pub fn to_query_string(&self) -> String {
+        let mut query = String::new();
+        if self.include_forks {
+            query.push_str("&include_forks=true");
+        }
+        if let Some(platform) = self.platform {
+            query.push_str(&format!("&platform={}", platform.as_str()));
+        }
+        if let Some(license) = &self.license {
+            query.push_str(&format!("&license={}", urlencoding::encode(license)));
+        }
+        query
+    }
+
+    fn platform_value(&self) -> &'static str {
+        self.platform.map_or("", |platform| platform.as_str())
+    }
+
+    fn license_is_unknown(&self) -> bool {
+        self.license.as_deref() == Some(UNKNOWN_LICENSE_FILTER)
+    }
+
+    // Matches an id of a space padded license expression whose parentheses are
+    // replaced by spaces, never matches when filtering unknown licenses
+    fn license_pattern(&self) -> String {
+        match &self.license {
+            Some(license) if !self.license_is_unknown() => format!("% {} %", license),
+            _ => String::new(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize)]
 pub enum PackageSortOrder {
     #[serde(alias = "asc")]
@@ -195,6 +310,16 @@ pub struct PackageVersion {
     pub rev: Option<String>,
     pub total_files: Option<i32>,
     pub total_size: Option<i32>,
+    pub manifest_hash: Option<String>,
+    pub changelog_content: Option<String>,
+    pub license_content: Option<String>,
+    pub readme_html: Option<String>,
+    pub docs_generated_at: Option<DateTime<Utc>>,
+    pub platform: String,
+    pub platform_detected: bool,
+    pub manifest_license: Option<String>,
+    pub license_spdx: Option<String>,
+    pub license_normalized: bool,
 }
 
 #[derive(Insertable)]
@@ -208,6 +333,35 @@ pub struct NewPackageVersion {
     pub total_files: i32,
     pub total_size: i32,
     pub downloads_count: i32,
+    pub manifest_hash: Option<String>,
+    pub changelog_content: Option<String>,
+    pub license_content: Option<String>,
+    pub readme_html: Option<String>,
+    pub platform: String,
+    pub manifest_license: Option<String>,
+    pub license_spdx: Option<String>,
+}
+
+#[derive(Debug, Serialize, Queryable, Identifiable, Clone)]
+pub struct PackageVersionDependency {
+    pub id: i32,
+    pub package_version_id: i32,
+    pub name: String,
+    pub git_url: Option<String>,
+    pub rev: Option<String>,
+    pub subdir: Option<String>,
+    pub local_path: Option<String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "package_version_dependencies"]
+struct NewPackageVersionDependency<'a> {
+    package_version_id: i32,
+    name: &'a str,
+    git_url: Option<&'a str>,
+    rev: Option<&'a str>,
+    subdir: Option<&'a str>,
+    local_path: Option<&'a str>,
 }
 
 #[derive(Serialize, Deserialize)]
@@ -264,19 +418,27 @@ impl Package {
     ) -> Result<Package> {
         let conn = pool.get()?;
         conn.transaction(|| -> Result<Package> {
-            let (record, package_owner_id) =
+            let (record, can_publish) =
                 match Package::get_by_name_and_repo_url(&github_data.name, repo_url, &conn) {
                     Ok(package) => {
-                        let collaborators =
-                            PackageCollaborator::get_by_package_id(package.id, &conn)?;
-                        let owner_id = if collaborators.len() > 0 {
-                            Some(collaborators[0])
-                        } else {
-                            None
+                        let can_publish = match account_id_ {
+                            Some(uid) => policy::can(&package, uid, Action::Publish, &conn)?,
+                            // The crawler only updates packages nobody published
+                            None => {
+                                package.organization_id.is_none()
+                                    && PackageCollaborator::get_by_package_id(package.id, &conn)?
+                                        .is_empty()
+                            }
                         };
-                        (package, owner_id)
+                        (package, can_publish)
                     }
                     Err(_) => {
+                        // Only crawled packages are linked, owners publish their forks on purpose
+                        let upstream_package_id = if account_id_.is_none() {
+                            Package::find_upstream(&github_data, &conn)?
+                        } else {
+                            None
+                        };
                         let mut new_package = NewPackage {
                             name: github_data.name.clone(),
                             description: package_description.to_string(),
@@ -284,17 +446,26 @@ impl Package {
                             slug: slugify_package_name(&github_data.name),
                             stars_count: github_data.stars_count,
                             forks_count: github_data.forks_count,
+                            upstream_package_id,
                         };
                         let maximum_allowed_collisions = std::env::var("MAX_COLLISIONS_ALLOWED")
                             .unwrap_or_else(|_| "3".to_string())
                             .parse::<usize>()
                             .unwrap();
-                        let mut insert_result = diesel::insert_into(packages::table)
-                            .values(new_package.clone())
-                            .on_conflict(packages::slug)
-                            .do_nothing()
-                            .returning(PACKAGE_COLUMNS)
-                            .get_result::<Package>(&conn);
+                        // Old slugs of renamed packages keep redirecting, like taken slugs
+                        // they get a suffix
+                        let mut insert_result =
+                            if Package::get_slug_history_owner(&new_package.slug, &conn)?.is_some()
+                            {
+                                Err(NotFound)
+                            } else {
+                                diesel::insert_into(packages::table)
+                                    .values(new_package.clone())
+                                    .on_conflict(packages::slug)
+                                    .do_nothing()
+                                    .returning(PACKAGE_COLUMNS)
+                                    .get_result::<Package>(&conn)
+                            };
                         if insert_result.is_err() {
                             for i in 0..maximum_allowed_collisions {
                                 new_package.slug = format!(
@@ -325,25 +496,21 @@ impl Package {
                                 &conn,
                             )?;
                         }
-                        (inserted_record, account_id_)
+                        (inserted_record, true)
                     }
                 };
 
-            // Only creates new version if same user with package owner
-            if package_owner_id == account_id_ {
+            if can_publish {
                 let package_version_not_exist = record.get_version(&github_data.version, &conn);
                 if package_version_not_exist.is_err() {
                     let e = package_version_not_exist.unwrap_err();
                     if let Error::Database(NotFound) = e {
-                        PackageVersion::create(
+                        PackageVersion::create_from_repo_data(
                             record.id,
-                            github_data.version,
-                            github_data.readme_content,
-                            github_data.license,
+                            github_data,
                             version_rev.to_string(),
                             version_files,
                             version_size,
-                            None,
                             &conn,
                         )?;
                     } else {
@@ -397,6 +564,71 @@ impl Package {
         Ok(result)
     }
 
+    /// Current slug of a package that was renamed from `old_slug`.
+    pub fn get_renamed_slug(old_slug: &str, conn: &DieselPgConnection) -> Result<Option<String>> {
+        let result = package_slug_histories::table
+            .inner_join(packages::table)
+            .filter(package_slug_histories::slug.eq(old_slug))
+            .select(packages::slug)
+            .first::<String>(conn)
+            .optional()?;
+
+        Ok(result)
+    }
+
+    /// The package that used to have `old_slug`, whose links keep redirecting to it.
+    pub fn get_slug_history_owner(
+        old_slug: &str,
+        conn: &DieselPgConnection,
+    ) -> Result<Option<i32>> {
+        let result = package_slug_histories::table
+            .filter(package_slug_histories::slug.eq(old_slug))
+            .select(package_slug_histories::package_id)
+            .first::<i32>(conn)
+            .optional()?;
+
+        Ok(result)
+    }
+
+    /// Changes the slug of the package, the old slug is kept in `package_slug_histories`
+    /// so that links to it can be redirected. Old slugs of other packages can't be taken.
+    pub fn rename_slug(&self, new_slug: &str, conn: &DieselPgConnection) -> Result<Self> {
+        if new_slug == self.slug {
+            return Package::get_by_slug(new_slug, conn);
+        }
+        if let Some(owner_id) = Package::get_slug_history_owner(new_slug, conn)? {
+            if owner_id != self.id {
+                return Err(Error::Generic(format!(
+                    "Slug {} redirects to package {}",
+                    new_slug, owner_id
+                )));
+            }
+        }
+        conn.transaction(|| -> Result<Self> {
+            let record = diesel::update(packages.find(self.id))
+                .set(packages::slug.eq(new_slug))
+                .returning(PACKAGE_COLUMNS)
+                .get_result::<Package>(conn)?;
+            // Taking back one of its own old slugs ends its redirect
+            diesel::delete(
+                package_slug_histories::table
+                    .filter(package_slug_histories::slug.eq(new_slug))
+                    .filter(package_slug_histories::package_id.eq(self.id)),
+            )
+            .execute(conn)?;
+            diesel::insert_into(package_slug_histories::table)
+                .values((
+                    package_slug_histories::package_id.eq(self.id),
+                    package_slug_histories::slug.eq(&self.slug),
+                ))
+                .on_conflict(package_slug_histories::slug)
+                .do_update()
+                .set(package_slug_histories::package_id.eq(self.id))
+                .execute(conn)?;
+            Ok(record)
+        })
+    }
+
     pub fn get_by_name_and_repo_url(
         package_name: &str,
         repo_url: &str,
@@ -410,6 +642,58 @@ impl Package {
         Ok(result)
     }
 
+    /// Finds the package a crawled repository was copied from, either because the
+    /// repository is a Github fork or because it contains an identical Move.toml.
+    pub fn find_upstream(
+        github_data: &GithubRepoData,
+        conn: &DieselPgConnection,
+    ) -> Result<Option<i32>> {
+        if let Some(source_url) = &github_data.fork_source_url {
+            let upstream_id = packages
+                .filter(name.eq(&github_data.name))
+                .filter(
+                    repository_url
+                        .eq(source_url)
+                        .or(repository_url.like(format!("{}/%", source_url))),
+                )
+                .filter(packages::upstream_package_id.is_null())
+                .order_by(packages::id.asc())
+                .select(packages::id)
+                .first::<i32>(conn)
+                .optional()?;
+            if upstream_id.is_some() {
+                return Ok(upstream_id);
+            }
+        }
+        if github_data.manifest_hash.is_empty() {
+            return Ok(None);
+        }
+        let upstream_id = packages::table
+            .inner_join(package_versions::table)
+            .filter(package_versions::manifest_hash.eq(&github_data.manifest_hash))
+            .filter(packages::upstream_package_id.is_null())
+            .order_by(packages::id.asc())
+            .select(packages::id)
+            .first::<i32>(conn)
+            .optional()?;
+
+        Ok(upstream_id)
+    }
+
+    pub fn get_upstream(&self, conn: &DieselPgConnection) -> Result<Option<Self>> {
+        let upstream_id = match self.upstream_package_id {
+            Some(upstream_id) => upstream_id,
+            None => return Ok(None),
+        };
+        let result = packages
+            .find(upstream_id)
+            .select(PACKAGE_COLUMNS)
+            .first::<Package>(conn)
+            .optional()?;
+
+        Ok(result)
+    }
+
     pub fn get_by_name_case_insensitive(
         package_name: &str,
         pool: &DieselPgPool,
@@ -422,6 +706,321 @@ impl Package {
             .load::<Package>(&connection)?)
     }
 
+    /// Returns the packages whose repository metadata is the most out of date.
+    pub fn get_for_metadata_refresh(batch_size: i64, pool: &DieselPgPool) -> Result<Vec<Self>> {
+        let connection = pool.get()?;
+        let result = packages
+            .select(PACKAGE_COLUMNS)
+            .order_by(packages::metadata_refreshed_at.asc().nulls_first())
+            .then_order_by(packages::id.asc())
+            .limit(batch_size)
+            .load::<Package>(&connection)?;
+
+        Ok(result)
+    }
+
+    /// Updates stars, forks, description and license with the latest data from Github.
+    /// `None` means the repository could not be found anymore.
+    pub fn refresh_metadata(
+        &self,
+        repo_info: Option<&GithubRepoInfo>,
+        conn: &DieselPgConnection,
+    ) -> Result<()> {
+        conn.transaction(|| -> Result<()> {
+            let repo_info = match repo_info {
+                Some(repo_info) => repo_info,
+                None => {
+                    diesel::update(packages.find(self.id))
+                        .set((
+                            packages::repository_status.eq(REPOSITORY_STATUS_MISSING),
+                            packages::metadata_refreshed_at.eq(now),
+                        ))
+                        .execute(conn)?;
+                    return Ok(());
+                }
+            };
+            let status = if repo_info.archived {
+                REPOSITORY_STATUS_ARCHIVED
+            } else {
+                REPOSITORY_STATUS_ACTIVE
+            };
+            // Keep generated descriptions when the repository doesn't have one
+            let new_description = match &repo_info.description {
+                Some(repo_description) if !repo_description.is_empty() => repo_description,
+                _ => &self.description,
+            };
+            diesel::update(packages.find(self.id))
+                .set((
+                    packages::stars_count.eq(repo_info.stargazers_count),
+                    packages::forks_count.eq(repo_info.forks_count),
+                    packages::description.eq(new_description),
+                    packages::repository_status.eq(status),
+                    packages::metadata_refreshed_at.eq(now),
+                ))
+                .execute(conn)?;
+
+            if let Some(repo_license) = &repo_info.license {
+                let latest_version = package_versions
+                    .filter(package_versions::package_id.eq(self.id))
+                    .order_by(package_versions::id.desc())
+                    .select((package_versions::id, package_versions::manifest_license))
+                    .first::<(i32, Option<String>)>(conn)
+                    .optional()?;
+                if let Some((latest_version_id, version_manifest_license)) = latest_version {
+                    // the license of the manifest still takes precedence
+                    let spdx = repo_license.resolve_spdx(version_manifest_license.as_deref());
+                    diesel::update(package_versions.find(latest_version_id))
+                        .set((
+                            package_versions::license.eq(&repo_license.name),
+                            package_versions::license_spdx.eq(&spdx),
+                        ))
+                        .execute(conn)?;
+                    diesel::update(packages.find(self.id))
+                        .set(packages::license_spdx.eq(&spdx))
+                        .execute(conn)?;
+                }
+            }
+            Ok(())
+        })
+    }
+
+    pub fn touch_metadata_refreshed_at(&self, conn: &DieselPgConnection) -> Result<()> {
+        diesel::update(packages.find(self.id))
+            .set(packages::metadata_refreshed_at.eq(now))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    /// Number of other packages with a version depending on this package's repository.
+    pub fn count_dependents(&self, conn: &DieselPgConnection) -> Result<i64> {
+        let (repo_url, subdir) = make_package_repo_and_subdir(&self.repository_url);
+        let repo_url = repo_url.trim_end_matches('/').to_lowercase();
+        let records = package_version_dependencies::table
+            .inner_join(package_versions::table)
+            .filter(package_version_dependencies::git_url.ilike(format!("{}%", repo_url)))
+            .filter(package_versions::package_id.ne(self.id))
+            .select((
+                package_versions::package_id,
+                package_version_dependencies::git_url,
+                package_version_dependencies::subdir,
+            ))
+            .load::<(i32, Option<String>, Option<String>)>(conn)?;
+        let mut dependent_ids = records
+            .into_iter()
+            .filter(|(_, git_url, dependency_subdir)| {
+                let git_url = git_url.as_deref().unwrap_or("").to_lowercase();
+                let git_url = git_url.trim_end_matches('/').trim_end_matches(".git");
+                git_url == repo_url
+                    && dependency_subdir.as_deref().unwrap_or("").trim_matches('/')
+                        == subdir.trim_matches('/')
+            })
+            .map(|(dependent_id, _, _)| dependent_id)
+            .collect::<Vec<i32>>();
+        dependent_ids.sort_unstable();
+        dependent_ids.dedup();
+        Ok(dependent_ids.len() as i64)
+    }
+
+    pub fn quality_inputs(&self, conn: &DieselPgConnection) -> Result<QualityInputs> {
+        let versions = package_versions
+            .filter(package_versions::package_id.eq(self.id))
+            .order_by(package_versions::created_at.desc())
+            .select((
+                package_versions::version,
+                package_versions::readme_content,
+                package_versions::created_at,
+            ))
+            .load::<(String, Option<String>, DateTime<Utc>)>(conn)?;
+        let is_claimed = package_collaborators::table
+            .filter(package_collaborators::package_id.eq(self.id))
+            .count()
+            .get_result::<i64>(conn)?
+            > 0;
+        Ok(QualityInputs {
+            readme_content: versions
+                .first()
+                .and_then(|(_, readme, _)| readme.clone())
+                .unwrap_or_default(),
+            license_spdx: self.license_spdx.clone(),
+            description: self.description.clone(),
+            latest_version_at: versions.first().map(|(_, _, published_at)| *published_at),
+            versions: versions
+                .into_iter()
+                .map(|(version_name, _, _)| version_name)
+                .collect(),
+            is_repository_active: self.repository_status == REPOSITORY_STATUS_ACTIVE,
+            stars_count: self.stars_count,
+            downloads_count: self.total_downloads_count,
+            dependents_count: self.count_dependents(conn)?,
+            is_claimed,
+        })
+    }
+
+    /// Stores the total of the quality score, which search results can be sorted by.
+    pub fn update_quality_score(&self, score: i32, conn: &DieselPgConnection) -> Result<bool> {
+        if score == self.quality_score {
+            return Ok(false);
+        }
+        diesel::update(packages.find(self.id))
+            .set(packages::quality_score.eq(score))
+            .execute(conn)?;
+        Ok(true)
+    }
+
+    /// Marks the whole package deprecated, `successor_slug` being the package to use instead.
+    pub fn deprecate(
+        &self,
+        reason: &str,
+        successor_slug: Option<&str>,
+        conn: &DieselPgConnection,
+    ) -> Result<Self> {
+        let record = diesel::update(packages.find(self.id))
+            .set((
+                packages::deprecated_at.eq(now),
+                packages::deprecation_reason.eq(reason.trim()),
+                packages::deprecation_successor.eq(successor_slug),
+            ))
+            .returning(PACKAGE_COLUMNS)
+            .get_result::<Package>(conn)?;
+        Ok(record)
+    }
+
+    pub fn undeprecate(&self, conn: &DieselPgConnection) -> Result<Self> {
+        let record = diesel::update(packages.find(self.id))
+            .set((
+                packages::deprecated_at.eq(None::<DateTime<Utc>>),
+                packages::deprecation_reason.eq(None::<String>),
+                packages::deprecation_successor.eq(None::<String>),
+            ))
+            .returning(PACKAGE_COLUMNS)
+            .get_result::<Package>(conn)?;
+        Ok(record)
+    }
+
+    /// Archived packages and packages scheduled for deletion accept no new versions.
+    pub fn is_read_only(&self) -> bool {
+        self.archived_at.is_some() || self.deletion_scheduled_at.is_some()
+    }
+
+    /// Makes the package read-only and hides it from search, it can still be viewed and downloaded.
+    pub fn archive(&self, conn: &DieselPgConnection) -> Result<Self> {
+        let record = diesel::update(packages.find(self.id))
+            .set(packages::archived_at.eq(now))
+            .returning(PACKAGE_COLUMNS)
+            .get_result::<Package>(conn)?;
+        Ok(record)
+    }
+
+    pub fn unarchive(&self, conn: &DieselPgConnection) -> Result<Self> {
+        let record = diesel::update(packages.find(self.id))
+            .set(packages::archived_at.eq(None::<DateTime<Utc>>))
+            .returning(PACKAGE_COLUMNS)
+            .get_result::<Package>(conn)?;
+        Ok(record)
+    }
+
+    pub fn deletion_blocker(&self, conn: &DieselPgConnection) -> Result<Option<DeletionBlocker>> {
+        if i64::from(self.total_downloads_count) > deletion_limit("DELETION_MAX_DOWNLOADS", 500) {
+            return Ok(Some(DeletionBlocker::Downloads(self.total_downloads_count)));
+        }
+        let dependents = self.count_dependents(conn)?;
+        if dependents > deletion_limit("DELETION_MAX_DEPENDENTS", 0) {
+            return Ok(Some(DeletionBlocker::Dependents(dependents)));
+        }
+        Ok(None)
+    }
+
+    /// Hides the package until it is purged, `PACKAGE_DELETION_GRACE_DAYS` later. The owner can
+    /// restore it in the meantime with `cancel_deletion`.
+    pub fn schedule_deletion(&self, conn: &DieselPgConnection) -> Result<Self> {
+        let scheduled_at = self.deletion_scheduled_at.unwrap_or_else(|| {
+            Utc::now() + Duration::days(deletion_limit("PACKAGE_DELETION_GRACE_DAYS", 7))
+        });
+        let record = diesel::update(packages.find(self.id))
+            .set(packages::deletion_scheduled_at.eq(scheduled_at))
+            .returning(PACKAGE_COLUMNS)
+            .get_result::<Package>(conn)?;
+        Ok(record)
+    }
+
+    pub fn cancel_deletion(&self, conn: &DieselPgConnection) -> Result<Self> {
+        let record = diesel::update(packages.find(self.id))
+            .set(packages::deletion_scheduled_at.eq(None::<DateTime<Utc>>))
+            .returning(PACKAGE_COLUMNS)
+            .get_result::<Package>(conn)?;
+        Ok(record)
+    }
+
+    pub fn get_due_for_purge(conn: &DieselPgConnection) -> Result<Vec<Self>> {
+        let result = packages
+            .filter(packages::deletion_scheduled_at.le(Utc::now()))
+            .select(PACKAGE_COLUMNS)
+            .order_by(packages::deletion_scheduled_at.asc())
+            .load::<Package>(conn)?;
+        Ok(result)
+    }
+
+    /// Deletes the package for good, along with its versions and collaborators.
+    pub fn purge(&self, conn: &DieselPgConnection) -> Result<()> {
+        conn.transaction(|| -> Result<()> {
+            diesel::delete(package_versions.filter(package_id.eq(self.id))).execute(conn)?;
+            diesel::delete(
+                package_collaborators::table
+                    .filter(package_collaborators::package_id.eq(self.id)),
+            )
+            .execute(conn)?;
+            diesel::delete(packages.find(self.id)).execute(conn)?;
+            Ok(())
+        })
+    }
+
+    /// Warning printed by the Move CLI when a deprecated package is downloaded.
+    pub fn deprecation_warning(&self) -> Option<String> {
+        self.deprecated_at?;
+        let mut warning = format!("Package {} is deprecated", self.name);
+        match self.deprecation_reason.as_deref().map(str::trim) {
+            Some(reason) if !reason.is_empty() => {
+                warning.push_str(&format!(": {}.", reason.trim_end_matches('.')))
+            }
+            _ => warning.push('.'),
+        }
+        if let Some(successor) = &self.deprecation_successor {
+            warning.push_str(&format!(" Use {} instead.", successor));
+        }
+        Some(warning)
+    }
+
+    /// Finds the package downloaded with the git url, rev and subdir sent to `/api/v1/packages/count`.
+    pub fn get_by_download_info(
+        url: &str,
+        subdir: &str,
+        conn: &DieselPgConnection,
+    ) -> Result<Option<Self>> {
+        let https_url = to_https_repo_url(url);
+        let candidates = packages
+            .filter(
+                repository_url
+                    .eq(&https_url)
+                    .or(repository_url.like(format!("{}/blob/%", https_url))),
+            )
+            .select(PACKAGE_COLUMNS)
+            .load::<Package>(conn)?;
+        Ok(candidates.into_iter().find(|package| {
+            let (_, package_subdir) = make_package_repo_and_subdir(&package.repository_url);
+            package_subdir.trim_matches('/') == subdir.trim_matches('/')
+        }))
+    }
+
+    pub fn get_all(pool: &DieselPgPool) -> Result<Vec<Self>> {
+        let connection = pool.get()?;
+        let result = packages
+            .select(PACKAGE_COLUMNS)
+            .order_by(packages::id.asc())
+            .load::<Package>(&connection)?;
+
+        Ok(result)
+    }
+
     pub fn get_badge_info(
         package_name: &str,
         pool: &DieselPgPool,
@@ -451,9 +1050,10 @@ impl Package {
         let result = packages
             .inner_join(package_collaborators::table)
             .filter(package_collaborators::account_id.eq(owner_id))
+            .filter(packages::archived_at.is_null().and(packages::deletion_scheduled_at.is_null()))
             .inner_join(package_versions::table)
-            .select((packages::id, packages::name, packages::description, packages::total_downloads_count, packages::created_at, packages::updated_at, packages::slug, packages::stars_count, packages::forks_count, diesel::dsl::sql::<diesel::sql_types::Text>("max(version) as version")))
-            .filter(diesel::dsl::sql("TRUE GROUP BY packages.id, name, description, total_downloads_count, packages.created_at, packages.updated_at, packages.slug, packages.stars_count, packages.forks_count")) // workaround since diesel 1.x doesn't support GROUP_BY dsl yet
+            .select((packages::id, packages::name, packages::description, packages::total_downloads_count, packages::created_at, packages::updated_at, packages::slug, packages::stars_count, packages::forks_count, packages::platform, packages::license_spdx, packages::deprecated_at, diesel::dsl::sql::<diesel::sql_types::Text>("max(version) as version")))
+            .filter(diesel::dsl::sql("TRUE GROUP BY packages.id, name, description, total_downloads_count, packages.created_at, packages.updated_at, packages.slug, packages.stars_count, packages.forks_count, packages.platform, packages.license_spdx, packages.deprecated_at")) // workaround since diesel 1.x doesn't support GROUP_BY dsl yet
             .load::<PackageSearchResult>(&connection)?;
 
         Ok(result)
@@ -466,6 +1066,43 @@ impl Package {
         page: Option<i64>,
         per_page: Option<i64>,
         pool: &DieselPgPool,
+    ) -> Result<(Vec<PackageSearchResult>, i64, i64)> {
+        Package::get_by_owner_paginated(
+            PackageOwner::Account(owner_id),
+            sort_field,
+            sort_order,
+            page,
+            per_page,
+            pool,
+        )
+    }
+
+    pub fn get_by_organization_paginated(
+        organization_id_: i32,
+        sort_field: &PackageSortField,
+        sort_order: &PackageSortOrder,
+        page: Option<i64>,
+        per_page: Option<i64>,
+        pool: &DieselPgPool,
+    ) -> Result<(Vec<PackageSearchResult>, i64, i64)> {
+        Package::get_by_owner_paginated(
+            PackageOwner::Organization(organization_id_),
+            sort_field,
+            sort_order,
+            page,
+            per_page,
+            pool,
+        )
+    }
+
+    // Packages listed on a public profile page, with their latest version
+    fn get_by_owner_paginated(
+        owner: PackageOwner,
+        sort_field: &PackageSortField,
+        sort_order: &PackageSortOrder,
+        page: Option<i64>,
+        per_page: Option<i64>,
+        pool: &DieselPgPool,
     ) -> Result<(Vec<PackageSearchResult>, i64, i64)> {
         let connection = pool.get()?;
         let field = sort_field.to_column_name();
@@ -478,12 +1115,29 @@ impl Package {
             return Err(Error::Generic(String::from("Invalid page number.")));
         }
 
-        let result: (Vec<PackageSearchResult>, i64, i64) = packages::table
-            .inner_join(package_collaborators::table)
-            .filter(package_collaborators::account_id.eq(owner_id).and(package_collaborators::role.eq(Role::Owner as i32)))
+        let query = packages::table
             .inner_join(package_versions::table)
-            .select((packages::id, packages::name, packages::description, packages::total_downloads_count, packages::created_at, packages::updated_at, packages::slug, packages::stars_count, packages::forks_count, diesel::dsl::sql::<diesel::sql_types::Text>("max(version) as version")))
-            .filter(diesel::dsl::sql("TRUE GROUP BY packages.id, name, description, total_downloads_count, packages.created_at, packages.updated_at, packages.slug, packages.stars_count, packages.forks_count")) // workaround since diesel 1.x doesn't support GROUP_BY dsl yet
+            .select((packages::id, packages::name, packages::description, packages::total_downloads_count, packages::created_at, packages::updated_at, packages::slug, packages::stars_count, packages::forks_count, packages::platform, packages::license_spdx, packages::deprecated_at, diesel::dsl::sql::<diesel::sql_types::Text>("max(version) as version")))
+            .into_boxed();
+        let query = match owner {
+            PackageOwner::Account(owner_id) => query.filter(
+                packages::id.eq_any(
+                    package_collaborators::table
+                        .filter(
+                            package_collaborators::account_id
+                                .eq(owner_id)
+                                .and(package_collaborators::role.eq(Role::Owner as i32)),
+                        )
+                        .select(package_collaborators::package_id),
+                ),
+            ),
+            PackageOwner::Organization(organization_id_) => {
+                query.filter(packages::organization_id.eq(organization_id_))
+            }
+        };
+        let result: (Vec<PackageSearchResult>, i64, i64) = query
+            .filter(packages::archived_at.is_null().and(packages::deletion_scheduled_at.is_null()))
+            .filter(diesel::dsl::sql("TRUE GROUP BY packages.id, name, description, total_downloads_count, packages.created_at, packages.updated_at, packages.slug, packages.stars_count, packages.forks_count, packages.platform, packages.license_spdx, packages.deprecated_at")) // workaround since diesel 1.x doesn't support GROUP_BY dsl yet
             .order(diesel::dsl::sql::<diesel::sql_types::Text>(&order_query))
             .load_with_pagination(&connection, Some(page), Some(per_page))?;
 
@@ -520,22 +1174,6 @@ impl Package {
         Ok(result)
     }
 
-    pub fn change_owner(
-        package_id_: i32,
-        new_owner_id: i32,
-        conn: &DieselPgConnection,
-    ) -> Result<()> {
-        diesel::update(package_collaborators::table)
-            .filter(
-                package_collaborators::package_id
-                    .eq(package_id_)
-                    .and(package_collaborators::role.eq(Role::Owner as i32)),
-            )
-            .set(package_collaborators::account_id.eq(new_owner_id))
-            .execute(conn)?;
-        Ok(())
-    }
-
     pub fn increase_download_count(
         url: &String,
         rev_: &String,
@@ -545,16 +1183,7 @@ impl Package {
     ) -> Result<usize> {
         let connection = pool.get()?;
 
-        let mut https_url = url.to_owned();
-        if url.starts_with("git@github.com") {
-            https_url = url
-                .replace(':', "/")
-                .replace("git@", "https://")
-                .replace(".git", "");
-        }
-        if https_url.ends_with(".git") {
-            https_url = https_url[0..https_url.len() - 4].to_string();
-        }
+        let mut https_url = to_https_repo_url(url);
 
         let package_id_ = packages
             .filter(repository_url.eq(&https_url))
@@ -583,15 +1212,13 @@ impl Package {
                             )?
                         };
 
-                        PackageVersion::create(
+                        let version_size = github_data.size;
+                        PackageVersion::create_from_repo_data(
                             package_id_,
-                            github_data.version,
-                            github_data.readme_content,
-                            github_data.license,
+                            github_data,
                             rev_.clone(),
                             -1,
-                            github_data.size,
-                            None,
+                            version_size,
                             &connection,
                         )?;
                     }
@@ -651,20 +1278,23 @@ impl Package {
         search_query: &str,
         sort_field: &PackageSortField,
         sort_order: &PackageSortOrder,
+        filters: &PackageSearchFilters,
         pool: &DieselPgPool,
-    ) -> Result<Vec<(String, String, String, String, i32, i32)>> {
+    ) -> Result<Vec<(String, String, String, String, i32, i32, String, Option<String>, bool)>> {
         let connection = pool.get()?;
-        let field = sort_field.to_column_name();
-        let order = sort_order.to_order_direction();
-        let order_query = format!("packages.{} {}", field, order);
+        let order_query = search_order_query(sort_field, sort_order);
 
-        let result: Vec<(String, String, String, String, i32, i32)> = packages::table
+        let result: Vec<(String, String, String, String, i32, i32, String, Option<String>, bool)> = packages::table
             .inner_join(package_versions::table)
             .filter(name.ilike(format!("%{}%", search_query)))
+            .filter(packages::upstream_package_id.is_null())
+            .filter(packages::archived_at.is_null().and(packages::deletion_scheduled_at.is_null()))
+            .filter(packages::platform.eq(filters.platform_value()).or(include_all(filters.platform.is_none())))
+            .filter(concat(" ", translate(packages::license_spdx, "()", "  "), " ").like(filters.license_pattern()).or(packages::license_spdx.is_null().and(include_all(filters.license_is_unknown()))).or(include_all(filters.license.is_none())))
             .filter(diesel::dsl::sql("TRUE GROUP BY packages.id, name, description, total_downloads_count, packages.created_at, packages.updated_at, slug"))
-            .select((packages::name, packages::description, diesel::dsl::sql::<diesel::sql_types::Text>("max(version) as version"), packages::slug, packages::stars_count, packages::forks_count))
+            .select((packages::name, packages::description, diesel::dsl::sql::<diesel::sql_types::Text>("max(version) as version"), packages::slug, packages::stars_count, packages::forks_count, packages::platform, packages::license_spdx, packages::deprecated_at.is_not_null()))
             .order(diesel::dsl::sql::<diesel::sql_types::Text>(&order_query))
-            .load::<(String, String, String, String, i32, i32)>(&connection)?;
+            .load::<(String, String, String, String, i32, i32, String, Option<String>, bool)>(&connection)?;
 
         Ok(result)
     }
@@ -675,12 +1305,11 @@ impl Package {
         sort_order: &PackageSortOrder,
         page: Option<i64>,
         per_page: Option<i64>,
+        filters: &PackageSearchFilters,
         pool: &DieselPgPool,
     ) -> Result<(Vec<PackageSearchResult>, i64, i64)> {
         let connection = pool.get()?;
-        let field = sort_field.to_column_name();
-        let order = sort_order.to_order_direction();
-        let order_query = format!("packages.{} {}", field, order);
+        let order_query = search_order_query(sort_field, sort_order);
         let search_query: &str = &search_query.split(' ').collect::<Vec<&str>>().join(" & ");
 
         let page = page.unwrap_or(1);
@@ -691,10 +1320,14 @@ impl Package {
 
         let result: (Vec<PackageSearchResult>, i64, i64) = packages::table
             .inner_join(package_versions::table)
-            .select((packages::id, packages::name, packages::description, packages::total_downloads_count, packages::created_at, packages::updated_at, packages::slug, packages::stars_count, packages::forks_count, diesel::dsl::sql::<diesel::sql_types::Text>("max(version) as version")))
+            .select((packages::id, packages::name, packages::description, packages::total_downloads_count, packages::created_at, packages::updated_at, packages::slug, packages::stars_count, packages::forks_count, packages::platform, packages::license_spdx, packages::deprecated_at, diesel::dsl::sql::<diesel::sql_types::Text>("max(version) as version")))
             .filter(name.ilike(format!("%{}%", search_query))
                 .or(tsv.matches(plainto_tsquery(search_query))))
-            .filter(diesel::dsl::sql("TRUE GROUP BY packages.id, name, description, total_downloads_count, packages.created_at, packages.updated_at, packages.slug, packages.stars_count, packages.forks_count")) // workaround since diesel 1.x doesn't support GROUP_BY dsl yet
+            .filter(packages::upstream_package_id.is_null().or(include_all(filters.include_forks)))
+            .filter(packages::archived_at.is_null().and(packages::deletion_scheduled_at.is_null()))
+            .filter(packages::platform.eq(filters.platform_value()).or(include_all(filters.platform.is_none())))
+            .filter(concat(" ", translate(packages::license_spdx, "()", "  "), " ").like(filters.license_pattern()).or(packages::license_spdx.is_null().and(include_all(filters.license_is_unknown()))).or(include_all(filters.license.is_none())))
+            .filter(diesel::dsl::sql("TRUE GROUP BY packages.id, name, description, total_downloads_count, packages.created_at, packages.updated_at, packages.slug, packages.stars_count, packages.forks_count, packages.platform, packages.license_spdx, packages.deprecated_at")) // workaround since diesel 1.x doesn't support GROUP_BY dsl yet
             .order(diesel::dsl::sql::<diesel::sql_types::Text>(&order_query))
             .load_with_pagination(&connection, Some(page), Some(per_page))?;
 
@@ -706,12 +1339,11 @@ impl Package {
         sort_order: &PackageSortOrder,
         page: Option<i64>,
         per_page: Option<i64>,
+        filters: &PackageSearchFilters,
         pool: &DieselPgPool,
     ) -> Result<(Vec<PackageSearchResult>, i64, i64)> {
         let connection = pool.get()?;
-        let field = sort_field.to_column_name();
-        let order = sort_order.to_order_direction();
-        let order_query = format!("packages.{} {}", field, order);
+        let order_query = search_order_query(sort_field, sort_order);
 
         let page = page.unwrap_or(1);
         let per_page = per_page.unwrap_or(PACKAGES_PER_PAGE);
@@ -721,8 +1353,12 @@ impl Package {
 
         let result: (Vec<PackageSearchResult>, i64, i64) = packages::table
             .inner_join(package_versions::table)
-            .select((packages::id, packages::name, packages::description, packages::total_downloads_count, packages::created_at, packages::updated_at, packages::slug, packages::stars_count, packages::forks_count, diesel::dsl::sql::<diesel::sql_types::Text>("max(version) as version")))
-            .filter(diesel::dsl::sql("TRUE GROUP BY packages.id, name, description, total_downloads_count, packages.created_at, packages.updated_at, packages.slug, packages.stars_count, packages.forks_count")) // workaround since diesel 1.x doesn't support GROUP_BY dsl yet
+            .select((packages::id, packages::name, packages::description, packages::total_downloads_count, packages::created_at, packages::updated_at, packages::slug, packages::stars_count, packages::forks_count, packages::platform, packages::license_spdx, packages::deprecated_at, diesel::dsl::sql::<diesel::sql_types::Text>("max(version) as version")))
+            .filter(packages::upstream_package_id.is_null().or(include_all(filters.include_forks)))
+            .filter(packages::archived_at.is_null().and(packages::deletion_scheduled_at.is_null()))
+            .filter(packages::platform.eq(filters.platform_value()).or(include_all(filters.platform.is_none())))
+            .filter(concat(" ", translate(packages::license_spdx, "()", "  "), " ").like(filters.license_pattern()).or(packages::license_spdx.is_null().and(include_all(filters.license_is_unknown()))).or(include_all(filters.license.is_none())))
+            .filter(diesel::dsl::sql("TRUE GROUP BY packages.id, name, description, total_downloads_count, packages.created_at, packages.updated_at, packages.slug, packages.stars_count, packages.forks_count, packages.platform, packages.license_spdx, packages.deprecated_at")) // workaround since diesel 1.x doesn't support GROUP_BY dsl yet
             .order(diesel::dsl::sql::<diesel::sql_types::Text>(&order_query))
             .load_with_pagination(&connection, Some(page), Some(per_page))?;
 
@@ -760,6 +1396,60 @@ impl Package {
     }
 }
 
+// Git urls sent by the Move CLI, e.g `git@github.com:owner/repo.git`, to repository urls
+fn to_https_repo_url(url: &str) -> String {
+    let mut https_url = url.to_owned();
+    if url.starts_with("git@github.com") {
+        https_url = url
+            .replace(':', "/")
+            .replace("git@", "https://")
+            .replace(".git", "");
+    }
+    if https_url.ends_with(".git") {
+        https_url = https_url[0..https_url.len() - 4].to_string();
+    }
+    https_url
+}
+
+/// Whose packages a public profile page lists.
+enum PackageOwner {
+    /// Packages the account owns itself
+    Account(i32),
+    Organization(i32),
+}
+
+// Deprecated packages are listed after the others, whatever the sort order
+fn search_order_query(sort_field: &PackageSortField, sort_order: &PackageSortOrder) -> String {
+    format!(
+        "packages.deprecated_at IS NOT NULL, packages.{} {}",
+        sort_field.to_column_name(),
+        sort_order.to_order_direction()
+    )
+}
+
+// Limits of the deletion rules and grace period, set in the environment
+fn deletion_limit(key: &str, default: i64) -> i64 {
+    std::env::var(key)
+        .ok()
+        .and_then(|limit| limit.parse::<i64>().ok())
+        .unwrap_or(default)
+}
+
+// Always true when a filter is disabled, so that filters can be chained
+// in front of the GROUP BY workaround
+fn include_all(disabled: bool) -> diesel::expression::SqlLiteral<diesel::sql_types::Bool> {
+    diesel::dsl::sql(if disabled { "TRUE" } else { "FALSE" })
+}
+
+// Files missing from the repository are fetched as empty strings
+fn non_empty(value: String) -> Option<String> {
+    if value.is_empty() {
+        None
+    } else {
+        Some(value)
+    }
+}
+
 impl PackageVersion {
     pub fn count(pool: &DieselPgPool) -> Result<i64> {
         let connection = pool.get()?;
@@ -793,13 +1483,61 @@ impl PackageVersion {
             package_id: version_package_id,
             version: version_name,
             readme_content: version_readme_content,
+            license_spdx: normalize_license(&version_license),
             license: version_license,
             rev: version_rev,
             total_files: version_files,
             total_size: version_size,
             downloads_count: version_download.unwrap_or(0),
+            manifest_hash: None,
+            changelog_content: None,
+            license_content: None,
+            readme_html: None,
+            platform: Platform::Unknown.as_str().to_string(),
+            manifest_license: None,
         };
 
+        Self::insert(new_package_version, conn)
+    }
+
+    pub fn create_from_repo_data(
+        version_package_id: i32,
+        github_data: GithubRepoData,
+        version_rev: String,
+        version_files: i32,
+        version_size: i32,
+        conn: &DieselPgConnection,
+    ) -> Result<PackageVersion> {
+        let new_package_version = NewPackageVersion {
+            package_id: version_package_id,
+            version: github_data.version,
+            readme_content: github_data.readme_content,
+            license: github_data.license,
+            rev: version_rev,
+            total_files: version_files,
+            total_size: version_size,
+            downloads_count: 0,
+            manifest_hash: non_empty(github_data.manifest_hash),
+            changelog_content: non_empty(github_data.changelog_content),
+            license_content: non_empty(github_data.license_content),
+            readme_html: non_empty(github_data.readme_html),
+            platform: Platform::from_value(&github_data.platform)
+                .as_str()
+                .to_string(),
+            manifest_license: non_empty(github_data.manifest_license),
+            license_spdx: non_empty(github_data.license_spdx),
+        };
+
+        let record = Self::insert(new_package_version, conn)?;
+        record.insert_dependencies(&github_data.dependencies, conn)?;
+        Ok(record)
+    }
+
+    fn insert(
+        new_package_version: NewPackageVersion,
+        conn: &DieselPgConnection,
+    ) -> Result<PackageVersion> {
+        let version_package_id = new_package_version.package_id;
         let record = diesel::insert_into(package_versions::table)
             .values(new_package_version)
             .get_result::<PackageVersion>(conn)?;
@@ -808,10 +1546,149 @@ impl PackageVersion {
             .filter(packages::id.eq(version_package_id))
             .set(packages::updated_at.eq(now))
             .execute(conn)?;
+        record.update_package_from_latest_version(conn)?;
 
         Ok(record)
     }
 
+    fn insert_dependencies(
+        &self,
+        dependencies: &[ManifestDependency],
+        conn: &DieselPgConnection,
+    ) -> Result<()> {
+        let new_dependencies = dependencies
+            .iter()
+            .map(|dependency| NewPackageVersionDependency {
+                package_version_id: self.id,
+                name: &dependency.name,
+                git_url: dependency.git.as_deref(),
+                rev: dependency.rev.as_deref(),
+                subdir: dependency.subdir.as_deref(),
+                local_path: dependency.local.as_deref(),
+            })
+            .collect::<Vec<NewPackageVersionDependency>>();
+        if !new_dependencies.is_empty() {
+            diesel::insert_into(package_version_dependencies::table)
+                .values(&new_dependencies)
+                .execute(conn)?;
+        }
+        Ok(())
+    }
+
+    /// Fills in the SPDX expression of the versions crawled before licenses were normalized.
+    /// Each version is only looked at once, licenses that aren't recognized stay NULL.
+    /// Returns the number of versions with a recognized license.
+    pub fn normalize_unresolved_licenses(conn: &DieselPgConnection) -> Result<usize> {
+        let unresolved = package_versions
+            .filter(package_versions::license_normalized.eq(false))
+            .select((
+                package_versions::id,
+                package_versions::license,
+                package_versions::manifest_license,
+            ))
+            .load::<(i32, Option<String>, Option<String>)>(conn)?;
+        let mut updated = 0;
+        for (version_id, version_license, version_manifest_license) in unresolved {
+            // the license of the manifest takes precedence, as for new versions
+            let spdx = resolve_license(&[
+                version_manifest_license.as_deref(),
+                version_license.as_deref(),
+            ]);
+            conn.transaction(|| -> Result<()> {
+                let record = diesel::update(package_versions.find(version_id))
+                    .set((
+                        package_versions::license_spdx.eq(&spdx),
+                        package_versions::license_normalized.eq(true),
+                    ))
+                    .get_result::<PackageVersion>(conn)?;
+                if spdx.is_some() {
+                    record.update_package_from_latest_version(conn)?;
+                }
+                Ok(())
+            })?;
+            if spdx.is_some() {
+                updated += 1;
+            }
+        }
+        Ok(updated)
+    }
+
+    // Packages are listed and filtered by the platform and license of their latest version
+    fn update_package_from_latest_version(&self, conn: &DieselPgConnection) -> Result<()> {
+        let latest_version_id = package_versions
+            .filter(package_id.eq(self.package_id))
+            .select(diesel::dsl::max(package_versions::id))
+            .first::<Option<i32>>(conn)?;
+        if latest_version_id == Some(self.id) {
+            diesel::update(packages)
+                .filter(packages::id.eq(self.package_id))
+                .set((
+                    packages::platform.eq(&self.platform),
+                    packages::license_spdx.eq(&self.license_spdx),
+                ))
+                .execute(conn)?;
+        }
+        Ok(())
+    }
+
+    /// Replaces the platform and dependencies detected from the Move.toml of the version.
+    pub fn update_platform(
+        &self,
+        version_platform: Platform,
+        dependencies: &[ManifestDependency],
+        conn: &DieselPgConnection,
+    ) -> Result<PackageVersion> {
+        let record = diesel::update(package_versions.find(self.id))
+            .set((
+                package_versions::platform.eq(version_platform.as_str()),
+                package_versions::platform_detected.eq(true),
+            ))
+            .get_result::<PackageVersion>(conn)?;
+        diesel::delete(
+            package_version_dependencies::table
+                .filter(package_version_dependencies::package_version_id.eq(self.id)),
+        )
+        .execute(conn)?;
+        record.insert_dependencies(dependencies, conn)?;
+        record.update_package_from_latest_version(conn)?;
+        Ok(record)
+    }
+
+    /// Versions crawled before platform detection, along with the repository url of their
+    /// package.
+    pub fn get_without_detected_platform(
+        batch_size: i64,
+        conn: &DieselPgConnection,
+    ) -> Result<Vec<(PackageVersion, String)>> {
+        let records = package_versions
+            .inner_join(packages::table)
+            .filter(package_versions::platform_detected.eq(false))
+            .order_by(package_versions::id.desc())
+            .limit(batch_size)
+            .select((package_versions::all_columns, packages::repository_url))
+            .load::<(PackageVersion, String)>(conn)?;
+        Ok(records)
+    }
+
+    /// For versions whose platform can't be detected, so that they are not looked at again.
+    pub fn mark_platform_detected(&self, conn: &DieselPgConnection) -> Result<()> {
+        diesel::update(package_versions.find(self.id))
+            .set(package_versions::platform_detected.eq(true))
+            .execute(conn)?;
+        Ok(())
+    }
+
+    pub fn get_dependencies(
+        &self,
+        conn: &DieselPgConnection,
+    ) -> Result<Vec<PackageVersionDependency>> {
+        let records = package_version_dependencies::table
+            .filter(package_version_dependencies::package_version_id.eq(self.id))
+            .order_by(package_version_dependencies::name)
+            .load::<PackageVersionDependency>(conn)?;
+        Ok(records)
+    }
+
     pub fn from_package_id(
         uid: i32,
         sort_type: &PackageVersionSort,
@@ -875,6 +1752,7 @@ impl Package {
             slug: slugify_package_name(package_name),
             stars_count: package_stars_count,
             forks_count: package_forks_count,
+            upstream_package_id: None,
         };
 
         let record = diesel::insert_into(packages::table)
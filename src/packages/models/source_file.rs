@@ -0,0 +1,135 @@
+//! Files of package versions, cached from Github: the rev of a version never changes,
+//! so neither does its source tree.
+
+use diesel::prelude::*;
+use diesel::{Insertable, Queryable};
+use jelly::serde::Serialize;
+use jelly::{DieselPgConnection, Result};
+use mockall_double::double;
+
+#[double]
+use crate::github_service::GithubService;
+use crate::github_service::GithubTreeEntry;
+use crate::packages::source::MAX_SOURCE_FILE_SIZE;
+use crate::packages::PackageVersion;
+use crate::schema::package_version_files;
+use crate::utils::presenter::make_package_repo_and_subdir;
+
+#[derive(Clone, Debug, Queryable, Serialize)]
+pub struct PackageVersionFile {
+    pub id: i32,
+    pub package_version_id: i32,
+    /// Relative to the package directory.
+    pub path: String,
+    pub size: i32,
+    #[serde(skip)]
+    pub content: Option<String>,
+}
+
+#[derive(Insertable)]
+#[table_name = "package_version_files"]
+struct NewPackageVersionFile<'a> {
+    package_version_id: i32,
+    path: &'a str,
+    size: i32,
+}
+
+impl PackageVersionFile {
+    pub fn get_by_package_version(version_id: i32, conn: &DieselPgConnection) -> Result<Vec<Self>> {
+        let records = package_version_files::table
+            .filter(package_version_files::package_version_id.eq(version_id))
+            .order_by(package_version_files::path)
+            .load::<Self>(conn)?;
+        Ok(records)
+    }
+
+    /// Stores the files listed by `GithubService::fetch_source_tree`, files already cached are kept.
+    pub fn cache_tree(
+        version_id: i32,
+        files: &[GithubTreeEntry],
+        conn: &DieselPgConnection,
+    ) -> Result<Vec<Self>> {
+        let new_files = files
+            .iter()
+            .map(|file| NewPackageVersionFile {
+                package_version_id: version_id,
+                path: &file.path,
+                size: file.size,
+            })
+            .collect::<Vec<NewPackageVersionFile>>();
+        if !new_files.is_empty() {
+            diesel::insert_into(package_version_files::table)
+                .values(&new_files)
+                .on_conflict((
+                    package_version_files::package_version_id,
+                    package_version_files::path,
+                ))
+                .do_nothing()
+                .execute(conn)?;
+        }
+        Self::get_by_package_version(version_id, conn)
+    }
+
+    /// Only files small enough to be rendered are cached.
+    pub fn cache_content(
+        version_id: i32,
+        path: &str,
+        content: &str,
+        conn: &DieselPgConnection,
+    ) -> Result<()> {
+        if content.len() > MAX_SOURCE_FILE_SIZE {
+            return Ok(());
+        }
+        diesel::update(
+            package_version_files::table
+                .filter(package_version_files::package_version_id.eq(version_id))
+                .filter(package_version_files::path.eq(path)),
+        )
+        .set(package_version_files::content.eq(content))
+        .execute(conn)?;
+        Ok(())
+    }
+
+    /// The files of the version at its rev, listed from Github the first time.
+    pub fn get_or_fetch_tree(
+        package_version: &PackageVersion,
+        repository_url: &str,
+        github_service: &GithubService,
+        conn: &DieselPgConnection,
+    ) -> Result<Vec<Self>> {
+        let files = Self::get_by_package_version(package_version.id, conn)?;
+        let rev = match package_version.rev.as_deref() {
+            Some(rev) if !rev.is_empty() && files.is_empty() => rev,
+            _ => return Ok(files),
+        };
+        let (repo_url, subdir) = make_package_repo_and_subdir(repository_url);
+        let tree = github_service.fetch_source_tree(&repo_url, rev, &subdir)?;
+        Self::cache_tree(package_version.id, &tree, conn)
+    }
+
+    /// The content of the file, fetched from Github the first time.
+    /// `None` when Github doesn't have the file anymore.
+    pub fn get_or_fetch_content(
+        &self,
+        package_version: &PackageVersion,
+        repository_url: &str,
+        github_service: &GithubService,
+        conn: &DieselPgConnection,
+    ) -> Result<Option<String>> {
+        if let Some(content) = &self.content {
+            return Ok(Some(content.clone()));
+        }
+        let rev = package_version.rev.as_deref().unwrap_or("");
+        let (repo_url, subdir) = make_package_repo_and_subdir(repository_url);
+        let repo_path = if subdir.is_empty() {
+            self.path.clone()
+        } else {
+            format!("{}/{}", subdir, self.path)
+        };
+        let content = github_service.fetch_source_file(&repo_url, rev, &repo_path)?;
+        if let Some(content) = &content {
+            Self::cache_content(self.package_version_id, &self.path, content, conn)?;
+        }
+        Ok(content)
+    }
+}
@@ -1,8 +1,11 @@
 use regex::Regex;
 use crate::test::{DatabaseTestContext, DB_POOL};
 
-use crate::github_service::GithubRepoData;
+use crate::github_service::{GithubRepoData, GithubTreeEntry};
+use crate::packages::models::source_file::PackageVersionFile;
 use crate::packages::models::*;
+use crate::packages::platform::{ManifestDependency, Platform};
+use crate::utils::license::UNKNOWN_LICENSE_FILTER;
 use crate::test::util::{create_stub_packages, setup_user};
 
 fn setup(account_id_: Option<i32>) -> Result<()> {
@@ -91,6 +94,7 @@ async fn search_by_single_word_works() {
         &PackageSortOrder::Desc,
         Some(1),
         None,
+        &PackageSearchFilters::default(),
         pool,
     )
     .unwrap();
@@ -112,6 +116,7 @@ async fn search_by_multiple_words_works() {
         &PackageSortOrder::Desc,
         Some(1),
         None,
+        &PackageSearchFilters::default(),
         pool,
     )
     .unwrap();
@@ -133,6 +138,7 @@ async fn search_return_multiple_result() {
         &PackageSortOrder::Desc,
         Some(1),
         Some(1),
+        &PackageSearchFilters::default(),
         pool,
     )
     .unwrap();
@@ -148,6 +154,7 @@ async fn search_return_multiple_result() {
         &PackageSortOrder::Desc,
         Some(2),
         Some(1),
+        &PackageSearchFilters::default(),
         pool,
     )
     .unwrap();
@@ -168,6 +175,7 @@ async fn search_by_partial_name_works() {
         &PackageSortOrder::Desc,
         Some(1),
         Some(2),
+        &PackageSearchFilters::default(),
         pool,
     )
     .unwrap();
@@ -190,6 +198,7 @@ async fn search_by_partial_description_works() {
         &PackageSortOrder::Asc,
         Some(1),
         Some(2),
+        &PackageSearchFilters::default(),
         pool,
     )
     .unwrap();
@@ -213,6 +222,7 @@ async fn search_sorted_by_newly_added_works() {
         &PackageSortOrder::Desc,
         Some(1),
         Some(2),
+        &PackageSearchFilters::default(),
         pool,
     )
     .unwrap();
@@ -258,6 +268,7 @@ async fn search_sorted_by_recently_updated_works() {
         &PackageSortOrder::Desc,
         Some(1),
         Some(2),
+        &PackageSearchFilters::default(),
         &DB_POOL,
     )
     .unwrap();
@@ -279,6 +290,7 @@ async fn all_packages_with_pagination() {
         &PackageSortOrder::Desc,
         Some(1),
         Some(2),
+        &PackageSearchFilters::default(),
         pool,
     )
     .unwrap();
@@ -294,6 +306,7 @@ async fn all_packages_with_pagination() {
         &PackageSortOrder::Desc,
         Some(2),
         Some(2),
+        &PackageSearchFilters::default(),
         pool,
     )
     .unwrap();
@@ -335,6 +348,7 @@ async fn all_packages_with_pagination_and_sort_by_recently_updated() {
         &PackageSortOrder::Desc,
         Some(1),
         Some(2),
+        &PackageSearchFilters::default(),
         pool,
     )
     .unwrap();
@@ -349,6 +363,7 @@ async fn all_packages_with_pagination_and_sort_by_recently_updated() {
         &PackageSortOrder::Desc,
         Some(2),
         Some(2),
+        &PackageSearchFilters::default(),
         pool,
     )
     .unwrap();
@@ -368,6 +383,7 @@ async fn all_packages_with_pagination_and_sort_by_newly_added() {
         &PackageSortOrder::Desc,
         Some(1),
         Some(2),
+        &PackageSearchFilters::default(),
         pool,
     )
     .unwrap();
@@ -383,6 +399,7 @@ async fn all_packages_with_pagination_and_sort_by_newly_added() {
         &PackageSortOrder::Desc,
         Some(2),
         Some(2),
+        &PackageSearchFilters::default(),
         pool,
     )
     .unwrap();
@@ -415,6 +432,7 @@ async fn create_package_works() {
                 forks_count: 0,
                 url: "".to_string(),
                 rev: "".to_string(),
+                ..Default::default()
             })
         });
 
@@ -454,6 +472,7 @@ async fn create_package_works() {
                 forks_count: 0,
                 url: "".to_string(),
                 rev: "".to_string(),
+                ..Default::default()
             })
         });
     let uid2 = Package::create(
@@ -505,6 +524,7 @@ async fn create_package_works() {
                 forks_count: 0,
                 url: "".to_string(),
                 rev: "".to_string(),
+                ..Default::default()
             })
         });
 
@@ -548,6 +568,7 @@ async fn get_versions_by_latest() {
                 forks_count: 0,
                 url: "".to_string(),
                 rev: "".to_string(),
+                ..Default::default()
             })
         });
 
@@ -606,6 +627,7 @@ async fn get_versions_by_oldest() {
                 forks_count: 0,
                 url: "".to_string(),
                 rev: "".to_string(),
+                ..Default::default()
             })
         });
 
@@ -662,6 +684,7 @@ async fn get_versions_by_most_downloads() {
                 forks_count: 0,
                 url: "".to_string(),
                 rev: "".to_string(),
+                ..Default::default()
             })
         });
 
@@ -780,6 +803,7 @@ async fn increase_download_count_works() {
                 forks_count: 0,
                 url: "".to_string(),
                 rev: "".to_string(),
+                ..Default::default()
             })
         });
 
@@ -838,6 +862,7 @@ async fn increase_download_count_for_nonexistent_package() {
                 forks_count: 0,
                 url: "".to_string(),
                 rev: "".to_string(),
+                ..Default::default()
             })
         });
 
@@ -936,6 +961,7 @@ async fn increase_download_count_for_multiple_versions() {
                 forks_count: 0,
                 url: "".to_string(),
                 rev: "".to_string(),
+                ..Default::default()
             })
         });
 
@@ -1065,6 +1091,7 @@ async fn get_by_account_with_pagination() {
         &PackageSortOrder::Desc,
         Some(2),
         Some(2),
+        &PackageSearchFilters::default(),
         pool,
     )
     .unwrap();
@@ -1098,3 +1125,731 @@ async fn get_package() {
     assert_eq!(res.slug, "the-first-package");
     assert_eq!(res.name, "The first package");
 }
+
+#[actix_rt::test]
+async fn create_from_crawled_data_links_forks_and_copies_to_upstream() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+
+    let upstream = Package::create_from_crawled_data(
+        "https://github.com/move-language/move/tree/main/language/move-stdlib",
+        "",
+        "rev",
+        -1,
+        0,
+        None,
+        GithubRepoData {
+            name: "MoveStdlib".to_string(),
+            version: "1.5.0".to_string(),
+            manifest_hash: "stdlib-hash".to_string(),
+            ..Default::default()
+        },
+        &DB_POOL,
+    )
+    .unwrap();
+    assert_eq!(upstream.upstream_package_id, None);
+
+    let fork = Package::create_from_crawled_data(
+        "https://github.com/someone/move/tree/main/language/move-stdlib",
+        "",
+        "rev",
+        -1,
+        0,
+        None,
+        GithubRepoData {
+            name: "MoveStdlib".to_string(),
+            version: "1.5.1".to_string(),
+            fork_source_url: Some("https://github.com/move-language/move".to_string()),
+            ..Default::default()
+        },
+        &DB_POOL,
+    )
+    .unwrap();
+    assert_eq!(fork.upstream_package_id, Some(upstream.id));
+
+    let copy = Package::create_from_crawled_data(
+        "https://github.com/someone/vendored/tree/main/deps/move-stdlib",
+        "",
+        "rev",
+        -1,
+        0,
+        None,
+        GithubRepoData {
+            name: "MoveStdlib".to_string(),
+            version: "1.5.0".to_string(),
+            manifest_hash: "stdlib-hash".to_string(),
+            ..Default::default()
+        },
+        &DB_POOL,
+    )
+    .unwrap();
+    assert_eq!(copy.upstream_package_id, Some(upstream.id));
+    let conn = DB_POOL.get().unwrap();
+    assert_eq!(copy.get_upstream(&conn).unwrap().unwrap().id, upstream.id);
+
+    let (search_result, total_count, _) = Package::search(
+        "MoveStdlib",
+        &PackageSortField::Name,
+        &PackageSortOrder::Asc,
+        None,
+        None,
+        &PackageSearchFilters::default(),
+        &DB_POOL,
+    )
+    .unwrap();
+    assert_eq!(total_count, 1);
+    assert_eq!(search_result[0].id, upstream.id);
+
+    let (_, total_count, _) = Package::all_packages(
+        &PackageSortField::Name,
+        &PackageSortOrder::Asc,
+        None,
+        None,
+        &PackageSearchFilters {
+            include_forks: true,
+            ..Default::default()
+        },
+        &DB_POOL,
+    )
+    .unwrap();
+    assert_eq!(total_count, 3);
+}
+
+#[actix_rt::test]
+async fn create_from_crawled_data_stores_changelog_and_license() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+
+    let package = Package::create_from_crawled_data(
+        "https://github.com/move-language/move",
+        "",
+        "rev",
+        -1,
+        0,
+        None,
+        GithubRepoData {
+            name: "Move".to_string(),
+            version: "1.0.0".to_string(),
+            changelog_content: "## 1.0.0".to_string(),
+            ..Default::default()
+        },
+        &DB_POOL,
+    )
+    .unwrap();
+
+    let version =
+        &PackageVersion::from_package_id(package.id, &PackageVersionSort::Latest, &DB_POOL)
+            .unwrap()[0];
+    assert_eq!(version.changelog_content, Some("## 1.0.0".to_string()));
+    assert_eq!(version.license_content, None);
+}
+
+#[actix_rt::test]
+async fn create_from_crawled_data_stores_platform_and_dependencies() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+
+    let package = Package::create_from_crawled_data(
+        "https://github.com/aptos-labs/coin",
+        "",
+        "rev",
+        -1,
+        0,
+        None,
+        GithubRepoData {
+            name: "AptosCoin".to_string(),
+            version: "1.0.0".to_string(),
+            platform: "aptos".to_string(),
+            dependencies: vec![ManifestDependency {
+                name: "AptosFramework".to_string(),
+                git: Some("https://github.com/aptos-labs/aptos-core.git".to_string()),
+                rev: Some("main".to_string()),
+                ..Default::default()
+            }],
+            ..Default::default()
+        },
+        &DB_POOL,
+    )
+    .unwrap();
+    Package::create_test_package(
+        &"Unknown".to_string(),
+        &"https://github.com/owner/unknown".to_string(),
+        &"".to_string(),
+        &"1.0.0".to_string(),
+        &"".to_string(),
+        &"".to_string(),
+        &"".to_string(),
+        0,
+        0,
+        0,
+        0,
+        None,
+        &DB_POOL,
+    )
+    .unwrap();
+
+    let package = Package::get(package.id, &DB_POOL).unwrap();
+    assert_eq!(package.platform, "aptos");
+    let version =
+        &PackageVersion::from_package_id(package.id, &PackageVersionSort::Latest, &DB_POOL)
+            .unwrap()[0];
+    assert_eq!(version.platform, "aptos");
+    let dependencies = version.get_dependencies(&DB_POOL.get().unwrap()).unwrap();
+    assert_eq!(dependencies.len(), 1);
+    assert_eq!(dependencies[0].name, "AptosFramework");
+    assert_eq!(dependencies[0].rev, Some("main".to_string()));
+
+    let (packages_result, total_count, _) = Package::all_packages(
+        &PackageSortField::Name,
+        &PackageSortOrder::Asc,
+        None,
+        None,
+        &PackageSearchFilters {
+            platform: Some(Platform::Aptos),
+            ..Default::default()
+        },
+        &DB_POOL,
+    )
+    .unwrap();
+    assert_eq!(total_count, 1);
+    assert_eq!(packages_result[0].name, "AptosCoin");
+    assert_eq!(packages_result[0].platform, "aptos");
+}
+
+#[actix_rt::test]
+async fn all_packages_filters_by_license() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    for (package_name, package_license) in [("dual", "mit or apache-2.0"), ("unlicensed", "")] {
+        Package::create_test_package(
+            &package_name.to_string(),
+            &format!("https://github.com/owner/{}", package_name),
+            &"".to_string(),
+            &"1.0.0".to_string(),
+            &"".to_string(),
+            &package_license.to_string(),
+            &"".to_string(),
+            0,
+            0,
+            0,
+            0,
+            None,
+            &DB_POOL,
+        )
+        .unwrap();
+    }
+    let filter_by_license = |filter_license: &str| {
+        let (packages_result, _, _) = Package::all_packages(
+            &PackageSortField::Name,
+            &PackageSortOrder::Asc,
+            None,
+            None,
+            &PackageSearchFilters {
+                license: Some(filter_license.to_string()),
+                ..Default::default()
+            },
+            &DB_POOL,
+        )
+        .unwrap();
+        packages_result
+            .into_iter()
+            .map(|package| package.name)
+            .collect::<Vec<String>>()
+    };
+
+    assert_eq!(filter_by_license("MIT"), vec!["dual"]);
+    assert_eq!(filter_by_license("Apache-2.0"), vec!["dual"]);
+    assert!(filter_by_license("GPL-3.0-only").is_empty());
+    assert_eq!(filter_by_license(UNKNOWN_LICENSE_FILTER), vec!["unlicensed"]);
+
+    let package = Package::get_by_name("dual", &DB_POOL).unwrap();
+    assert_eq!(package.license_spdx, Some("MIT OR Apache-2.0".to_string()));
+}
+
+#[test]
+fn package_search_filters_encode_the_license() {
+    let filters = PackageSearchFilters {
+        license: Some("MIT OR Apache-2.0+&x".to_string()),
+        ..Default::default()
+    };
+    assert_eq!(
+        filters.to_query_string(),
+        "&license=MIT%20OR%20Apache-2.0%2B%26x"
+    );
+}
+
+#[actix_rt::test]
+async fn normalize_unresolved_licenses_fills_in_crawled_versions() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    for (package_name, package_license) in [("apache", "Apache License 2.0"), ("custom", "Custom")]
+    {
+        Package::create_test_package(
+            &package_name.to_string(),
+            &format!("https://github.com/owner/{}", package_name),
+            &"".to_string(),
+            &"1.0.0".to_string(),
+            &"".to_string(),
+            &package_license.to_string(),
+            &"".to_string(),
+            0,
+            0,
+            0,
+            0,
+            None,
+            &DB_POOL,
+        )
+        .unwrap();
+    }
+    let conn = DB_POOL.get().unwrap();
+    diesel::update(package_versions)
+        .set((
+            package_versions::license_spdx.eq(None::<String>),
+            package_versions::license_normalized.eq(false),
+        ))
+        .execute(&conn)
+        .unwrap();
+    diesel::update(packages)
+        .set(packages::license_spdx.eq(None::<String>))
+        .execute(&conn)
+        .unwrap();
+
+    assert_eq!(
+        PackageVersion::normalize_unresolved_licenses(&conn).unwrap(),
+        1
+    );
+    let apache = Package::get_by_name("apache", &DB_POOL).unwrap();
+    assert_eq!(apache.license_spdx, Some("Apache-2.0".to_string()));
+    let custom = Package::get_by_name("custom", &DB_POOL).unwrap();
+    assert_eq!(custom.license_spdx, None);
+
+    // Unrecognized licenses are not looked at again
+    let unnormalized_count = package_versions
+        .filter(package_versions::license_normalized.eq(false))
+        .count()
+        .get_result::<i64>(&conn)
+        .unwrap();
+    assert_eq!(unnormalized_count, 0);
+    assert_eq!(
+        PackageVersion::normalize_unresolved_licenses(&conn).unwrap(),
+        0
+    );
+}
+
+#[actix_rt::test]
+async fn all_packages_lists_deprecated_packages_last() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    for package_name in ["coin", "coin2", "wallet"] {
+        Package::create_test_package(
+            &package_name.to_string(),
+            &format!("https://github.com/owner/{}", package_name),
+            &"".to_string(),
+            &"1.0.0".to_string(),
+            &"".to_string(),
+            &"".to_string(),
+            &"".to_string(),
+            0,
+            0,
+            0,
+            0,
+            None,
+            &DB_POOL,
+        )
+        .unwrap();
+    }
+    let conn = DB_POOL.get().unwrap();
+    let coin = Package::get_by_name("coin", &DB_POOL)
+        .unwrap()
+        .deprecate("Unmaintained.", Some("coin2"), &conn)
+        .unwrap();
+    assert_eq!(
+        coin.deprecation_warning(),
+        Some("Package coin is deprecated: Unmaintained. Use coin2 instead.".to_string())
+    );
+
+    let (packages_result, _, _) = Package::all_packages(
+        &PackageSortField::Name,
+        &PackageSortOrder::Asc,
+        None,
+        None,
+        &PackageSearchFilters::default(),
+        &DB_POOL,
+    )
+    .unwrap();
+    let names = packages_result
+        .iter()
+        .map(|package| package.name.as_str())
+        .collect::<Vec<&str>>();
+    assert_eq!(names, vec!["coin2", "wallet", "coin"]);
+    assert!(packages_result[2].deprecated_at.is_some());
+
+    let coin = coin.undeprecate(&conn).unwrap();
+    assert_eq!(coin.deprecation_warning(), None);
+    assert_eq!(coin.deprecation_successor, None);
+}
+
+#[actix_rt::test]
+async fn rename_slug_keeps_old_slug_for_redirects() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    setup(None).unwrap();
+    let conn = DB_POOL.get().unwrap();
+
+    let package = Package::get_by_slug("the-first-package", &conn).unwrap();
+    let package = package.rename_slug("first-package", &conn).unwrap();
+    assert_eq!(package.slug, "first-package");
+    assert_eq!(
+        Package::get_renamed_slug("the-first-package", &conn).unwrap(),
+        Some("first-package".to_string())
+    );
+
+    let package = package.rename_slug("first", &conn).unwrap();
+    assert_eq!(
+        Package::get_renamed_slug("the-first-package", &conn).unwrap(),
+        Some("first".to_string())
+    );
+    assert_eq!(
+        Package::get_renamed_slug("first-package", &conn).unwrap(),
+        Some("first".to_string())
+    );
+
+    // Renaming back to an old slug drops it from the history
+    let package = package.rename_slug("the-first-package", &conn).unwrap();
+    assert_eq!(package.slug, "the-first-package");
+    assert!(Package::get_renamed_slug("the-first-package", &conn)
+        .unwrap()
+        .is_none());
+    assert!(Package::get_renamed_slug("unknown", &conn)
+        .unwrap()
+        .is_none());
+}
+
+#[actix_rt::test]
+async fn old_slugs_are_not_given_to_other_packages() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    setup(None).unwrap();
+    let conn = DB_POOL.get().unwrap();
+
+    let package = Package::get_by_slug("the-first-package", &conn).unwrap();
+    package.rename_slug("first-package", &conn).unwrap();
+
+    let other = Package::get_by_slug("the-first-diva", &conn).unwrap();
+    assert!(other.rename_slug("the-first-package", &conn).is_err());
+    assert_eq!(
+        Package::get_by_slug("the-first-diva", &conn).unwrap().id,
+        other.id
+    );
+
+    let crawled = Package::create_from_crawled_data(
+        "https://github.com/someone/the-first-package",
+        "",
+        "rev",
+        -1,
+        0,
+        None,
+        GithubRepoData {
+            name: "The first package".to_string(),
+            version: "1.0.0".to_string(),
+            ..Default::default()
+        },
+        &DB_POOL,
+    )
+    .unwrap();
+    assert!(crawled.slug.starts_with("the-first-package-"));
+    assert_eq!(
+        Package::get_renamed_slug("the-first-package", &conn).unwrap(),
+        Some("first-package".to_string())
+    );
+}
+
+#[actix_rt::test]
+async fn archived_and_deleted_packages_are_hidden_from_search() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let uid = setup_user(None, None);
+    for package_name in ["coin", "coin2", "wallet"] {
+        Package::create_test_package(
+            &package_name.to_string(),
+            &format!("https://github.com/owner/{}", package_name),
+            &"".to_string(),
+            &"1.0.0".to_string(),
+            &"".to_string(),
+            &"".to_string(),
+            &"".to_string(),
+            0,
+            0,
+            0,
+            0,
+            Some(uid),
+            &DB_POOL,
+        )
+        .unwrap();
+    }
+    let conn = DB_POOL.get().unwrap();
+    let coin = Package::get_by_name("coin", &DB_POOL)
+        .unwrap()
+        .archive(&conn)
+        .unwrap();
+    assert!(coin.is_read_only());
+    let coin2 = Package::get_by_name("coin2", &DB_POOL)
+        .unwrap()
+        .schedule_deletion(&conn)
+        .unwrap();
+    assert!(coin2.is_read_only());
+
+    let (packages_result, total_count, _) = Package::all_packages(
+        &PackageSortField::Name,
+        &PackageSortOrder::Asc,
+        None,
+        None,
+        &PackageSearchFilters::default(),
+        &DB_POOL,
+    )
+    .unwrap();
+    assert_eq!(total_count, 1);
+    assert_eq!(packages_result[0].name, "wallet");
+    let (packages_result, _, _) = Package::search(
+        "coin",
+        &PackageSortField::Name,
+        &PackageSortOrder::Asc,
+        None,
+        None,
+        &PackageSearchFilters::default(),
+        &DB_POOL,
+    )
+    .unwrap();
+    assert!(packages_result.is_empty());
+    let owned_packages = Package::get_by_account(uid, &DB_POOL).unwrap();
+    assert_eq!(owned_packages.len(), 1);
+    assert_eq!(owned_packages[0].name, "wallet");
+    let (_, total_count, _) = Package::get_by_account_paginated(
+        uid,
+        &PackageSortField::Name,
+        &PackageSortOrder::Asc,
+        None,
+        None,
+        &DB_POOL,
+    )
+    .unwrap();
+    assert_eq!(total_count, 1);
+
+    // Still resolvable by slug, and listed again once restored
+    assert!(Package::get_by_slug("coin", &conn).is_ok());
+    coin.unarchive(&conn).unwrap();
+    coin2.cancel_deletion(&conn).unwrap();
+    let (_, total_count, _) = Package::all_packages(
+        &PackageSortField::Name,
+        &PackageSortOrder::Asc,
+        None,
+        None,
+        &PackageSearchFilters::default(),
+        &DB_POOL,
+    )
+    .unwrap();
+    assert_eq!(total_count, 3);
+}
+
+#[actix_rt::test]
+async fn deletion_blocker_checks_downloads_and_dependents() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let conn = DB_POOL.get().unwrap();
+    let popular_id = Package::create_test_package_with_downloads(
+        &"popular".to_string(),
+        &"https://github.com/owner/popular".to_string(),
+        &"".to_string(),
+        10_000,
+        0,
+        0,
+        &DB_POOL,
+    )
+    .unwrap();
+    assert_eq!(
+        Package::get(popular_id, &DB_POOL)
+            .unwrap()
+            .deletion_blocker(&conn)
+            .unwrap(),
+        Some(DeletionBlocker::Downloads(10_000))
+    );
+
+    for package_name in ["coin", "wallet"] {
+        Package::create_test_package(
+            &package_name.to_string(),
+            &format!("https://github.com/owner/{}", package_name),
+            &"".to_string(),
+            &"1.0.0".to_string(),
+            &"".to_string(),
+            &"".to_string(),
+            &"".to_string(),
+            0,
+            0,
+            0,
+            0,
+            None,
+            &DB_POOL,
+        )
+        .unwrap();
+    }
+    let coin = Package::get_by_name("coin", &DB_POOL).unwrap();
+    let wallet = Package::get_by_name("wallet", &DB_POOL).unwrap();
+    assert_eq!(coin.deletion_blocker(&conn).unwrap(), None);
+
+    PackageVersion::from_package_id(wallet.id, &PackageVersionSort::Latest, &DB_POOL)
+        .unwrap()
+        .remove(0)
+        .update_platform(
+            Platform::Unknown,
+            &[ManifestDependency {
+                name: "Coin".to_string(),
+                git: Some("https://github.com/owner/coin.git".to_string()),
+                rev: Some("main".to_string()),
+                ..Default::default()
+            }],
+            &conn,
+        )
+        .unwrap();
+    assert_eq!(
+        coin.deletion_blocker(&conn).unwrap(),
+        Some(DeletionBlocker::Dependents(1))
+    );
+    assert_eq!(wallet.deletion_blocker(&conn).unwrap(), None);
+}
+
+#[actix_rt::test]
+async fn status_changes_keep_updated_at() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    setup(None).unwrap();
+    let conn = DB_POOL.get().unwrap();
+    let package = Package::get_by_name("The first package", &DB_POOL).unwrap();
+
+    let deprecated = package.deprecate("unmaintained", None, &conn).unwrap();
+    let archived = deprecated.archive(&conn).unwrap();
+    let scheduled = archived.schedule_deletion(&conn).unwrap();
+    scheduled.update_quality_score(42, &conn).unwrap();
+
+    let package_after = Package::get(package.id, &DB_POOL).unwrap();
+    assert_eq!(package_after.quality_score, 42);
+    assert_eq!(package_after.updated_at, package.updated_at);
+
+    let renamed = package_after.rename_slug("renamed", &conn).unwrap();
+    assert!(renamed.updated_at > package.updated_at);
+}
+
+#[actix_rt::test]
+async fn source_files_are_fetched_from_github_once() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    let package_id_ = Package::create_test_package(
+        &"coin".to_string(),
+        &"https://github.com/owner/coin/tree/main/packages/coin".to_string(),
+        &"".to_string(),
+        &"1.0.0".to_string(),
+        &"".to_string(),
+        &"".to_string(),
+        &"rev".to_string(),
+        0,
+        0,
+        0,
+        0,
+        None,
+        &DB_POOL,
+    )
+    .unwrap();
+    let package = Package::get(package_id_, &DB_POOL).unwrap();
+    let version =
+        PackageVersion::from_package_id(package_id_, &PackageVersionSort::Latest, &DB_POOL)
+            .unwrap()
+            .remove(0);
+    let conn = DB_POOL.get().unwrap();
+
+    let mut mock_github_service = GithubService::new();
+    mock_github_service
+        .expect_fetch_source_tree()
+        .times(1)
+        .returning(|_, _, _| {
+            Ok(vec![
+                GithubTreeEntry {
+                    path: "Move.toml".to_string(),
+                    entry_type: "blob".to_string(),
+                    size: 10,
+                },
+                GithubTreeEntry {
+                    path: "sources/coin.move".to_string(),
+                    entry_type: "blob".to_string(),
+                    size: 20,
+                },
+            ])
+        });
+    mock_github_service
+        .expect_fetch_source_file()
+        .withf(|_, rev, path| rev == "rev" && path == "packages/coin/sources/coin.move")
+        .times(1)
+        .returning(|_, _, _| Ok(Some("module 0x1::coin {}".to_string())));
+
+    let files = PackageVersionFile::get_or_fetch_tree(
+        &version,
+        &package.repository_url,
+        &mock_github_service,
+        &conn,
+    )
+    .unwrap();
+    assert_eq!(files.len(), 2);
+    assert_eq!(files[1].path, "sources/coin.move");
+    assert_eq!(files[1].size, 20);
+
+    let files = PackageVersionFile::get_or_fetch_tree(
+        &version,
+        &package.repository_url,
+        &mock_github_service,
+        &conn,
+    )
+    .unwrap();
+    assert_eq!(files.len(), 2);
+    let content = files[1]
+        .get_or_fetch_content(
+            &version,
+            &package.repository_url,
+            &mock_github_service,
+            &conn,
+        )
+        .unwrap();
+    assert_eq!(content.as_deref(), Some("module 0x1::coin {}"));
+
+    let files = PackageVersionFile::get_by_package_version(version.id, &conn).unwrap();
+    let content = files[1]
+        .get_or_fetch_content(
+            &version,
+            &package.repository_url,
+            &mock_github_service,
+            &conn,
+        )
+        .unwrap();
+    assert_eq!(content.as_deref(), Some("module 0x1::coin {}"));
+}
+
+#[actix_rt::test]
+async fn source_tree_is_empty_for_versions_without_rev() {
+    crate::test::init();
+    let _ctx = DatabaseTestContext::new();
+    setup(None).unwrap();
+    let package = Package::get_by_name("The first package", &DB_POOL).unwrap();
+    let version =
+        PackageVersion::from_package_id(package.id, &PackageVersionSort::Latest, &DB_POOL)
+            .unwrap()
+            .remove(0);
+    let conn = DB_POOL.get().unwrap();
+
+    let mut mock_github_service = GithubService::new();
+    mock_github_service.expect_fetch_source_tree().never();
+    let files = PackageVersionFile::get_or_fetch_tree(
+        &version,
+        &package.repository_url,
+        &mock_github_service,
+        &conn,
+    )
+    .unwrap();
+    assert!(files.is_empty());
+}
@@ -0,0 +1,288 @@
+//! Target platform detection from the `[dependencies]` and `[addresses]` of a Move.toml.
+
+use jelly::serde::{Deserialize, Serialize};
+use toml::Value;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Platform {
+    Aptos,
+    Sui,
+    Starcoin,
+    MoveStdlib,
+    Unknown,
+}
+
+// Lowercased dependency names, dependency git urls and named addresses of each framework
+struct PlatformMarkers {
+    platform: Platform,
+    dependency_names: &'static [&'static str],
+    git_urls: &'static [&'static str],
+    addresses: &'static [&'static str],
+}
+
+// Chains come first, their frameworks also depend on the Move stdlib
+const PLATFORM_MARKERS: [PlatformMarkers; 4] = [
+    PlatformMarkers {
+        platform: Platform::Aptos,
+        dependency_names: &[
+            "aptosframework",
+            "aptosstdlib",
+            "aptostoken",
+            "aptostokenobjects",
+        ],
+        git_urls: &["aptos-labs/aptos-core", "aptos-labs/aptos-framework"],
+        addresses: &["aptos_framework", "aptos_std", "aptos_token"],
+    },
+    PlatformMarkers {
+        platform: Platform::Sui,
+        dependency_names: &["sui", "suiframework", "suisystem"],
+        git_urls: &["mystenlabs/sui"],
+        addresses: &["sui", "sui_system"],
+    },
+    PlatformMarkers {
+        platform: Platform::Starcoin,
+        dependency_names: &["starcoinframework", "starcoinstdlib"],
+        git_urls: &["starcoinorg/"],
+        addresses: &[
+            "starcoinframework",
+            "starcoinassociation",
+            "starcoin_framework",
+        ],
+    },
+    PlatformMarkers {
+        platform: Platform::MoveStdlib,
+        dependency_names: &["movestdlib", "movenursery"],
+        git_urls: &["move-language/move", "diem/diem", "diem/move"],
+        addresses: &["std"],
+    },
+];
+
+impl Platform {
+    /// Platforms that can be detected, in the order they are listed in filters.
+    pub const DETECTED: [Platform; 4] = [
+        Platform::Aptos,
+        Platform::Sui,
+        Platform::Starcoin,
+        Platform::MoveStdlib,
+    ];
+
+    /// Value stored in the database and used in urls.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Platform::Aptos => "aptos",
+            Platform::Sui => "sui",
+            Platform::Starcoin => "starcoin",
+            Platform::MoveStdlib => "move_stdlib",
+            Platform::Unknown => "unknown",
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Platform::Aptos => "Aptos",
+            Platform::Sui => "Sui",
+            Platform::Starcoin => "Starcoin",
+            Platform::MoveStdlib => "Move stdlib",
+            Platform::Unknown => "Unknown",
+        }
+    }
+
+    pub fn from_value(value: &str) -> Self {
+        Platform::DETECTED
+            .iter()
+            .find(|platform| platform.as_str() == value)
+            .copied()
+            .unwrap_or(Platform::Unknown)
+    }
+
+    /// Parses the `platform` query parameter, unknown values don't filter anything.
+    pub fn from_filter(value: Option<&str>) -> Option<Self> {
+        value
+            .map(Platform::from_value)
+            .filter(|platform| *platform != Platform::Unknown)
+    }
+
+    pub fn filter_options() -> Vec<PlatformOption> {
+        Platform::DETECTED
+            .iter()
+            .map(|platform| PlatformOption {
+                value: platform.as_str(),
+                label: platform.label(),
+            })
+            .collect()
+    }
+}
+
+/// A platform listed in the filters and chips of the templates.
+#[derive(Debug, Serialize)]
+pub struct PlatformOption {
+    pub value: &'static str,
+    pub label: &'static str,
+}
+
+/// A `[dependencies]` entry of a Move.toml.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ManifestDependency {
+    pub name: String,
+    pub git: Option<String>,
+    pub rev: Option<String>,
+    pub subdir: Option<String>,
+    pub local: Option<String>,
+}
+
+pub fn parse_dependencies(move_toml_content: &str) -> Vec<ManifestDependency> {
+    let manifest = match move_toml_content.parse::<Value>() {
+        Ok(manifest) => manifest,
+        Err(_) => return vec![],
+    };
+    let dependencies = match manifest.get("dependencies").and_then(Value::as_table) {
+        Some(dependencies) => dependencies,
+        None => return vec![],
+    };
+    let field = |dependency: &Value, key: &str| {
+        dependency
+            .get(key)
+            .and_then(Value::as_str)
+            .map(|value| value.to_string())
+    };
+    dependencies
+        .iter()
+        .map(|(name, dependency)| ManifestDependency {
+            name: name.clone(),
+            git: field(dependency, "git"),
+            rev: field(dependency, "rev"),
+            subdir: field(dependency, "subdir"),
+            local: field(dependency, "local"),
+        })
+        .collect()
+}
+
+fn named_addresses(move_toml_content: &str) -> Vec<String> {
+    move_toml_content
+        .parse::<Value>()
+        .ok()
+        .and_then(|manifest| {
+            manifest
+                .get("addresses")
+                .and_then(Value::as_table)
+                .map(|addresses| addresses.keys().map(|name| name.to_lowercase()).collect())
+        })
+        .unwrap_or_default()
+}
+
+/// Classifies a package by the framework it depends on. Dependencies win over named
+/// addresses, which are sometimes copied from other packages.
+pub fn detect_platform(move_toml_content: &str) -> Platform {
+    let dependencies = parse_dependencies(move_toml_content);
+    let depends_on = |markers: &PlatformMarkers| {
+        dependencies.iter().any(|dependency| {
+            let git = dependency.git.as_deref().unwrap_or("").to_lowercase();
+            markers
+                .dependency_names
+                .contains(&dependency.name.to_lowercase().as_str())
+                || markers.git_urls.iter().any(|url| git.contains(url))
+        })
+    };
+    if let Some(markers) = PLATFORM_MARKERS.iter().find(|markers| depends_on(markers)) {
+        return markers.platform;
+    }
+
+    let addresses = named_addresses(move_toml_content);
+    PLATFORM_MARKERS
+        .iter()
+        .find(|markers| {
+            addresses
+                .iter()
+                .any(|address| markers.addresses.contains(&address.as_str()))
+        })
+        .map_or(Platform::Unknown, |markers| markers.platform)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn detect_platform_uses_dependencies() {
+        let aptos = r#"
+[package]
+name = "coin"
+version = "0.1.0"
+
+[dependencies]
+AptosFramework = { git = "https://github.com/aptos-labs/aptos-core.git", subdir = "aptos-move/framework/aptos-framework", rev = "main" }
+"#;
+        assert_eq!(detect_platform(aptos), Platform::Aptos);
+
+        // the Sui stdlib is a Move stdlib dependency hosted in the Sui repository
+        let sui = r#"
+[package]
+name = "nft"
+version = "0.1.0"
+
+[dependencies]
+MoveStdlib = { git = "https://github.com/MystenLabs/sui.git", subdir = "crates/sui-framework/deps/move-stdlib", rev = "devnet" }
+"#;
+        assert_eq!(detect_platform(sui), Platform::Sui);
+
+        let stdlib = r#"
+[package]
+name = "vector_utils"
+version = "0.1.0"
+
+[dependencies]
+MoveStdlib = { git = "https://github.com/move-language/move.git", subdir = "language/move-stdlib", rev = "main" }
+"#;
+        assert_eq!(detect_platform(stdlib), Platform::MoveStdlib);
+    }
+
+    #[test]
+    fn detect_platform_falls_back_to_named_addresses() {
+        let starcoin = r#"
+[package]
+name = "swap"
+version = "0.1.0"
+
+[addresses]
+StarcoinFramework = "0x1"
+"#;
+        assert_eq!(detect_platform(starcoin), Platform::Starcoin);
+        assert_eq!(
+            detect_platform("[package]\nname = \"empty\"\nversion = \"0.1.0\"\n"),
+            Platform::Unknown
+        );
+        assert_eq!(detect_platform("not toml"), Platform::Unknown);
+    }
+
+    #[test]
+    fn from_filter_ignores_unknown_platforms() {
+        assert_eq!(Platform::from_filter(Some("sui")), Some(Platform::Sui));
+        assert_eq!(Platform::from_filter(Some("unknown")), None);
+        assert_eq!(Platform::from_filter(Some("")), None);
+        assert_eq!(Platform::from_filter(None), None);
+    }
+
+    #[test]
+    fn parse_dependencies_works() {
+        let dependencies = parse_dependencies(
+            "[dependencies]\nLocal = { local = \"../local\" }\nSui = { git = \"https://github.com/MystenLabs/sui.git\", rev = \"devnet\" }\n",
+        );
+        assert_eq!(
+            dependencies,
+            vec![
+                ManifestDependency {
+                    name: "Local".to_string(),
+                    local: Some("../local".to_string()),
+                    ..Default::default()
+                },
+                ManifestDependency {
+                    name: "Sui".to_string(),
+                    git: Some("https://github.com/MystenLabs/sui.git".to_string()),
+                    rev: Some("devnet".to_string()),
+                    ..Default::default()
+                },
+            ]
+        );
+    }
+}
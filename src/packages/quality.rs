@@ -0,0 +1,258 @@
+//! Quality score of a package, out of 100, made of checks that can be verified on the
+//! package page. It ranks maintained packages above crawled abandonware.
+
+use jelly::chrono::{DateTime, Utc};
+use jelly::serde::Serialize;
+
+pub const MAX_QUALITY_SCORE: i32 = 100;
+// READMEs shorter than this are mostly a title and a one line description
+const SUBSTANTIVE_README_LENGTH: usize = 500;
+
+/// What the score of a package is computed from, gathered by `Package::quality_inputs`.
+#[derive(Debug)]
+pub struct QualityInputs {
+    pub readme_content: String,
+    pub license_spdx: Option<String>,
+    pub description: String,
+    pub versions: Vec<String>,
+    pub latest_version_at: Option<DateTime<Utc>>,
+    pub is_repository_active: bool,
+    pub stars_count: i32,
+    pub downloads_count: i32,
+    pub dependents_count: i64,
+    pub is_claimed: bool,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct QualityCheck {
+    pub label: &'static str,
+    pub points: i32,
+    pub max_points: i32,
+    pub detail: String,
+}
+
+#[derive(Debug, PartialEq, Serialize)]
+pub struct QualityScore {
+    pub total: i32,
+    pub checks: Vec<QualityCheck>,
+}
+
+fn check(label: &'static str, points: i32, max_points: i32, detail: String) -> QualityCheck {
+    QualityCheck {
+        label,
+        points: points.min(max_points),
+        max_points,
+        detail,
+    }
+}
+
+// 0 for 0, then grows by `points_per_step` each time `count` is multiplied by `base`
+fn log_points(count: i64, base: f64, points_per_step: f64) -> i32 {
+    ((count.max(0) as f64 + 1.0).log(base) * points_per_step).floor() as i32
+}
+
+fn plural(count: i64, noun: &str) -> String {
+    if count == 1 {
+        format!("{} {}", count, noun)
+    } else {
+        format!("{} {}s", count, noun)
+    }
+}
+
+fn readme_check(readme_content: &str) -> QualityCheck {
+    let length = readme_content.trim().chars().count();
+    let (points, detail) = if length >= SUBSTANTIVE_README_LENGTH {
+        (15, "Has a README".to_string())
+    } else if length > 0 {
+        (5, "README is very short".to_string())
+    } else {
+        (0, "No README".to_string())
+    };
+    check("README", points, 15, detail)
+}
+
+fn versions_check(versions: &[String]) -> QualityCheck {
+    let valid_count = versions
+        .iter()
+        .filter(|version| semver::Version::parse(version).is_ok())
+        .count();
+    let (points, detail) = if versions.is_empty() {
+        (0, "No versions".to_string())
+    } else if valid_count == versions.len() {
+        (10, "Every version follows semantic versioning".to_string())
+    } else if valid_count > 0 {
+        (
+            5,
+            format!(
+                "{} of {} versions follow semantic versioning",
+                valid_count,
+                versions.len()
+            ),
+        )
+    } else {
+        (0, "No version follows semantic versioning".to_string())
+    };
+    check("Semantic versioning", points, 10, detail)
+}
+
+fn activity_check(inputs: &QualityInputs, now: DateTime<Utc>) -> QualityCheck {
+    if !inputs.is_repository_active {
+        return check(
+            "Recent activity",
+            0,
+            15,
+            "Repository is archived or missing".to_string(),
+        );
+    }
+    let (points, detail) = match inputs.latest_version_at {
+        Some(latest_version_at) => {
+            let days = (now - latest_version_at).num_days().max(0);
+            let points = match days {
+                0..=180 => 15,
+                181..=365 => 10,
+                366..=730 => 5,
+                _ => 0,
+            };
+            (
+                points,
+                format!("Latest version published {} ago", plural(days, "day")),
+            )
+        }
+        None => (0, "No versions".to_string()),
+    };
+    check("Recent activity", points, 15, detail)
+}
+
+/// Scores a package, `now` is the time the recent activity is measured from.
+pub fn compute_quality(inputs: &QualityInputs, now: DateTime<Utc>) -> QualityScore {
+    let checks = vec![
+        readme_check(&inputs.readme_content),
+        match &inputs.license_spdx {
+            Some(license) => check("License", 10, 10, license.clone()),
+            None => check("License", 0, 10, "No recognized license".to_string()),
+        },
+        if inputs.description.trim().is_empty() {
+            check("Description", 0, 5, "No description".to_string())
+        } else {
+            check("Description", 5, 5, "Has a description".to_string())
+        },
+        versions_check(&inputs.versions),
+        activity_check(inputs, now),
+        check(
+            "Stars",
+            log_points(inputs.stars_count as i64, 2.0, 2.0),
+            10,
+            plural(inputs.stars_count as i64, "star"),
+        ),
+        check(
+            "Downloads",
+            log_points(inputs.downloads_count as i64, 10.0, 4.0),
+            10,
+            plural(inputs.downloads_count as i64, "download"),
+        ),
+        check(
+            "Dependents",
+            (inputs.dependents_count * 2).min(10) as i32,
+            10,
+            format!(
+                "{} depending on it",
+                plural(inputs.dependents_count, "package")
+            ),
+        ),
+        if inputs.is_claimed {
+            check("Owner", 15, 15, "Published by its owner".to_string())
+        } else {
+            check(
+                "Owner",
+                0,
+                15,
+                "Crawled, not claimed by an owner".to_string(),
+            )
+        },
+    ];
+    QualityScore {
+        total: checks.iter().map(|check| check.points).sum(),
+        checks,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use jelly::chrono::Duration;
+
+    fn maintained_package(now: DateTime<Utc>) -> QualityInputs {
+        QualityInputs {
+            readme_content: "A".repeat(SUBSTANTIVE_README_LENGTH),
+            license_spdx: Some("MIT".to_string()),
+            description: "Coins".to_string(),
+            versions: vec!["0.1.0".to_string(), "1.0.0".to_string()],
+            latest_version_at: Some(now - Duration::days(10)),
+            is_repository_active: true,
+            stars_count: 31,
+            downloads_count: 316,
+            dependents_count: 5,
+            is_claimed: true,
+        }
+    }
+
+    #[test]
+    fn compute_quality_gives_full_score_to_maintained_packages() {
+        let now = Utc::now();
+        let score = compute_quality(&maintained_package(now), now);
+        assert_eq!(score.total, MAX_QUALITY_SCORE);
+        assert_eq!(
+            score
+                .checks
+                .iter()
+                .map(|check| check.max_points)
+                .sum::<i32>(),
+            MAX_QUALITY_SCORE
+        );
+    }
+
+    #[test]
+    fn compute_quality_demotes_crawled_abandonware() {
+        let now = Utc::now();
+        let score = compute_quality(
+            &QualityInputs {
+                readme_content: "# coin".to_string(),
+                license_spdx: None,
+                versions: vec!["v1".to_string()],
+                latest_version_at: Some(now - Duration::days(1000)),
+                stars_count: 1,
+                downloads_count: 0,
+                dependents_count: 0,
+                is_claimed: false,
+                ..maintained_package(now)
+            },
+            now,
+        );
+        // a short README, a description and a single star
+        assert_eq!(score.total, 5 + 5 + 2);
+        let activity = &score.checks[4];
+        assert_eq!(activity.points, 0);
+        assert_eq!(activity.detail, "Latest version published 1000 days ago");
+    }
+
+    #[test]
+    fn compute_quality_ignores_activity_of_archived_repositories() {
+        let now = Utc::now();
+        let score = compute_quality(
+            &QualityInputs {
+                is_repository_active: false,
+                ..maintained_package(now)
+            },
+            now,
+        );
+        assert_eq!(score.total, MAX_QUALITY_SCORE - 15);
+    }
+
+    #[test]
+    fn log_points_works() {
+        assert_eq!(log_points(0, 2.0, 2.0), 0);
+        assert_eq!(log_points(1, 2.0, 2.0), 2);
+        assert_eq!(log_points(31, 2.0, 2.0), 10);
+        assert_eq!(log_points(9, 10.0, 4.0), 4);
+    }
+}
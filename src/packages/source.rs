@@ -0,0 +1,215 @@
+//! Source file browser helpers: path checks and Move syntax highlighting.
+
+/// Larger files are linked to Github instead of being rendered.
+pub const MAX_SOURCE_FILE_SIZE: usize = 512 * 1024;
+
+const MOVE_KEYWORDS: [&str; 37] = [
+    "abort",
+    "acquires",
+    "address",
+    "as",
+    "break",
+    "const",
+    "continue",
+    "copy",
+    "drop",
+    "else",
+    "entry",
+    "enum",
+    "false",
+    "friend",
+    "fun",
+    "has",
+    "if",
+    "inline",
+    "invariant",
+    "key",
+    "let",
+    "loop",
+    "match",
+    "module",
+    "move",
+    "mut",
+    "native",
+    "phantom",
+    "public",
+    "return",
+    "script",
+    "spec",
+    "store",
+    "struct",
+    "true",
+    "use",
+    "while",
+];
+const MOVE_PRIMITIVE_TYPES: [&str; 9] = [
+    "u8", "u16", "u32", "u64", "u128", "u256", "bool", "signer", "vector",
+];
+
+/// Paths come from the url, only plain relative paths inside the package are allowed.
+pub fn is_safe_path(path: &str) -> bool {
+    !path.is_empty()
+        && !path.starts_with('/')
+        && !path.contains('\\')
+        && path
+            .split('/')
+            .all(|segment| !segment.is_empty() && segment != "." && segment != "..")
+}
+
+/// Returns one escaped html string per line, `.move` files are highlighted.
+pub fn render_source_lines(path: &str, content: &str) -> Vec<String> {
+    if path.ends_with(".move") {
+        highlight_move(content)
+    } else {
+        content.lines().map(escape).collect()
+    }
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+struct Lines {
+    lines: Vec<String>,
+    current: String,
+}
+
+impl Lines {
+    // Tokens spanning several lines, e.g block comments, are split so that every line stays valid html
+    fn push(&mut self, text: &str, class: Option<&str>) {
+        let mut pieces = text.split('\n').peekable();
+        while let Some(piece) = pieces.next() {
+            let piece = piece.trim_end_matches('\r');
+            if !piece.is_empty() {
+                match class {
+                    Some(class) => self.current.push_str(&format!(
+                        "<span class=\"move-{}\">{}</span>",
+                        class,
+                        escape(piece)
+                    )),
+                    None => self.current.push_str(&escape(piece)),
+                }
+            }
+            if pieces.peek().is_some() {
+                self.lines.push(std::mem::take(&mut self.current));
+            }
+        }
+    }
+}
+
+/// Highlights keywords, primitive types, comments, literals and addresses of Move code.
+pub fn highlight_move(content: &str) -> Vec<String> {
+    let mut lines = Lines {
+        lines: vec![],
+        current: String::new(),
+    };
+    let chars = content.char_indices().collect::<Vec<(usize, char)>>();
+    let byte_at = |index: usize| chars.get(index).map_or(content.len(), |(byte, _)| *byte);
+    let char_at = |index: usize| chars.get(index).map(|(_, c)| *c);
+
+    let mut i = 0;
+    while i < chars.len() {
+        let c = chars[i].1;
+        let start = i;
+        let class = if c == '/' && char_at(i + 1) == Some('/') {
+            while i < chars.len() && chars[i].1 != '\n' {
+                i += 1;
+            }
+            Some("comment")
+        } else if c == '/' && char_at(i + 1) == Some('*') {
+            i += 2;
+            while i < chars.len() && !(chars[i].1 == '*' && char_at(i + 1) == Some('/')) {
+                i += 1;
+            }
+            i = (i + 2).min(chars.len());
+            Some("comment")
+        } else if (c == 'b' || c == 'x') && char_at(i + 1) == Some('"') {
+            i += 2;
+            while i < chars.len() && chars[i].1 != '"' {
+                if chars[i].1 == '\\' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            i = (i + 1).min(chars.len());
+            Some("string")
+        } else if c == '@' {
+            i += 1;
+            while i < chars.len() && (chars[i].1.is_alphanumeric() || chars[i].1 == '_') {
+                i += 1;
+            }
+            Some("address")
+        } else if c.is_ascii_digit() {
+            while i < chars.len() && (chars[i].1.is_alphanumeric() || chars[i].1 == '_') {
+                i += 1;
+            }
+            Some("number")
+        } else if c.is_alphabetic() || c == '_' {
+            while i < chars.len() && (chars[i].1.is_alphanumeric() || chars[i].1 == '_') {
+                i += 1;
+            }
+            let word = &content[byte_at(start)..byte_at(i)];
+            if MOVE_KEYWORDS.contains(&word) {
+                Some("keyword")
+            } else if MOVE_PRIMITIVE_TYPES.contains(&word) {
+                Some("type")
+            } else {
+                None
+            }
+        } else {
+            i += 1;
+            None
+        };
+        lines.push(&content[byte_at(start)..byte_at(i)], class);
+    }
+    if !lines.current.is_empty() {
+        lines.lines.push(lines.current);
+    }
+    lines.lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn is_safe_path_rejects_paths_outside_of_package() {
+        assert!(is_safe_path("sources/coin.move"));
+        assert!(!is_safe_path("../secret"));
+        assert!(!is_safe_path("sources/../../secret"));
+        assert!(!is_safe_path("/etc/passwd"));
+        assert!(!is_safe_path(""));
+    }
+
+    #[test]
+    fn highlight_move_works() {
+        let lines = highlight_move(
+            "module 0x1::coin {\n    /* supply\n    cap */\n    const MAX: u64 = 100; // max\n    fun f(): vector<u8> { b\"<a>\"; @std }\n}",
+        );
+        assert_eq!(lines.len(), 6);
+        assert_eq!(
+            lines[0],
+            "<span class=\"move-keyword\">module</span> <span class=\"move-number\">0x1</span>::coin {"
+        );
+        assert_eq!(
+            lines[1],
+            "    <span class=\"move-comment\">/* supply</span>"
+        );
+        assert_eq!(lines[2], "<span class=\"move-comment\">    cap */</span>");
+        assert_eq!(
+            lines[3],
+            "    <span class=\"move-keyword\">const</span> MAX: <span class=\"move-type\">u64</span> = <span class=\"move-number\">100</span>; <span class=\"move-comment\">// max</span>"
+        );
+        assert!(lines[4].contains("<span class=\"move-string\">b&quot;&lt;a&gt;&quot;</span>"));
+        assert!(lines[4].contains("<span class=\"move-address\">@std</span>"));
+    }
+
+    #[test]
+    fn render_source_lines_escapes_other_files() {
+        let lines = render_source_lines("Move.toml", "[package]\nname = \"<coin>\"");
+        assert_eq!(lines, vec!["[package]", "name = &quot;&lt;coin&gt;&quot;"]);
+    }
+}
@@ -1,20 +1,43 @@
-use jelly::actix_web::{web::Path, web::Query, HttpRequest};
+use diesel::result::Error as DBError;
+use jelly::actix_session::UserSession;
+use jelly::actix_web::{web, web::Path, web::Query, HttpRequest};
 use jelly::anyhow::anyhow;
+use jelly::chrono::{NaiveDateTime, Utc};
 use jelly::forms::TextField;
 use jelly::prelude::*;
 use jelly::request::DatabasePool;
-use jelly::Result;
-use std::collections::HashSet;
+use jelly::{DieselPgConnection, Result};
+use mockall_double::double;
+use std::collections::{HashMap, HashSet};
 
 use crate::accounts::Account;
+use crate::constants::{CLAIM_PACKAGE_SESSION_KEY, GITHUB_ADMIN_REPOSITORY_SESSION_KEY};
+#[double]
+use crate::github_service::GithubService;
+use crate::organizations::models::{Organization, OrganizationRole};
 use crate::package_collaborators::models::external_invitation::ExternalInvitation;
+use crate::package_collaborators::models::owner_approval::OwnerApproval;
 use crate::package_collaborators::models::owner_invitation::OwnerInvitation;
-use crate::package_collaborators::package_collaborator::PackageCollaborator;
-use crate::packages::models::{PackageSortField, PackageSortOrder, PACKAGES_PER_PAGE};
+use crate::package_collaborators::models::package_claim::{PackageClaim, VERIFICATION_FILE};
+use crate::package_collaborators::package_collaborator::{PackageCollaborator, Role};
+use crate::package_collaborators::policy::{self, Action};
+use crate::packages::docs::search::{search_symbols, MAX_SYMBOL_RESULTS};
+use crate::packages::docs::MoveModule;
+use crate::packages::models::audit_log::PackageAuditLog;
+use crate::packages::models::source_file::PackageVersionFile;
+use crate::packages::models::{
+    PackageSearchFilters, PackageSortField, PackageSortOrder, PACKAGES_PER_PAGE,
+};
+use crate::packages::platform::Platform;
+use crate::packages::quality::compute_quality;
+use crate::packages::source;
 use crate::packages::{Package, PackageVersion, PackageVersionSort};
+use crate::utils::license::{parse_license_filter, FILTER_LICENSES, UNKNOWN_LICENSE_FILTER};
+use crate::utils::markdown::{self, MarkdownLinks};
 use crate::utils::presenter;
+use crate::utils::request_utils;
 
-use super::serializer::{SerializableInvitation, Status};
+use super::serializer::{SerializableApproval, SerializableInvitation, Status};
 
 #[derive(serde::Serialize, serde::Deserialize)]
 struct PackageShowParams {
@@ -25,9 +48,107 @@ pub async fn show_package(
     request: HttpRequest,
     Path(package_slug): Path<String>,
 ) -> Result<HttpResponse> {
+    if let Some(response) = redirect_renamed_package(&request, &package_slug)? {
+        return Ok(response);
+    }
+    let (mut ctx, package, _) = package_tab_context(&request, &package_slug, "readme")?;
+    let conn = request.db_pool()?.get()?;
+    let quality = compute_quality(&package.quality_inputs(&conn)?, Utc::now());
+
+    ctx.insert("quality", &quality);
+    request.render(200, "packages/show.html", ctx)
+}
+
+pub async fn show_package_changelog(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+) -> Result<HttpResponse> {
+    if let Some(response) = redirect_renamed_package(&request, &package_slug)? {
+        return Ok(response);
+    }
+    let (mut ctx, package, package_version) =
+        package_tab_context(&request, &package_slug, "changelog")?;
+    // the changelog comes from the repository as is, it is only shown sanitized
+    let changelog_html = package_version
+        .changelog_content
+        .as_deref()
+        .map(|content| {
+            markdown::render_markdown(content, &markdown_links(&package, &package_version))
+        })
+        .unwrap_or_default();
+    ctx.insert("changelog_html", &changelog_html);
+    request.render(200, "packages/changelog.html", ctx)
+}
+
+// Relative links of a file crawled from the package directory point to the same rev
+fn markdown_links(package: &Package, package_version: &PackageVersion) -> MarkdownLinks {
+    let (repo_url, subdir) = presenter::make_package_repo_and_subdir(&package.repository_url);
+    let rev = package_version.rev.as_deref().unwrap_or("master");
+    MarkdownLinks {
+        raw_base: format!(
+            "{}/{}",
+            repo_url.replace("https://github.com", "https://raw.githubusercontent.com"),
+            rev
+        ),
+        blob_base: format!("{}/blob/{}", repo_url, rev),
+        dir: subdir,
+    }
+}
+
+pub async fn show_package_license(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+) -> Result<HttpResponse> {
+    if let Some(response) = redirect_renamed_package(&request, &package_slug)? {
+        return Ok(response);
+    }
+    render_package_tab(request, package_slug, "license", "packages/license.html")
+}
+
+// Old slugs of renamed packages permanently redirect to the same page under the current slug
+fn redirect_renamed_package(
+    request: &HttpRequest,
+    package_slug: &str,
+) -> Result<Option<HttpResponse>> {
+    let conn = request.db_pool()?.get()?;
+    match Package::get_by_slug(package_slug, &conn) {
+        Err(Error::Database(DBError::NotFound)) => {}
+        _ => return Ok(None),
+    }
+    let current_slug = match Package::get_renamed_slug(package_slug, &conn)? {
+        Some(current_slug) => current_slug,
+        None => return Ok(None),
+    };
+    let old_prefix = format!("/packages/{}", package_slug);
+    let location = match request.path().strip_prefix(&old_prefix) {
+        Some(rest) => format!("/packages/{}{}", current_slug, rest),
+        None => format!("/packages/{}", current_slug),
+    };
+    Ok(Some(request_utils::permanent_redirect(
+        &location,
+        request.query_string(),
+    )))
+}
+
+fn render_package_tab(
+    request: HttpRequest,
+    package_slug: String,
+    package_tab: &str,
+    template: &str,
+) -> Result<HttpResponse> {
+    let (ctx, _, _) = package_tab_context(&request, &package_slug, package_tab)?;
+    request.render(200, template, ctx)
+}
+
+// Readme, changelog, license and source tabs show the same package version, selected with `?version=`
+fn package_tab_context(
+    request: &HttpRequest,
+    package_slug: &str,
+    package_tab: &str,
+) -> Result<(Context, Package, PackageVersion)> {
     let db = request.db_pool()?;
     let conn = db.get()?;
-    let package = Package::get_by_slug(&package_slug, &conn)?;
+    let package = Package::get_by_slug(package_slug, &conn)?;
     let collaborators = PackageCollaborator::get_by_package_id(package.id, &conn)?;
 
     let default_version: String = String::from("");
@@ -48,21 +169,190 @@ pub async fn show_package(
     let (account_name, account_slug_url) = presenter::make_account_name(&package, db)?;
     let (instruction_repo_url, instruction_subdir) =
         presenter::make_package_install_instruction(&package.repository_url);
+    let upstream_package = package.get_upstream(&conn)?;
+
+    let mut ctx = Context::new();
+    ctx.insert("package", &package);
+    ctx.insert("upstream_package", &upstream_package);
+    ctx.insert("package_version", &package_version);
+    ctx.insert("package_tab", package_tab);
+    ctx.insert("is_crawled", &collaborators.is_empty());
+    ctx.insert("is_anonymous", &request.user()?.is_anonymous);
+
+    ctx.insert("account_name", &account_name);
+    ctx.insert("account_slug_url", &account_slug_url);
+    ctx.insert("instruction_subdir", &instruction_subdir);
+    ctx.insert("instruction_repo_url", &instruction_repo_url);
+    ctx.insert("version_query", &params.version);
+    ctx.insert(
+        "platform_label",
+        &Platform::from_filter(Some(&package_version.platform)).map(|platform| platform.label()),
+    );
+    Ok((ctx, package, package_version))
+}
 
-    request.render(200, "packages/show.html", {
-        let mut ctx = Context::new();
-        ctx.insert("package", &package);
-        ctx.insert("package_version", &package_version);
-        ctx.insert("package_tab", "readme");
-        ctx.insert("is_crawled", &collaborators.is_empty());
-        ctx.insert("is_anonymous", &request.user()?.is_anonymous);
+pub async fn show_package_source(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+) -> Result<HttpResponse> {
+    if let Some(response) = redirect_renamed_package(&request, &package_slug)? {
+        return Ok(response);
+    }
+    let (mut ctx, package, package_version) =
+        package_tab_context(&request, &package_slug, "source")?;
+
+    let files = with_github_service(&request, move |github_service, conn| {
+        PackageVersionFile::get_or_fetch_tree(
+            &package_version,
+            &package.repository_url,
+            github_service,
+            conn,
+        )
+    })
+    .await
+    .unwrap_or_else(|e| {
+        error!(
+            "Error getting source tree. package: {}, error: {:?}",
+            package_slug, e
+        );
+        vec![]
+    });
+
+    ctx.insert("source_unavailable", &files.is_empty());
+    ctx.insert("files", &files);
+    request.render(200, "packages/source.html", ctx)
+}
 
-        ctx.insert("account_name", &account_name);
-        ctx.insert("account_slug_url", &account_slug_url);
-        ctx.insert("instruction_subdir", &instruction_subdir);
-        ctx.insert("instruction_repo_url", &instruction_repo_url);
-        ctx
+pub async fn show_package_source_file(
+    request: HttpRequest,
+    Path((package_slug, file_path)): Path<(String, String)>,
+) -> Result<HttpResponse> {
+    if let Some(response) = redirect_renamed_package(&request, &package_slug)? {
+        return Ok(response);
+    }
+    if !source::is_safe_path(&file_path) {
+        return request.render(404, "404.html", Context::new());
+    }
+    let (mut ctx, package, package_version) =
+        package_tab_context(&request, &package_slug, "source")?;
+    let rev = match &package_version.rev {
+        Some(rev) => rev.clone(),
+        None => return request.render(404, "404.html", Context::new()),
+    };
+    let (repo_url, subdir) = presenter::make_package_repo_and_subdir(&package.repository_url);
+    let repo_path = if subdir.is_empty() {
+        file_path.clone()
+    } else {
+        format!("{}/{}", subdir, file_path)
+    };
+
+    let requested_path = file_path.clone();
+    // `None` for files that are not in the tree, `Some(None)` for files too large to render
+    let content = with_github_service(&request, move |github_service, conn| {
+        let tree = PackageVersionFile::get_or_fetch_tree(
+            &package_version,
+            &package.repository_url,
+            github_service,
+            conn,
+        )?;
+        let file = match tree.into_iter().find(|file| file.path == requested_path) {
+            Some(file) => file,
+            None => return Ok(None),
+        };
+        if file.size as usize > source::MAX_SOURCE_FILE_SIZE {
+            return Ok(Some(None));
+        }
+        let content = file.get_or_fetch_content(
+            &package_version,
+            &package.repository_url,
+            github_service,
+            conn,
+        )?;
+        Ok(content.map(Some))
     })
+    .await?;
+    let content = match content {
+        Some(content) => content,
+        None => return request.render(404, "404.html", Context::new()),
+    };
+    let is_too_large = content
+        .as_ref()
+        .map_or(true, |content| content.len() > source::MAX_SOURCE_FILE_SIZE);
+    let lines = match &content {
+        Some(content) if !is_too_large => source::render_source_lines(&file_path, content),
+        _ => vec![],
+    };
+
+    ctx.insert("file_path", &file_path);
+    ctx.insert("file_lines", &lines);
+    ctx.insert("file_too_large", &is_too_large);
+    ctx.insert(
+        "file_github_url",
+        &format!("{}/blob/{}/{}", repo_url, rev, repo_path),
+    );
+    request.render(200, "packages/source_file.html", ctx)
+}
+
+/// Runs `f` on the blocking thread pool, Github calls and the database queries around them
+/// must not hold up the server threads.
+async fn with_github_service<T, F>(request: &HttpRequest, f: F) -> Result<T>
+where
+    F: FnOnce(&GithubService, &DieselPgConnection) -> Result<T> + Send + 'static,
+    T: Send + 'static,
+{
+    let pool = request.db_pool()?.clone();
+    let result = web::block(move || {
+        let conn = pool.get().map_err(|e| anyhow!("{:?}", e))?;
+        f(&GithubService::new(), &conn).map_err(|e| anyhow!("{:?}", e))
+    })
+    .await
+    .map_err(|e| anyhow!("{:?}", e))?;
+    Ok(result)
+}
+
+pub async fn show_package_docs(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+) -> Result<HttpResponse> {
+    if let Some(response) = redirect_renamed_package(&request, &package_slug)? {
+        return Ok(response);
+    }
+    let (mut ctx, _, package_version) = package_tab_context(&request, &package_slug, "docs")?;
+    let conn = request.db_pool()?.get()?;
+    let modules = MoveModule::get_by_package_version(package_version.id, &conn)?;
+
+    ctx.insert("modules", &modules);
+    request.render(200, "packages/docs.html", ctx)
+}
+
+pub async fn show_package_module_docs(
+    request: HttpRequest,
+    Path((package_slug, module_address, module_name)): Path<(String, String, String)>,
+) -> Result<HttpResponse> {
+    if let Some(response) = redirect_renamed_package(&request, &package_slug)? {
+        return Ok(response);
+    }
+    let (mut ctx, _, package_version) = package_tab_context(&request, &package_slug, "docs")?;
+    let conn = request.db_pool()?.get()?;
+    let module = match MoveModule::get_by_package_version_and_path(
+        package_version.id,
+        &module_address,
+        &module_name,
+        &conn,
+    )? {
+        Some(module) => module,
+        None => return request.render(404, "404.html", Context::new()),
+    };
+
+    ctx.insert(
+        "modules",
+        &MoveModule::get_by_package_version(package_version.id, &conn)?,
+    );
+    ctx.insert("structs", &module.get_structs(&conn)?);
+    ctx.insert("functions", &module.get_functions(&conn)?);
+    ctx.insert("dependencies", &module.get_dependencies(&conn)?);
+    ctx.insert("module", &module);
+    request.render(200, "packages/module_docs.html", ctx)
 }
 
 #[derive(serde::Serialize, serde::Deserialize)]
@@ -74,6 +364,9 @@ pub async fn show_package_versions(
     request: HttpRequest,
     Path(package_slug): Path<String>,
 ) -> Result<HttpResponse> {
+    if let Some(response) = redirect_renamed_package(&request, &package_slug)? {
+        return Ok(response);
+    }
     let db = request.db_pool()?;
     let conn = db.get()?;
     let package = Package::get_by_slug(&package_slug, &conn)?;
@@ -113,6 +406,9 @@ pub async fn show_package_settings(
     request: HttpRequest,
     Path(package_slug): Path<String>,
 ) -> Result<HttpResponse> {
+    if let Some(response) = redirect_renamed_package(&request, &package_slug)? {
+        return Ok(response);
+    }
     let db_pool = request.db_pool()?;
     let db_connection = db_pool.get()?;
     let package = Package::get_by_slug(&package_slug, &db_connection)?;
@@ -120,20 +416,30 @@ pub async fn show_package_settings(
         &PackageVersion::from_package_id(package.id, &PackageVersionSort::Latest, &db_pool)?[0];
 
     // get movey account that is already a collaborator
-    let accepted_ids: Vec<i32> =
-        PackageCollaborator::get_by_package_id(package.id, &db_connection)?;
-    let owner_id = if accepted_ids.len() > 0 {
-        accepted_ids[0]
-    } else {
-        -1
-    };
+    let collaborators = PackageCollaborator::get_all_by_package_id(package.id, &db_connection)?;
+    let roles: HashMap<i32, Role> = collaborators
+        .iter()
+        .filter_map(|collaborator| {
+            Role::from_i32(collaborator.role).map(|role| (collaborator.account_id, role))
+        })
+        .collect();
+    let is_owner = |account_id: &i32| roles.get(account_id) == Some(&Role::Owner);
     // need hashset to find PendingOwner
-    let accepted_ids: HashSet<i32> = accepted_ids.into_iter().collect();
+    let accepted_ids: HashSet<i32> = collaborators
+        .iter()
+        .map(|collaborator| collaborator.account_id)
+        .collect();
     let mut all_invitations: Vec<SerializableInvitation>;
 
-    let mut is_current_user_owner = false;
-    let mut is_user_collaborator = false;
     let user = request.user()?;
+    let current_user_role = if user.is_anonymous {
+        None
+    } else {
+        policy::get_role(&package, user.id, &db_connection)?
+    };
+    let is_current_user_owner = current_user_role == Some(Role::Owner);
+    let is_user_collaborator = current_user_role.is_some() && !is_current_user_owner;
+    let can = |action: Action| current_user_role.map_or(false, |role| role.allows(action));
 
     let current_user_email = if user.is_anonymous {
         None
@@ -143,17 +449,14 @@ pub async fn show_package_settings(
             .and_then(|account| Some(account.email))
     };
 
-    if accepted_ids.contains(&user.id) {
-        if owner_id == user.id {
-            is_current_user_owner = true;
-        } else {
-            is_user_collaborator = true;
-        }
+    if is_current_user_owner || is_user_collaborator {
         // get movey account that received an collaborator invitation
-        let pending_ids: HashSet<i32> =
-            OwnerInvitation::find_by_package_id(package.id, &db_connection)?
-                .into_iter()
+        let expirations: HashMap<i32, NaiveDateTime> =
+            OwnerInvitation::find_all_by_package_id(package.id, &db_connection)?
+                .iter()
+                .map(|invitation| (invitation.invited_user_id, invitation.expires_at()))
                 .collect();
+        let pending_ids: HashSet<i32> = expirations.keys().copied().collect();
         let pending_owners_ids: HashSet<i32> = accepted_ids
             .intersection(&pending_ids)
             // convert &i32 to i32
@@ -172,36 +475,45 @@ pub async fn show_package_settings(
                 } else {
                     account.email.clone()
                 };
-                if account.id == owner_id {
+                if is_owner(&account.id) {
                     SerializableInvitation {
                         status: Status::Owner,
                         email: email_or_gh_login,
+                        role: None,
+                        expires_at: None,
                     }
                 } else if pending_owners_ids.contains(&account.id) {
                     SerializableInvitation {
                         status: Status::PendingOwner,
                         email: email_or_gh_login,
+                        role: roles.get(&account.id).copied(),
+                        expires_at: expirations.get(&account.id).copied(),
                     }
                 } else if accepted_ids.contains(&account.id) {
                     SerializableInvitation {
                         status: Status::Collaborator,
                         email: email_or_gh_login,
+                        role: roles.get(&account.id).copied(),
+                        expires_at: None,
                     }
                 } else {
                     SerializableInvitation {
                         status: Status::PendingCollaborator,
                         email: email_or_gh_login,
+                        role: None,
+                        expires_at: expirations.get(&account.id).copied(),
                     }
                 }
             })
             .collect();
         let mut external_email: Vec<SerializableInvitation> =
-            ExternalInvitation::find_by_package_id(package.id, &db_connection)
-                .unwrap()
+            ExternalInvitation::find_all_by_package_id(package.id, &db_connection)?
                 .iter()
-                .map(|email| SerializableInvitation {
+                .map(|invitation| SerializableInvitation {
                     status: Status::External,
-                    email: email.clone(),
+                    email: invitation.external_user_email.clone(),
+                    role: None,
+                    expires_at: Some(invitation.expires_at()),
                 })
                 .collect();
         all_invitations.append(&mut external_email);
@@ -210,15 +522,19 @@ pub async fn show_package_settings(
             Account::get_accounts(&accepted_ids.into_iter().collect(), &db_connection)?
                 .iter()
                 .map(|account| {
-                    if account.id == owner_id {
+                    if is_owner(&account.id) {
                         SerializableInvitation {
                             status: Status::Owner,
                             email: account.email.clone(),
+                            role: None,
+                            expires_at: None,
                         }
                     } else {
                         SerializableInvitation {
                             status: Status::Collaborator,
                             email: account.email.clone(),
+                            role: roles.get(&account.id).copied(),
+                            expires_at: None,
                         }
                     }
                 })
@@ -226,6 +542,33 @@ pub async fn show_package_settings(
     }
     // Owner -> Collaborator -> PendingCollaborator -> PendingOwner -> External
     all_invitations.sort_by_key(|invitation| invitation.status.clone());
+    let audit_logs = if is_current_user_owner {
+        PackageAuditLog::get_entries_by_package_id(package.id, &db_connection)?
+    } else {
+        vec![]
+    };
+    let owners_count = policy::get_owner_ids(&package, &db_connection)?.len();
+    let requires_approval = policy::requires_approval(&package, &db_connection)?;
+    let approvals = if is_current_user_owner {
+        make_approvals(&package, user.id, &db_connection)?
+    } else {
+        vec![]
+    };
+    let organization = match package.organization_id {
+        Some(organization_id) => Some(Organization::get(organization_id, &db_connection)?),
+        None => None,
+    };
+    // organizations the package can be transferred to
+    let administered_organizations: Vec<Organization> =
+        if is_current_user_owner && organization.is_none() {
+            Organization::get_by_member(user.id, &db_connection)?
+                .into_iter()
+                .filter(|(_, role)| *role == OrganizationRole::Admin as i32)
+                .map(|(organization, _)| organization)
+                .collect()
+        } else {
+            vec![]
+        };
     request.render(200, "packages/owner_settings.html", {
         let mut ctx = Context::new();
         ctx.insert("package", &package);
@@ -238,16 +581,133 @@ pub async fn show_package_settings(
         ctx.insert("current_email", &current_user_email);
         ctx.insert("is_current_user_owner", &is_current_user_owner);
         ctx.insert("is_current_user_collaborator", &is_user_collaborator);
+        ctx.insert("can_invite", &can(Action::Invite));
+        ctx.insert("can_remove_collaborator", &can(Action::RemoveCollaborator));
+        ctx.insert("can_change_role", &can(Action::ChangeRole));
+        ctx.insert("audit_logs", &audit_logs);
+        ctx.insert("owners_count", &owners_count);
+        ctx.insert("requires_approval", &requires_approval);
+        ctx.insert("approvals", &approvals);
+        ctx.insert("organization", &organization);
+        ctx.insert("administered_organizations", &administered_organizations);
         ctx
     })
 }
 
+// Pending approvals with the accounts and organizations they are about
+fn make_approvals(
+    package: &Package,
+    current_user_id: i32,
+    conn: &DieselPgConnection,
+) -> Result<Vec<SerializableApproval>> {
+    let approvals = OwnerApproval::get_by_package_id(package.id, conn)?;
+    let account_ids: Vec<i32> = approvals
+        .iter()
+        .flat_map(|approval| {
+            std::iter::once(approval.requested_by).chain(approval.target_account_id)
+        })
+        .collect();
+    let names: HashMap<i32, String> = Account::get_accounts(&account_ids, conn)?
+        .into_iter()
+        .map(|account| {
+            let name = if account.is_generated_email() {
+                account.github_login.clone().unwrap_or(account.email)
+            } else {
+                account.email
+            };
+            (account.id, name)
+        })
+        .collect();
+    let mut serializable_approvals = vec![];
+    for approval in approvals {
+        let target = match approval.organization_id {
+            Some(organization_id) => Some(Organization::get(organization_id, conn)?.name),
+            None => approval
+                .target_account_id
+                .and_then(|account_id| names.get(&account_id).cloned()),
+        };
+        serializable_approvals.push(SerializableApproval {
+            id: approval.id,
+            action: approval.action,
+            target,
+            requested_by: names
+                .get(&approval.requested_by)
+                .cloned()
+                .unwrap_or_default(),
+            is_requested_by_current_user: approval.requested_by == current_user_id,
+            created_at: approval.created_at,
+        });
+    }
+    Ok(serializable_approvals)
+}
+
+pub async fn show_package_claim(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+) -> Result<HttpResponse> {
+    if let Some(response) = redirect_renamed_package(&request, &package_slug)? {
+        return Ok(response);
+    }
+    let user = request.user()?;
+    let db_pool = request.db_pool()?;
+    let conn = db_pool.get()?;
+    let package = Package::get_by_slug(&package_slug, &conn)?;
+    // the claim, and its token, only exists while nobody owns the package
+    let claim = if PackageClaim::is_claimable(&package, &conn)? {
+        Some(PackageClaim::get_or_create(package.id, user.id, &conn)?)
+    } else {
+        None
+    };
+    let github_login = Account::get(user.id, db_pool)?.github_login;
+    let github_admin_repository = request
+        .get_session()
+        .get::<String>(GITHUB_ADMIN_REPOSITORY_SESSION_KEY)?;
+    let is_github_verified = github_admin_repository.as_ref() == Some(&package.repository_url);
+
+    request.render(200, "packages/claim.html", {
+        let mut ctx = Context::new();
+        ctx.insert("package", &package);
+        ctx.insert("claim", &claim);
+        ctx.insert("github_login", &github_login);
+        ctx.insert("is_github_verified", &is_github_verified);
+        ctx.insert("verification_file", VERIFICATION_FILE);
+        ctx
+    })
+}
+
+/// Signs in with GitHub again, the callback then checks the permissions of the account on
+/// the repository of the package.
+pub async fn start_github_claim(
+    request: HttpRequest,
+    Path(package_slug): Path<String>,
+) -> Result<HttpResponse> {
+    let conn = request.db_pool()?.get()?;
+    let package = Package::get_by_slug(&package_slug, &conn)?;
+    request
+        .get_session()
+        .set(CLAIM_PACKAGE_SESSION_KEY, &package.slug)?;
+    request.redirect("/accounts/oauth")
+}
+
 #[derive(serde::Serialize, serde::Deserialize)]
 pub struct PackageSearchParams {
     pub query: TextField,
     pub field: Option<PackageSortField>,
     pub order: Option<PackageSortOrder>,
     pub page: Option<i64>,
+    pub include_forks: Option<bool>,
+    pub platform: Option<String>,
+    pub license: Option<String>,
+}
+
+impl PackageSearchParams {
+    pub fn filters(&self) -> PackageSearchFilters {
+        PackageSearchFilters {
+            include_forks: self.include_forks.unwrap_or(false),
+            platform: Platform::from_filter(self.platform.as_deref()),
+            license: parse_license_filter(self.license.as_deref()),
+        }
+    }
 }
 
 pub async fn show_search_results(
@@ -265,12 +725,14 @@ pub async fn show_search_results(
             Some(PackageSortOrder::Desc)
         }
     }
+    let filters = search.filters();
     let (packages, total_count, total_pages) = Package::search(
         &search.query.value,
         search.field.as_ref().unwrap(),
         search.order.as_ref().unwrap(),
         search.page,
         None,
+        &filters,
         db,
     )?;
 
@@ -287,6 +749,16 @@ pub async fn show_search_results(
         let mut ctx = Context::new();
         ctx.insert("query", &search.query.value);
         ctx.insert("sort_type", &field_name);
+        ctx.insert("include_forks", &filters.include_forks);
+        ctx.insert(
+            "platform",
+            &filters.platform.map(|platform| platform.as_str()),
+        );
+        ctx.insert("platforms", &Platform::filter_options());
+        ctx.insert("license", &filters.license);
+        ctx.insert("licenses", &FILTER_LICENSES);
+        ctx.insert("unknown_license", UNKNOWN_LICENSE_FILTER);
+        ctx.insert("filter_query", &filters.to_query_string());
         ctx.insert("current_page", &current_page);
         ctx.insert("packages", &packages);
         ctx.insert("total_count", &total_count);
@@ -295,6 +767,29 @@ pub async fn show_search_results(
     })
 }
 
+#[derive(serde::Serialize, serde::Deserialize)]
+pub struct SymbolSearchParams {
+    pub query: TextField,
+    pub package: Option<String>,
+}
+
+pub async fn show_symbol_search_results(
+    request: HttpRequest,
+    search: Query<SymbolSearchParams>,
+) -> Result<HttpResponse> {
+    let conn = request.db_pool()?.get()?;
+    let symbols = search_symbols(&search.query.value, search.package.as_deref(), &conn)?;
+
+    request.render(200, "search/symbol_results.html", {
+        let mut ctx = Context::new();
+        ctx.insert("query", &search.query.value);
+        ctx.insert("package", &search.package);
+        ctx.insert("symbols", &symbols);
+        ctx.insert("max_results", &MAX_SYMBOL_RESULTS);
+        ctx
+    })
+}
+
 pub async fn show_owned_packages(request: HttpRequest) -> Result<HttpResponse> {
     let db = request.db_pool()?;
     if let Ok(user) = request.user() {
@@ -303,6 +798,7 @@ pub async fn show_owned_packages(request: HttpRequest) -> Result<HttpResponse> {
         request.render(200, "search/search_results.html", {
             let mut ctx = Context::new();
             ctx.insert("packages", &packages);
+            ctx.insert("platforms", &Platform::filter_options());
             ctx
         })
     } else {
@@ -315,6 +811,19 @@ pub struct PackageIndexParams {
     pub field: Option<PackageSortField>,
     pub order: Option<PackageSortOrder>,
     pub page: Option<i64>,
+    pub include_forks: Option<bool>,
+    pub platform: Option<String>,
+    pub license: Option<String>,
+}
+
+impl PackageIndexParams {
+    pub fn filters(&self) -> PackageSearchFilters {
+        PackageSearchFilters {
+            include_forks: self.include_forks.unwrap_or(false),
+            platform: Platform::from_filter(self.platform.as_deref()),
+            license: parse_license_filter(self.license.as_deref()),
+        }
+    }
 }
 
 pub async fn packages_index(
@@ -332,11 +841,13 @@ pub async fn packages_index(
             Some(PackageSortOrder::Desc)
         }
     }
+    let filters = params.filters();
     let (packages, total_count, total_pages) = Package::all_packages(
         params.field.as_ref().unwrap(),
         params.order.as_ref().unwrap(),
         params.page,
         None,
+        &filters,
         db,
     )?;
 
@@ -354,6 +865,16 @@ pub async fn packages_index(
     request.render(200, "packages/index.html", {
         let mut ctx = Context::new();
         ctx.insert("sort_type", &field_name);
+        ctx.insert("include_forks", &filters.include_forks);
+        ctx.insert(
+            "platform",
+            &filters.platform.map(|platform| platform.as_str()),
+        );
+        ctx.insert("platforms", &Platform::filter_options());
+        ctx.insert("license", &filters.license);
+        ctx.insert("licenses", &FILTER_LICENSES);
+        ctx.insert("unknown_license", UNKNOWN_LICENSE_FILTER);
+        ctx.insert("filter_query", &filters.to_query_string());
         ctx.insert("current_page", &current_page);
         ctx.insert("packages", &packages);
         ctx.insert("display_pagination_start", &display_pagination_start);
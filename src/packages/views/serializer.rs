@@ -1,11 +1,17 @@
 use convert_case::{Boundary, Case, Casing};
+use jelly::chrono::{DateTime, NaiveDateTime, Utc};
 use serde::Serialize;
 use std::hash::{Hash, Hasher};
 
+use crate::package_collaborators::package_collaborator::Role;
+
 #[derive(Serialize, Eq)]
 pub struct SerializableInvitation {
     pub status: Status,
     pub email: String,
+    pub role: Option<Role>,
+    /// When the pending invitation stops being valid
+    pub expires_at: Option<NaiveDateTime>,
 }
 
 impl PartialEq for SerializableInvitation {
@@ -29,6 +35,18 @@ pub enum Status {
     External,
 }
 
+/// A destructive action waiting for a second owner, as listed on the package settings page.
+#[derive(Serialize)]
+pub struct SerializableApproval {
+    pub id: i32,
+    pub action: String,
+    /// The account or organization the action is about
+    pub target: Option<String>,
+    pub requested_by: String,
+    pub is_requested_by_current_user: bool,
+    pub created_at: DateTime<Utc>,
+}
+
 pub fn slugify_package_name(name: &str) -> String {
     slug::slugify(
         name.from_case(Case::Pascal)
@@ -42,7 +42,7 @@ impl Job for SendContactRequestEmail {
                 },
                 state.templates,
             );
-            email?.send()?;
+            email?.deliver(&state.pool)?;
 
             Ok(())
         })
@@ -70,7 +70,7 @@ impl Job for SendContactEmail {
                 Context::new(),
                 state.templates,
             );
-            email?.send()?;
+            email?.deliver(&state.pool)?;
 
             Ok(())
         })
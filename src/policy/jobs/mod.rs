@@ -1,9 +1,9 @@
-use jelly::jobs::{JobState, WorkerConfig};
+use jelly::jobs::{JobState, RegisterJob, WorkerConfig};
 
 mod contact;
 pub use contact::*;
 
 pub fn configure(config: WorkerConfig<JobState>) -> WorkerConfig<JobState> {
-    let config = config.register::<SendContactRequestEmail>();
-    config.register::<SendContactEmail>()
+    let config = config.register_job::<SendContactRequestEmail>();
+    config.register_job::<SendContactEmail>()
 }
@@ -14,6 +14,18 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    account_slug_histories (id) {
+        id -> Int4,
+        account_id -> Int4,
+        slug -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::{TsVector as Tsvector};
@@ -47,6 +59,49 @@ diesel::table! {
         name -> Varchar,
         created_at -> Timestamptz,
         last_used_at -> Nullable<Timestamptz>,
+        prune_warned_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    crawl_cursors (name) {
+        name -> Text,
+        next_page -> Int4,
+        completed_at -> Nullable<Timestamptz>,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    crawl_errors (id) {
+        id -> Int4,
+        crawl_run_id -> Int4,
+        url -> Text,
+        message -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    crawl_runs (id) {
+        id -> Int4,
+        mode -> Text,
+        status -> Text,
+        found_count -> Int4,
+        new_count -> Int4,
+        updated_count -> Int4,
+        failed_count -> Int4,
+        started_at -> Timestamptz,
+        finished_at -> Nullable<Timestamptz>,
     }
 }
 
@@ -75,6 +130,176 @@ diesel::table! {
         invited_by_user_id -> Int4,
         package_id -> Int4,
         created_at -> Timestamp,
+        expires_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    jelly_email_outbox (id) {
+        id -> Int4,
+        template -> Text,
+        recipient -> Text,
+        subject -> Text,
+        status -> Text,
+        provider_response -> Nullable<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    jelly_jobs (id) {
+        id -> Uuid,
+        queue -> Text,
+        name -> Text,
+        info -> Jsonb,
+        status -> Text,
+        attempts -> Int4,
+        run_at -> Timestamptz,
+        started_at -> Nullable<Timestamptz>,
+        runner_id -> Nullable<Uuid>,
+        last_error -> Nullable<Text>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    jelly_schedules (name) {
+        name -> Text,
+        cron -> Text,
+        next_run_at -> Timestamptz,
+        last_started_at -> Nullable<Timestamptz>,
+        last_finished_at -> Nullable<Timestamptz>,
+        last_error -> Nullable<Text>,
+        locked_until -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    maintenance_runs (id) {
+        id -> Int4,
+        status -> Text,
+        expired_invitations_count -> Int4,
+        expired_accounts_count -> Int4,
+        warned_tokens_count -> Int4,
+        pruned_tokens_count -> Int4,
+        failed_count -> Int4,
+        started_at -> Timestamptz,
+        finished_at -> Nullable<Timestamptz>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    move_functions (id) {
+        id -> Int4,
+        move_module_id -> Int4,
+        name -> Text,
+        visibility -> Text,
+        is_entry -> Bool,
+        signature -> Text,
+        doc -> Text,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    move_modules (id) {
+        id -> Int4,
+        package_version_id -> Int4,
+        address -> Text,
+        name -> Text,
+        doc -> Text,
+        file_path -> Text,
+        used_modules -> Array<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    move_structs (id) {
+        id -> Int4,
+        move_module_id -> Int4,
+        name -> Text,
+        signature -> Text,
+        doc -> Text,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    organization_invitations (invited_user_id, organization_id) {
+        organization_id -> Int4,
+        invited_user_id -> Int4,
+        invited_by_user_id -> Int4,
+        role -> Int4,
+        token -> Text,
+        created_at -> Timestamp,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    organization_members (organization_id, account_id) {
+        organization_id -> Int4,
+        account_id -> Int4,
+        role -> Int4,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    organizations (id) {
+        id -> Int4,
+        name -> Text,
+        slug -> Text,
+        description -> Text,
+        created_by -> Nullable<Int4>,
+        created_at -> Timestamptz,
+        updated_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    owner_approvals (id) {
+        id -> Int4,
+        package_id -> Int4,
+        action -> Text,
+        target_account_id -> Nullable<Int4>,
+        target_role -> Nullable<Int4>,
+        organization_id -> Nullable<Int4>,
+        requested_by -> Int4,
+        created_at -> Timestamptz,
     }
 }
 
@@ -89,6 +314,34 @@ diesel::table! {
         token -> Text,
         is_transferring -> Bool,
         created_at -> Timestamp,
+        expires_at -> Nullable<Timestamp>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    package_audit_logs (id) {
+        id -> Int4,
+        package_id -> Int4,
+        package_slug -> Text,
+        account_id -> Nullable<Int4>,
+        action -> Text,
+        details -> Nullable<Text>,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    package_claims (package_id, account_id) {
+        package_id -> Int4,
+        account_id -> Int4,
+        token -> Text,
+        created_at -> Timestamptz,
     }
 }
 
@@ -105,6 +358,46 @@ diesel::table! {
     }
 }
 
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    package_slug_histories (id) {
+        id -> Int4,
+        package_id -> Int4,
+        slug -> Text,
+        created_at -> Timestamptz,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    package_version_dependencies (id) {
+        id -> Int4,
+        package_version_id -> Int4,
+        name -> Text,
+        git_url -> Nullable<Text>,
+        rev -> Nullable<Text>,
+        subdir -> Nullable<Text>,
+        local_path -> Nullable<Text>,
+    }
+}
+
+diesel::table! {
+    use diesel::sql_types::*;
+    use diesel_full_text_search::{TsVector as Tsvector};
+
+    package_version_files (id) {
+        id -> Int4,
+        package_version_id -> Int4,
+        path -> Text,
+        size -> Int4,
+        content -> Nullable<Text>,
+    }
+}
+
 diesel::table! {
     use diesel::sql_types::*;
     use diesel_full_text_search::{TsVector as Tsvector};
@@ -121,6 +414,16 @@ diesel::table! {
         rev -> Nullable<Text>,
         total_files -> Nullable<Int4>,
         total_size -> Nullable<Int4>,
+        manifest_hash -> Nullable<Text>,
+        changelog_content -> Nullable<Text>,
+        license_content -> Nullable<Text>,
+        readme_html -> Nullable<Text>,
+        docs_generated_at -> Nullable<Timestamptz>,
+        platform -> Text,
+        platform_detected -> Bool,
+        manifest_license -> Nullable<Text>,
+        license_spdx -> Nullable<Text>,
+        license_normalized -> Bool,
     }
 }
 
@@ -140,6 +443,18 @@ diesel::table! {
         slug -> Text,
         stars_count -> Int4,
         forks_count -> Int4,
+        repository_status -> Text,
+        metadata_refreshed_at -> Nullable<Timestamptz>,
+        upstream_package_id -> Nullable<Int4>,
+        platform -> Text,
+        license_spdx -> Nullable<Text>,
+        quality_score -> Int4,
+        deprecated_at -> Nullable<Timestamptz>,
+        deprecation_reason -> Nullable<Text>,
+        deprecation_successor -> Nullable<Text>,
+        archived_at -> Nullable<Timestamptz>,
+        deletion_scheduled_at -> Nullable<Timestamptz>,
+        organization_id -> Nullable<Int4>,
     }
 }
 
@@ -154,21 +469,59 @@ diesel::table! {
     }
 }
 
+diesel::joinable!(account_slug_histories -> accounts (account_id));
 diesel::joinable!(api_tokens -> accounts (account_id));
+diesel::joinable!(crawl_errors -> crawl_runs (crawl_run_id));
 diesel::joinable!(external_invitations -> accounts (invited_by_user_id));
 diesel::joinable!(external_invitations -> packages (package_id));
+diesel::joinable!(move_functions -> move_modules (move_module_id));
+diesel::joinable!(move_modules -> package_versions (package_version_id));
+diesel::joinable!(move_structs -> move_modules (move_module_id));
+diesel::joinable!(organization_invitations -> organizations (organization_id));
+diesel::joinable!(organization_members -> accounts (account_id));
+diesel::joinable!(organization_members -> organizations (organization_id));
+diesel::joinable!(organizations -> accounts (created_by));
+diesel::joinable!(owner_approvals -> organizations (organization_id));
+diesel::joinable!(owner_approvals -> packages (package_id));
 diesel::joinable!(owner_invitations -> packages (package_id));
+diesel::joinable!(package_audit_logs -> accounts (account_id));
+diesel::joinable!(package_claims -> accounts (account_id));
+diesel::joinable!(package_claims -> packages (package_id));
 diesel::joinable!(package_collaborators -> packages (package_id));
+diesel::joinable!(package_slug_histories -> packages (package_id));
+diesel::joinable!(package_version_dependencies -> package_versions (package_version_id));
+diesel::joinable!(package_version_files -> package_versions (package_version_id));
 diesel::joinable!(package_versions -> packages (package_id));
+diesel::joinable!(packages -> organizations (organization_id));
 
 diesel::allow_tables_to_appear_in_same_query!(
     _sqlx_migrations,
+    account_slug_histories,
     accounts,
     api_tokens,
+    crawl_cursors,
+    crawl_errors,
+    crawl_runs,
     domains,
     external_invitations,
+    jelly_email_outbox,
+    jelly_jobs,
+    jelly_schedules,
+    maintenance_runs,
+    move_functions,
+    move_modules,
+    move_structs,
+    organization_invitations,
+    organization_members,
+    organizations,
+    owner_approvals,
     owner_invitations,
+    package_audit_logs,
+    package_claims,
     package_collaborators,
+    package_slug_histories,
+    package_version_dependencies,
+    package_version_files,
     package_versions,
     packages,
     users,
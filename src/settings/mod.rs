@@ -16,9 +16,13 @@ pub fn configure(config: &mut ServiceConfig) {
                     .route(get().to(views::controller::profile))
                     .route(post().to(views::controller::change_password)),
             )
+            .service(resource("/profile/name").route(post().to(views::controller::change_name)))
             .service(resource("/packages").route(get().to(views::controller::show_packages)))
             .service(resource("/downloads").route(get().to(views::controller::show_downloads)))
             .service(resource("/tokens").route(get().to(views::controller::show_tokens)))
-            .service(resource("/invitations").route(get().to(views::controller::show_invitations))),
+            .service(resource("/invitations").route(get().to(views::controller::show_invitations)))
+            .service(
+                resource("/organizations").route(get().to(views::controller::show_organizations)),
+            ),
     );
 }
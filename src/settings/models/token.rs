@@ -6,10 +6,13 @@ use crate::schema::api_tokens::dsl::*;
 use crate::utils::token::SecureToken;
 use diesel::prelude::*;
 use diesel::{Associations, ExpressionMethods, Identifiable, Queryable, RunQueryDsl};
-use jelly::chrono::NaiveDateTime;
+use jelly::chrono::{Duration, NaiveDateTime, Utc};
 use jelly::serde::Serialize;
-use jelly::DieselPgPool;
 use jelly::Result;
+use jelly::{DieselPgConnection, DieselPgPool};
+
+/// How old `last_used_at` may get before a request using the token refreshes it
+const LAST_USED_AT_PRECISION_MINUTES: i64 = 60;
 
 #[derive(Clone, Debug, PartialEq, Eq, Identifiable, Queryable, Associations, Serialize)]
 #[belongs_to(Account)]
@@ -20,6 +23,8 @@ pub struct ApiToken {
     pub name: String,
     pub created_at: NaiveDateTime,
     pub last_used_at: Option<NaiveDateTime>,
+    /// When the owner was told the token would be pruned for not being used
+    pub prune_warned_at: Option<NaiveDateTime>,
 }
 
 impl ApiToken {
@@ -54,6 +59,23 @@ impl ApiToken {
             .filter(api_tokens::token.eq(formatted_sha256))
             .first::<ApiToken>(&connection)?;
 
+        // using the token again calls off its pruning, otherwise last_used_at only needs
+        // refreshing once in a while
+        let stale_before =
+            Utc::now().naive_utc() - Duration::minutes(LAST_USED_AT_PRECISION_MINUTES);
+        if matched_token.prune_warned_at.is_some()
+            || matched_token
+                .last_used_at
+                .map_or(true, |at| at < stale_before)
+        {
+            diesel::update(api_tokens.find(matched_token.id))
+                .set((
+                    api_tokens::last_used_at.eq(Utc::now().naive_utc()),
+                    api_tokens::prune_warned_at.eq(None::<NaiveDateTime>),
+                ))
+                .execute(&connection)?;
+        }
+
         let account = accounts
             .filter(accounts::id.eq(matched_token.account_id))
             .first::<Account>(&connection)?;
@@ -107,6 +129,47 @@ impl ApiToken {
 
         Ok(())
     }
+
+    /// Finds the tokens not used for `unused_days` whose owners haven't been warned yet
+    /// that they are to be pruned.
+    pub fn find_unused(unused_days: i64, conn: &DieselPgConnection) -> Result<Vec<Self>> {
+        let unused_since = Utc::now().naive_utc() - Duration::days(unused_days);
+        let result = api_tokens
+            .filter(api_tokens::prune_warned_at.is_null())
+            .filter(
+                api_tokens::last_used_at
+                    .lt(unused_since)
+                    .or(api_tokens::last_used_at
+                        .is_null()
+                        .and(api_tokens::created_at.lt(unused_since))),
+            )
+            .load::<Self>(conn)?;
+
+        Ok(result)
+    }
+
+    /// Records that the owner was warned, which starts the notice before pruning.
+    pub fn mark_prune_warned(&self, conn: &DieselPgConnection) -> Result<()> {
+        diesel::update(
+            api_tokens
+                .find(self.id)
+                .filter(api_tokens::prune_warned_at.is_null()),
+        )
+        .set(api_tokens::prune_warned_at.eq(Utc::now().naive_utc()))
+        .execute(conn)?;
+
+        Ok(())
+    }
+
+    /// Deletes the tokens still unused `notice_days` after their owners were warned.
+    pub fn prune_warned(notice_days: i64, conn: &DieselPgConnection) -> Result<usize> {
+        let warned_before = Utc::now().naive_utc() - Duration::days(notice_days);
+        let result =
+            diesel::delete(api_tokens.filter(api_tokens::prune_warned_at.lt(warned_before)))
+                .execute(conn)?;
+
+        Ok(result)
+    }
 }
 
 pub struct CreatedApiToken {
@@ -117,13 +180,26 @@ pub struct CreatedApiToken {
 #[cfg(test)]
 mod tests {
     use crate::accounts::Account;
+    use crate::schema::api_tokens;
     use crate::settings::models::token::ApiToken;
     use crate::test::util::setup_user;
     use crate::test::{DatabaseTestContext, DB_POOL};
+    use diesel::prelude::*;
     use diesel::result::DatabaseErrorKind;
     use diesel::result::Error::DatabaseError;
+    use jelly::chrono::{Duration, NaiveDateTime, Utc};
     use jelly::error::Error;
 
+    fn set_usage(token_id: i32, last_used: NaiveDateTime, warned: Option<NaiveDateTime>) {
+        diesel::update(api_tokens::table.find(token_id))
+            .set((
+                api_tokens::last_used_at.eq(last_used),
+                api_tokens::prune_warned_at.eq(warned),
+            ))
+            .execute(&DB_POOL.get().unwrap())
+            .unwrap();
+    }
+
     #[actix_rt::test]
     async fn api_token_insert_works() {
         crate::test::init();
@@ -168,6 +244,79 @@ mod tests {
         }
     }
 
+    #[actix_rt::test]
+    async fn associated_account_only_refreshes_stale_usage() {
+        crate::test::init();
+        let _ctx = DatabaseTestContext::new();
+
+        let uid = setup_user(None, None);
+        let account = Account::get(uid, &DB_POOL).unwrap();
+        let created = ApiToken::insert(&account, "name1", &DB_POOL).unwrap();
+
+        let recently = Utc::now().naive_utc() - Duration::minutes(5);
+        set_usage(created.model.id, recently, None);
+        let before = ApiToken::get_by_id(created.model.id, &DB_POOL).unwrap();
+        ApiToken::associated_account(&created.plaintext, &DB_POOL).unwrap();
+        let token = ApiToken::get_by_id(created.model.id, &DB_POOL).unwrap();
+        assert_eq!(token.last_used_at, before.last_used_at);
+
+        let long_ago = Utc::now().naive_utc() - Duration::days(2);
+        set_usage(created.model.id, long_ago, None);
+        ApiToken::associated_account(&created.plaintext, &DB_POOL).unwrap();
+        let token = ApiToken::get_by_id(created.model.id, &DB_POOL).unwrap();
+        assert!(token.last_used_at.unwrap() > long_ago);
+    }
+
+    #[actix_rt::test]
+    async fn associated_account_calls_off_pruning() {
+        crate::test::init();
+        let _ctx = DatabaseTestContext::new();
+
+        let uid = setup_user(None, None);
+        let account = Account::get(uid, &DB_POOL).unwrap();
+        let created = ApiToken::insert(&account, "name1", &DB_POOL).unwrap();
+
+        let recently = Utc::now().naive_utc() - Duration::minutes(5);
+        set_usage(created.model.id, recently, Some(recently));
+        ApiToken::associated_account(&created.plaintext, &DB_POOL).unwrap();
+        let token = ApiToken::get_by_id(created.model.id, &DB_POOL).unwrap();
+        assert_eq!(token.prune_warned_at, None);
+    }
+
+    #[actix_rt::test]
+    async fn unused_tokens_are_found_until_their_owner_is_warned() {
+        crate::test::init();
+        let _ctx = DatabaseTestContext::new();
+
+        let uid = setup_user(None, None);
+        let account = Account::get(uid, &DB_POOL).unwrap();
+        let created = ApiToken::insert(&account, "name1", &DB_POOL).unwrap();
+        ApiToken::insert(&account, "name2", &DB_POOL).unwrap();
+        set_usage(
+            created.model.id,
+            Utc::now().naive_utc() - Duration::days(400),
+            None,
+        );
+
+        let conn = DB_POOL.get().unwrap();
+        let unused = ApiToken::find_unused(365, &conn).unwrap();
+        assert_eq!(unused.len(), 1);
+        assert_eq!(unused[0].id, created.model.id);
+        assert_eq!(
+            ApiToken::get_by_id(created.model.id, &DB_POOL)
+                .unwrap()
+                .prune_warned_at,
+            None
+        );
+
+        unused[0].mark_prune_warned(&conn).unwrap();
+        assert!(ApiToken::find_unused(365, &conn).unwrap().is_empty());
+        assert!(ApiToken::get_by_id(created.model.id, &DB_POOL)
+            .unwrap()
+            .prune_warned_at
+            .is_some());
+    }
+
     #[actix_rt::test]
     async fn api_token_get_by_account_works() {
         crate::test::init();
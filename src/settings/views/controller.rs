@@ -1,6 +1,8 @@
-use crate::accounts::forms::ChangePasswordForm;
+use crate::accounts::forms::{ChangeNameForm, ChangePasswordForm};
 use crate::accounts::Account;
 use crate::constants;
+use crate::organizations::models::invitation::OrganizationInvitation;
+use crate::organizations::models::{Organization, OrganizationRole};
 use crate::package_collaborators::models::owner_invitation::OwnerInvitation;
 use crate::packages::Package;
 use crate::settings::models::token::ApiToken;
@@ -88,6 +90,30 @@ pub async fn change_password(
     })
 }
 
+pub async fn change_name(request: HttpRequest, form: Form<ChangeNameForm>) -> Result<HttpResponse> {
+    let mut form = form.into_inner();
+    let user = request.user()?;
+    let db = request.db_pool()?;
+
+    // The public profile slug follows the new name, old links are redirected
+    if form.is_valid() && Account::update_name(user.id, &form.name.value, db)? {
+        return request.redirect("/settings/profile");
+    }
+    if form.name.errors.is_empty() {
+        form.name
+            .errors
+            .push(String::from("This name is taken, please pick another one."));
+    }
+    let account = Account::get(user.id, db)?;
+    request.render(200, "settings/profile.html", {
+        let mut context = Context::new();
+        context.insert("name_form", &form);
+        context.insert("account", &account);
+        context.insert("profile_tab", "profile");
+        context
+    })
+}
+
 pub async fn show_packages(request: HttpRequest) -> Result<HttpResponse> {
     let db = request.db_pool()?;
     if let Ok(user) = request.user() {
@@ -139,6 +165,39 @@ pub async fn show_invitations(request: HttpRequest) -> Result<HttpResponse> {
     }
 }
 
+pub async fn show_organizations(request: HttpRequest) -> Result<HttpResponse> {
+    let db = request.db_pool()?;
+    let conn = db.get()?;
+
+    if let Ok(user) = request.user() {
+        let mut organizations = vec![];
+        for (organization, role) in Organization::get_by_member(user.id, &conn)? {
+            let is_admin = role == OrganizationRole::Admin as i32;
+            // only admins manage members, so only they need the list
+            let members = if is_admin {
+                organization.get_members(&conn)?
+            } else {
+                vec![]
+            };
+            organizations.push((organization, is_admin, members));
+        }
+        let invitations = OrganizationInvitation::find_by_invited_account(user.id, &conn)?
+            .into_iter()
+            .map(|(_, organization)| organization)
+            .collect::<Vec<Organization>>();
+
+        request.render(200, "settings/organizations.html", {
+            let mut ctx = Context::new();
+            ctx.insert("profile_tab", "organizations");
+            ctx.insert("organizations", &organizations);
+            ctx.insert("invitations", &invitations);
+            ctx
+        })
+    } else {
+        Ok(HttpResponse::NotFound().body("Cannot find user"))
+    }
+}
+
 pub async fn show_tokens(request: HttpRequest) -> Result<HttpResponse> {
     let db = request.db_pool()?;
     if let Ok(user) = request.user() {
@@ -1,3 +1,5 @@
-use diesel::sql_types::Text;
+use diesel::sql_types::{Nullable, Text};
 
 sql_function!(fn lower(x: Text) -> Text);
+sql_function!(fn translate(x: Nullable<Text>, from: Text, to: Text) -> Nullable<Text>);
+sql_function!(fn concat(a: Text, b: Nullable<Text>, c: Text) -> Text);
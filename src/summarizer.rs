@@ -0,0 +1,356 @@
+use jelly::error::Error;
+use jelly::error::Error::Generic;
+use reqwest::blocking::multipart;
+use serde::Deserialize;
+use std::env;
+
+use crate::constants::DEEP_AI_URL;
+use crate::github_service::APP_USER_AGENT;
+
+pub const MAX_DESCRIPTION_LENGTH: usize = 100;
+
+/// Turns a README into a short summary, used as package description when the
+/// repository doesn't have one.
+pub trait Summarizer: Send + Sync {
+    fn summarize(&self, content: &str) -> Result<String, Error>;
+}
+
+/// Returns the summarizer chosen with the `SUMMARIZER` environment variable:
+/// `extractive` (default) or `deepai`.
+pub fn from_env() -> Box<dyn Summarizer> {
+    match env::var("SUMMARIZER")
+        .unwrap_or_else(|_| "".to_string())
+        .to_lowercase()
+        .as_str()
+    {
+        "deepai" => match DeepAiSummarizer::from_env() {
+            Some(summarizer) => Box::new(summarizer),
+            None => {
+                warn!("SUMMARIZER is set to deepai but DEEP_AI_API_KEY is missing, using extractive summarizer");
+                Box::new(ExtractiveSummarizer)
+            }
+        },
+        _ => Box::new(ExtractiveSummarizer),
+    }
+}
+
+/// Generates a package description of at most `MAX_DESCRIPTION_LENGTH` characters,
+/// falling back to the extractive summarizer if the given one fails.
+pub fn generate_description(summarizer: &dyn Summarizer, readme_content: &str) -> String {
+    let description = match summarizer.summarize(readme_content) {
+        Ok(summary) => strip_markdown::strip_markdown(&summary),
+        Err(e) => {
+            warn!(
+                "Error summarizing readme, using extractive summarizer. error: {:?}",
+                e
+            );
+            String::new()
+        }
+    };
+    let description = if description.trim().is_empty() {
+        ExtractiveSummarizer
+            .summarize(readme_content)
+            .unwrap_or_default()
+    } else {
+        description
+    };
+    truncate_at_word(description.trim(), MAX_DESCRIPTION_LENGTH)
+}
+
+fn truncate_at_word(text: &str, max_chars: usize) -> String {
+    if text.chars().count() <= max_chars {
+        return text.to_string();
+    }
+    let truncated: String = text.chars().take(max_chars).collect();
+    match truncated.rfind(char::is_whitespace) {
+        Some(index) if index > 0 => truncated[..index].trim_end().to_string(),
+        _ => truncated,
+    }
+}
+
+/// Picks the first meaningful paragraph of a README, skipping headings, badges,
+/// html blocks, code blocks, tables and lists.
+pub struct ExtractiveSummarizer;
+
+impl Summarizer for ExtractiveSummarizer {
+    fn summarize(&self, content: &str) -> Result<String, Error> {
+        let mut paragraphs: Vec<Vec<&str>> = vec![];
+        let mut current: Vec<&str> = vec![];
+        let mut in_code_block = false;
+        for line in content.lines() {
+            let trimmed = line.trim();
+            if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+                in_code_block = !in_code_block;
+                if !current.is_empty() {
+                    paragraphs.push(current);
+                    current = vec![];
+                }
+                continue;
+            }
+            // lines indented by four spaces outside a paragraph form an indented code block,
+            // the indentation is gone once trimmed
+            let is_indented_code = current.is_empty()
+                && !trimmed.is_empty()
+                && (line.starts_with("    ") || line.starts_with('\t'));
+            if in_code_block || is_indented_code {
+                continue;
+            }
+            if trimmed.is_empty() {
+                if !current.is_empty() {
+                    paragraphs.push(current);
+                    current = vec![];
+                }
+            } else {
+                current.push(trimmed);
+            }
+        }
+        if !current.is_empty() {
+            paragraphs.push(current);
+        }
+
+        let summary = paragraphs
+            .into_iter()
+            .filter(|lines| is_meaningful_paragraph(lines))
+            .map(|lines| {
+                let text = strip_markdown::strip_markdown(&lines.join(" "));
+                text.split_whitespace().collect::<Vec<&str>>().join(" ")
+            })
+            .find(|text| text.chars().any(char::is_alphabetic))
+            .unwrap_or_default();
+        Ok(summary)
+    }
+}
+
+fn is_meaningful_paragraph(lines: &[&str]) -> bool {
+    let first = lines[0];
+    let last = lines[lines.len() - 1];
+    let is_setext_heading =
+        lines.len() > 1 && (last.chars().all(|c| c == '=') || last.chars().all(|c| c == '-'));
+    !(first.starts_with('#')
+        || first.starts_with('<')
+        || first.starts_with('|')
+        || first.starts_with('>')
+        || first.starts_with("- ")
+        || first.starts_with("* ")
+        || first.starts_with("+ ")
+        || first.starts_with("---")
+        || first.starts_with("***")
+        || first.starts_with("[//]")
+        || is_numbered_list_item(first)
+        || is_setext_heading
+        || lines.iter().all(|line| is_badge_line(line)))
+}
+
+fn is_numbered_list_item(line: &str) -> bool {
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    digits > 0 && line[digits..].starts_with(". ")
+}
+
+// e.g: [![Build Status](https://github.com/.../badge.svg)](https://github.com/...) ![License](...)
+fn is_badge_line(line: &str) -> bool {
+    let mut rest = line.trim();
+    while !rest.is_empty() {
+        let link = if rest.starts_with("[![") || rest.starts_with("![") {
+            rest
+        } else {
+            return false;
+        };
+        // skip to the end of the image, then of the wrapping link if any
+        let mut end = match link.find(")") {
+            Some(index) => index + 1,
+            None => return false,
+        };
+        if link.starts_with("[![") {
+            end = match link[end..].find(")") {
+                Some(index) => end + index + 1,
+                None => return false,
+            };
+        }
+        rest = link[end..].trim_start();
+    }
+    true
+}
+
+/// Summarizes with the deepai.org API, requires `DEEP_AI_API_KEY`.
+pub struct DeepAiSummarizer {
+    pub url: String,
+    pub api_key: String,
+}
+
+impl DeepAiSummarizer {
+    pub fn from_env() -> Option<Self> {
+        let api_key = env::var("DEEP_AI_API_KEY").ok()?;
+        if api_key.is_empty() {
+            return None;
+        }
+        Some(DeepAiSummarizer {
+            url: DEEP_AI_URL.to_string(),
+            api_key,
+        })
+    }
+
+    fn call_api(&self, content: String) -> Result<String, Error> {
+        let client = reqwest::blocking::Client::builder()
+            .user_agent(APP_USER_AGENT)
+            .build()?;
+        let form = multipart::Form::new().text("text", content);
+        let response = client
+            .post(&self.url)
+            .header("api-key", &self.api_key)
+            .multipart(form)
+            .send()?;
+        if !response.status().is_success() {
+            // e.g: 401 when running out of credits
+            return Err(Generic(format!(
+                "Error getting response from deepai.org. status: {}, body: {}",
+                response.status(),
+                response.text().unwrap_or_default()
+            )));
+        }
+
+        #[derive(Deserialize)]
+        struct DeepApiResponse {
+            output: String,
+        }
+
+        match response.json::<DeepApiResponse>() {
+            Ok(response) => Ok(response.output),
+            Err(error) => Err(Generic(format!(
+                "Error getting response from deepai.org. error: {}",
+                error
+            ))),
+        }
+    }
+}
+
+impl Summarizer for DeepAiSummarizer {
+    fn summarize(&self, content: &str) -> Result<String, Error> {
+        let mut summary = self.call_api(content.to_string())?;
+        // summaries of long readmes are often still too long, summarize them once more
+        if summary.chars().count() > MAX_DESCRIPTION_LENGTH {
+            let shorter_summary = self.call_api(summary.clone())?;
+            if !shorter_summary.is_empty() {
+                summary = shorter_summary;
+            }
+        }
+        Ok(summary)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use httpmock::prelude::*;
+    use httpmock::MockServer;
+    use serde_json::json;
+
+    use super::*;
+
+    fn deep_ai_summarizer(server: &MockServer) -> DeepAiSummarizer {
+        DeepAiSummarizer {
+            url: format!("{}/api/summarization", &server.base_url()),
+            api_key: "api key".to_string(),
+        }
+    }
+
+    #[test]
+    fn extractive_summarizer_skips_headings_badges_and_code() {
+        let readme = "# Movey\n\
+            [![Build](https://github.com/a/b/badge.svg)](https://github.com/a/b) ![License](https://img.shields.io/x)\n\
+            \n\
+            <p align=\"center\"><img src=\"logo.png\"></p>\n\
+            \n\
+            ```bash\nmove build\n```\n\
+            \n\
+            Movey is a **package registry** for the [Move](https://github.com/move-language/move)\n\
+            programming language.\n\
+            \n\
+            Another paragraph.";
+        let summary = ExtractiveSummarizer.summarize(readme).unwrap();
+        assert_eq!(
+            summary,
+            "Movey is a package registry for the Move programming language."
+        );
+    }
+
+    #[test]
+    fn extractive_summarizer_skips_indented_code_blocks() {
+        // the indented continuation line still belongs to the paragraph
+        let readme = "    cargo install move-cli\n    move build\n\n\
+            Movey is a registry\n    for Move packages.";
+        let summary = ExtractiveSummarizer.summarize(readme).unwrap();
+        assert_eq!(summary, "Movey is a registry for Move packages.");
+    }
+
+    #[test]
+    fn extractive_summarizer_returns_empty_string_without_paragraph() {
+        let readme = "Title\n=====\n\n- item\n- item 2\n\n| a | b |\n";
+        assert_eq!(ExtractiveSummarizer.summarize(readme).unwrap(), "");
+    }
+
+    #[test]
+    fn generate_description_truncates_at_word_boundary() {
+        let readme = "word ".repeat(30);
+        let description = generate_description(&ExtractiveSummarizer, &readme);
+        assert!(description.chars().count() <= MAX_DESCRIPTION_LENGTH);
+        assert!(description.ends_with("word"));
+    }
+
+    #[test]
+    fn deep_ai_summarizer_works() {
+        let server = MockServer::start();
+        // TODO: check if the request content type is multipart
+        // https://github.com/alexliesenfeld/httpmock/tree/master/tests/examples
+        let server_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/summarization")
+                .header("User-Agent", APP_USER_AGENT)
+                .header("api-key", "api key");
+            then.status(200)
+                .json_body(json!({ "output":"summarized text" }));
+        });
+
+        let result = deep_ai_summarizer(&server).summarize("original text");
+        server_mock.assert();
+        assert_eq!(result.unwrap(), "summarized text");
+    }
+
+    #[test]
+    fn deep_ai_summarizer_returns_empty_string_if_deep_ai_response_body_is_empty() {
+        let server = MockServer::start();
+        let server_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/summarization")
+                .header("User-Agent", APP_USER_AGENT)
+                .header("api-key", "api key");
+            then.status(200).json_body(json!({ "output":"" }));
+        });
+
+        let result = deep_ai_summarizer(&server).summarize("original text");
+        server_mock.assert();
+        assert!(result.unwrap().is_empty());
+    }
+
+    #[test]
+    fn deep_ai_summarizer_returns_err_if_out_of_free_credit() {
+        let server = MockServer::start();
+        let server_mock = server.mock(|when, then| {
+            when.method(POST)
+                .path("/api/summarization")
+                .header("User-Agent", APP_USER_AGENT)
+                .header("api-key", "api key");
+            then.status(401).json_body(json!({
+                "status":"Out of free credits \
+                    - please enter payment info in your dashboard: https://deepai.org/dashboard"
+            }));
+        });
+
+        let summarizer = deep_ai_summarizer(&server);
+        let result = summarizer.summarize("original text");
+        server_mock.assert();
+        assert!(result.is_err());
+
+        // the description falls back to the extractive summary
+        let description = generate_description(&summarizer, "# Title\n\noriginal text");
+        assert_eq!(description, "original text");
+    }
+}
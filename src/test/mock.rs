@@ -63,6 +63,7 @@ impl GithubService {
                     forks_count: 0,
                     url: "".to_string(),
                     rev: "".to_string(),
+                    ..Default::default()
                 })
             });
         mock_gh_service
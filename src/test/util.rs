@@ -65,3 +65,24 @@ pub fn create_stub_packages(account_id: i32, num_of_packages: i32) {
         .unwrap();
     }
 }
+
+/// A package with a single version "0.1.0" at `rev`, owned by `account_id` if given.
+pub fn create_package(name: &str, repo_url: &str, rev: &str, account_id: Option<i32>) -> Package {
+    let package_id = Package::create_test_package(
+        &name.to_string(),
+        &repo_url.to_string(),
+        &"".to_string(),
+        &"0.1.0".to_string(),
+        &"".to_string(),
+        &"".to_string(),
+        &rev.to_string(),
+        0,
+        0,
+        0,
+        0,
+        account_id,
+        &DB_POOL,
+    )
+    .unwrap();
+    Package::get(package_id, &DB_POOL).unwrap()
+}
@@ -1,3 +1,4 @@
+use diesel::result::Error as DBError;
 use jelly::actix_web::web::Path;
 use jelly::actix_web::web::Query;
 use jelly::prelude::*;
@@ -9,6 +10,7 @@ use crate::packages::models::PackageSortOrder;
 use crate::packages::models::PACKAGES_PER_PAGE;
 use crate::packages::views::controller::PackageIndexParams;
 use crate::packages::Package;
+use crate::utils::request_utils;
 
 pub async fn get_public_profile(
     request: HttpRequest,
@@ -28,7 +30,19 @@ pub async fn get_public_profile(
         }
     }
 
-    let mut account = Account::get_by_slug(&user_slug, db)?;
+    let mut account = match Account::get_by_slug(&user_slug, db) {
+        Ok(account) => account,
+        Err(Error::Database(DBError::NotFound)) => {
+            return match Account::get_renamed_slug(&user_slug, db)? {
+                Some(current_slug) => Ok(request_utils::permanent_redirect(
+                    &format!("/users/{}", current_slug),
+                    request.query_string(),
+                )),
+                None => Err(Error::Database(DBError::NotFound)),
+            };
+        }
+        Err(e) => return Err(e),
+    };
     if !account.has_verified_email {
         return request.render(400, "400.html", Context::new());
     }
@@ -0,0 +1,301 @@
+//! Normalization of free-text license names to SPDX license expressions, e.g
+//! `Apache License 2.0` to `Apache-2.0` or `mit or apache-2.0` to `MIT OR Apache-2.0`.
+
+/// Licenses listed in the license filter of the search results.
+pub const FILTER_LICENSES: [&str; 7] = [
+    "MIT",
+    "Apache-2.0",
+    "BSD-3-Clause",
+    "GPL-3.0-only",
+    "LGPL-3.0-only",
+    "MPL-2.0",
+    "Unlicense",
+];
+
+/// Filter value listing the packages without a recognized license.
+pub const UNKNOWN_LICENSE_FILTER: &str = "unknown";
+
+// SPDX ids of the licenses GitHub detects and of other licenses common in Move packages
+const LICENSE_IDS: [&str; 42] = [
+    "0BSD",
+    "AFL-3.0",
+    "AGPL-3.0-only",
+    "AGPL-3.0-or-later",
+    "Apache-1.1",
+    "Apache-2.0",
+    "Artistic-2.0",
+    "BSD-2-Clause",
+    "BSD-3-Clause",
+    "BSD-3-Clause-Clear",
+    "BSD-4-Clause",
+    "BSL-1.0",
+    "BUSL-1.1",
+    "CC-BY-4.0",
+    "CC-BY-SA-4.0",
+    "CC0-1.0",
+    "ECL-2.0",
+    "EPL-1.0",
+    "EPL-2.0",
+    "EUPL-1.1",
+    "EUPL-1.2",
+    "GPL-2.0-only",
+    "GPL-2.0-or-later",
+    "GPL-3.0-only",
+    "GPL-3.0-or-later",
+    "ISC",
+    "LGPL-2.1-only",
+    "LGPL-2.1-or-later",
+    "LGPL-3.0-only",
+    "LGPL-3.0-or-later",
+    "LPPL-1.3c",
+    "MIT",
+    "MIT-0",
+    "MPL-2.0",
+    "MS-PL",
+    "NCSA",
+    "ODbL-1.0",
+    "OFL-1.1",
+    "OSL-3.0",
+    "Unlicense",
+    "WTFPL",
+    "Zlib",
+];
+
+const LICENSE_EXCEPTION_IDS: [&str; 4] = [
+    "Classpath-exception-2.0",
+    "GCC-exception-3.1",
+    "LLVM-exception",
+    "OpenJDK-assembly-exception-1.0",
+];
+
+// Deprecated SPDX ids, still returned by GitHub for the GNU licenses
+const DEPRECATED_LICENSE_IDS: [(&str, &str); 8] = [
+    ("gpl-2.0", "GPL-2.0-only"),
+    ("gpl-2.0+", "GPL-2.0-or-later"),
+    ("gpl-3.0", "GPL-3.0-only"),
+    ("gpl-3.0+", "GPL-3.0-or-later"),
+    ("lgpl-2.1", "LGPL-2.1-only"),
+    ("lgpl-2.1+", "LGPL-2.1-or-later"),
+    ("lgpl-3.0", "LGPL-3.0-only"),
+    ("agpl-3.0", "AGPL-3.0-only"),
+];
+
+// Lowercased license names returned by GitHub and common spellings in manifests
+const LICENSE_NAMES: [(&str, &str); 40] = [
+    ("academic free license v3.0", "AFL-3.0"),
+    ("apache license 2.0", "Apache-2.0"),
+    ("apache license, version 2.0", "Apache-2.0"),
+    ("apache 2.0", "Apache-2.0"),
+    ("apache-2", "Apache-2.0"),
+    ("apache2", "Apache-2.0"),
+    ("artistic license 2.0", "Artistic-2.0"),
+    ("boost software license 1.0", "BSL-1.0"),
+    ("bsd 2-clause \"simplified\" license", "BSD-2-Clause"),
+    (
+        "bsd 3-clause \"new\" or \"revised\" license",
+        "BSD-3-Clause",
+    ),
+    ("bsd 3-clause clear license", "BSD-3-Clause-Clear"),
+    ("bsd zero clause license", "0BSD"),
+    ("business source license 1.1", "BUSL-1.1"),
+    ("creative commons zero v1.0 universal", "CC0-1.0"),
+    (
+        "creative commons attribution 4.0 international",
+        "CC-BY-4.0",
+    ),
+    (
+        "creative commons attribution share alike 4.0 international",
+        "CC-BY-SA-4.0",
+    ),
+    ("do what the f*ck you want to public license", "WTFPL"),
+    ("educational community license v2.0", "ECL-2.0"),
+    ("eclipse public license 1.0", "EPL-1.0"),
+    ("eclipse public license 2.0", "EPL-2.0"),
+    ("european union public license 1.1", "EUPL-1.1"),
+    ("european union public license 1.2", "EUPL-1.2"),
+    ("gnu affero general public license v3.0", "AGPL-3.0-only"),
+    ("gnu general public license v2.0", "GPL-2.0-only"),
+    ("gnu general public license v3.0", "GPL-3.0-only"),
+    ("gnu lesser general public license v2.1", "LGPL-2.1-only"),
+    ("gnu lesser general public license v3.0", "LGPL-3.0-only"),
+    ("gplv2", "GPL-2.0-only"),
+    ("gplv3", "GPL-3.0-only"),
+    ("isc license", "ISC"),
+    ("latex project public license v1.3c", "LPPL-1.3c"),
+    ("microsoft public license", "MS-PL"),
+    ("mit license", "MIT"),
+    ("mit no attribution", "MIT-0"),
+    ("mozilla public license 2.0", "MPL-2.0"),
+    ("odc open database license v1.0", "ODbL-1.0"),
+    ("open software license 3.0", "OSL-3.0"),
+    ("sil open font license 1.1", "OFL-1.1"),
+    ("the unlicense", "Unlicense"),
+    ("university of illinois/ncsa open source license", "NCSA"),
+];
+
+fn find_id(ids: &[&'static str], token: &str) -> Option<&'static str> {
+    ids.iter()
+        .find(|id| id.eq_ignore_ascii_case(token))
+        .copied()
+}
+
+fn license_id(token: &str) -> Option<&'static str> {
+    find_id(&LICENSE_IDS, token).or_else(|| {
+        let token = token.to_lowercase();
+        DEPRECATED_LICENSE_IDS
+            .iter()
+            .chain(LICENSE_NAMES.iter())
+            .find(|(name, _)| *name == token)
+            .map(|(_, id)| *id)
+    })
+}
+
+fn tokenize(expression: &str) -> Vec<String> {
+    expression
+        .replace('(', " ( ")
+        .replace(')', " ) ")
+        .split_whitespace()
+        .map(|token| token.to_string())
+        .collect()
+}
+
+// `MIT`, `MIT OR Apache-2.0`, `(MIT AND Zlib) OR GPL-2.0-only WITH Classpath-exception-2.0`
+fn normalize_expression(expression: &str) -> Option<String> {
+    let mut normalized: Vec<&str> = vec![];
+    let mut depth = 0;
+    let mut expects_license = true;
+    let mut after_with = false;
+    for token in tokenize(expression) {
+        let operator = token.to_uppercase();
+        match operator.as_str() {
+            "(" if expects_license => depth += 1,
+            ")" if !expects_license && depth > 0 => depth -= 1,
+            "AND" | "OR" if !expects_license => expects_license = true,
+            "WITH" if !expects_license && !after_with => {
+                expects_license = true;
+                after_with = true;
+                normalized.push("WITH");
+                continue;
+            }
+            _ if expects_license => {
+                let id = if after_with {
+                    find_id(&LICENSE_EXCEPTION_IDS, &token)?
+                } else {
+                    license_id(&token)?
+                };
+                normalized.push(id);
+                expects_license = false;
+                continue;
+            }
+            _ => return None,
+        }
+        after_with = false;
+        normalized.push(match operator.as_str() {
+            "(" => "(",
+            ")" => ")",
+            "AND" => "AND",
+            _ => "OR",
+        });
+    }
+    if expects_license || depth != 0 {
+        return None;
+    }
+    Some(normalized.join(" ").replace("( ", "(").replace(" )", ")"))
+}
+
+/// Converts a license name or expression to an SPDX license expression, `None` when
+/// the license is missing or not recognized.
+pub fn normalize_license(license: &str) -> Option<String> {
+    let license = license.trim();
+    if license.is_empty() {
+        return None;
+    }
+    match license_id(license) {
+        Some(id) => Some(id.to_string()),
+        None => normalize_expression(license),
+    }
+}
+
+/// The first license that can be normalized, in order of preference.
+pub fn resolve_license(candidates: &[Option<&str>]) -> Option<String> {
+    candidates
+        .iter()
+        .flatten()
+        .find_map(|license| normalize_license(license))
+}
+
+/// Parses the `license` query parameter, either an SPDX license id or
+/// `UNKNOWN_LICENSE_FILTER`. Other values don't filter anything.
+pub fn parse_license_filter(value: Option<&str>) -> Option<String> {
+    let value = value?.trim();
+    if value.eq_ignore_ascii_case(UNKNOWN_LICENSE_FILTER) {
+        return Some(UNKNOWN_LICENSE_FILTER.to_string());
+    }
+    license_id(value).map(|id| id.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn normalize_license_works_for_names_and_ids() {
+        assert_eq!(normalize_license("MIT License"), Some("MIT".to_string()));
+        assert_eq!(
+            normalize_license("apache-2.0"),
+            Some("Apache-2.0".to_string())
+        );
+        assert_eq!(
+            normalize_license("GNU General Public License v3.0"),
+            Some("GPL-3.0-only".to_string())
+        );
+        assert_eq!(
+            normalize_license("GPL-3.0"),
+            Some("GPL-3.0-only".to_string())
+        );
+        assert_eq!(
+            normalize_license("The Unlicense"),
+            Some("Unlicense".to_string())
+        );
+        assert_eq!(normalize_license(""), None);
+        assert_eq!(normalize_license("Other"), None);
+        assert_eq!(normalize_license("NOASSERTION"), None);
+    }
+
+    #[test]
+    fn normalize_license_works_for_expressions() {
+        assert_eq!(
+            normalize_license("mit or apache-2.0"),
+            Some("MIT OR Apache-2.0".to_string())
+        );
+        assert_eq!(
+            normalize_license("( MIT AND Zlib ) or GPL-2.0 WITH classpath-exception-2.0"),
+            Some("(MIT AND Zlib) OR GPL-2.0-only WITH Classpath-exception-2.0".to_string())
+        );
+        assert_eq!(normalize_license("MIT OR"), None);
+        assert_eq!(normalize_license("(MIT"), None);
+        assert_eq!(normalize_license("MIT Apache-2.0"), None);
+        assert_eq!(normalize_license("MIT OR Proprietary"), None);
+        assert_eq!(normalize_license("MIT WITH Apache-2.0"), None);
+    }
+
+    #[test]
+    fn resolve_license_prefers_first_recognized_license() {
+        assert_eq!(
+            resolve_license(&[Some("Proprietary"), None, Some("MIT License")]),
+            Some("MIT".to_string())
+        );
+        assert_eq!(resolve_license(&[Some("Other"), None]), None);
+    }
+
+    #[test]
+    fn parse_license_filter_works() {
+        assert_eq!(parse_license_filter(Some("mit")), Some("MIT".to_string()));
+        assert_eq!(
+            parse_license_filter(Some("Unknown")),
+            Some(UNKNOWN_LICENSE_FILTER.to_string())
+        );
+        assert_eq!(parse_license_filter(Some("MIT OR Apache-2.0")), None);
+        assert_eq!(parse_license_filter(None), None);
+    }
+}
@@ -0,0 +1,284 @@
+use pulldown_cmark::escape::escape_html;
+use pulldown_cmark::{html, CowStr, Event, HeadingLevel, Options, Parser, Tag};
+use std::borrow::Cow;
+use std::collections::HashMap;
+
+const IMAGE_EXTENSIONS: [&str; 7] = ["png", "jpg", "jpeg", "gif", "svg", "webp", "bmp"];
+const MIN_TOC_HEADINGS: usize = 3;
+// Heading ids are prefixed like on Github so a README can't clobber the ids of the page
+const ANCHOR_PREFIX: &str = "user-content-";
+
+/// Where relative urls of a markdown file point to, e.g for a README in
+/// `language/move-stdlib` of https://github.com/move-language/move at rev `main`:
+/// raw_base: https://raw.githubusercontent.com/move-language/move/main
+/// blob_base: https://github.com/move-language/move/blob/main
+/// dir: language/move-stdlib
+#[derive(Clone)]
+pub struct MarkdownLinks {
+    pub raw_base: String,
+    pub blob_base: String,
+    pub dir: String,
+}
+
+struct Heading {
+    level: HeadingLevel,
+    anchor: String,
+    text: String,
+}
+
+/// Renders markdown to sanitized html, with relative links resolved against the
+/// repository, anchors on headings and a table of contents for longer documents.
+pub fn render_markdown(content: &str, links: &MarkdownLinks) -> String {
+    let events = Parser::new_ext(content, Options::all()).collect::<Vec<Event>>();
+
+    let mut headings = vec![];
+    let mut anchors: HashMap<String, usize> = HashMap::new();
+    let mut current_heading: Option<(HeadingLevel, String)> = None;
+    for event in &events {
+        match event {
+            Event::Start(Tag::Heading(level, _, _)) => {
+                current_heading = Some((*level, String::new()))
+            }
+            Event::Text(text) | Event::Code(text) => {
+                if let Some((_, heading_text)) = current_heading.as_mut() {
+                    heading_text.push_str(text);
+                }
+            }
+            Event::End(Tag::Heading(..)) => {
+                if let Some((level, text)) = current_heading.take() {
+                    let anchor = unique_anchor(&text, &mut anchors);
+                    headings.push(Heading {
+                        level,
+                        anchor,
+                        text,
+                    });
+                }
+            }
+            _ => {}
+        }
+    }
+
+    let mut heading_index = 0;
+    let events = events.into_iter().map(|event| match event {
+        Event::Start(Tag::Heading(level, _, _)) => {
+            let anchor = &headings[heading_index].anchor;
+            Event::Html(CowStr::from(format!("<{} id=\"{}\">", level, anchor)))
+        }
+        Event::End(Tag::Heading(level, _, _)) => {
+            heading_index += 1;
+            Event::Html(CowStr::from(format!("</{}>\n", level)))
+        }
+        Event::Start(Tag::Link(link_type, url, title)) => Event::Start(Tag::Link(
+            link_type,
+            CowStr::from(resolve_url(&url, links, false)),
+            title,
+        )),
+        Event::Start(Tag::Image(link_type, url, title)) => Event::Start(Tag::Image(
+            link_type,
+            CowStr::from(resolve_url(&url, links, true)),
+            title,
+        )),
+        event => event,
+    });
+
+    let mut unsafe_html = String::new();
+    html::push_html(&mut unsafe_html, events);
+    let body = sanitize(&unsafe_html, links);
+
+    if headings.len() < MIN_TOC_HEADINGS {
+        return body;
+    }
+    let mut toc = String::from("<nav class=\"readme-toc\"><ul>");
+    for heading in &headings {
+        toc.push_str(&format!(
+            "<li class=\"toc-{}\"><a href=\"#{}{}\">",
+            heading.level, ANCHOR_PREFIX, heading.anchor
+        ));
+        // escaping to a String never fails
+        escape_html(&mut toc, &heading.text).unwrap();
+        toc.push_str("</a></li>");
+    }
+    toc.push_str("</ul></nav>");
+    toc + &body
+}
+
+fn sanitize(unsafe_html: &str, links: &MarkdownLinks) -> String {
+    ammonia::Builder::default()
+        .id_prefix(Some(ANCHOR_PREFIX))
+        .add_tag_attributes("h1", &["id"])
+        .add_tag_attributes("h2", &["id"])
+        .add_tag_attributes("h3", &["id"])
+        .add_tag_attributes("h4", &["id"])
+        .add_tag_attributes("h5", &["id"])
+        .add_tag_attributes("h6", &["id"])
+        .add_tag_attributes("p", &["align"])
+        .add_tag_attributes("div", &["align"])
+        .url_relative(ammonia::UrlRelative::Custom(Box::new(HtmlUrlResolver(
+            links.clone(),
+        ))))
+        .clean(unsafe_html)
+        .to_string()
+}
+
+// Relative urls left after rendering come from html embedded in the markdown
+struct HtmlUrlResolver(MarkdownLinks);
+
+impl ammonia::UrlRelativeEvaluate for HtmlUrlResolver {
+    fn evaluate<'a>(&self, url: &'a str) -> Option<Cow<'a, str>> {
+        Some(Cow::Owned(resolve_url(url, &self.0, is_image_path(url))))
+    }
+}
+
+// Same rules as Github: lowercase, spaces become dashes and punctuation is dropped
+fn unique_anchor(text: &str, anchors: &mut HashMap<String, usize>) -> String {
+    let anchor = text
+        .trim()
+        .to_lowercase()
+        .chars()
+        .filter_map(|c| match c {
+            ' ' => Some('-'),
+            c if c.is_alphanumeric() || c == '-' || c == '_' => Some(c),
+            _ => None,
+        })
+        .collect::<String>();
+    let count = anchors.entry(anchor.clone()).or_insert(0);
+    *count += 1;
+    if *count == 1 {
+        anchor
+    } else {
+        format!("{}-{}", anchor, *count - 1)
+    }
+}
+
+fn is_image_path(url: &str) -> bool {
+    let path = url.split(|c| c == '?' || c == '#').next().unwrap_or("");
+    match path.rsplit_once('.') {
+        Some((_, extension)) => IMAGE_EXTENSIONS.contains(&extension.to_lowercase().as_str()),
+        None => false,
+    }
+}
+
+fn is_relative_url(url: &str) -> bool {
+    !(url.is_empty()
+        || url.starts_with('#')
+        || url.starts_with("//")
+        || url
+            .split('/')
+            .next()
+            .map_or(false, |first_segment| first_segment.contains(':')))
+}
+
+/// Images are served from raw.githubusercontent.com, other files are linked to their Github page.
+/// Links to a fragment of the document point to the prefixed heading ids.
+fn resolve_url(url: &str, links: &MarkdownLinks, is_image: bool) -> String {
+    if let Some(fragment) = url.strip_prefix('#') {
+        if fragment.is_empty() || fragment.starts_with(ANCHOR_PREFIX) {
+            return url.to_string();
+        }
+        return format!("#{}{}", ANCHOR_PREFIX, fragment);
+    }
+    if !is_relative_url(url) {
+        return url.to_string();
+    }
+    let (path, suffix) = match url.find(|c| c == '?' || c == '#') {
+        Some(index) => url.split_at(index),
+        None => (url, ""),
+    };
+    let mut segments: Vec<&str> = vec![];
+    // urls starting with a slash are relative to the repository root
+    if !path.starts_with('/') {
+        segments.extend(links.dir.split('/').filter(|segment| !segment.is_empty()));
+    }
+    for segment in path.split('/') {
+        match segment {
+            "" | "." => {}
+            ".." => {
+                segments.pop();
+            }
+            segment => segments.push(segment),
+        }
+    }
+    let base = if is_image {
+        &links.raw_base
+    } else {
+        &links.blob_base
+    };
+    format!("{}/{}{}", base, segments.join("/"), suffix)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn links() -> MarkdownLinks {
+        MarkdownLinks {
+            raw_base: "https://raw.githubusercontent.com/move-language/move/main".to_string(),
+            blob_base: "https://github.com/move-language/move/blob/main".to_string(),
+            dir: "language/move-stdlib".to_string(),
+        }
+    }
+
+    #[test]
+    fn render_markdown_resolves_relative_urls() {
+        let content = "![logo](./assets/logo.png) [docs](../docs/README.md#usage) \
+            [home](/README.md) [site](https://movey.net) [top](#install) [ref][1]\n\n\
+            <img src=\"img/diagram.svg\">\n\n\
+            [1]: CHANGELOG.md\n";
+        let html = render_markdown(content, &links());
+
+        assert!(html.contains("src=\"https://raw.githubusercontent.com/move-language/move/main/language/move-stdlib/assets/logo.png\""));
+        assert!(html.contains("href=\"https://github.com/move-language/move/blob/main/language/docs/README.md#usage\""));
+        assert!(html.contains("href=\"https://github.com/move-language/move/blob/main/README.md\""));
+        assert!(html.contains("href=\"https://movey.net\""));
+        assert!(html.contains("href=\"#user-content-install\""));
+        assert!(html.contains("href=\"https://github.com/move-language/move/blob/main/language/move-stdlib/CHANGELOG.md\""));
+        assert!(html.contains("src=\"https://raw.githubusercontent.com/move-language/move/main/language/move-stdlib/img/diagram.svg\""));
+    }
+
+    #[test]
+    fn render_markdown_leaves_code_blocks_untouched() {
+        let content = "```\n<img src=\"logo.png\"> [docs](docs.md)\n```\n";
+        let html = render_markdown(content, &links());
+        assert!(html.contains("&lt;img src=\"logo.png\"&gt; [docs](docs.md)"));
+        assert!(!html.contains("githubusercontent"));
+    }
+
+    #[test]
+    fn render_markdown_removes_scripts_and_event_handlers() {
+        let content = "<script>alert(1)</script>\n\n<img src=\"a.png\" onerror=\"alert(1)\">\n\n[x](javascript:alert(1))";
+        let html = render_markdown(content, &links());
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("onerror"));
+        assert!(!html.contains("javascript:"));
+    }
+
+    #[test]
+    fn render_markdown_adds_anchors_and_table_of_contents() {
+        let content = "# Move Stdlib\n\n## Install\n\n## Usage\n\n## Usage\n";
+        let html = render_markdown(content, &links());
+
+        assert!(html.contains("<h1 id=\"user-content-move-stdlib\">Move Stdlib</h1>"));
+        assert!(html.contains("<h2 id=\"user-content-usage\">Usage</h2>"));
+        assert!(html.contains("<h2 id=\"user-content-usage-1\">Usage</h2>"));
+        assert!(html.starts_with("<nav class=\"readme-toc\"><ul><li class=\"toc-h1\"><a href=\"#user-content-move-stdlib\">Move Stdlib</a></li>"));
+    }
+
+    #[test]
+    fn render_markdown_prefixes_ids_and_fragment_links() {
+        let content = "# Search\n\n[search](#search) <a href=\"#search\">search</a>\n\n\
+            <h2 id=\"csrf_token\">Token</h2>\n";
+        let html = render_markdown(content, &links());
+
+        assert!(html.contains("<h1 id=\"user-content-search\">Search</h1>"));
+        assert!(html.contains("<h2 id=\"user-content-csrf_token\">Token</h2>"));
+        assert!(!html.contains("id=\"csrf_token\""));
+        assert!(!html.contains("href=\"#search\""));
+        assert_eq!(html.matches("href=\"#user-content-search\"").count(), 2);
+    }
+
+    #[test]
+    fn render_markdown_skips_table_of_contents_for_short_documents() {
+        let html = render_markdown("# Title\n\ntext", &links());
+        assert!(!html.contains("readme-toc"));
+    }
+}
@@ -1,5 +1,7 @@
 use jelly::guards::Auth;
 
+pub mod license;
+pub mod markdown;
 pub mod paginate;
 pub mod presenter;
 pub mod request_utils;
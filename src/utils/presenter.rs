@@ -3,6 +3,7 @@ use jelly::DieselPgPool;
 use jelly::Result;
 
 use crate::accounts::Account;
+use crate::organizations::models::Organization;
 use crate::package_collaborators::package_collaborator::PackageCollaborator;
 use crate::packages::Package;
 
@@ -20,6 +21,11 @@ pub fn censor_email(email: &str) -> Result<String> {
 
 pub fn make_account_name(package: &Package, db: &DieselPgPool) -> Result<(String, String)> {
     let connection = db.get()?;
+    if let Some(organization_id) = package.organization_id {
+        let organization = Organization::get(organization_id, &connection)?;
+        let slug_url = format!("/organizations/{}", organization.slug);
+        return Ok((organization.name, slug_url));
+    }
     let collaborators = PackageCollaborator::get_by_package_id(package.id, &connection)?;
     let package_owner_id = if collaborators.len() > 0 {
         Some(collaborators[0])
@@ -53,22 +59,26 @@ pub fn make_account_name(package: &Package, db: &DieselPgPool) -> Result<(String
 
 pub fn make_package_install_instruction(repo_url: &str) -> (String, String) {
     // Display url for install instruction
+    let (mut instruction_repo_url, instruction_subdir) = make_package_repo_and_subdir(repo_url);
+    instruction_repo_url.push_str(".git");
+    (instruction_repo_url, instruction_subdir)
+}
+
+pub fn make_package_repo_and_subdir(repo_url: &str) -> (String, String) {
     // example: https://github.com/move-language/move/tree/main/language/evm/hardhat-examples/contracts/ABIStruct
     //          -> repo_url: https://github.com/move-language/move
     //             subdir: language/evm/hardhat-examples/contracts/ABIStruct
-    let mut instruction_subdir = String::from("");
-    let mut instruction_repo_url: String;
     let repo_url_tokens = repo_url.split('/').collect::<Vec<&str>>();
     if repo_url_tokens.len() > 7 {
-        instruction_repo_url = repo_url_tokens[..5].join("/");
-        instruction_subdir = repo_url_tokens[7..].join("/");
+        (
+            repo_url_tokens[..5].join("/"),
+            repo_url_tokens[7..].join("/"),
+        )
     } else {
         // Should be the root directory, not a subdir,
         // like https://github.com/move-language/move
-        instruction_repo_url = repo_url.to_string();
+        (repo_url.to_string(), String::new())
     }
-    instruction_repo_url.push_str(".git");
-    (instruction_repo_url, instruction_subdir)
 }
 
 pub fn validate_version(package_version: &str) -> Vec<&'static str> {
@@ -22,6 +22,18 @@ pub fn clear_cookie(request: &HttpRequest) -> HttpResponse {
         .body("")
 }
 
+/// 301 to the page under its current slug, keeping the query string.
+pub fn permanent_redirect(location: &str, query_string: &str) -> HttpResponse {
+    let location = if query_string.is_empty() {
+        location.to_string()
+    } else {
+        format!("{}?{}", location, query_string)
+    };
+    HttpResponse::MovedPermanently()
+        .header(header::LOCATION, location)
+        .finish()
+}
+
 pub fn renew_token(request: &HttpRequest) -> Result<bool> {
     if request.get_session().get::<User>("sku")?.is_none() {
         if let Some(cookie) = request.cookie("remember_me_token") {
@@ -0,0 +1,31 @@
+class Claim {
+  constructor() {
+    this.packageSlug = $(".claim-container").data("package-slug");
+    this.init();
+  }
+
+  init() {
+    $("button.claim-btn").click((e) => {
+      this.claimPackage($(e.target).data("method"));
+    });
+  }
+
+  claimPackage = (method) => {
+    $.ajax({
+      type: "POST",
+      dataType: "json",
+      url: "/api/v1/packages/" + this.packageSlug + "/claim",
+      contentType: "application/json",
+      processData: false,
+      headers: {},
+      data: JSON.stringify({ method: method }),
+      success: () => {
+        window.location.href = "/packages/" + this.packageSlug + "/collaborators";
+      },
+      error: (data) => {
+        $("#claim-message").text(data.responseJSON.msg);
+        $("#claim-message").addClass("error");
+      },
+    });
+  };
+}
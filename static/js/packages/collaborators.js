@@ -52,6 +52,12 @@ class Collaborator {
       this.transfer_modal.foundation("open");
     });
     this.remove_btn.click(this.removeBtnListener);
+    $(".ownership_btn.resend").click((e) => {
+      this.resendInvitation(
+        e.target.parentElement.parentElement.querySelector(".email_address")
+          .innerText
+      );
+    });
 
     this.transfer_modal.find(".submit").on("click", () => {
       this.transferOwnership();
@@ -73,6 +79,47 @@ class Collaborator {
       this.userName = this.userNameInput.val();
     });
 
+    $(".deprecation_btn.deprecate").click(() => {
+      this.updateDeprecation("PUT", {
+        reason: $(".deprecation_reason").val(),
+        successor: $(".deprecation_successor").val() || null,
+      });
+    });
+    $(".deprecation_btn.undeprecate").click(() => {
+      this.updateDeprecation("DELETE");
+    });
+    $(".deprecation_btn.archive").click(() => {
+      this.updatePackageState("PUT", "archive", "#archive-message");
+    });
+    $(".deprecation_btn.unarchive").click(() => {
+      this.updatePackageState("DELETE", "archive", "#archive-message");
+    });
+    $(".deprecation_btn.delete").click(() => {
+      if (!confirm("Delete " + this.packageSlug + "? It can be restored until the grace period ends.")) return;
+      this.updatePackageState("PUT", "deletion", "#deletion-message");
+    });
+    $(".deprecation_btn.restore").click(() => {
+      this.updatePackageState("DELETE", "deletion", "#deletion-message");
+    });
+    $(".rename_btn").click(() => {
+      this.renamePackage($(".rename_slug").val());
+    });
+    $(".transfer_organization_btn").click(() => {
+      const organization = $(".transfer_organization_select").val();
+      if (!confirm("Transfer " + this.packageSlug + " to " + organization + "?")) return;
+      this.transferToOrganization(organization);
+    });
+
+    $(".collaborator_role_select").change((e) => {
+      this.changeRole($(e.target).data("user"), $(e.target).val());
+    });
+    $(".deprecation_btn.approve").click((e) => {
+      this.updateApproval("POST", $(e.target).data("approval"));
+    });
+    $(".deprecation_btn.dismiss").click((e) => {
+      this.updateApproval("DELETE", $(e.target).data("approval"));
+    });
+
     // handle required input
     $("#user_email").change(() => {
       $(".add_collaborators_btn").css("background-color", "var(--blue-color)");
@@ -82,6 +129,17 @@ class Collaborator {
   inviteCollaborator = () => {
     const collaboratorEmail = $(".collaborators_input").val();
     if (!collaboratorEmail || !this.userName) return;
+    const users = this.userName
+      .split(/[,\s]+/)
+      .filter((user) => user.length > 0);
+    const expirationDays = $(".expiration_days_select").val();
+    const data = {
+      expiration_days: expirationDays ? parseInt(expirationDays) : null,
+    };
+    if (users.length > 1) {
+      this.inviteCollaborators(users, data);
+      return;
+    }
     let collaboratorUrl =
       "/api/v1/collaborators/packages/" +
       this.packageSlug +
@@ -93,13 +151,13 @@ class Collaborator {
       contentType: "application/json",
       processData: false,
       headers: {},
-      data: JSON.stringify({ user: this.userName }),
+      data: JSON.stringify({ user: users[0], ...data }),
       success: (data) => {
         if (data.ok) {
-          this.updateRow(this.userName, "Collaborator");
+          this.updateRow(users[0], "Collaborator");
           this.messageReturn(data.msg, true);
         } else {
-          this.updateRow(this.userName, "External");
+          this.updateRow(users[0], "External");
           this.messageReturn(data.msg, true);
         }
       },
@@ -109,6 +167,50 @@ class Collaborator {
     });
   };
 
+  inviteCollaborators = (users, data) => {
+    $.ajax({
+      type: "POST",
+      dataType: "json",
+      url: "/api/v1/collaborators/packages/" + this.packageSlug + "/bulk",
+      contentType: "application/json",
+      processData: false,
+      headers: {},
+      data: JSON.stringify({ users: users, ...data }),
+      success: (data) => {
+        data.results
+          .filter((result) => result.ok)
+          .forEach((result) => this.updateRow(result.user, "Pending"));
+        this.messageReturn(
+          data.results.map((result) => result.user + ": " + result.msg).join(" "),
+          data.ok
+        );
+      },
+      error: (data) => {
+        this.messageReturn(data.responseJSON.msg, false);
+      },
+    });
+  };
+
+  resendInvitation = (user) => {
+    $.ajax({
+      type: "POST",
+      dataType: "json",
+      url: "/api/v1/collaborators/packages/" + this.packageSlug + "/invitations/resend",
+      contentType: "application/json",
+      processData: false,
+      headers: {},
+      data: JSON.stringify({ user: user }),
+      success: (data) => {
+        $('#success_modal_message').text(data.msg);
+        this.success_modal.foundation("open");
+      },
+      error: (data) => {
+        $('#success_modal_message').text(data.responseJSON.msg);
+        this.success_modal.foundation("open");
+      },
+    });
+  };
+
   transferOwnership = () => {
     let collaboratorUrl =
       "/api/v1/collaborators/packages/" + this.packageSlug + "/transfer";
@@ -128,7 +230,7 @@ class Collaborator {
         this.current_transfer_target.parent().parent()
           .find('.collaborator_name')
           .after(`
-                <div class="sending_status">invitation sent</div>
+                <div class="sending_status">${data.pending_approval ? "approval pending" : "invitation sent"}</div>
             `)
         this.current_transfer_target = undefined
       },
@@ -164,6 +266,119 @@ class Collaborator {
     });
   };
 
+  changeRole = (user, role) => {
+    $.ajax({
+      type: "PUT",
+      dataType: "json",
+      url: "/api/v1/collaborators/packages/" + this.packageSlug + "/role",
+      contentType: "application/json",
+      processData: false,
+      headers: {},
+      data: JSON.stringify({ user: user, role: role }),
+      success: (data) => {
+        $('#success_modal_message').text(data.msg);
+        this.success_modal.foundation("open");
+      },
+      error: (data) => {
+        $('#success_modal_message').text(data.responseJSON.msg);
+        this.success_modal.foundation("open");
+      },
+    });
+  };
+
+  updateApproval = (type, approvalId) => {
+    $.ajax({
+      type: type,
+      dataType: "json",
+      url: "/api/v1/collaborators/packages/" + this.packageSlug + "/approvals/" + approvalId,
+      contentType: "application/json",
+      processData: false,
+      headers: {},
+      success: () => {
+        window.location.reload();
+      },
+      error: (data) => {
+        $("#approval-message").text(data.responseJSON.msg);
+        $("#approval-message").addClass("error");
+      },
+    });
+  };
+
+  updateDeprecation = (type, deprecation) => {
+    $.ajax({
+      type: type,
+      dataType: "json",
+      url: "/api/v1/packages/" + this.packageSlug + "/deprecation",
+      contentType: "application/json",
+      processData: false,
+      headers: {},
+      data: deprecation ? JSON.stringify(deprecation) : undefined,
+      success: () => {
+        window.location.reload();
+      },
+      error: (data) => {
+        $("#deprecation-message").text(data.responseJSON.msg);
+        $("#deprecation-message").addClass("error");
+      },
+    });
+  };
+
+  updatePackageState = (type, state, messageSelector) => {
+    $.ajax({
+      type: type,
+      dataType: "json",
+      url: "/api/v1/packages/" + this.packageSlug + "/" + state,
+      contentType: "application/json",
+      processData: false,
+      headers: {},
+      success: () => {
+        window.location.reload();
+      },
+      error: (data) => {
+        $(messageSelector).text(data.responseJSON.msg);
+        $(messageSelector).addClass("error");
+      },
+    });
+  };
+
+  renamePackage = (slug) => {
+    $.ajax({
+      type: "PUT",
+      dataType: "json",
+      url: "/api/v1/packages/" + this.packageSlug + "/slug",
+      contentType: "application/json",
+      processData: false,
+      headers: {},
+      data: JSON.stringify({ slug: slug }),
+      success: (data) => {
+        window.location.href = "/packages/" + data.slug + "/collaborators";
+      },
+      error: (data) => {
+        $("#rename-message").text(data.responseJSON.msg);
+        $("#rename-message").addClass("error");
+      },
+    });
+  };
+
+  transferToOrganization = (organization) => {
+    $.ajax({
+      type: "PUT",
+      dataType: "json",
+      url: "/api/v1/packages/" + this.packageSlug + "/organization",
+      contentType: "application/json",
+      processData: false,
+      headers: {},
+      data: JSON.stringify({ organization: organization }),
+      success: () => {
+        window.location.reload();
+      },
+      error: (data) => {
+        $("#organization-message").text(data.responseJSON.msg);
+        $("#organization-message").addClass("error");
+      },
+    });
+  };
+
   updateRow = (name, role) => {
     $(".collaborators_table").append(`
       <div class="collaborator_row">
@@ -14,7 +14,7 @@ class PackagesIndex {
 
         this.sortElement.on('change', function(e) {
             const $elem = $(e.currentTarget);
-            window.location = $elem.data('url') + '?field=' + $elem.val();
+            window.location = $elem.data('url') + '?field=' + $elem.val() + $elem.data('filters');
         });
 
         this.packagesContainerElement.on('click', '.paginationjs-page', (e) => {
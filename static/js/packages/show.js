@@ -11,7 +11,10 @@ class PackageShow {
         var converter = new showdown.Converter({
             'tables': 'true'
         });
-        this.readmeElement.html(converter.makeHtml(this.readmeElement.text()));
+        // versions crawled before server-side rendering only have the raw readme
+        if (!this.readmeElement.hasClass('readme-rendered')) {
+            this.readmeElement.html(converter.makeHtml(this.readmeElement.text()));
+        }
         this.packageDescription.html(converter.makeHtml(this.packageDescription.html()));
         this.instructionElement.on('click', (e) => {
             navigator.clipboard.writeText(this.instructionElement.find('.instruction-command').text());
@@ -20,7 +23,7 @@ class PackageShow {
                 $('.copy-tooltip').hide();
             }, 400);
         });
-        Array.from($(".package-readme-content a")).forEach(e => e.setAttribute('target', '_blank'));
+        Array.from($(".package-readme-content a:not([href^='#'])")).forEach(e => e.setAttribute('target', '_blank'));
         this.packageSizeElement.text(this.niceBytes(this.packageSizeElement.data("value")));
     }
 
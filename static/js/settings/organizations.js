@@ -0,0 +1,116 @@
+class Organizations {
+  constructor() {
+    this.organizationForm = $("#organization-form");
+    this.init();
+  }
+
+  init() {
+    this.organizationForm.submit((event) => {
+      event.preventDefault();
+      this.createOrganization(
+        this.organizationForm.find(".organization_name_input").val(),
+        this.organizationForm.find(".organization_description_input").val()
+      );
+    });
+
+    $(".organization_member_form").submit((event) => {
+      event.preventDefault();
+      const form = $(event.target);
+      this.updateMember("POST", form, {
+        user: form.find(".member_input").val(),
+        role: form.find(".member_role_select").val(),
+      });
+    });
+
+    $(".member_role_update").change((event) => {
+      const member = $(event.target).closest(".organization_member");
+      this.updateMember("PUT", member, {
+        user: member.data("member"),
+        role: $(event.target).val(),
+      });
+    });
+
+    $(".member_remove_btn").click((event) => {
+      const member = $(event.target).closest(".organization_member");
+      if (!confirm("Remove " + member.data("member") + " from the organization?")) return;
+      this.updateMember("DELETE", member, { user: member.data("member") });
+    });
+
+    $(".invitation_accept_btn").click((event) => {
+      this.handleInvitation($(event.target), true);
+    });
+
+    $(".invitation_decline_btn").click((event) => {
+      this.handleInvitation($(event.target), false);
+    });
+  }
+
+  createOrganization = (name, description) => {
+    $.ajax({
+      type: "POST",
+      dataType: "json",
+      url: "/api/v1/organizations",
+      contentType: "application/json",
+      processData: false,
+      headers: {},
+      data: JSON.stringify({ name: name, description: description }),
+      success: () => {
+        window.location.reload();
+      },
+      error: (data) => {
+        $("#organization-message").text(data.responseJSON.msg);
+        $("#organization-message").addClass("error");
+      },
+    });
+  };
+
+  updateMember = (type, target, member) => {
+    const organization = target.closest(".organization_wrapper");
+    $.ajax({
+      type: type,
+      dataType: "json",
+      url: "/api/v1/organizations/" + organization.data("organization-slug") + "/members",
+      contentType: "application/json",
+      processData: false,
+      headers: {},
+      data: JSON.stringify(member),
+      success: (data) => {
+        // Invited accounts are only listed once they accept
+        if (type === "POST") {
+          const message = organization.find(".member_message");
+          message.text(data.msg);
+          message.removeClass("error");
+          target.find(".member_input").val("");
+          return;
+        }
+        window.location.reload();
+      },
+      error: (data) => {
+        const message = organization.find(".member_message");
+        message.text(data.responseJSON.msg);
+        message.addClass("error");
+      },
+    });
+  };
+
+  handleInvitation = (target, accepted) => {
+    const organization = target.closest(".organization_wrapper");
+    $.ajax({
+      type: "POST",
+      dataType: "json",
+      url: "/api/v1/organizations/" + organization.data("organization-slug") + "/invitation",
+      contentType: "application/json",
+      processData: false,
+      headers: {},
+      data: JSON.stringify({ accepted: accepted }),
+      success: () => {
+        window.location.reload();
+      },
+      error: (data) => {
+        const message = organization.find(".member_message");
+        message.text(data.responseJSON.msg);
+        message.addClass("error");
+      },
+    });
+  };
+}
@@ -5,7 +5,7 @@ class Profile {
     this.passwordConfirm = document.getElementById('password-confirm')
     this.saveButton = document.getElementById('save-btn')
     this.discardButton = document.getElementById('discard-btn')
-    this.form = $('.profile-form form')
+    this.form = $('#password-form')
     this.init()
   }
 